@@ -10,6 +10,7 @@ import {
     Matrix4 as mat4,
     Quaternion as quat,
     Quaternion2 as quat2,
+    FieldOfView,
 } from '../pkg/gl_matrix_wasm';
 
 describe("mat4", function () {
@@ -640,6 +641,37 @@ describe("mat4", function () {
         });
     });
 
+    describe("decompose", function () {
+        let t, r, s, shear, persp;
+        beforeEach(function () {
+            t = vec3.create();
+            r = quat.create();
+            s = vec3.create();
+            shear = vec3.create();
+            persp = vec4.create();
+        });
+
+        it("should round-trip with fromRotationTranslationScale", function () {
+            let q = quat.create();
+            quat.setAxisAngle(q, vec3.fromValues(0, 0, 1), 0.5);
+            mat4.fromRotationTranslationScale(out, q, vec3.fromValues(1, 2, 3), vec3.fromValues(2, 3, -4));
+            expect(mat4.decompose(out, t, r, s, shear, persp)).toBe(true);
+            expect(t).toBeEqualish([1, 2, 3]);
+            expect(s).toBeEqualish([-2, -3, -4]);
+            expect(shear).toBeEqualish([0, 0, 0]);
+            expect(persp).toBeEqualish([0, 0, 0, 1]);
+
+            let back = mat4.create();
+            mat4.fromRotationTranslationScale(back, r, t, s);
+            expect(back).toBeEqualish(out);
+        });
+
+        it("should return false for a singular matrix", function () {
+            mat4.fromScaling(out, vec3.fromValues(1, 0, 1));
+            expect(mat4.decompose(out, t, r, s, shear, persp)).toBe(false);
+        });
+    });
+
     describe("frustum", function () {
         beforeEach(function () { result = mat4.frustum(out, -1, 1, -1, 1, -1, 1); });
         it("should place values into out", function () {
@@ -703,6 +735,36 @@ describe("mat4", function () {
         });
     });
 
+    describe("perspectiveFromFieldOfView", function () {
+        describe("with a FieldOfView", function () {
+            beforeEach(function () {
+                result = mat4.perspectiveFromFieldOfView(out, FieldOfView.fromValues(30, 40, 50, 20), 0.5, 200);
+            });
+            it("should calculate correct matrix", function () {
+                expect(out).toBeEqualish([
+                    1.2855752, 0, 0, 0,
+                    0, 1.4119807, 0, 0,
+                    -0.5320889, -0.1847925, -1.0025063, -1,
+                    0, 0, -0.5012531, 0
+                ]);
+            });
+        });
+
+        describe("with a plain object", function () {
+            beforeEach(function () {
+                result = mat4.perspectiveFromFieldOfViewObject(out, { upDegrees: 45, downDegrees: 45, leftDegrees: 45, rightDegrees: 45 }, 0.1, 100);
+            });
+            it("should calculate correct matrix", function () {
+                expect(out).toBeEqualish([
+                    1, 0, 0, 0,
+                    0, 1, 0, 0,
+                    0, 0, -1.001001, -1,
+                    0, 0, -0.1001001, 0
+                ]);
+            });
+        });
+    });
+
     describe("ortho", function () {
         beforeEach(function () { result = mat4.ortho(out, -1, 1, -1, 1, -1, 1); });
         it("should place values into out", function () {
@@ -716,6 +778,89 @@ describe("mat4", function () {
         
     });
 
+    describe("perspectiveZO", function () {
+        beforeEach(function () { result = mat4.perspectiveZO(out, 45 * Math.PI / 180.0, 640 / 480, 0.1, 200); });
+        it("should calculate correct matrix", function () {
+            expect(out).toBeEqualish([
+                1.81066, 0, 0, 0,
+                0, 2.414213, 0, 0,
+                0, 0, -1.0005003, -1,
+                0, 0, -0.10005003, 0
+            ]);
+        });
+    });
+
+    describe("perspectiveReversedZ", function () {
+        describe("with infinite far plane", function () {
+            beforeEach(function () { result = mat4.perspectiveReversedZ(out, 45 * Math.PI / 180.0, 640 / 480, 0.1, Infinity); });
+            it("should calculate correct matrix", function () {
+                expect(out).toBeEqualish([
+                    1.81066, 0, 0, 0,
+                    0, 2.414213, 0, 0,
+                    0, 0, 0, -1,
+                    0, 0, 0.1, 0
+                ]);
+            });
+        });
+    });
+
+    describe("view", function () {
+        it("should alias the elements of the matrix", function () {
+            const view = matA.view();
+            expect(Array.from(view)).toBeEqualish(matA.elements);
+            mat4.copy(matA, matB);
+            expect(Array.from(view)).toBeEqualish(matB.elements);
+        });
+    });
+
+    describe("copyFromArray", function () {
+        beforeEach(function () { mat4.copyFromArray(out, new Float32Array(matB.elements)); });
+        it("should place values into out", function () { expect(out).toBeEqualish(matB); });
+    });
+
+    describe("fromFloat32Array", function () {
+        beforeEach(function () { result = mat4.fromFloat32Array(new Float32Array(matA.elements)); });
+        it("should create a matrix with the values", function () { expect(result).toBeEqualish(matA); });
+        it("should throw a RangeError on a short array", function () {
+            expect(() => mat4.fromFloat32Array(new Float32Array(15))).toThrowError(RangeError, "expected 16 elements, found 15");
+            expect(() => mat4.copyFromArray(out, new Float32Array(4))).toThrowError(RangeError);
+        });
+    });
+
+    describe("multiplyArray", function () {
+        let buffer;
+        beforeEach(function () {
+            buffer = new Float32Array(32);
+            mat4.multiplyArray(buffer, new Float32Array([...matA.elements, ...matB.elements]), new Float32Array([...matB.elements, ...matA.elements]), 2);
+        });
+        it("should multiply each pair of matrices", function () {
+            expect(Array.from(buffer.subarray(0, 16))).toBeEqualish([
+                1, 0, 0, 0,
+                0, 1, 0, 0,
+                0, 0, 1, 0,
+                5, 7, 9, 1
+            ]);
+            expect(Array.from(buffer.subarray(16))).toBeEqualish([
+                1, 0, 0, 0,
+                0, 1, 0, 0,
+                0, 0, 1, 0,
+                5, 7, 9, 1
+            ]);
+        });
+    });
+
+    describe("orthoZO", function () {
+        beforeEach(function () { result = mat4.orthoZO(out, -1, 1, -1, 1, -1, 1); });
+        it("should place values into out", function () {
+            expect(out).toBeEqualish([
+                1, 0, 0, 0,
+                0, 1, 0, 0,
+                0, 0, -0.5, 0,
+                0, 0, 0.5, 1
+            ]);
+        });
+    });
+
     describe("lookAt", function () {
         let eye, center, up;
         let view, right;
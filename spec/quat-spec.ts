@@ -10,6 +10,8 @@ import {
     Matrix4 as mat4,
     Quaternion as quat,
     Quaternion2 as quat2,
+    EulerOrder,
+    AngleUnit,
 } from '../pkg/gl_matrix_wasm';
 
 describe("quat", function() {
@@ -236,6 +238,59 @@ describe("quat", function() {
         });
     });
 
+    describe("fromEulerOrder", function() {
+        it("should match fromEuler with the ZYX order", function() {
+            let expected = quat.create();
+            quat.fromEuler(expected, 10, -20, 30);
+            quat.fromEulerOrder(out, 10, -20, 30, EulerOrder.ZYX, AngleUnit.Degrees);
+            expect(out).toBeEqualish(expected);
+        });
+
+        it("should round-trip with toEuler", function() {
+            let angles = vec3.create();
+            quat.fromEulerOrder(out, 0.8, -0.5, 1.1, EulerOrder.YXZ, AngleUnit.Radians);
+            quat.toEuler(angles, out, EulerOrder.YXZ, AngleUnit.Radians);
+            expect(angles).toBeEqualish([0.8, -0.5, 1.1]);
+        });
+    });
+
+    describe("pow", function() {
+        it("should halve the angle with an exponent of 0.5", function() {
+            let expected = quat.create();
+            quat.setAxisAngle(quatA, vec3.fromValues(1, 0, 0), 1.2);
+            quat.setAxisAngle(expected, vec3.fromValues(1, 0, 0), 0.6);
+            quat.pow(out, quatA, 0.5);
+            expect(out).toBeEqualish(expected);
+        });
+    });
+
+    describe("spline", function() {
+        it("should pass through the keys", function() {
+            let keys = new Float32Array([0, 0, 0, 1, 0, 0.247404, 0, 0.968912, 0.479426, 0, 0, 0.877583]);
+            quat.spline(out, keys, 1);
+            expect(out).toBeEqualish([0, 0.247404, 0, 0.968912]);
+            quat.spline(out, keys, 2);
+            expect(out).toBeEqualish([0.479426, 0, 0, 0.877583]);
+        });
+    });
+
+    describe("swingTwist", function() {
+        it("should split into a swing and a twist about the axis", function() {
+            let swing = quat.create();
+            let twist = quat.create();
+            let axis = vec3.fromValues(0, 1, 0);
+            quat.setAxisAngle(twist, axis, 0.7);
+            quat.setAxisAngle(swing, vec3.fromValues(1, 0, 0), 0.4);
+            quat.multiply(quatA, swing, twist);
+
+            let outSwing = quat.create();
+            let outTwist = quat.create();
+            quat.swingTwist(quatA, axis, outSwing, outTwist);
+            expect(outSwing).toBeEqualish(swing);
+            expect(outTwist).toBeEqualish(twist);
+        });
+    });
+
     describe("setAxes", function() {
         let r;
         beforeEach(function() { r = vec3.create(); });
@@ -588,6 +588,29 @@ describe("quat2", function() {
         });
     });
 
+    describe("sclerp", function() {
+        it("should move along the screw between the transforms", function() {
+            let q = quat.create();
+            let a = quat2.create();
+            let b = quat2.create();
+            quat.setAxisAngle(q, vec3.fromValues(0, 0, 1), Math.PI / 2);
+            quat2.fromRotationTranslation(b, q, vec3.fromValues(1, -1, 2));
+
+            let expected = quat2.create();
+            quat2.pow(expected, b, 0.5);
+            quat2.sclerp(out, a, b, 0.5);
+            expect(out).toBeEqualishQuat2(expected);
+
+            let axis = vec3.create();
+            let moment = vec3.create();
+            let anglePitch = vec2.create();
+            quat2.toScrew(axis, moment, anglePitch, out);
+            expect(axis).toBeEqualish([0, 0, 1]);
+            expect(moment).toBeEqualish([0, -1, 0]);
+            expect(anglePitch).toBeEqualish([Math.PI / 4, 1]);
+        });
+    });
+
     describe("lerp", function() {
         describe("with a separate output quaternion", function() {
             beforeEach(function() { result = quat2.lerp(out, quat2A, quat2B, 0.7); });
@@ -0,0 +1,380 @@
+/**
+ * @File   : arena.rs
+ * @Author : dtysky (dtysky@outlook.com)
+ * @Link   : http://dtysky.moe
+ * @Date   : 2019/2/7 下午9:53:03
+ */
+#[cfg(feature = "wasm")]
+use js_sys::Float32Array;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(not(feature = "std"))]
+use super::common::Vec;
+use super::matrix4::*;
+use super::vector3::*;
+
+// Slots of one type, freed slots are reused before the storage grows.
+struct Pool<T> {
+    slots: Vec<T>,
+    alive: Vec<bool>,
+    free: Vec<u32>,
+    live: u32,
+    peak: u32,
+}
+
+impl<T: Copy> Pool<T> {
+    fn new(capacity: usize) -> Pool<T> {
+        Pool {
+            slots: Vec::with_capacity(capacity),
+            alive: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            live: 0,
+            peak: 0,
+        }
+    }
+
+    fn alloc(&mut self, value: T) -> u32 {
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index as usize] = value;
+                index
+            }
+            None => {
+                self.slots.push(value);
+                self.alive.push(false);
+                (self.slots.len() - 1) as u32
+            }
+        };
+        self.alive[index as usize] = true;
+        self.live += 1;
+        self.peak = u32::max(self.peak, self.live);
+        index
+    }
+
+    // Panics on an index that was never allocated or is freed, in release builds too.
+    fn check(&self, index: u32) {
+        assert!(
+            self.alive.get(index as usize) == Some(&true),
+            "slot {} is not allocated",
+            index
+        );
+    }
+
+    fn free(&mut self, index: u32) {
+        self.check(index);
+        self.alive[index as usize] = false;
+        self.free.push(index);
+        self.live -= 1;
+    }
+
+    fn reset(&mut self) {
+        self.free.clear();
+        self.free.extend((0..self.slots.len() as u32).rev());
+        for alive in self.alive.iter_mut() {
+            *alive = false;
+        }
+        self.live = 0;
+    }
+
+    fn get(&self, index: u32) -> &T {
+        self.check(index);
+        &self.slots[index as usize]
+    }
+
+    fn get_mut(&mut self, index: u32) -> &mut T {
+        self.check(index);
+        &mut self.slots[index as usize]
+    }
+}
+
+// Preallocated `Vector3` and `Matrix4` slots addressed by index, so no js object is created per value.
+// `reset` frees every slot at once, such as at the end of a frame, the indices are invalid after it.
+// Work on the slots with the operations between indices and read them with the views, a freed index panics.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct MathArena {
+    vector3: Pool<Vector3>,
+    matrix4: Pool<Matrix4>,
+    peak: u32,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl MathArena {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(vector3Capacity: usize, matrix4Capacity: usize) -> MathArena {
+        MathArena {
+            vector3: Pool::new(vector3Capacity),
+            matrix4: Pool::new(matrix4Capacity),
+            peak: 0,
+        }
+    }
+
+    pub fn allocVector3(&mut self) -> u32 {
+        let index = self.vector3.alloc(Vector3::create());
+        self.updatePeak();
+        index
+    }
+
+    pub fn allocMatrix4(&mut self) -> u32 {
+        let index = self.matrix4.alloc(Matrix4::create());
+        self.updatePeak();
+        index
+    }
+
+    pub fn freeVector3(&mut self, index: u32) {
+        self.vector3.free(index);
+    }
+
+    pub fn freeMatrix4(&mut self, index: u32) {
+        self.matrix4.free(index);
+    }
+
+    pub fn reset(&mut self) {
+        self.vector3.reset();
+        self.matrix4.reset();
+    }
+
+    // Copies the slot out. In js every call allocates a new `Vector3` in wasm memory and a wrapper that
+    // must be `free()`d, which is what the arena avoids, so use `vector3View` outside of debugging.
+    pub fn getVector3(&self, index: u32) -> Vector3 {
+        *self.vector3.get(index)
+    }
+
+    pub fn setVector3(&mut self, index: u32, a: &Vector3) {
+        Vector3::copy(self.vector3.get_mut(index), a);
+    }
+
+    pub fn setVector3Values(&mut self, index: u32, x: f32, y: f32, z: f32) {
+        Vector3::set(self.vector3.get_mut(index), x, y, z);
+    }
+
+    // Same cost as `getVector3`, use `matrix4View` instead.
+    pub fn getMatrix4(&self, index: u32) -> Matrix4 {
+        *self.matrix4.get(index)
+    }
+
+    pub fn setMatrix4(&mut self, index: u32, a: &Matrix4) {
+        Matrix4::copy(self.matrix4.get_mut(index), a);
+    }
+
+    pub fn identityMatrix4(&mut self, index: u32) {
+        Matrix4::identity(self.matrix4.get_mut(index));
+    }
+
+    // `Matrix4::multiply` between slots, `out` may be `a` or `b`.
+    pub fn multiplyMatrix4(&mut self, out: u32, a: u32, b: u32) {
+        let a = *self.matrix4.get(a);
+        let b = *self.matrix4.get(b);
+        Matrix4::multiply(self.matrix4.get_mut(out), &a, &b);
+    }
+
+    // `Matrix4::invert` between slots, `out` is left untouched and false is returned when `a` is singular.
+    pub fn invertMatrix4(&mut self, out: u32, a: u32) -> bool {
+        let a = *self.matrix4.get(a);
+        Matrix4::tryInvert(self.matrix4.get_mut(out), &a, 0.)
+    }
+
+    // `Matrix4::translate` by the vector slot `v`.
+    pub fn translateMatrix4(&mut self, out: u32, a: u32, v: u32) {
+        let a = *self.matrix4.get(a);
+        let v = *self.vector3.get(v);
+        Matrix4::translate(self.matrix4.get_mut(out), &a, &v);
+    }
+
+    // `Vector3::transformMat4` between slots.
+    pub fn transformVector3(&mut self, out: u32, a: u32, m: u32) {
+        let a = *self.vector3.get(a);
+        let m = *self.matrix4.get(m);
+        Vector3::transformMat4(self.vector3.get_mut(out), &a, &m);
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn liveVector3(&self) -> u32 {
+        self.vector3.live
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn peakVector3(&self) -> u32 {
+        self.vector3.peak
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn liveMatrix4(&self) -> u32 {
+        self.matrix4.live
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn peakMatrix4(&self) -> u32 {
+        self.matrix4.peak
+    }
+
+    // Live slots of every type, a count that keeps growing across frames means a leak.
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn liveCount(&self) -> u32 {
+        self.vector3.live + self.matrix4.live
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn peakCount(&self) -> u32 {
+        self.peak
+    }
+}
+
+impl MathArena {
+    pub fn vector3(&self, index: u32) -> &Vector3 {
+        self.vector3.get(index)
+    }
+
+    pub fn vector3Mut(&mut self, index: u32) -> &mut Vector3 {
+        self.vector3.get_mut(index)
+    }
+
+    pub fn matrix4(&self, index: u32) -> &Matrix4 {
+        self.matrix4.get(index)
+    }
+
+    pub fn matrix4Mut(&mut self, index: u32) -> &mut Matrix4 {
+        self.matrix4.get_mut(index)
+    }
+
+    fn updatePeak(&mut self) {
+        self.peak = u32::max(self.peak, self.liveCount());
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl MathArena {
+    // Same as `Vector3::view`, detached when the storage or the wasm memory grows.
+    pub fn vector3View(&self, index: u32) -> Float32Array {
+        self.vector3.get(index).view()
+    }
+
+    pub fn matrix4View(&self, index: u32) -> Float32Array {
+        self.matrix4.get(index).view()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spec_helper::*;
+
+    #[test]
+    fn alloc() {
+        let arena = &mut MathArena::new(2, 2);
+        let a = arena.allocVector3();
+        let b = arena.allocVector3();
+        let m = arena.allocMatrix4();
+        assert_ne!(a, b);
+        assert_eq!(arena.getVector3(a), Vector3::create());
+        assert_eq!(arena.getMatrix4(m), Matrix4::create());
+        assert_eq!(arena.liveVector3(), 2);
+        assert_eq!(arena.liveMatrix4(), 1);
+        assert_eq!(arena.liveCount(), 3);
+    }
+
+    #[test]
+    fn free() {
+        let arena = &mut MathArena::new(2, 0);
+        let a = arena.allocVector3();
+        let b = arena.allocVector3();
+        arena.setVector3(a, &Vector3::fromValues(1., 2., 3.));
+        arena.freeVector3(a);
+        assert_eq!(arena.liveVector3(), 1);
+        assert_eq!(arena.peakVector3(), 2);
+
+        let c = arena.allocVector3();
+        assert_eq!(c, a);
+        assert_eq!(arena.getVector3(c), Vector3::create());
+        assert_eq!(arena.peakVector3(), 2);
+        arena.freeVector3(b);
+        arena.freeVector3(c);
+        assert_eq!(arena.liveCount(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn freeTwice() {
+        let arena = &mut MathArena::new(1, 0);
+        let a = arena.allocVector3();
+        arena.freeVector3(a);
+        arena.freeVector3(a);
+    }
+
+    #[test]
+    fn reset() {
+        let arena = &mut MathArena::new(0, 0);
+        for _ in 0..3 {
+            arena.allocVector3();
+            arena.allocMatrix4();
+        }
+        arena.reset();
+        assert_eq!(arena.liveCount(), 0);
+        assert_eq!(arena.peakCount(), 6);
+        assert_eq!(arena.peakMatrix4(), 3);
+
+        arena.allocMatrix4();
+        assert_eq!(arena.liveMatrix4(), 1);
+        assert_eq!(arena.peakCount(), 6);
+    }
+
+    #[test]
+    fn slots() {
+        let arena = &mut MathArena::new(1, 2);
+        let v = arena.allocVector3();
+        let a = arena.allocMatrix4();
+        let b = arena.allocMatrix4();
+        Matrix4::fromTranslation(arena.matrix4Mut(a), &Vector3::fromValues(1., 2., 3.));
+        arena.setMatrix4(
+            b,
+            &Matrix4::fromValues(
+                1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 4., 5., 6., 1.,
+            ),
+        );
+        arena.multiplyMatrix4(a, a, b);
+        expect_equalish(
+            &arena.matrix4(a).elements(),
+            &[
+                1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 5., 7., 9., 1.,
+            ],
+        );
+
+        Vector3::set(arena.vector3Mut(v), 1., 1., 1.);
+        arena.transformVector3(v, v, a);
+        expect_equalish(&arena.vector3(v).elements(), &[6., 8., 10.]);
+
+        arena.setVector3Values(v, -5., -7., -9.);
+        arena.translateMatrix4(b, a, v);
+        expect_equalish(&arena.matrix4(b).elements(), &Matrix4::create().elements());
+        assert!(arena.invertMatrix4(b, a));
+        arena.multiplyMatrix4(b, b, a);
+        expect_equalish(&arena.matrix4(b).elements(), &Matrix4::create().elements());
+        arena.setMatrix4(
+            b,
+            &Matrix4::fromValues(
+                0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            ),
+        );
+        arena.identityMatrix4(a);
+        assert!(!arena.invertMatrix4(a, b));
+        expect_equalish(&arena.matrix4(a).elements(), &Matrix4::create().elements());
+    }
+
+    #[test]
+    #[should_panic(expected = "slot 0 is not allocated")]
+    fn getFreed() {
+        let arena = &mut MathArena::new(1, 0);
+        let a = arena.allocVector3();
+        arena.freeVector3(a);
+        arena.vector3(a);
+    }
+
+    #[test]
+    #[should_panic(expected = "slot 1 is not allocated")]
+    fn setUnallocated() {
+        let arena = &mut MathArena::new(0, 1);
+        arena.allocMatrix4();
+        arena.identityMatrix4(1);
+    }
+}
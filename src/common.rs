@@ -4,22 +4,552 @@
  * @Link   : http://dtysky.moe
  * @Date   : 2019/2/7 下午9:53:03
  */
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
+use core::fmt;
+use core::ops::{Add, Div, Mul, Neg, Sub};
+use core::str::FromStr;
+use core::sync::atomic::{AtomicU32, AtomicU64, AtomicU8, Ordering};
+
+// The `alloc` part of the std prelude, every module imports it with `common::*`.
+#[cfg(not(feature = "std"))]
+pub(crate) use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
 pub static EPSILON: f32 = 0.0001;
 
-pub static PI: f32 = 3.141592653589793;
+pub static PI: f32 = core::f32::consts::PI;
 
 pub static INFINITY: f32 = 1.0_f32 / 0.0_f32;
 
 pub static NEG_INFINITY: f32 = -1.0_f32 / 0.0_f32;
 
+// How `equals` compares elements, within a relative epsilon as gl-matrix or within units in the last place.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EqualsMode {
+    Epsilon = 0,
+    Ulps = 1,
+}
+
+// Order of the rotations of Euler angles, the angles are always about the x, y and z axes.
+// Intrinsic orders rotate about the axes of the rotated frame, `YXZ` is the matrix `Ry * Rx * Rz`.
+// Extrinsic orders rotate about the fixed axes, `ExtrinsicXYZ` is the same rotation as `ZYX`.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EulerOrder {
+    XYZ = 0,
+    XZY = 1,
+    YXZ = 2,
+    YZX = 3,
+    ZXY = 4,
+    ZYX = 5,
+    ExtrinsicXYZ = 6,
+    ExtrinsicXZY = 7,
+    ExtrinsicYXZ = 8,
+    ExtrinsicYZX = 9,
+    ExtrinsicZXY = 10,
+    ExtrinsicZYX = 11,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AngleUnit {
+    Radians = 0,
+    Degrees = 1,
+}
+
+static EPSILON_BITS: AtomicU32 = AtomicU32::new(0x38D1_B717);
+
+static MAX_ULPS: AtomicU32 = AtomicU32::new(4);
+
+static EQUALS_MODE: AtomicU8 = AtomicU8::new(EqualsMode::Epsilon as u8);
+
+// Tolerance of `equals` and of the singular check in `invert`, `EPSILON` by default.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn setEpsilon(epsilon: f32) {
+    EPSILON_BITS.store(epsilon.to_bits(), Ordering::Relaxed);
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn getEpsilon() -> f32 {
+    f32::from_bits(EPSILON_BITS.load(Ordering::Relaxed))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn setMaxUlps(maxUlps: u32) {
+    MAX_ULPS.store(maxUlps, Ordering::Relaxed);
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn getMaxUlps() -> u32 {
+    MAX_ULPS.load(Ordering::Relaxed)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn setEqualsMode(mode: EqualsMode) {
+    EQUALS_MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn getEqualsMode() -> EqualsMode {
+    match EQUALS_MODE.load(Ordering::Relaxed) {
+        1 => EqualsMode::Ulps,
+        _ => EqualsMode::Epsilon,
+    }
+}
+
+// Element type of the math types, f32 for the default types and f64 for the `double` module.
+pub trait Scalar:
+    Copy
+    + PartialOrd
+    + FromStr
+    + From<f32>
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    fn abs(self) -> Self;
+    fn min(self, b: Self) -> Self;
+    fn max(self, b: Self) -> Self;
+    fn ceil(self) -> Self;
+    fn floor(self) -> Self;
+    fn round(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn isNan(self) -> bool;
+    fn isSignNegative(self) -> bool;
+    // Distance between the bit patterns, the count of floats between two values of the same sign.
+    fn bitsDistance(self, b: Self) -> u64;
+}
+
+// `ceil`, `floor`, `round` and `sqrt` come from libm without std, they are exact in both.
+macro_rules! impl_scalar {
+    ($f:ident, $ceil:ident, $floor:ident, $round:ident, $sqrt:ident) => {
+        impl Scalar for $f {
+            fn abs(self) -> $f {
+                $f::abs(self)
+            }
+
+            fn min(self, b: $f) -> $f {
+                $f::min(self, b)
+            }
+
+            fn max(self, b: $f) -> $f {
+                $f::max(self, b)
+            }
+
+            fn ceil(self) -> $f {
+                #[cfg(feature = "std")]
+                return $f::ceil(self);
+                #[cfg(not(feature = "std"))]
+                return libm::$ceil(self);
+            }
+
+            fn floor(self) -> $f {
+                #[cfg(feature = "std")]
+                return $f::floor(self);
+                #[cfg(not(feature = "std"))]
+                return libm::$floor(self);
+            }
+
+            fn round(self) -> $f {
+                #[cfg(feature = "std")]
+                return $f::round(self);
+                #[cfg(not(feature = "std"))]
+                return libm::$round(self);
+            }
+
+            fn sqrt(self) -> $f {
+                #[cfg(feature = "std")]
+                return $f::sqrt(self);
+                #[cfg(not(feature = "std"))]
+                return libm::$sqrt(self);
+            }
+
+            fn isNan(self) -> bool {
+                self.is_nan()
+            }
+
+            fn isSignNegative(self) -> bool {
+                self.is_sign_negative()
+            }
+
+            fn bitsDistance(self, b: $f) -> u64 {
+                let a = self.to_bits();
+                let b = b.to_bits();
+                (if a > b { a - b } else { b - a }) as u64
+            }
+        }
+    };
+}
+
+impl_scalar!(f32, ceilf, floorf, roundf, sqrtf);
+impl_scalar!(f64, ceil, floor, round, sqrt);
+
+// Keeps its tokens in the f32 version of a module only, such as the dispatch to the f32 simd kernels.
+macro_rules! f32_only {
+    (f32, $($tokens:tt)*) => {
+        $($tokens)*
+    };
+    (f64, $($tokens:tt)*) => {};
+}
+
+pub(crate) use f32_only;
+
+// The other float functions std adds to f32 and f64, from libm without std. The inherent methods win
+// when they exist, so `f32::sin(x)` compiles the same with and without std. They also exist when
+// another crate of the build links std, leaving the trait unused.
+#[cfg(not(any(feature = "std", test)))]
+#[allow(dead_code)]
+pub(crate) trait Float: Sized {
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn tan(self) -> Self;
+    fn asin(self) -> Self;
+    fn acos(self) -> Self;
+    fn atan2(self, b: Self) -> Self;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+    fn hypot(self, b: Self) -> Self;
+    fn powi(self, n: i32) -> Self;
+}
+
+#[cfg(not(any(feature = "std", test)))]
+macro_rules! impl_float {
+    ($f:ident, $sin:ident, $cos:ident, $tan:ident, $asin:ident, $acos:ident, $atan2:ident, $exp:ident, $ln:ident, $hypot:ident) => {
+        impl Float for $f {
+            fn sin(self) -> $f {
+                libm::$sin(self)
+            }
+
+            fn cos(self) -> $f {
+                libm::$cos(self)
+            }
+
+            fn tan(self) -> $f {
+                libm::$tan(self)
+            }
+
+            fn asin(self) -> $f {
+                libm::$asin(self)
+            }
+
+            fn acos(self) -> $f {
+                libm::$acos(self)
+            }
+
+            fn atan2(self, b: $f) -> $f {
+                libm::$atan2(self, b)
+            }
+
+            fn exp(self) -> $f {
+                libm::$exp(self)
+            }
+
+            fn ln(self) -> $f {
+                libm::$ln(self)
+            }
+
+            fn hypot(self, b: $f) -> $f {
+                libm::$hypot(self, b)
+            }
+
+            // Square-and-multiply as the compiler builtin of std's `powi`.
+            fn powi(self, n: i32) -> $f {
+                let mut a = self;
+                let mut b = n.unsigned_abs();
+                let mut r = 1.;
+                loop {
+                    if b & 1 != 0 {
+                        r *= a;
+                    }
+                    b /= 2;
+                    if b == 0 {
+                        break;
+                    }
+                    a *= a;
+                }
+                if n < 0 {
+                    1. / r
+                } else {
+                    r
+                }
+            }
+        }
+    };
+}
+
+#[cfg(not(any(feature = "std", test)))]
+impl_float!(f32, sinf, cosf, tanf, asinf, acosf, atan2f, expf, logf, hypotf);
+#[cfg(not(any(feature = "std", test)))]
+impl_float!(f64, sin, cos, tan, asin, acos, atan2, exp, log, hypot);
+
+// Same as gl-matrix's `equals` on a single element.
+pub fn equalsEpsilon<T: Scalar>(a: T, b: T, epsilon: T) -> bool {
+    Scalar::abs(a - b)
+        <= epsilon * Scalar::max(T::from(1.0), Scalar::max(Scalar::abs(a), Scalar::abs(b)))
+}
+
+// Equal when at most `maxUlps` representable floats lie between them, NaN is never equal.
+pub fn equalsUlps<T: Scalar>(a: T, b: T, maxUlps: u32) -> bool {
+    if a == b {
+        return true;
+    }
+    if a.isNan() || b.isNan() || a.isSignNegative() != b.isSignNegative() {
+        return false;
+    }
+    a.bitsDistance(b) <= maxUlps as u64
+}
+
+// Compares with the current mode, epsilon and max ulps.
+pub fn equalsScalar<T: Scalar>(a: T, b: T) -> bool {
+    match getEqualsMode() {
+        EqualsMode::Epsilon => equalsEpsilon(a, b, T::from(getEpsilon())),
+        EqualsMode::Ulps => equalsUlps(a, b, getMaxUlps()),
+    }
+}
+
+// PCG32 (XSH RR) generator, the same seed always gives the same sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pcg32 {
+    state: u64,
+}
+
+const PCG_MULTIPLIER: u64 = 6364136223846793005;
+
+const PCG_INCREMENT: u64 = 1442695040888963407;
+
+const DEFAULT_SEED: u32 = 0x9E37_79B9;
+
+impl Pcg32 {
+    pub const fn new(seed: u32) -> Self {
+        let state = Pcg32::step(0).wrapping_add(seed as u64);
+        Pcg32 {
+            state: Pcg32::step(state),
+        }
+    }
+
+    const fn step(state: u64) -> u64 {
+        state
+            .wrapping_mul(PCG_MULTIPLIER)
+            .wrapping_add(PCG_INCREMENT)
+    }
+
+    fn output(state: u64) -> u32 {
+        let xorshifted = (((state >> 18) ^ state) >> 27) as u32;
+        xorshifted.rotate_right((state >> 59) as u32)
+    }
+
+    pub fn nextU32(&mut self) -> u32 {
+        let state = self.state;
+        self.state = Pcg32::step(state);
+        Pcg32::output(state)
+    }
+
+    // Uniform in [0, 1).
+    pub fn nextF32(&mut self) -> f32 {
+        (self.nextU32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    // Uniform in [0, 1), with the 53 bits of a f64 from two outputs.
+    pub fn nextF64(&mut self) -> f64 {
+        let high = u64::from(self.nextU32() >> 5);
+        let low = u64::from(self.nextU32() >> 6);
+        ((high << 26) | low) as f64 / (1u64 << 53) as f64
+    }
+}
+
+static RANDOM_STATE: AtomicU64 = AtomicU64::new(Pcg32::new(DEFAULT_SEED).state);
+
+// Restarts the sequence of `RANDOM`, which is used by every `random` function.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn setSeed(seed: u32) {
+    RANDOM_STATE.store(Pcg32::new(seed).state, Ordering::Relaxed);
+}
+
+#[cfg(all(feature = "js-random", target_arch = "wasm32"))]
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = Math)]
     fn random() -> f32;
+
+    #[wasm_bindgen(js_namespace = Math, js_name = random)]
+    fn random64() -> f64;
 }
 
+// `Math.random` of the js host, `setSeed` has no effect on it.
+#[cfg(all(feature = "js-random", target_arch = "wasm32"))]
 pub fn RANDOM() -> f32 {
     random()
 }
+
+#[cfg(not(all(feature = "js-random", target_arch = "wasm32")))]
+pub fn RANDOM() -> f32 {
+    let state = RANDOM_STATE
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |state| {
+            Some(Pcg32::step(state))
+        })
+        .unwrap();
+    Pcg32 { state }.nextF32()
+}
+
+// `RANDOM` with all the bits of a f64, for the `double` types.
+#[cfg(all(feature = "js-random", target_arch = "wasm32"))]
+pub fn RANDOM64() -> f64 {
+    random64()
+}
+
+#[cfg(not(all(feature = "js-random", target_arch = "wasm32")))]
+pub fn RANDOM64() -> f64 {
+    let state = RANDOM_STATE
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |state| {
+            Some(Pcg32::step(Pcg32::step(state)))
+        })
+        .unwrap();
+    Pcg32 { state }.nextF64()
+}
+
+// Comparison of `equals`, with the mode and tolerance set by `setEqualsMode` and `setEpsilon`.
+pub trait ApproxEq {
+    fn approx_eq(&self, other: &Self) -> bool;
+}
+
+// Error of reading back the `str` format, such as `vec3(1, 2, 3)`, or of an array that is too short.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    Prefix(&'static str),
+    Length(usize, usize),
+    Number(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Prefix(prefix) => write!(f, "expected `{}(...)`", prefix),
+            ParseError::Length(expected, found) => {
+                write!(f, "expected {} elements, found {}", expected, found)
+            }
+            ParseError::Number(s) => write!(f, "invalid number `{}`", s),
+        }
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+// A `RangeError` in js.
+#[cfg(feature = "wasm")]
+impl From<ParseError> for JsValue {
+    fn from(e: ParseError) -> JsValue {
+        js_sys::RangeError::new(&e.to_string()).into()
+    }
+}
+
+// The array readers, such as `Matrix4.fromFloat32Array`, need at least `expected` elements.
+pub fn check_length(expected: usize, found: usize) -> Result<(), ParseError> {
+    if found < expected {
+        return Err(ParseError::Length(expected, found));
+    }
+    Ok(())
+}
+
+// Reads `prefix(a, b, ...)` into `out`, the count of elements must match its length.
+pub fn parse_values<T: FromStr>(
+    s: &str,
+    prefix: &'static str,
+    out: &mut [T],
+) -> Result<(), ParseError> {
+    let body = s
+        .trim()
+        .strip_prefix(prefix)
+        .map(str::trim_start)
+        .and_then(|s| s.strip_prefix('('))
+        .and_then(|s| s.strip_suffix(')'))
+        .ok_or(ParseError::Prefix(prefix))?;
+    let values: Vec<&str> = body.split(',').map(str::trim).collect();
+    if values.len() != out.len() {
+        return Err(ParseError::Length(out.len(), values.len()));
+    }
+    for (o, v) in out.iter_mut().zip(values) {
+        *o = v.parse().map_err(|_| ParseError::Number(v.to_string()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spec_helper::lock_random;
+
+    #[test]
+    fn pcg32() {
+        let mut a = Pcg32::new(7);
+        let mut b = Pcg32::new(7);
+        let mut c = Pcg32::new(8);
+        for _ in 0..100 {
+            let value = a.nextF32();
+            assert!((0. ..1.).contains(&value));
+            assert_eq!(value, b.nextF32());
+        }
+        assert_ne!(a.nextU32(), c.nextU32());
+
+        // The f64 values have more than the 24 bits of a f32.
+        let value = a.nextF64();
+        assert!((0. ..1.).contains(&value));
+        assert_ne!(value, f64::from(value as f32));
+    }
+
+    #[test]
+    fn setSeed() {
+        let _lock = lock_random();
+        super::setSeed(7);
+        let values = [RANDOM(), RANDOM(), RANDOM()];
+        let mut rng = Pcg32::new(7);
+        assert_eq!(values, [rng.nextF32(), rng.nextF32(), rng.nextF32()]);
+
+        super::setSeed(7);
+        let values = [RANDOM64(), RANDOM64()];
+        let mut rng = Pcg32::new(7);
+        assert_eq!(values, [rng.nextF64(), rng.nextF64()]);
+    }
+
+    #[test]
+    fn check_length() {
+        assert_eq!(super::check_length(3, 3), Ok(()));
+        assert_eq!(super::check_length(3, 16), Ok(()));
+        let e = super::check_length(16, 4).unwrap_err();
+        assert_eq!(e, ParseError::Length(16, 4));
+        assert_eq!(e.to_string(), "expected 16 elements, found 4");
+    }
+
+    #[test]
+    fn equalsUlps() {
+        let one_up = f32::from_bits(1f32.to_bits() + 1);
+        assert!(super::equalsUlps(1., one_up, 1));
+        assert!(!super::equalsUlps(1., one_up, 0));
+        assert!(super::equalsUlps(0., -0., 0));
+        assert!(!super::equalsUlps(f32::MIN_POSITIVE, -f32::MIN_POSITIVE, 4));
+        assert!(!super::equalsUlps(f32::NAN, f32::NAN, 4));
+    }
+
+    // The setters change process-wide state that every other test reads, they are covered by
+    // tests/settings.rs, which runs in its own process.
+    #[test]
+    fn settings() {
+        assert_eq!(getEpsilon(), EPSILON);
+        assert_eq!(getMaxUlps(), 4);
+        assert_eq!(getEqualsMode(), EqualsMode::Epsilon);
+        assert!(equalsScalar(1., 1.00001));
+
+        assert!(!equalsEpsilon(1., 1.0005, EPSILON));
+        assert!(equalsEpsilon(1., 1.0005, 0.001));
+        assert!(!super::equalsUlps(1., 1.00001, 4));
+        assert!(super::equalsUlps(1., f32::from_bits(1f32.to_bits() + 4), 4));
+    }
+}
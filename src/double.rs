@@ -0,0 +1,177 @@
+/**
+ * @File   : double.rs
+ * @Author : dtysky (dtysky@outlook.com)
+ * @Link   : http://dtysky.moe
+ * @Date   : 2019/2/7 下午9:53:03
+ */
+// f64 versions of every type, such as `Matrix4d` and `Vector3d`, with the same functions as the f32 ones
+// and `Float64Array` elements in js. Each module is the f32 one's `define_*` macro with the f64 names.
+// `Matrix2d` is already the f32 2x3 matrix, so the f64 matrices of 2 columns are `Matrix2x2d` and `Matrix2x3d`.
+// `common` is the f64 side of `crate::common`, the modules below find it as `super::common` where the
+// f32 ones find `crate::common`. The settings are shared with the f32 types.
+mod common {
+    pub use crate::common::*;
+
+    pub const EPSILON: f64 = 0.0001;
+
+    pub const PI: f64 = core::f64::consts::PI;
+
+    pub const INFINITY: f64 = f64::INFINITY;
+
+    pub const NEG_INFINITY: f64 = f64::NEG_INFINITY;
+
+    pub fn getEpsilon() -> f64 {
+        f64::from(crate::common::getEpsilon())
+    }
+
+    pub fn RANDOM() -> f64 {
+        crate::common::RANDOM64()
+    }
+}
+
+// Invokes the `define_*` macro of a f32 module with the f64 names.
+macro_rules! define_double {
+    ($module:ident, $define:ident) => {
+        crate::$module::$define!(
+            f64,
+            Float64Array,
+            fromFloat64Array,
+            Matrix2x2d,
+            Matrix2x3d,
+            Matrix3d,
+            Matrix4d,
+            Quaterniond,
+            Quaternion2d,
+            Vector2d,
+            Vector3d,
+            Vector4d
+        );
+    };
+}
+
+pub mod matrix2 {
+    define_double!(matrix2, define_matrix2);
+}
+
+pub mod matrix2d {
+    define_double!(matrix2d, define_matrix2d);
+}
+
+pub mod matrix3 {
+    define_double!(matrix3, define_matrix3);
+}
+
+pub mod matrix4 {
+    use crate::matrix4::FieldOfView;
+    #[cfg(feature = "wasm")]
+    use crate::matrix4::FieldOfViewLike;
+
+    define_double!(matrix4, define_matrix4);
+}
+
+mod ops {
+    use core::ops::{Add, AddAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign};
+
+    use crate::ops::{
+        impl_assign_op, impl_binary_op, impl_index, impl_matrix_ops, impl_quaternion_ops,
+        impl_scalar_mul, impl_vector_ops,
+    };
+
+    define_double!(ops, define_ops);
+}
+
+pub mod quaternion {
+    define_double!(quaternion, define_quaternion);
+}
+
+pub mod quaternion2 {
+    define_double!(quaternion2, define_quaternion2);
+}
+
+pub mod vector2 {
+    define_double!(vector2, define_vector2);
+}
+
+pub mod vector3 {
+    define_double!(vector3, define_vector3);
+}
+
+pub mod vector4 {
+    define_double!(vector4, define_vector4);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matrix4::*;
+    use super::quaternion::*;
+    use super::vector3::*;
+
+    #[test]
+    fn precision() {
+        // Too far from the origin for f32 to keep the centimeters.
+        let out = &mut Vector3d::create();
+        let m = &mut Matrix4d::create();
+        Matrix4d::fromTranslation(m, &Vector3d::fromValues(1e7, 0., -1e7));
+        Vector3d::transformMat4(out, &Vector3d::fromValues(0.01, 0.02, 0.03), m);
+        assert_eq!(out.elements()[..], [1e7 + 0.01, 0.02, -1e7 + 0.03]);
+
+        let f = &mut crate::vector3::Vector3::create();
+        crate::vector3::Vector3::add(
+            f,
+            &crate::vector3::Vector3::fromValues(1e7, 0., -1e7),
+            &crate::vector3::Vector3::fromValues(0.01, 0.02, 0.03),
+        );
+        assert_eq!(f.0, 1e7);
+    }
+
+    #[test]
+    fn invert() {
+        let out = &mut Matrix4d::create();
+        let m = &Matrix4d::fromValues(
+            1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+        );
+        assert!(Matrix4d::invert(out, m));
+        assert_eq!(
+            out.elements()[..],
+            [1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., -1., -2., -3., 1.]
+        );
+        assert!(!Matrix4d::invert(
+            out,
+            &Matrix4d::fromValues(1., 2., 3., 4., 2., 4., 6., 8., 0., 0., 1., 0., 0., 0., 0., 1.,)
+        ));
+    }
+
+    #[test]
+    fn slerp() {
+        let out = &mut Quaterniond::create();
+        Quaterniond::slerp(
+            out,
+            &Quaterniond::fromValues(0., 0., 0., 1.),
+            &Quaterniond::fromValues(0., 1., 0., 0.),
+            0.5,
+        );
+        let s = std::f64::consts::FRAC_1_SQRT_2;
+        assert!(Quaterniond::equalsWithUlps(
+            out,
+            &Quaterniond::fromValues(0., s, 0., s),
+            4
+        ));
+    }
+
+    #[test]
+    fn strings() {
+        let a = Vector3d::fromValues(1., 0.1, 3.);
+        assert_eq!(a.to_string(), "vec3(1, 0.1, 3)");
+        let b: Vector3d = "vec3(1, 0.1, 3)".parse().unwrap();
+        assert!(Vector3d::exactEquals(&a, &b));
+    }
+
+    #[test]
+    fn operators() {
+        let a = Vector3d::fromValues(1., 2., 3.);
+        let b = Vector3d::fromValues(4., 5., 6.);
+        assert_eq!((a + b).elements()[..], [5., 7., 9.]);
+        assert_eq!((b - a).elements()[..], [3., 3., 3.]);
+        assert_eq!((a * 2.).elements()[..], [2., 4., 6.]);
+    }
+}
@@ -0,0 +1,167 @@
+/**
+ * @File   : interop.rs
+ * @Author : dtysky (dtysky@outlook.com)
+ * @Link   : http://dtysky.moe
+ * @Date   : 2019/2/7 下午9:53:03
+ */
+// Impls of the `bytemuck` and `mint` features. Every type is `#[repr(C)]` with only its elements,
+// so it can be cast to bytes for GPU buffers or viewed as the mint type of the same layout.
+use super::double::matrix2::*;
+use super::double::matrix2d::*;
+use super::double::matrix3::*;
+use super::double::matrix4::*;
+use super::double::quaternion::*;
+#[cfg(feature = "bytemuck")]
+use super::double::quaternion2::*;
+use super::double::vector2::*;
+use super::double::vector3::*;
+use super::double::vector4::*;
+#[cfg(feature = "mint")]
+use super::kernels::Elements;
+use super::matrix2::*;
+use super::matrix2d::*;
+use super::matrix3::*;
+use super::matrix4::*;
+use super::quaternion::*;
+#[cfg(feature = "bytemuck")]
+use super::quaternion2::*;
+use super::vector2::*;
+use super::vector3::*;
+use super::vector4::*;
+
+#[cfg(feature = "bytemuck")]
+macro_rules! impl_pod {
+    ($($t:ident),*) => {$(
+        unsafe impl bytemuck::Zeroable for $t {}
+        unsafe impl bytemuck::Pod for $t {}
+    )*};
+}
+
+#[cfg(feature = "bytemuck")]
+impl_pod!(
+    Vector2,
+    Vector3,
+    Vector4,
+    Quaternion,
+    Quaternion2,
+    Matrix2,
+    Matrix2d,
+    Matrix3,
+    Matrix4,
+    Vector2d,
+    Vector3d,
+    Vector4d,
+    Quaterniond,
+    Quaternion2d,
+    Matrix2x2d,
+    Matrix2x3d,
+    Matrix3d,
+    Matrix4d
+);
+
+// Matrices are column-major as the mint `ColumnMatrix` types, `Matrix2d` is the 2x3 one.
+#[cfg(feature = "mint")]
+macro_rules! impl_mint {
+    ($($t:ident: $m:ident<$f:ident>, $n:expr;)*) => {$(
+        const _: () = assert!(core::mem::size_of::<$t>() == core::mem::size_of::<mint::$m<$f>>());
+
+        impl From<mint::$m<$f>> for $t {
+            fn from(m: mint::$m<$f>) -> $t {
+                let elements: [$f; $n] = m.into();
+                $t::fromSlice(&elements)
+            }
+        }
+
+        impl From<$t> for mint::$m<$f> {
+            fn from(a: $t) -> mint::$m<$f> {
+                mint::$m::from(*a.array())
+            }
+        }
+
+        impl mint::IntoMint for $t {
+            type MintType = mint::$m<$f>;
+        }
+
+        impl AsRef<mint::$m<$f>> for $t {
+            fn as_ref(&self) -> &mint::$m<$f> {
+                unsafe { &*(self as *const $t).cast::<mint::$m<$f>>() }
+            }
+        }
+
+        impl AsMut<mint::$m<$f>> for $t {
+            fn as_mut(&mut self) -> &mut mint::$m<$f> {
+                unsafe { &mut *(self as *mut $t).cast::<mint::$m<$f>>() }
+            }
+        }
+    )*};
+}
+
+#[cfg(feature = "mint")]
+impl_mint! {
+    Vector2: Vector2<f32>, 2;
+    Vector3: Vector3<f32>, 3;
+    Vector4: Vector4<f32>, 4;
+    Quaternion: Quaternion<f32>, 4;
+    Matrix2: ColumnMatrix2<f32>, 4;
+    Matrix2d: ColumnMatrix2x3<f32>, 6;
+    Matrix3: ColumnMatrix3<f32>, 9;
+    Matrix4: ColumnMatrix4<f32>, 16;
+    Vector2d: Vector2<f64>, 2;
+    Vector3d: Vector3<f64>, 3;
+    Vector4d: Vector4<f64>, 4;
+    Quaterniond: Quaternion<f64>, 4;
+    Matrix2x2d: ColumnMatrix2<f64>, 4;
+    Matrix2x3d: ColumnMatrix2x3<f64>, 6;
+    Matrix3d: ColumnMatrix3<f64>, 9;
+    Matrix4d: ColumnMatrix4<f64>, 16;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn pod() {
+        let matrices = &mut [Matrix4::create(); 2];
+        Matrix4::fromTranslation(&mut matrices[1], &Vector3::fromValues(1., 2., 3.));
+        let elements: &[f32] = bytemuck::cast_slice(matrices);
+        assert_eq!(elements.len(), 32);
+        assert_eq!(elements[28..31], [1., 2., 3.]);
+        assert_eq!(bytemuck::bytes_of(&Vector3d::create()).len(), 24);
+
+        let v: &Vector4 = bytemuck::from_bytes(bytemuck::bytes_of(&[1f32, 2., 3., 4.]));
+        assert_eq!(*v, Vector4::fromValues(1., 2., 3., 4.));
+        assert_eq!(
+            <Quaternion as bytemuck::Zeroable>::zeroed(),
+            Quaternion::fromValues(0., 0., 0., 0.)
+        );
+    }
+
+    #[cfg(feature = "mint")]
+    #[test]
+    fn mint() {
+        let m = &mut Matrix4::create();
+        Matrix4::fromTranslation(m, &Vector3::fromValues(1., 2., 3.));
+        let c: mint::ColumnMatrix4<f32> = (*m).into();
+        assert_eq!(c.w, mint::Vector4::from([1., 2., 3., 1.]));
+        assert_eq!(Matrix4::from(c), *m);
+        let r: &mint::ColumnMatrix4<f32> = m.as_ref();
+        assert_eq!(*r, c);
+
+        let q = Quaternion::fromValues(1., 2., 3., 4.);
+        let mq = mint::Quaternion::from(q);
+        assert_eq!(mq.v, mint::Vector3::from([1., 2., 3.]));
+        assert_eq!(mq.s, 4.);
+        assert_eq!(Quaternion::from(mq), q);
+
+        let v = &mut Vector3d::fromValues(1., 2., 3.);
+        AsMut::<mint::Vector3<f64>>::as_mut(v).z = 4.;
+        assert_eq!(*v, Vector3d::fromValues(1., 2., 4.));
+
+        let affine = Matrix2d::fromValues(1., 2., 3., 4., 5., 6.);
+        let c = mint::ColumnMatrix2x3::from(affine);
+        assert_eq!(c.z, mint::Vector2::from([5., 6.]));
+        assert_eq!(Matrix2d::from(c), affine);
+    }
+}
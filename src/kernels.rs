@@ -0,0 +1,187 @@
+/**
+ * @File   : kernels.rs
+ * @Author : dtysky (dtysky@outlook.com)
+ * @Link   : http://dtysky.moe
+ * @Date   : 2019/2/7 下午9:53:03
+ */
+// Element-wise kernels written once for every type, dimension and precision. A type implements
+// `Elements` to be seen as its array of `N` scalars, the kernels keep the operation order of the
+// hand-written functions they replace, so the results are the same to the bit.
+use super::common::*;
+
+/// # Safety
+/// Only for `#[repr(C)]` types made of exactly `N` fields of `T`.
+pub(crate) unsafe trait Elements<T, const N: usize>: Sized {
+    // Fails to compile for a type of another size.
+    const LAYOUT: () = assert!(core::mem::size_of::<Self>() == core::mem::size_of::<[T; N]>());
+
+    fn array(&self) -> &[T; N] {
+        let () = Self::LAYOUT;
+        unsafe { &*(self as *const Self).cast::<[T; N]>() }
+    }
+
+    fn arrayMut(&mut self) -> &mut [T; N] {
+        let () = Self::LAYOUT;
+        unsafe { &mut *(self as *mut Self).cast::<[T; N]>() }
+    }
+}
+
+fn map<T: Scalar, const N: usize>(out: &mut [T; N], a: &[T; N], f: impl Fn(T) -> T) {
+    for i in 0..N {
+        out[i] = f(a[i]);
+    }
+}
+
+fn zip<T: Scalar, const N: usize>(out: &mut [T; N], a: &[T; N], b: &[T; N], f: impl Fn(T, T) -> T) {
+    for i in 0..N {
+        out[i] = f(a[i], b[i]);
+    }
+}
+
+pub(crate) fn add<T: Scalar, const N: usize>(out: &mut [T; N], a: &[T; N], b: &[T; N]) {
+    zip(out, a, b, |a, b| a + b);
+}
+
+pub(crate) fn subtract<T: Scalar, const N: usize>(out: &mut [T; N], a: &[T; N], b: &[T; N]) {
+    zip(out, a, b, |a, b| a - b);
+}
+
+pub(crate) fn multiply<T: Scalar, const N: usize>(out: &mut [T; N], a: &[T; N], b: &[T; N]) {
+    zip(out, a, b, |a, b| a * b);
+}
+
+pub(crate) fn divide<T: Scalar, const N: usize>(out: &mut [T; N], a: &[T; N], b: &[T; N]) {
+    zip(out, a, b, |a, b| a / b);
+}
+
+pub(crate) fn min<T: Scalar, const N: usize>(out: &mut [T; N], a: &[T; N], b: &[T; N]) {
+    zip(out, a, b, Scalar::min);
+}
+
+pub(crate) fn max<T: Scalar, const N: usize>(out: &mut [T; N], a: &[T; N], b: &[T; N]) {
+    zip(out, a, b, Scalar::max);
+}
+
+pub(crate) fn ceil<T: Scalar, const N: usize>(out: &mut [T; N], a: &[T; N]) {
+    map(out, a, Scalar::ceil);
+}
+
+pub(crate) fn floor<T: Scalar, const N: usize>(out: &mut [T; N], a: &[T; N]) {
+    map(out, a, Scalar::floor);
+}
+
+pub(crate) fn round<T: Scalar, const N: usize>(out: &mut [T; N], a: &[T; N]) {
+    map(out, a, Scalar::round);
+}
+
+pub(crate) fn negate<T: Scalar, const N: usize>(out: &mut [T; N], a: &[T; N]) {
+    map(out, a, |a| -a);
+}
+
+pub(crate) fn inverse<T: Scalar, const N: usize>(out: &mut [T; N], a: &[T; N]) {
+    map(out, a, |a| T::from(1.) / a);
+}
+
+pub(crate) fn scale<T: Scalar, const N: usize>(out: &mut [T; N], a: &[T; N], b: T) {
+    map(out, a, |a| a * b);
+}
+
+pub(crate) fn scaleAndAdd<T: Scalar, const N: usize>(
+    out: &mut [T; N],
+    a: &[T; N],
+    b: &[T; N],
+    scale: T,
+) {
+    zip(out, a, b, |a, b| a + (b * scale));
+}
+
+pub(crate) fn lerp<T: Scalar, const N: usize>(out: &mut [T; N], a: &[T; N], b: &[T; N], t: T) {
+    zip(out, a, b, |a, b| a + t * (b - a));
+}
+
+pub(crate) fn dot<T: Scalar, const N: usize>(a: &[T; N], b: &[T; N]) -> T {
+    (1..N).fold(a[0] * b[0], |sum, i| sum + a[i] * b[i])
+}
+
+pub(crate) fn squaredLength<T: Scalar, const N: usize>(a: &[T; N]) -> T {
+    dot(a, a)
+}
+
+pub(crate) fn length<T: Scalar, const N: usize>(a: &[T; N]) -> T {
+    squaredLength(a).sqrt()
+}
+
+pub(crate) fn squaredDistance<T: Scalar, const N: usize>(a: &[T; N], b: &[T; N]) -> T {
+    let d = &mut [T::from(0.); N];
+    subtract(d, b, a);
+    squaredLength(d)
+}
+
+pub(crate) fn distance<T: Scalar, const N: usize>(a: &[T; N], b: &[T; N]) -> T {
+    squaredDistance(a, b).sqrt()
+}
+
+// When the squared length is not above `epsilon`, `a` is scaled by its squared length instead, as the
+// gl-matrix ports did, so vectors that are too short to normalize shrink toward zero.
+pub(crate) fn normalize<T: Scalar, const N: usize>(out: &mut [T; N], a: &[T; N], epsilon: T) {
+    let mut len = squaredLength(a);
+    if len > epsilon {
+        len = T::from(1.) / len.sqrt();
+    }
+    scale(out, a, len);
+}
+
+pub(crate) fn exactEquals<T: Scalar, const N: usize>(a: &[T; N], b: &[T; N]) -> bool {
+    a.iter().zip(b).all(|(a, b)| a == b)
+}
+
+pub(crate) fn equalsWithEpsilon<T: Scalar, const N: usize>(
+    a: &[T; N],
+    b: &[T; N],
+    epsilon: T,
+) -> bool {
+    a.iter().zip(b).all(|(a, b)| equalsEpsilon(*a, *b, epsilon))
+}
+
+pub(crate) fn equalsWithUlps<T: Scalar, const N: usize>(
+    a: &[T; N],
+    b: &[T; N],
+    maxUlps: u32,
+) -> bool {
+    a.iter().zip(b).all(|(a, b)| equalsUlps(*a, *b, maxUlps))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elementWise() {
+        let out = &mut [0f32; 3];
+        add(out, &[1., 2., 3.], &[4., 5., 6.]);
+        assert_eq!(out, &[5., 7., 9.]);
+        lerp(out, &[1., 2., 3.], &[4., 5., 6.], 0.5);
+        assert_eq!(out, &[2.5, 3.5, 4.5]);
+        assert_eq!(dot(&[1f64, 2., 3., 4.], &[5., 6., 7., 8.]), 70.);
+        assert_eq!(distance(&[1f32, 2.], &[4., 6.]), 5.);
+    }
+
+    #[test]
+    fn normalize() {
+        let out = &mut [0f32; 2];
+        super::normalize(out, &[3., 4.], 0.000001);
+        assert_eq!(out, &[0.6, 0.8]);
+        super::normalize(out, &[0., 0.], 0.000001);
+        assert_eq!(out, &[0., 0.]);
+        super::normalize(out, &[0.0001, 0.], 0.000001);
+        assert_eq!(out, &[0.0001 * (0.0001 * 0.0001), 0.]);
+    }
+
+    #[test]
+    fn equals() {
+        assert!(exactEquals(&[1f32, 2.], &[1., 2.]));
+        assert!(!exactEquals(&[1f32, 2.], &[1., 2.1]));
+        assert!(equalsWithEpsilon(&[1f32, 2.], &[1.00001, 2.], 0.0001));
+        assert!(!equalsWithUlps(&[1f32, 2.], &[1.00001, 2.], 4));
+    }
+}
@@ -1,18 +1,36 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+// Names and control flow follow gl-matrix's source one to one.
+#![allow(non_snake_case)]
 /**
  * @File   : lib.rs
  * @Author : dtysky (dtysky@outlook.com)
  * @Link   : http://dtysky.moe
  * @Date   : 2019/2/7 下午9:53:03
  */
+extern crate alloc;
+
 // #[macro_use]
 // pub mod utils;
+pub mod arena;
 pub mod common;
+pub mod double;
+#[cfg(any(feature = "bytemuck", feature = "mint"))]
+mod interop;
+mod kernels;
 pub mod matrix2;
 pub mod matrix2d;
 pub mod matrix3;
 pub mod matrix4;
+mod ops;
 pub mod quaternion;
 pub mod quaternion2;
+#[cfg(feature = "serde")]
+pub mod serialization;
+mod simd;
+pub mod transform_buffer;
 pub mod vector2;
 pub mod vector3;
 pub mod vector4;
+
+#[cfg(test)]
+mod spec_helper;
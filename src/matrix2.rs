@@ -4,238 +4,678 @@
  * @Link   : http://dtysky.moe
  * @Date   : 2019/2/7 下午9:53:03
  */
-use wasm_bindgen::prelude::*;
-
-use super::common::*;
-use super::vector2::*;
-
-#[wasm_bindgen]
-pub struct Matrix2(pub f32, pub f32, pub f32, pub f32);
-
-#[wasm_bindgen]
-impl Matrix2 {
-    #[wasm_bindgen(getter)]
-    pub fn elements(&self) -> Box<[f32]> {
-        Box::new([self.0, self.1, self.2, self.3])
-    }
-
-    pub fn create() -> Self {
-        Matrix2(1., 0., 0., 1.)
-    }
-
-    pub fn clone(a: &Matrix2) -> Self {
-        Matrix2(a.0, a.1, a.2, a.3)
-    }
-
-    pub fn copy(out: &mut Matrix2, a: &Matrix2) {
-        out.0 = a.0;
-        out.1 = a.1;
-        out.2 = a.2;
-        out.3 = a.3;
-    }
-
-    pub fn identity(out: &mut Matrix2) {
-        out.0 = 1.;
-        out.1 = 0.;
-        out.2 = 0.;
-        out.3 = 1.;
-    }
-
-    pub fn fromValues(m00: f32, m01: f32, m10: f32, m11: f32) -> Self {
-        Matrix2(m00, m01, m10, m11)
-    }
-
-    pub fn set(out: &mut Matrix2, m00: f32, m01: f32, m10: f32, m11: f32) {
-        out.0 = m00;
-        out.1 = m01;
-        out.2 = m10;
-        out.3 = m11;
-    }
-
-    pub fn transpose(out: &mut Matrix2, a: &Matrix2) {
-        // If we are transposing ourselves we can skip a few steps but have to cache
-        // some values
-        if (out as *const Matrix2) == (a as *const Matrix2) {
-            let a1 = a.1;
-            out.1 = a.2;
-            out.2 = a1;
-        } else {
-            out.0 = a.0;
-            out.1 = a.2;
-            out.2 = a.1;
-            out.3 = a.3;
+// `Matrix2` here and `Matrix2x2d` in `double`. `$F` is the element type, `$FloatArray` the js array of it
+// that `$fromFloatArray` reads, and the other names are the types of the same precision.
+macro_rules! define_matrix2 {
+    (
+        $F:ident,
+        $FloatArray:ident,
+        $fromFloatArray:ident,
+        $Matrix2:ident,
+        $Matrix2d:ident,
+        $Matrix3:ident,
+        $Matrix4:ident,
+        $Quaternion:ident,
+        $Quaternion2:ident,
+        $Vector2:ident,
+        $Vector3:ident,
+        $Vector4:ident
+    ) => {
+        #[cfg(feature = "wasm")]
+        use js_sys::$FloatArray;
+        #[cfg(feature = "wasm")]
+        use wasm_bindgen::prelude::*;
+
+        use core::fmt;
+        use core::str::FromStr;
+
+        use super::common::*;
+        use super::vector2::*;
+        use crate::kernels::{self, Elements};
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        #[derive(Clone, Copy, Debug)]
+        #[repr(C)]
+        pub struct $Matrix2(pub $F, pub $F, pub $F, pub $F);
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        impl $Matrix2 {
+            #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+            pub fn elements(&self) -> Box<[$F]> {
+                Box::new([self.0, self.1, self.2, self.3])
+            }
+
+            pub fn create() -> Self {
+                $Matrix2(1., 0., 0., 1.)
+            }
+
+            #[allow(clippy::should_implement_trait)]
+            pub fn clone(a: &$Matrix2) -> Self {
+                $Matrix2(a.0, a.1, a.2, a.3)
+            }
+
+            pub fn copy(out: &mut $Matrix2, a: &$Matrix2) {
+                *out = *a;
+            }
+
+            pub fn identity(out: &mut $Matrix2) {
+                out.0 = 1.;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 1.;
+            }
+
+            pub fn fromValues(m00: $F, m01: $F, m10: $F, m11: $F) -> Self {
+                $Matrix2(m00, m01, m10, m11)
+            }
+
+            pub fn set(out: &mut $Matrix2, m00: $F, m01: $F, m10: $F, m11: $F) {
+                out.0 = m00;
+                out.1 = m01;
+                out.2 = m10;
+                out.3 = m11;
+            }
+
+            pub fn transpose(out: &mut $Matrix2, a: &$Matrix2) {
+                // If we are transposing ourselves we can skip a few steps but have to cache
+                // some values
+                if core::ptr::eq(out, a) {
+                    let a1 = a.1;
+                    out.1 = a.2;
+                    out.2 = a1;
+                } else {
+                    out.0 = a.0;
+                    out.1 = a.2;
+                    out.2 = a.1;
+                    out.3 = a.3;
+                }
+            }
+
+            // Returns false and leaves `out` untouched when the matrix is singular.
+            pub fn invert(out: &mut $Matrix2, a: &$Matrix2) -> bool {
+                $Matrix2::tryInvert(out, a, 0.)
+            }
+
+            // Same as `invert`, but the matrix is singular when the absolute determinant is below `tolerance`,
+            // or zero or not finite.
+            pub fn tryInvert(out: &mut $Matrix2, a: &$Matrix2, tolerance: $F) -> bool {
+                let a0 = a.0;
+                let a1 = a.1;
+                let a2 = a.2;
+                let a3 = a.3;
+
+                // Calculate the determinant
+                let det = a0 * a3 - a2 * a1;
+
+                if det == 0. || !det.is_finite() || det.abs() < tolerance {
+                    return false;
+                }
+
+                let det = 1.0 / det;
+
+                out.0 = a3 * det;
+                out.1 = -a1 * det;
+                out.2 = -a2 * det;
+                out.3 = a0 * det;
+
+                true
+            }
+
+            pub fn adjoint(out: &mut $Matrix2, a: &$Matrix2) {
+                // Caching this value is nessecary if out == a
+                if core::ptr::eq(out, a) {
+                    let a = $Matrix2::clone(a);
+                    out.0 = a.3;
+                    out.1 = -a.1;
+                    out.2 = -a.2;
+                    out.3 = a.0;
+                    return;
+                }
+
+                out.0 = a.3;
+                out.1 = -a.1;
+                out.2 = -a.2;
+                out.3 = a.0;
+
+                // console_log!("{} {} {} {}", out.0, out.1, out.2, out.3);
+            }
+
+            pub fn determinant(a: &mut $Matrix2) -> $F {
+                a.0 * a.3 - a.2 * a.1
+            }
+
+            pub fn multiply(out: &mut $Matrix2, a: &$Matrix2, b: &$Matrix2) {
+                let a0 = a.0;
+                let a1 = a.1;
+                let a2 = a.2;
+                let a3 = a.3;
+                let b0 = b.0;
+                let b1 = b.1;
+                let b2 = b.2;
+                let b3 = b.3;
+                out.0 = a0 * b0 + a2 * b1;
+                out.1 = a1 * b0 + a3 * b1;
+                out.2 = a0 * b2 + a2 * b3;
+                out.3 = a1 * b2 + a3 * b3;
+            }
+
+            pub fn rotate(out: &mut $Matrix2, a: &$Matrix2, rad: $F) {
+                let a0 = a.0;
+                let a1 = a.1;
+                let a2 = a.2;
+                let a3 = a.3;
+                let s = $F::sin(rad);
+                let c = $F::cos(rad);
+                out.0 = a0 * c + a2 * s;
+                out.1 = a1 * c + a3 * s;
+                out.2 = a0 * -s + a2 * c;
+                out.3 = a1 * -s + a3 * c;
+            }
+
+            pub fn scale(out: &mut $Matrix2, a: &$Matrix2, v: &$Vector2) {
+                let a0 = a.0;
+                let a1 = a.1;
+                let a2 = a.2;
+                let a3 = a.3;
+                let v0 = v.0;
+                let v1 = v.1;
+                out.0 = a0 * v0;
+                out.1 = a1 * v0;
+                out.2 = a2 * v1;
+                out.3 = a3 * v1;
+            }
+
+            pub fn fromRotation(out: &mut $Matrix2, rad: $F) {
+                let s = $F::sin(rad);
+                let c = $F::cos(rad);
+                out.0 = c;
+                out.1 = s;
+                out.2 = -s;
+                out.3 = c;
+            }
+
+            pub fn fromScaling(out: &mut $Matrix2, v: &$Vector2) {
+                out.0 = v.0;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = v.1;
+            }
+
+            pub fn str(a: &$Matrix2) -> String {
+                a.to_string()
+            }
+
+            pub fn frob(a: &$Matrix2) -> $F {
+                kernels::length(a.array())
+            }
+
+            pub fn LDU(L: &mut $Matrix2, _D: &mut $Matrix2, U: &mut $Matrix2, a: &$Matrix2) {
+                L.2 = a.2 / a.0;
+                U.0 = a.0;
+                U.1 = a.1;
+                U.3 = a.3 - L.2 * U.1;
+            }
+
+            pub fn add(out: &mut $Matrix2, a: &$Matrix2, b: &$Matrix2) {
+                kernels::add(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn subtract(out: &mut $Matrix2, a: &$Matrix2, b: &$Matrix2) {
+                kernels::subtract(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn exactEquals(a: &$Matrix2, b: &$Matrix2) -> bool {
+                kernels::exactEquals(a.array(), b.array())
+            }
+
+            pub fn equals(a: &$Matrix2, b: &$Matrix2) -> bool {
+                match getEqualsMode() {
+                    EqualsMode::Epsilon => $Matrix2::equalsWithEpsilon(a, b, getEpsilon()),
+                    EqualsMode::Ulps => $Matrix2::equalsWithUlps(a, b, getMaxUlps()),
+                }
+            }
+
+            pub fn equalsWithEpsilon(a: &$Matrix2, b: &$Matrix2, epsilon: $F) -> bool {
+                kernels::equalsWithEpsilon(a.array(), b.array(), epsilon)
+            }
+
+            pub fn equalsWithUlps(a: &$Matrix2, b: &$Matrix2, maxUlps: u32) -> bool {
+                kernels::equalsWithUlps(a.array(), b.array(), maxUlps)
+            }
+
+            pub fn multiplyScalar(out: &mut $Matrix2, a: &$Matrix2, b: $F) {
+                kernels::scale(out.arrayMut(), a.array(), b);
+            }
+
+            pub fn multiplyScalarAndAdd(out: &mut $Matrix2, a: &$Matrix2, b: &$Matrix2, scale: $F) {
+                kernels::scaleAndAdd(out.arrayMut(), a.array(), b.array(), scale);
+            }
+
+            pub fn mul(out: &mut $Matrix2, a: &$Matrix2, b: &$Matrix2) {
+                $Matrix2::multiply(out, a, b);
+            }
+
+            pub fn sub(out: &mut $Matrix2, a: &$Matrix2, b: &$Matrix2) {
+                $Matrix2::subtract(out, a, b);
+            }
         }
-    }
 
-    pub fn invert(out: &mut Matrix2, a: &Matrix2) {
-        let a0 = a.0;
-        let a1 = a.1;
-        let a2 = a.2;
-        let a3 = a.3;
+        // Flat f32 buffers, such as the uniforms or the batch functions.
+        impl $Matrix2 {
+            pub fn fromSlice(a: &[$F]) -> $Matrix2 {
+                $Matrix2(a[0], a[1], a[2], a[3])
+            }
 
-        // Calculate the determinant
-        let det = a0 * a3 - a2 * a1;
+            pub fn writeSlice(&self, out: &mut [$F]) {
+                out[..4].copy_from_slice(self.array());
+            }
 
-        if det == 0. {
-            return;
+            pub fn asSlice(&self) -> &[$F] {
+                self.array()
+            }
         }
 
-        let det = 1.0 / det;
-
-        out.0 = a3 * det;
-        out.1 = -a1 * det;
-        out.2 = -a2 * det;
-        out.3 = a0 * det;
-    }
-
-    pub fn adjoint(out: &mut Matrix2, a: &Matrix2) {
-        // Caching this value is nessecary if out == a
-        if (out as *const Matrix2) == (a as *const Matrix2) {
-            let a = Matrix2::clone(a);
-            out.0 = a.3;
-            out.1 = -a.1;
-            out.2 = -a.2;
-            out.3 = a.0;
-            return;
+        // `#[repr(C)]` keeps the 4 elements contiguous.
+        unsafe impl Elements<$F, 4> for $Matrix2 {}
+
+        #[cfg(feature = "wasm")]
+        #[wasm_bindgen]
+        impl $Matrix2 {
+            // Aliases the elements in wasm memory without copying. The view is detached when the memory grows,
+            // so don't keep it across calls that may allocate.
+            pub fn view(&self) -> $FloatArray {
+                unsafe { $FloatArray::view(self.asSlice()) }
+            }
+
+            // Reads the first 4 elements of `a`, without allocating in wasm memory. Throws a `RangeError`
+            // when `a` is shorter.
+            pub fn $fromFloatArray(a: &$FloatArray) -> Result<$Matrix2, JsValue> {
+                check_length(4, a.length() as usize)?;
+                let mut elements = [0.; 4];
+                a.subarray(0, 4).copy_to(&mut elements);
+                Ok($Matrix2::fromSlice(&elements))
+            }
+
+            pub fn copyFromArray(out: &mut $Matrix2, a: &$FloatArray) -> Result<(), JsValue> {
+                *out = $Matrix2::$fromFloatArray(a)?;
+                Ok(())
+            }
         }
 
-        out.0 = a.3;
-        out.1 = -a.1;
-        out.2 = -a.2;
-        out.3 = a.0;
-
-        // console_log!("{} {} {} {}", out.0, out.1, out.2, out.3);
-    }
-
-    pub fn determinant(a: &mut Matrix2) -> f32 {
-        a.0 * a.3 - a.2 * a.1
-    }
-
-    pub fn multiply(out: &mut Matrix2, a: &Matrix2, b: &Matrix2) {
-        let a0 = a.0;
-        let a1 = a.1;
-        let a2 = a.2;
-        let a3 = a.3;
-        let b0 = b.0;
-        let b1 = b.1;
-        let b2 = b.2;
-        let b3 = b.3;
-        out.0 = a0 * b0 + a2 * b1;
-        out.1 = a1 * b0 + a3 * b1;
-        out.2 = a0 * b2 + a2 * b3;
-        out.3 = a1 * b2 + a3 * b3;
-    }
-
-    pub fn rotate(out: &mut Matrix2, a: &Matrix2, rad: f32) {
-        let a0 = a.0;
-        let a1 = a.1;
-        let a2 = a.2;
-        let a3 = a.3;
-        let s = f32::sin(rad);
-        let c = f32::cos(rad);
-        out.0 = a0 * c + a2 * s;
-        out.1 = a1 * c + a3 * s;
-        out.2 = a0 * -s + a2 * c;
-        out.3 = a1 * -s + a3 * c;
-    }
-
-    pub fn scale(out: &mut Matrix2, a: &Matrix2, v: &Vector2) {
-        let a0 = a.0;
-        let a1 = a.1;
-        let a2 = a.2;
-        let a3 = a.3;
-        let v0 = v.0;
-        let v1 = v.1;
-        out.0 = a0 * v0;
-        out.1 = a1 * v0;
-        out.2 = a2 * v1;
-        out.3 = a3 * v1;
-    }
-
-    pub fn fromRotation(out: &mut Matrix2, rad: f32) {
-        let s = f32::sin(rad);
-        let c = f32::cos(rad);
-        out.0 = c;
-        out.1 = s;
-        out.2 = -s;
-        out.3 = c;
-    }
-
-    pub fn fromScaling(out: &mut Matrix2, v: &Vector2) {
-        out.0 = v.0;
-        out.1 = 0.;
-        out.2 = 0.;
-        out.3 = v.1;
-    }
-
-    //   pub fn str(a: &Matrix2) -> String {
-    //     "mat2(" + a.0 + ", " + a.1 + ", " + a.2 + ", " + a.3 + ")"
-    //   }
-
-    pub fn frob(a: &Matrix2) -> f32 {
-        (a.0.powi(2) + a.1.powi(2) + a.2.powi(2) + a.3.powi(2)).sqrt()
-    }
-
-    pub fn LDU(L: &mut Matrix2, D: &mut Matrix2, U: &mut Matrix2, a: &Matrix2) {
-        L.2 = a.2 / a.0;
-        U.0 = a.0;
-        U.1 = a.1;
-        U.3 = a.3 - L.2 * U.1;
-    }
-
-    pub fn add(out: &mut Matrix2, a: &Matrix2, b: &Matrix2) {
-        out.0 = a.0 + b.0;
-        out.1 = a.1 + b.1;
-        out.2 = a.2 + b.2;
-        out.3 = a.3 + b.3;
-    }
-
-    pub fn subtract(out: &mut Matrix2, a: &Matrix2, b: &Matrix2) {
-        out.0 = a.0 - b.0;
-        out.1 = a.1 - b.1;
-        out.2 = a.2 - b.2;
-        out.3 = a.3 - b.3;
-    }
-
-    pub fn exactEquals(a: &Matrix2, b: &Matrix2) -> bool {
-        return a.0 == b.0 && a.1 == b.1 && a.2 == b.2 && a.3 == b.3;
-    }
-
-    pub fn equals(a: &Matrix2, b: &Matrix2) -> bool {
-        let a0 = a.0;
-        let a1 = a.1;
-        let a2 = a.2;
-        let a3 = a.3;
-        let b0 = b.0;
-        let b1 = b.1;
-        let b2 = b.2;
-        let b3 = b.3;
-
-        f32::abs(a0 - b0) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a0), f32::abs(b0)))
-            && f32::abs(a1 - b1) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a1), f32::abs(b1)))
-            && f32::abs(a2 - b2) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a2), f32::abs(b2)))
-            && f32::abs(a3 - b3) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a3), f32::abs(b3)))
-    }
-
-    pub fn multiplyScalar(out: &mut Matrix2, a: &Matrix2, b: f32) {
-        out.0 = a.0 * b;
-        out.1 = a.1 * b;
-        out.2 = a.2 * b;
-        out.3 = a.3 * b;
-    }
-
-    pub fn multiplyScalarAndAdd(out: &mut Matrix2, a: &Matrix2, b: &Matrix2, scale: f32) {
-        out.0 = a.0 + (b.0 * scale);
-        out.1 = a.1 + (b.1 * scale);
-        out.2 = a.2 + (b.2 * scale);
-        out.3 = a.3 + (b.3 * scale);
-    }
-
-    pub fn mul(out: &mut Matrix2, a: &Matrix2, b: &Matrix2) {
-        Matrix2::multiply(out, a, b);
-    }
-
-    pub fn sub(out: &mut Matrix2, a: &Matrix2, b: &Matrix2) {
-        Matrix2::subtract(out, a, b);
-    }
+        impl PartialEq for $Matrix2 {
+            fn eq(&self, other: &$Matrix2) -> bool {
+                $Matrix2::exactEquals(self, other)
+            }
+        }
+
+        impl ApproxEq for $Matrix2 {
+            fn approx_eq(&self, other: &$Matrix2) -> bool {
+                $Matrix2::equals(self, other)
+            }
+        }
+
+        impl Default for $Matrix2 {
+            fn default() -> $Matrix2 {
+                $Matrix2::create()
+            }
+        }
+
+        impl fmt::Display for $Matrix2 {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "mat2({}, {}, {}, {})", self.0, self.1, self.2, self.3)
+            }
+        }
+
+        impl FromStr for $Matrix2 {
+            type Err = ParseError;
+
+            fn from_str(s: &str) -> Result<$Matrix2, ParseError> {
+                let mut v = [0.; 4];
+                parse_values(s, "mat2", &mut v)?;
+                Ok($Matrix2(v[0], v[1], v[2], v[3]))
+            }
+        }
+
+        #[cfg(test)]
+        mod tests {
+            use super::*;
+            use crate::spec_helper::*;
+
+            fn setup() -> ($Matrix2, $Matrix2, $Matrix2) {
+                (
+                    $Matrix2::fromValues(0., 0., 0., 0.),
+                    $Matrix2::fromValues(1., 2., 3., 4.),
+                    $Matrix2::fromValues(5., 6., 7., 8.),
+                )
+            }
+
+            #[test]
+            fn create() {
+                let result = $Matrix2::create();
+                expect_equalish(&result.elements(), &[1., 0., 0., 1.]);
+            }
+
+            #[test]
+            fn clone() {
+                let (_, matA, _) = &mut setup();
+                let result = $Matrix2::clone(matA);
+                expect_equalish(&result.elements(), &matA.elements());
+            }
+
+            #[test]
+            fn copy() {
+                let (out, matA, _) = &mut setup();
+                $Matrix2::copy(out, matA);
+                expect_equalish(&out.elements(), &matA.elements());
+            }
+
+            #[test]
+            fn identity() {
+                let (out, _, _) = &mut setup();
+                $Matrix2::identity(out);
+                expect_equalish(&out.elements(), &[1., 0., 0., 1.]);
+            }
+
+            #[test]
+            fn transpose() {
+                let (out, matA, _) = &mut setup();
+                $Matrix2::transpose(out, matA);
+                expect_equalish(&out.elements(), &[1., 3., 2., 4.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4.]);
+
+                $Matrix2::transpose(matA, &$Matrix2::clone(matA));
+                expect_equalish(&matA.elements(), &[1., 3., 2., 4.]);
+            }
+
+            #[test]
+            fn invert() {
+                let (out, matA, _) = &mut setup();
+                assert!($Matrix2::invert(out, matA));
+                expect_equalish(&out.elements(), &[-2., 1., 1.5, -0.5]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4.]);
+
+                assert!($Matrix2::invert(matA, &$Matrix2::clone(matA)));
+                expect_equalish(&matA.elements(), &[-2., 1., 1.5, -0.5]);
+            }
+
+            #[test]
+            fn adjoint() {
+                let (out, matA, _) = &mut setup();
+                $Matrix2::adjoint(out, matA);
+                expect_equalish(&out.elements(), &[4., -2., -3., 1.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4.]);
+
+                $Matrix2::adjoint(matA, &$Matrix2::clone(matA));
+                expect_equalish(&matA.elements(), &[4., -2., -3., 1.]);
+            }
+
+            #[test]
+            fn determinant() {
+                let (_, matA, _) = &mut setup();
+                expect_equalish_scalar($Matrix2::determinant(matA), -2.);
+            }
+
+            #[test]
+            fn multiply() {
+                let (out, matA, matB) = &mut setup();
+                $Matrix2::multiply(out, matA, matB);
+                expect_equalish(&out.elements(), &[23., 34., 31., 46.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4.]);
+                expect_equalish(&matB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix2::multiply(matA, &$Matrix2::clone(matA), matB);
+                expect_equalish(&matA.elements(), &[23., 34., 31., 46.]);
+                expect_equalish(&matB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix2::multiply(matB, matA, &$Matrix2::clone(matB));
+                expect_equalish(&matB.elements(), &[23., 34., 31., 46.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn rotate() {
+                let (out, matA, _) = &mut setup();
+                $Matrix2::rotate(out, matA, PI * 0.5);
+                expect_equalish(&out.elements(), &[3., 4., -1., -2.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4.]);
+
+                $Matrix2::rotate(matA, &$Matrix2::clone(matA), PI * 0.5);
+                expect_equalish(&matA.elements(), &[3., 4., -1., -2.]);
+            }
+
+            #[test]
+            fn scale() {
+                let (out, matA, _) = &mut setup();
+                let vecA = &$Vector2::fromValues(2., 3.);
+                $Matrix2::scale(out, matA, vecA);
+                expect_equalish(&out.elements(), &[2., 4., 9., 12.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4.]);
+
+                $Matrix2::scale(matA, &$Matrix2::clone(matA), vecA);
+                expect_equalish(&matA.elements(), &[2., 4., 9., 12.]);
+            }
+
+            #[test]
+            fn frob() {
+                let (_, matA, _) = &mut setup();
+                expect_equalish_scalar(
+                    $Matrix2::frob(matA),
+                    $F::sqrt($F::powi(1., 2) + $F::powi(2., 2) + $F::powi(3., 2) + $F::powi(4., 2)),
+                );
+            }
+
+            #[test]
+            fn LDU() {
+                let L = &mut $Matrix2::create();
+                let D = &mut $Matrix2::create();
+                let U = &mut $Matrix2::create();
+                $Matrix2::LDU(L, D, U, &$Matrix2::fromValues(4., 3., 6., 3.));
+                let L_result = &mut $Matrix2::create();
+                L_result.2 = 1.5;
+                let D_result = &mut $Matrix2::create();
+                let U_result = &mut $Matrix2::create();
+                U_result.0 = 4.;
+                U_result.1 = 3.;
+                U_result.3 = -1.5;
+                expect_equalish(&L.elements(), &L_result.elements());
+                expect_equalish(&D.elements(), &D_result.elements());
+                expect_equalish(&U.elements(), &U_result.elements());
+            }
+
+            #[test]
+            fn add() {
+                let (out, matA, matB) = &mut setup();
+                $Matrix2::add(out, matA, matB);
+                expect_equalish(&out.elements(), &[6., 8., 10., 12.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4.]);
+                expect_equalish(&matB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix2::add(matA, &$Matrix2::clone(matA), matB);
+                expect_equalish(&matA.elements(), &[6., 8., 10., 12.]);
+                expect_equalish(&matB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix2::add(matB, matA, &$Matrix2::clone(matB));
+                expect_equalish(&matB.elements(), &[6., 8., 10., 12.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn subtract() {
+                let (out, matA, matB) = &mut setup();
+                $Matrix2::subtract(out, matA, matB);
+                expect_equalish(&out.elements(), &[-4., -4., -4., -4.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4.]);
+                expect_equalish(&matB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix2::subtract(matA, &$Matrix2::clone(matA), matB);
+                expect_equalish(&matA.elements(), &[-4., -4., -4., -4.]);
+                expect_equalish(&matB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix2::subtract(matB, matA, &$Matrix2::clone(matB));
+                expect_equalish(&matB.elements(), &[-4., -4., -4., -4.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn fromValues() {
+                let result = $Matrix2::fromValues(1., 2., 3., 4.);
+                expect_equalish(&result.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn set() {
+                let (out, _, _) = &mut setup();
+                $Matrix2::set(out, 1., 2., 3., 4.);
+                expect_equalish(&out.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn multiplyScalar() {
+                let (out, matA, _) = &mut setup();
+                $Matrix2::multiplyScalar(out, matA, 2.);
+                expect_equalish(&out.elements(), &[2., 4., 6., 8.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4.]);
+
+                $Matrix2::multiplyScalar(matA, &$Matrix2::clone(matA), 2.);
+                expect_equalish(&matA.elements(), &[2., 4., 6., 8.]);
+            }
+
+            #[test]
+            fn multiplyScalarAndAdd() {
+                let (out, matA, matB) = &mut setup();
+                $Matrix2::multiplyScalarAndAdd(out, matA, matB, 0.5);
+                expect_equalish(&out.elements(), &[3.5, 5., 6.5, 8.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4.]);
+                expect_equalish(&matB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix2::multiplyScalarAndAdd(matA, &$Matrix2::clone(matA), matB, 0.5);
+                expect_equalish(&matA.elements(), &[3.5, 5., 6.5, 8.]);
+                expect_equalish(&matB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix2::multiplyScalarAndAdd(matB, matA, &$Matrix2::clone(matB), 0.5);
+                expect_equalish(&matB.elements(), &[3.5, 5., 6.5, 8.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn exactEquals() {
+                let matA = &$Matrix2::fromValues(0., 1., 2., 3.);
+                let matB = &$Matrix2::fromValues(0., 1., 2., 3.);
+                let matC = &$Matrix2::fromValues(1., 2., 3., 4.);
+                assert!($Matrix2::exactEquals(matA, matB));
+                assert!(!$Matrix2::exactEquals(matA, matC));
+                expect_equalish(&matA.elements(), &[0., 1., 2., 3.]);
+                expect_equalish(&matB.elements(), &[0., 1., 2., 3.]);
+            }
+
+            #[test]
+            fn equals() {
+                let matA = &$Matrix2::fromValues(0., 1., 2., 3.);
+                let matB = &$Matrix2::fromValues(0., 1., 2., 3.);
+                let matC = &$Matrix2::fromValues(1., 2., 3., 4.);
+                let matD = &$Matrix2::fromValues(1e-16, 1., 2., 3.);
+                assert!($Matrix2::equals(matA, matB));
+                assert!(!$Matrix2::equals(matA, matC));
+                assert!($Matrix2::equals(matA, matD));
+                expect_equalish(&matA.elements(), &[0., 1., 2., 3.]);
+                expect_equalish(&matB.elements(), &[0., 1., 2., 3.]);
+            }
+
+            #[test]
+            fn traits() {
+                let a = $Matrix2::fromValues(1., 2., 3., 4.);
+                let b = a;
+                assert_eq!(a, b);
+                assert_ne!(a, $Matrix2::fromValues(1., 2., 3., 5.));
+                assert_ne!(a, $Matrix2::fromValues(1., 2., 3., 4.00001));
+                assert!(a.approx_eq(&$Matrix2::fromValues(1., 2., 3., 4.00001)));
+                assert!(!a.approx_eq(&$Matrix2::fromValues(1., 2., 3., 5.)));
+                assert_eq!($Matrix2::default(), $Matrix2::create());
+                assert_eq!(
+                    format!("{:?}", $Matrix2::create()),
+                    concat!(stringify!($Matrix2), "(1.0, 0.0, 0.0, 1.0)")
+                );
+            }
+
+            #[test]
+            fn str() {
+                let a = $Matrix2::fromValues(1., 2., 3., 4.);
+                assert_eq!($Matrix2::str(&a), "mat2(1, 2, 3, 4)");
+                assert_eq!("mat2(1, 2, 3, 4)".parse::<$Matrix2>(), Ok(a));
+            }
+
+            #[test]
+            fn tryInvert() {
+                let (out, _, _) = &mut setup();
+                let singular = &$Matrix2::fromValues(1., 2., 2., 4.);
+                assert!(!$Matrix2::invert(out, singular));
+                // A zero tolerance still rejects a zero or NaN determinant.
+                assert!(!$Matrix2::tryInvert(out, singular, 0.));
+                assert!(!$Matrix2::tryInvert(
+                    out,
+                    &$Matrix2::fromValues($F::NAN, 0., 0., 1.),
+                    0.
+                ));
+                expect_equalish(&out.elements(), &[0., 0., 0., 0.]);
+
+                let small = &$Matrix2::fromValues(0.0078125, 0., 0., 0.0078125);
+                assert!(!$Matrix2::tryInvert(out, small, 1.));
+                expect_equalish(&out.elements(), &[0., 0., 0., 0.]);
+                assert!($Matrix2::tryInvert(out, small, 1e-12));
+                expect_equalish(&out.elements(), &[128., 0., 0., 128.]);
+
+                $Matrix2::set(out, 0., 0., 0., 0.);
+                // Only an exactly zero determinant is singular for Matrix2, as gl-matrix.
+                assert!($Matrix2::invert(out, small));
+                expect_equalish(&out.elements(), &[128., 0., 0., 128.]);
+            }
+
+            #[test]
+            fn equalsWith() {
+                let a = &$Matrix2::fromValues(1., 2., 3., 4.);
+                let b = &$Matrix2::fromValues(1.001, 2., 3., 4.);
+                let c = &$Matrix2::fromValues($F::from_bits($F::to_bits(1.) + 2), 2., 3., 4.);
+                assert!(!$Matrix2::equalsWithEpsilon(a, b, 0.0001));
+                assert!($Matrix2::equalsWithEpsilon(a, b, 0.01));
+                assert!($Matrix2::equalsWithUlps(a, c, 2));
+                assert!(!$Matrix2::equalsWithUlps(a, c, 1));
+                assert!(!$Matrix2::equalsWithUlps(a, b, 4));
+            }
+
+            #[test]
+            fn slice() {
+                let a = $Matrix2::fromValues(1., 2., 3., 4.);
+                assert_eq!(a.asSlice(), &a.elements()[..]);
+
+                let buffer = &mut [0.; 5];
+                a.writeSlice(&mut buffer[1..]);
+                assert_eq!($Matrix2::fromSlice(&buffer[1..]), a);
+            }
+        }
+    };
 }
+
+pub(crate) use define_matrix2;
+
+define_matrix2!(
+    f32,
+    Float32Array,
+    fromFloat32Array,
+    Matrix2,
+    Matrix2d,
+    Matrix3,
+    Matrix4,
+    Quaternion,
+    Quaternion2,
+    Vector2,
+    Vector3,
+    Vector4
+);
@@ -4,264 +4,683 @@
  * @Link   : http://dtysky.moe
  * @Date   : 2019/2/7 下午9:53:03
  */
-use wasm_bindgen::prelude::*;
-
-use super::common::*;
-use super::vector2::*;
-
-#[wasm_bindgen]
-pub struct Matrix2d(pub f32, pub f32, pub f32, pub f32, pub f32, pub f32);
-
-#[wasm_bindgen]
-impl Matrix2d {
-    #[wasm_bindgen(getter)]
-    pub fn elements(&self) -> Box<[f32]> {
-        Box::new([self.0, self.1, self.2, self.3, self.4, self.5])
-    }
-
-    pub fn create() -> Matrix2d {
-        Matrix2d(1., 0., 0., 1., 0., 0.)
-    }
-
-    pub fn clone(a: &Matrix2d) -> Matrix2d {
-        Matrix2d(a.0, a.1, a.2, a.3, a.4, a.5)
-    }
-
-    pub fn copy(out: &mut Matrix2d, a: &Matrix2d) {
-        out.0 = a.0;
-        out.1 = a.1;
-        out.2 = a.2;
-        out.3 = a.3;
-        out.4 = a.4;
-        out.5 = a.5;
-    }
-
-    pub fn identity(out: &mut Matrix2d) {
-        out.0 = 1.;
-        out.1 = 0.;
-        out.2 = 0.;
-        out.3 = 1.;
-        out.4 = 0.;
-        out.5 = 0.;
-    }
-
-    pub fn fromValues(a: f32, b: f32, c: f32, d: f32, tx: f32, ty: f32) -> Matrix2d {
-        Matrix2d(a, b, c, d, tx, ty)
-    }
-
-    pub fn set(out: &mut Matrix2d, a: f32, b: f32, c: f32, d: f32, tx: f32, ty: f32) {
-        out.0 = a;
-        out.1 = b;
-        out.2 = c;
-        out.3 = d;
-        out.4 = tx;
-        out.5 = ty;
-    }
-
-    pub fn invert(out: &mut Matrix2d, a: &Matrix2d) {
-        let aa = a.0;
-        let ab = a.1;
-        let ac = a.2;
-        let ad = a.3;
-        let atx = a.4;
-        let aty = a.5;
-
-        let mut det = aa * ad - ab * ac;
-
-        if det.abs() < EPSILON {
-            return;
+// `Matrix2d` here and `Matrix2x3d` in `double`. `$F` is the element type, `$FloatArray` the js array of it
+// that `$fromFloatArray` reads, and the other names are the types of the same precision.
+macro_rules! define_matrix2d {
+    (
+        $F:ident,
+        $FloatArray:ident,
+        $fromFloatArray:ident,
+        $Matrix2:ident,
+        $Matrix2d:ident,
+        $Matrix3:ident,
+        $Matrix4:ident,
+        $Quaternion:ident,
+        $Quaternion2:ident,
+        $Vector2:ident,
+        $Vector3:ident,
+        $Vector4:ident
+    ) => {
+        #[cfg(feature = "wasm")]
+        use js_sys::$FloatArray;
+        #[cfg(feature = "wasm")]
+        use wasm_bindgen::prelude::*;
+
+        use core::fmt;
+        use core::str::FromStr;
+
+        use super::common::*;
+        use super::vector2::*;
+        use crate::kernels::{self, Elements};
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        #[derive(Clone, Copy, Debug)]
+        #[repr(C)]
+        pub struct $Matrix2d(pub $F, pub $F, pub $F, pub $F, pub $F, pub $F);
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        impl $Matrix2d {
+            #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+            pub fn elements(&self) -> Box<[$F]> {
+                Box::new([self.0, self.1, self.2, self.3, self.4, self.5])
+            }
+
+            pub fn create() -> $Matrix2d {
+                $Matrix2d(1., 0., 0., 1., 0., 0.)
+            }
+
+            #[allow(clippy::should_implement_trait)]
+            pub fn clone(a: &$Matrix2d) -> $Matrix2d {
+                $Matrix2d(a.0, a.1, a.2, a.3, a.4, a.5)
+            }
+
+            pub fn copy(out: &mut $Matrix2d, a: &$Matrix2d) {
+                *out = *a;
+            }
+
+            pub fn identity(out: &mut $Matrix2d) {
+                out.0 = 1.;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 1.;
+                out.4 = 0.;
+                out.5 = 0.;
+            }
+
+            pub fn fromValues(a: $F, b: $F, c: $F, d: $F, tx: $F, ty: $F) -> $Matrix2d {
+                $Matrix2d(a, b, c, d, tx, ty)
+            }
+
+            pub fn set(out: &mut $Matrix2d, a: $F, b: $F, c: $F, d: $F, tx: $F, ty: $F) {
+                out.0 = a;
+                out.1 = b;
+                out.2 = c;
+                out.3 = d;
+                out.4 = tx;
+                out.5 = ty;
+            }
+
+            // Returns false and leaves `out` untouched when the matrix is singular.
+            pub fn invert(out: &mut $Matrix2d, a: &$Matrix2d) -> bool {
+                $Matrix2d::tryInvert(out, a, getEpsilon())
+            }
+
+            // Same as `invert`, but the matrix is singular when the absolute determinant is below `tolerance`,
+            // or zero or not finite.
+            pub fn tryInvert(out: &mut $Matrix2d, a: &$Matrix2d, tolerance: $F) -> bool {
+                let aa = a.0;
+                let ab = a.1;
+                let ac = a.2;
+                let ad = a.3;
+                let atx = a.4;
+                let aty = a.5;
+
+                let mut det = aa * ad - ab * ac;
+
+                if det == 0. || !det.is_finite() || det.abs() < tolerance {
+                    return false;
+                }
+                det = 1.0 / det;
+
+                out.0 = ad * det;
+                out.1 = -ab * det;
+                out.2 = -ac * det;
+                out.3 = aa * det;
+                out.4 = (ac * aty - ad * atx) * det;
+                out.5 = (ab * atx - aa * aty) * det;
+
+                true
+            }
+
+            pub fn determinant(a: &$Matrix2d) -> $F {
+                a.0 * a.3 - a.1 * a.2
+            }
+
+            pub fn multiply(out: &mut $Matrix2d, a: &$Matrix2d, b: &$Matrix2d) {
+                let a0 = a.0;
+                let a1 = a.1;
+                let a2 = a.2;
+                let a3 = a.3;
+                let a4 = a.4;
+                let a5 = a.5;
+                let b0 = b.0;
+                let b1 = b.1;
+                let b2 = b.2;
+                let b3 = b.3;
+                let b4 = b.4;
+                let b5 = b.5;
+                out.0 = a0 * b0 + a2 * b1;
+                out.1 = a1 * b0 + a3 * b1;
+                out.2 = a0 * b2 + a2 * b3;
+                out.3 = a1 * b2 + a3 * b3;
+                out.4 = a0 * b4 + a2 * b5 + a4;
+                out.5 = a1 * b4 + a3 * b5 + a5;
+            }
+
+            pub fn rotate(out: &mut $Matrix2d, a: &$Matrix2d, rad: $F) {
+                let a0 = a.0;
+                let a1 = a.1;
+                let a2 = a.2;
+                let a3 = a.3;
+                let a4 = a.4;
+                let a5 = a.5;
+                let s = $F::sin(rad);
+                let c = $F::cos(rad);
+                out.0 = a0 * c + a2 * s;
+                out.1 = a1 * c + a3 * s;
+                out.2 = a0 * -s + a2 * c;
+                out.3 = a1 * -s + a3 * c;
+                out.4 = a4;
+                out.5 = a5;
+            }
+
+            pub fn scale(out: &mut $Matrix2d, a: &$Matrix2d, v: &$Vector2) {
+                let a0 = a.0;
+                let a1 = a.1;
+                let a2 = a.2;
+                let a3 = a.3;
+                let a4 = a.4;
+                let a5 = a.5;
+                let v0 = v.0;
+                let v1 = v.1;
+                out.0 = a0 * v0;
+                out.1 = a1 * v0;
+                out.2 = a2 * v1;
+                out.3 = a3 * v1;
+                out.4 = a4;
+                out.5 = a5;
+            }
+
+            pub fn translate(out: &mut $Matrix2d, a: &$Matrix2d, v: &$Vector2) {
+                let a0 = a.0;
+                let a1 = a.1;
+                let a2 = a.2;
+                let a3 = a.3;
+                let a4 = a.4;
+                let a5 = a.5;
+                let v0 = v.0;
+                let v1 = v.1;
+                out.0 = a0;
+                out.1 = a1;
+                out.2 = a2;
+                out.3 = a3;
+                out.4 = a0 * v0 + a2 * v1 + a4;
+                out.5 = a1 * v0 + a3 * v1 + a5;
+            }
+
+            pub fn fromRotation(out: &mut $Matrix2d, rad: $F) {
+                let s = $F::sin(rad);
+                let c = $F::cos(rad);
+                out.0 = c;
+                out.1 = s;
+                out.2 = -s;
+                out.3 = c;
+                out.4 = 0.;
+                out.5 = 0.;
+            }
+
+            pub fn fromScaling(out: &mut $Matrix2d, v: &$Vector2) {
+                out.0 = v.0;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = v.1;
+                out.4 = 0.;
+                out.5 = 0.;
+            }
+
+            pub fn fromTranslation(out: &mut $Matrix2d, v: &$Vector2) {
+                out.0 = 1.;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 1.;
+                out.4 = v.0;
+                out.5 = v.1;
+            }
+
+            pub fn str(a: &$Matrix2d) -> String {
+                a.to_string()
+            }
+
+            pub fn frob(a: &$Matrix2d) -> $F {
+                (a.0.powi(2)
+                    + a.1.powi(2)
+                    + a.2.powi(2)
+                    + a.3.powi(2)
+                    + a.4.powi(2)
+                    + a.5.powi(2)
+                    + 1.)
+                    .sqrt()
+            }
+
+            pub fn add(out: &mut $Matrix2d, a: &$Matrix2d, b: &$Matrix2d) {
+                kernels::add(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn subtract(out: &mut $Matrix2d, a: &$Matrix2d, b: &$Matrix2d) {
+                kernels::subtract(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn multiplyScalar(out: &mut $Matrix2d, a: &$Matrix2d, b: $F) {
+                kernels::scale(out.arrayMut(), a.array(), b);
+            }
+
+            pub fn multiplyScalarAndAdd(
+                out: &mut $Matrix2d,
+                a: &$Matrix2d,
+                b: &$Matrix2d,
+                scale: $F,
+            ) {
+                kernels::scaleAndAdd(out.arrayMut(), a.array(), b.array(), scale);
+            }
+
+            pub fn exactEquals(a: &$Matrix2d, b: &$Matrix2d) -> bool {
+                kernels::exactEquals(a.array(), b.array())
+            }
+
+            pub fn equals(a: &$Matrix2d, b: &$Matrix2d) -> bool {
+                match getEqualsMode() {
+                    EqualsMode::Epsilon => $Matrix2d::equalsWithEpsilon(a, b, getEpsilon()),
+                    EqualsMode::Ulps => $Matrix2d::equalsWithUlps(a, b, getMaxUlps()),
+                }
+            }
+
+            pub fn equalsWithEpsilon(a: &$Matrix2d, b: &$Matrix2d, epsilon: $F) -> bool {
+                kernels::equalsWithEpsilon(a.array(), b.array(), epsilon)
+            }
+
+            pub fn equalsWithUlps(a: &$Matrix2d, b: &$Matrix2d, maxUlps: u32) -> bool {
+                kernels::equalsWithUlps(a.array(), b.array(), maxUlps)
+            }
+
+            pub fn mul(out: &mut $Matrix2d, a: &$Matrix2d, b: &$Matrix2d) {
+                $Matrix2d::multiply(out, a, b);
+            }
+
+            pub fn sub(out: &mut $Matrix2d, a: &$Matrix2d, b: &$Matrix2d) {
+                $Matrix2d::subtract(out, a, b);
+            }
+        }
+
+        // Flat f32 buffers, such as the uniforms or the batch functions.
+        impl $Matrix2d {
+            pub fn fromSlice(a: &[$F]) -> $Matrix2d {
+                $Matrix2d(a[0], a[1], a[2], a[3], a[4], a[5])
+            }
+
+            pub fn writeSlice(&self, out: &mut [$F]) {
+                out[..6].copy_from_slice(self.array());
+            }
+
+            pub fn asSlice(&self) -> &[$F] {
+                self.array()
+            }
+        }
+
+        // `#[repr(C)]` keeps the 6 elements contiguous.
+        unsafe impl Elements<$F, 6> for $Matrix2d {}
+
+        #[cfg(feature = "wasm")]
+        #[wasm_bindgen]
+        impl $Matrix2d {
+            // Aliases the elements in wasm memory without copying. The view is detached when the memory grows,
+            // so don't keep it across calls that may allocate.
+            pub fn view(&self) -> $FloatArray {
+                unsafe { $FloatArray::view(self.asSlice()) }
+            }
+
+            // Reads the first 6 elements of `a`, without allocating in wasm memory. Throws a `RangeError`
+            // when `a` is shorter.
+            pub fn $fromFloatArray(a: &$FloatArray) -> Result<$Matrix2d, JsValue> {
+                check_length(6, a.length() as usize)?;
+                let mut elements = [0.; 6];
+                a.subarray(0, 6).copy_to(&mut elements);
+                Ok($Matrix2d::fromSlice(&elements))
+            }
+
+            pub fn copyFromArray(out: &mut $Matrix2d, a: &$FloatArray) -> Result<(), JsValue> {
+                *out = $Matrix2d::$fromFloatArray(a)?;
+                Ok(())
+            }
+        }
+
+        impl PartialEq for $Matrix2d {
+            fn eq(&self, other: &$Matrix2d) -> bool {
+                $Matrix2d::exactEquals(self, other)
+            }
+        }
+
+        impl ApproxEq for $Matrix2d {
+            fn approx_eq(&self, other: &$Matrix2d) -> bool {
+                $Matrix2d::equals(self, other)
+            }
+        }
+
+        impl Default for $Matrix2d {
+            fn default() -> $Matrix2d {
+                $Matrix2d::create()
+            }
         }
-        det = 1.0 / det;
-
-        out.0 = ad * det;
-        out.1 = -ab * det;
-        out.2 = -ac * det;
-        out.3 = aa * det;
-        out.4 = (ac * aty - ad * atx) * det;
-        out.5 = (ab * atx - aa * aty) * det;
-    }
-
-    pub fn determinant(a: &Matrix2d) -> f32 {
-        a.0 * a.3 - a.1 * a.2
-    }
-
-    pub fn multiply(out: &mut Matrix2d, a: &Matrix2d, b: &Matrix2d) {
-        let a0 = a.0;
-        let a1 = a.1;
-        let a2 = a.2;
-        let a3 = a.3;
-        let a4 = a.4;
-        let a5 = a.5;
-        let b0 = b.0;
-        let b1 = b.1;
-        let b2 = b.2;
-        let b3 = b.3;
-        let b4 = b.4;
-        let b5 = b.5;
-        out.0 = a0 * b0 + a2 * b1;
-        out.1 = a1 * b0 + a3 * b1;
-        out.2 = a0 * b2 + a2 * b3;
-        out.3 = a1 * b2 + a3 * b3;
-        out.4 = a0 * b4 + a2 * b5 + a4;
-        out.5 = a1 * b4 + a3 * b5 + a5;
-    }
-
-    pub fn rotate(out: &mut Matrix2d, a: &Matrix2d, rad: f32) {
-        let a0 = a.0;
-        let a1 = a.1;
-        let a2 = a.2;
-        let a3 = a.3;
-        let a4 = a.4;
-        let a5 = a.5;
-        let s = f32::sin(rad);
-        let c = f32::cos(rad);
-        out.0 = a0 * c + a2 * s;
-        out.1 = a1 * c + a3 * s;
-        out.2 = a0 * -s + a2 * c;
-        out.3 = a1 * -s + a3 * c;
-        out.4 = a4;
-        out.5 = a5;
-    }
-
-    pub fn scale(out: &mut Matrix2d, a: &Matrix2d, v: &Vector2) {
-        let a0 = a.0;
-        let a1 = a.1;
-        let a2 = a.2;
-        let a3 = a.3;
-        let a4 = a.4;
-        let a5 = a.5;
-        let v0 = v.0;
-        let v1 = v.1;
-        out.0 = a0 * v0;
-        out.1 = a1 * v0;
-        out.2 = a2 * v1;
-        out.3 = a3 * v1;
-        out.4 = a4;
-        out.5 = a5;
-    }
-
-    pub fn translate(out: &mut Matrix2d, a: &Matrix2d, v: &Vector2) {
-        let a0 = a.0;
-        let a1 = a.1;
-        let a2 = a.2;
-        let a3 = a.3;
-        let a4 = a.4;
-        let a5 = a.5;
-        let v0 = v.0;
-        let v1 = v.1;
-        out.0 = a0;
-        out.1 = a1;
-        out.2 = a2;
-        out.3 = a3;
-        out.4 = a0 * v0 + a2 * v1 + a4;
-        out.5 = a1 * v0 + a3 * v1 + a5;
-    }
-
-    pub fn fromRotation(out: &mut Matrix2d, rad: f32) {
-        let s = f32::sin(rad);
-        let c = f32::cos(rad);
-        out.0 = c;
-        out.1 = s;
-        out.2 = -s;
-        out.3 = c;
-        out.4 = 0.;
-        out.5 = 0.;
-    }
-
-    pub fn fromScaling(out: &mut Matrix2d, v: &Vector2) {
-        out.0 = v.0;
-        out.1 = 0.;
-        out.2 = 0.;
-        out.3 = v.1;
-        out.4 = 0.;
-        out.5 = 0.;
-    }
-
-    pub fn fromTranslation(out: &mut Matrix2d, v: &Vector2) {
-        out.0 = 1.;
-        out.1 = 0.;
-        out.2 = 0.;
-        out.3 = 1.;
-        out.4 = v.0;
-        out.5 = v.1;
-    }
-
-    // pub fn str(a: &Matrix2d) {
-    //   return "mat2d(" + a.0 + ", " + a.1 + ", " + a.2 + ", " +
-    //           a.3 + ", " + a.4 + ", " + a.5 + ")";
-    // }
-
-    pub fn frob(a: &Matrix2d) -> f32 {
-        (a.0.powi(2) + a.1.powi(2) + a.2.powi(2) + a.3.powi(2) + a.4.powi(2) + a.5.powi(2) + 1.)
-            .sqrt()
-    }
-
-    pub fn add(out: &mut Matrix2d, a: &Matrix2d, b: &Matrix2d) {
-        out.0 = a.0 + b.0;
-        out.1 = a.1 + b.1;
-        out.2 = a.2 + b.2;
-        out.3 = a.3 + b.3;
-        out.4 = a.4 + b.4;
-        out.5 = a.5 + b.5;
-    }
-
-    pub fn subtract(out: &mut Matrix2d, a: &Matrix2d, b: &Matrix2d) {
-        out.0 = a.0 - b.0;
-        out.1 = a.1 - b.1;
-        out.2 = a.2 - b.2;
-        out.3 = a.3 - b.3;
-        out.4 = a.4 - b.4;
-        out.5 = a.5 - b.5;
-    }
-
-    pub fn multiplyScalar(out: &mut Matrix2d, a: &Matrix2d, b: f32) {
-        out.0 = a.0 * b;
-        out.1 = a.1 * b;
-        out.2 = a.2 * b;
-        out.3 = a.3 * b;
-        out.4 = a.4 * b;
-        out.5 = a.5 * b;
-    }
-
-    pub fn multiplyScalarAndAdd(out: &mut Matrix2d, a: &Matrix2d, b: &Matrix2d, scale: f32) {
-        out.0 = a.0 + (b.0 * scale);
-        out.1 = a.1 + (b.1 * scale);
-        out.2 = a.2 + (b.2 * scale);
-        out.3 = a.3 + (b.3 * scale);
-        out.4 = a.4 + (b.4 * scale);
-        out.5 = a.5 + (b.5 * scale);
-    }
-
-    pub fn exactEquals(a: &Matrix2d, b: &Matrix2d) -> bool {
-        a.0 == b.0 && a.1 == b.1 && a.2 == b.2 && a.3 == b.3 && a.4 == b.4 && a.5 == b.5
-    }
-
-    pub fn equals(a: &Matrix2d, b: &Matrix2d) -> bool {
-        let a0 = a.0;
-        let a1 = a.1;
-        let a2 = a.2;
-        let a3 = a.3;
-        let a4 = a.4;
-        let a5 = a.5;
-        let b0 = b.0;
-        let b1 = b.1;
-        let b2 = b.2;
-        let b3 = b.3;
-        let b4 = b.4;
-        let b5 = b.5;
-        f32::abs(a0 - b0) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a0), f32::abs(b0)))
-            && f32::abs(a1 - b1) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a1), f32::abs(b1)))
-            && f32::abs(a2 - b2) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a2), f32::abs(b2)))
-            && f32::abs(a3 - b3) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a3), f32::abs(b3)))
-            && f32::abs(a4 - b4) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a4), f32::abs(b4)))
-            && f32::abs(a5 - b5) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a5), f32::abs(b5)))
-    }
-
-    pub fn mul(out: &mut Matrix2d, a: &Matrix2d, b: &Matrix2d) {
-        Matrix2d::multiply(out, a, b);
-    }
-
-    pub fn sub(out: &mut Matrix2d, a: &Matrix2d, b: &Matrix2d) {
-        Matrix2d::subtract(out, a, b);
-    }
+
+        impl fmt::Display for $Matrix2d {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "mat2d({}, {}, {}, {}, {}, {})",
+                    self.0, self.1, self.2, self.3, self.4, self.5
+                )
+            }
+        }
+
+        impl FromStr for $Matrix2d {
+            type Err = ParseError;
+
+            fn from_str(s: &str) -> Result<$Matrix2d, ParseError> {
+                let mut v = [0.; 6];
+                parse_values(s, "mat2d", &mut v)?;
+                Ok($Matrix2d(v[0], v[1], v[2], v[3], v[4], v[5]))
+            }
+        }
+
+        #[cfg(test)]
+        mod tests {
+            use super::*;
+            use crate::spec_helper::*;
+
+            fn setup() -> ($Matrix2d, $Matrix2d, $Matrix2d) {
+                (
+                    $Matrix2d::fromValues(0., 0., 0., 0., 0., 0.),
+                    $Matrix2d::fromValues(1., 2., 3., 4., 5., 6.),
+                    $Matrix2d::fromValues(7., 8., 9., 10., 11., 12.),
+                )
+            }
+
+            #[test]
+            fn create() {
+                let result = $Matrix2d::create();
+                expect_equalish(&result.elements(), &[1., 0., 0., 1., 0., 0.]);
+            }
+
+            #[test]
+            fn clone() {
+                let (_, matA, _) = &mut setup();
+                let result = $Matrix2d::clone(matA);
+                expect_equalish(&result.elements(), &matA.elements());
+            }
+
+            #[test]
+            fn copy() {
+                let (out, matA, _) = &mut setup();
+                $Matrix2d::copy(out, matA);
+                expect_equalish(&out.elements(), &matA.elements());
+            }
+
+            #[test]
+            fn identity() {
+                let (out, _, _) = &mut setup();
+                $Matrix2d::identity(out);
+                expect_equalish(&out.elements(), &[1., 0., 0., 1., 0., 0.]);
+            }
+
+            #[test]
+            fn invert() {
+                let (out, matA, _) = &mut setup();
+                assert!($Matrix2d::invert(out, matA));
+                expect_equalish(&out.elements(), &[-2., 1., 1.5, -0.5, 1., -2.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6.]);
+
+                assert!($Matrix2d::invert(matA, &$Matrix2d::clone(matA)));
+                expect_equalish(&matA.elements(), &[-2., 1., 1.5, -0.5, 1., -2.]);
+            }
+
+            #[test]
+            fn determinant() {
+                let (_, matA, _) = &mut setup();
+                expect_equalish_scalar($Matrix2d::determinant(matA), -2.);
+            }
+
+            #[test]
+            fn multiply() {
+                let (out, matA, matB) = &mut setup();
+                $Matrix2d::multiply(out, matA, matB);
+                expect_equalish(&out.elements(), &[31., 46., 39., 58., 52., 76.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6.]);
+                expect_equalish(&matB.elements(), &[7., 8., 9., 10., 11., 12.]);
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix2d::multiply(matA, &$Matrix2d::clone(matA), matB);
+                expect_equalish(&matA.elements(), &[31., 46., 39., 58., 52., 76.]);
+                expect_equalish(&matB.elements(), &[7., 8., 9., 10., 11., 12.]);
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix2d::multiply(matB, matA, &$Matrix2d::clone(matB));
+                expect_equalish(&matB.elements(), &[31., 46., 39., 58., 52., 76.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6.]);
+            }
+
+            #[test]
+            fn rotate() {
+                let (out, matA, _) = &mut setup();
+                $Matrix2d::rotate(out, matA, PI * 0.5);
+                expect_equalish(&out.elements(), &[3., 4., -1., -2., 5., 6.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6.]);
+
+                $Matrix2d::rotate(matA, &$Matrix2d::clone(matA), PI * 0.5);
+                expect_equalish(&matA.elements(), &[3., 4., -1., -2., 5., 6.]);
+            }
+
+            #[test]
+            fn scale() {
+                let (out, matA, _) = &mut setup();
+                let vecA = &$Vector2::fromValues(2., 3.);
+                $Matrix2d::scale(out, matA, vecA);
+                expect_equalish(&out.elements(), &[2., 4., 9., 12., 5., 6.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6.]);
+
+                $Matrix2d::scale(matA, &$Matrix2d::clone(matA), vecA);
+                expect_equalish(&matA.elements(), &[2., 4., 9., 12., 5., 6.]);
+            }
+
+            #[test]
+            fn translate() {
+                let (out, matA, _) = &mut setup();
+                let vecA = &$Vector2::fromValues(2., 3.);
+                $Matrix2d::translate(out, matA, vecA);
+                expect_equalish(&out.elements(), &[1., 2., 3., 4., 16., 22.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6.]);
+
+                $Matrix2d::translate(matA, &$Matrix2d::clone(matA), vecA);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 16., 22.]);
+            }
+
+            #[test]
+            fn frob() {
+                let (_, matA, _) = &mut setup();
+                expect_equalish_scalar(
+                    $Matrix2d::frob(matA),
+                    $F::sqrt(
+                        $F::powi(1., 2)
+                            + $F::powi(2., 2)
+                            + $F::powi(3., 2)
+                            + $F::powi(4., 2)
+                            + $F::powi(5., 2)
+                            + $F::powi(6., 2)
+                            + 1.,
+                    ),
+                );
+            }
+
+            #[test]
+            fn add() {
+                let (out, matA, matB) = &mut setup();
+                $Matrix2d::add(out, matA, matB);
+                expect_equalish(&out.elements(), &[8., 10., 12., 14., 16., 18.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6.]);
+                expect_equalish(&matB.elements(), &[7., 8., 9., 10., 11., 12.]);
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix2d::add(matA, &$Matrix2d::clone(matA), matB);
+                expect_equalish(&matA.elements(), &[8., 10., 12., 14., 16., 18.]);
+                expect_equalish(&matB.elements(), &[7., 8., 9., 10., 11., 12.]);
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix2d::add(matB, matA, &$Matrix2d::clone(matB));
+                expect_equalish(&matB.elements(), &[8., 10., 12., 14., 16., 18.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6.]);
+            }
+
+            #[test]
+            fn subtract() {
+                let (out, matA, matB) = &mut setup();
+                $Matrix2d::subtract(out, matA, matB);
+                expect_equalish(&out.elements(), &[-6., -6., -6., -6., -6., -6.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6.]);
+                expect_equalish(&matB.elements(), &[7., 8., 9., 10., 11., 12.]);
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix2d::subtract(matA, &$Matrix2d::clone(matA), matB);
+                expect_equalish(&matA.elements(), &[-6., -6., -6., -6., -6., -6.]);
+                expect_equalish(&matB.elements(), &[7., 8., 9., 10., 11., 12.]);
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix2d::subtract(matB, matA, &$Matrix2d::clone(matB));
+                expect_equalish(&matB.elements(), &[-6., -6., -6., -6., -6., -6.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6.]);
+            }
+
+            #[test]
+            fn fromValues() {
+                let result = $Matrix2d::fromValues(1., 2., 3., 4., 5., 6.);
+                expect_equalish(&result.elements(), &[1., 2., 3., 4., 5., 6.]);
+            }
+
+            #[test]
+            fn set() {
+                let (out, _, _) = &mut setup();
+                $Matrix2d::set(out, 1., 2., 3., 4., 5., 6.);
+                expect_equalish(&out.elements(), &[1., 2., 3., 4., 5., 6.]);
+            }
+
+            #[test]
+            fn multiplyScalar() {
+                let (out, matA, _) = &mut setup();
+                $Matrix2d::multiplyScalar(out, matA, 2.);
+                expect_equalish(&out.elements(), &[2., 4., 6., 8., 10., 12.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6.]);
+
+                $Matrix2d::multiplyScalar(matA, &$Matrix2d::clone(matA), 2.);
+                expect_equalish(&matA.elements(), &[2., 4., 6., 8., 10., 12.]);
+            }
+
+            #[test]
+            fn multiplyScalarAndAdd() {
+                let (out, matA, matB) = &mut setup();
+                $Matrix2d::multiplyScalarAndAdd(out, matA, matB, 0.5);
+                expect_equalish(&out.elements(), &[4.5, 6., 7.5, 9., 10.5, 12.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6.]);
+                expect_equalish(&matB.elements(), &[7., 8., 9., 10., 11., 12.]);
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix2d::multiplyScalarAndAdd(matA, &$Matrix2d::clone(matA), matB, 0.5);
+                expect_equalish(&matA.elements(), &[4.5, 6., 7.5, 9., 10.5, 12.]);
+                expect_equalish(&matB.elements(), &[7., 8., 9., 10., 11., 12.]);
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix2d::multiplyScalarAndAdd(matB, matA, &$Matrix2d::clone(matB), 0.5);
+                expect_equalish(&matB.elements(), &[4.5, 6., 7.5, 9., 10.5, 12.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6.]);
+            }
+
+            #[test]
+            fn exactEquals() {
+                let matA = &$Matrix2d::fromValues(0., 1., 2., 3., 4., 5.);
+                let matB = &$Matrix2d::fromValues(0., 1., 2., 3., 4., 5.);
+                let matC = &$Matrix2d::fromValues(1., 2., 3., 4., 5., 6.);
+                assert!($Matrix2d::exactEquals(matA, matB));
+                assert!(!$Matrix2d::exactEquals(matA, matC));
+                expect_equalish(&matA.elements(), &[0., 1., 2., 3., 4., 5.]);
+                expect_equalish(&matB.elements(), &[0., 1., 2., 3., 4., 5.]);
+            }
+
+            #[test]
+            fn equals() {
+                let matA = &$Matrix2d::fromValues(0., 1., 2., 3., 4., 5.);
+                let matB = &$Matrix2d::fromValues(0., 1., 2., 3., 4., 5.);
+                let matC = &$Matrix2d::fromValues(1., 2., 3., 4., 5., 6.);
+                let matD = &$Matrix2d::fromValues(1e-16, 1., 2., 3., 4., 5.);
+                assert!($Matrix2d::equals(matA, matB));
+                assert!(!$Matrix2d::equals(matA, matC));
+                assert!($Matrix2d::equals(matA, matD));
+                expect_equalish(&matA.elements(), &[0., 1., 2., 3., 4., 5.]);
+                expect_equalish(&matB.elements(), &[0., 1., 2., 3., 4., 5.]);
+            }
+
+            #[test]
+            fn traits() {
+                let a = $Matrix2d::fromValues(1., 2., 3., 4., 5., 6.);
+                let b = a;
+                assert_eq!(a, b);
+                assert_ne!(a, $Matrix2d::fromValues(1., 2., 3., 4., 5., 7.));
+                assert_ne!(a, $Matrix2d::fromValues(1., 2., 3., 4., 5., 6.00001));
+                assert!(a.approx_eq(&$Matrix2d::fromValues(1., 2., 3., 4., 5., 6.00001)));
+                assert!(!a.approx_eq(&$Matrix2d::fromValues(1., 2., 3., 4., 5., 7.)));
+                assert_eq!($Matrix2d::default(), $Matrix2d::create());
+                assert_eq!(
+                    format!("{:?}", $Matrix2d::create()),
+                    concat!(stringify!($Matrix2d), "(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)")
+                );
+            }
+
+            #[test]
+            fn str() {
+                let a = $Matrix2d::fromValues(1., 2., 3., 4., 5., 6.);
+                assert_eq!($Matrix2d::str(&a), "mat2d(1, 2, 3, 4, 5, 6)");
+                assert_eq!("mat2d(1, 2, 3, 4, 5, 6)".parse::<$Matrix2d>(), Ok(a));
+            }
+
+            #[test]
+            fn tryInvert() {
+                let (out, _, _) = &mut setup();
+                let singular = &$Matrix2d::fromValues(1., 2., 2., 4., 5., 6.);
+                assert!(!$Matrix2d::invert(out, singular));
+                // A zero tolerance still rejects a zero or NaN determinant.
+                assert!(!$Matrix2d::tryInvert(out, singular, 0.));
+                assert!(!$Matrix2d::tryInvert(
+                    out,
+                    &$Matrix2d::fromValues($F::NAN, 0., 0., 1., 0., 0.),
+                    0.
+                ));
+                expect_equalish(&out.elements(), &[0., 0., 0., 0., 0., 0.]);
+
+                let small = &$Matrix2d::fromValues(0.0078125, 0., 0., 0.0078125, 0., 0.);
+                assert!(!$Matrix2d::tryInvert(out, small, 1.));
+                expect_equalish(&out.elements(), &[0., 0., 0., 0., 0., 0.]);
+                assert!($Matrix2d::tryInvert(out, small, 1e-12));
+                expect_equalish(&out.elements(), &[128., 0., 0., 128., 0., 0.]);
+
+                $Matrix2d::set(out, 0., 0., 0., 0., 0., 0.);
+                assert!(!$Matrix2d::invert(out, small));
+                expect_equalish(&out.elements(), &[0., 0., 0., 0., 0., 0.]);
+            }
+
+            #[test]
+            fn equalsWith() {
+                let a = &$Matrix2d::fromValues(1., 2., 3., 4., 5., 6.);
+                let b = &$Matrix2d::fromValues(1.001, 2., 3., 4., 5., 6.);
+                let c =
+                    &$Matrix2d::fromValues($F::from_bits($F::to_bits(1.) + 2), 2., 3., 4., 5., 6.);
+                assert!(!$Matrix2d::equalsWithEpsilon(a, b, 0.0001));
+                assert!($Matrix2d::equalsWithEpsilon(a, b, 0.01));
+                assert!($Matrix2d::equalsWithUlps(a, c, 2));
+                assert!(!$Matrix2d::equalsWithUlps(a, c, 1));
+                assert!(!$Matrix2d::equalsWithUlps(a, b, 4));
+            }
+
+            #[test]
+            fn slice() {
+                let a = $Matrix2d::fromValues(1., 2., 3., 4., 5., 6.);
+                assert_eq!(a.asSlice(), &a.elements()[..]);
+
+                let buffer = &mut [0.; 7];
+                a.writeSlice(&mut buffer[1..]);
+                assert_eq!($Matrix2d::fromSlice(&buffer[1..]), a);
+            }
+        }
+    };
 }
+
+pub(crate) use define_matrix2d;
+
+define_matrix2d!(
+    f32,
+    Float32Array,
+    fromFloat32Array,
+    Matrix2,
+    Matrix2d,
+    Matrix3,
+    Matrix4,
+    Quaternion,
+    Quaternion2,
+    Vector2,
+    Vector3,
+    Vector4
+);
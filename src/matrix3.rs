@@ -4,584 +4,1241 @@
  * @Link   : http://dtysky.moe
  * @Date   : 2019/2/7 下午9:53:03
  */
-use wasm_bindgen::prelude::*;
-
-use super::common::*;
-use super::matrix2d::*;
-use super::matrix4::*;
-use super::quaternion::*;
-use super::vector2::*;
-
-#[wasm_bindgen]
-pub struct Matrix3(
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-);
+// `Matrix3` here and `Matrix3d` in `double`. `$F` is the element type, `$FloatArray` the js array of it
+// that `$fromFloatArray` reads, and the other names are the types of the same precision.
+macro_rules! define_matrix3 {
+    (
+        $F:ident,
+        $FloatArray:ident,
+        $fromFloatArray:ident,
+        $Matrix2:ident,
+        $Matrix2d:ident,
+        $Matrix3:ident,
+        $Matrix4:ident,
+        $Quaternion:ident,
+        $Quaternion2:ident,
+        $Vector2:ident,
+        $Vector3:ident,
+        $Vector4:ident
+    ) => {
+        #[cfg(feature = "wasm")]
+        use js_sys::$FloatArray;
+        #[cfg(feature = "wasm")]
+        use wasm_bindgen::prelude::*;
+
+        use core::fmt;
+        use core::str::FromStr;
+
+        use super::common::*;
+        use super::matrix2d::*;
+        use super::matrix4::*;
+        use super::quaternion::*;
+        use super::vector2::*;
+        use super::vector3::*;
+        use crate::kernels::{self, Elements};
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        #[derive(Clone, Copy, Debug)]
+        #[repr(C)]
+        pub struct $Matrix3(
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+        );
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        impl $Matrix3 {
+            #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+            pub fn elements(&self) -> Box<[$F]> {
+                Box::new([
+                    self.0, self.1, self.2, self.3, self.4, self.5, self.6, self.7, self.8,
+                ])
+            }
+
+            pub fn create() -> $Matrix3 {
+                $Matrix3(1., 0., 0., 0., 1., 0., 0., 0., 1.)
+            }
+
+            pub fn fromMat4(out: &mut $Matrix3, a: &$Matrix4) {
+                out.0 = a.0;
+                out.1 = a.1;
+                out.2 = a.2;
+                out.3 = a.4;
+                out.4 = a.5;
+                out.5 = a.6;
+                out.6 = a.8;
+                out.7 = a.9;
+                out.8 = a.10;
+            }
+
+            #[allow(clippy::should_implement_trait)]
+            pub fn clone(a: &$Matrix3) -> $Matrix3 {
+                $Matrix3(a.0, a.1, a.2, a.3, a.4, a.5, a.6, a.7, a.8)
+            }
+
+            pub fn copy(out: &mut $Matrix3, a: &$Matrix3) {
+                *out = *a;
+            }
+
+            #[allow(clippy::too_many_arguments)]
+            pub fn fromValues(
+                m00: $F,
+                m01: $F,
+                m02: $F,
+                m10: $F,
+                m11: $F,
+                m12: $F,
+                m20: $F,
+                m21: $F,
+                m22: $F,
+            ) -> $Matrix3 {
+                $Matrix3(m00, m01, m02, m10, m11, m12, m20, m21, m22)
+            }
+
+            #[allow(clippy::too_many_arguments)]
+            pub fn set(
+                out: &mut $Matrix3,
+                m00: $F,
+                m01: $F,
+                m02: $F,
+                m10: $F,
+                m11: $F,
+                m12: $F,
+                m20: $F,
+                m21: $F,
+                m22: $F,
+            ) {
+                out.0 = m00;
+                out.1 = m01;
+                out.2 = m02;
+                out.3 = m10;
+                out.4 = m11;
+                out.5 = m12;
+                out.6 = m20;
+                out.7 = m21;
+                out.8 = m22;
+            }
+
+            pub fn identity(out: &mut $Matrix3) {
+                out.0 = 1.;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 0.;
+                out.4 = 1.;
+                out.5 = 0.;
+                out.6 = 0.;
+                out.7 = 0.;
+                out.8 = 1.;
+            }
+
+            pub fn transpose(out: &mut $Matrix3, a: &$Matrix3) {
+                // If we are transposing ourselves we can skip a few steps but have to cache some values
+                if core::ptr::eq(out, a) {
+                    let a01 = a.1;
+                    let a02 = a.2;
+                    let a12 = a.5;
+                    out.1 = a.3;
+                    out.2 = a.6;
+                    out.3 = a01;
+                    out.5 = a.7;
+                    out.6 = a02;
+                    out.7 = a12;
+                } else {
+                    out.0 = a.0;
+                    out.1 = a.3;
+                    out.2 = a.6;
+                    out.3 = a.1;
+                    out.4 = a.4;
+                    out.5 = a.7;
+                    out.6 = a.2;
+                    out.7 = a.5;
+                    out.8 = a.8;
+                }
+            }
+
+            // Returns false and leaves `out` untouched when the matrix is singular.
+            pub fn invert(out: &mut $Matrix3, a: &$Matrix3) -> bool {
+                $Matrix3::tryInvert(out, a, getEpsilon())
+            }
+
+            // Same as `invert`, but the matrix is singular when the absolute determinant is below `tolerance`,
+            // or zero or not finite.
+            pub fn tryInvert(out: &mut $Matrix3, a: &$Matrix3, tolerance: $F) -> bool {
+                let a00 = a.0;
+                let a01 = a.1;
+                let a02 = a.2;
+                let a10 = a.3;
+                let a11 = a.4;
+                let a12 = a.5;
+                let a20 = a.6;
+                let a21 = a.7;
+                let a22 = a.8;
+
+                let b01 = a22 * a11 - a12 * a21;
+                let b11 = -a22 * a10 + a12 * a20;
+                let b21 = a21 * a10 - a11 * a20;
+
+                // Calculate the determinant
+                let mut det = a00 * b01 + a01 * b11 + a02 * b21;
+
+                if det == 0. || !det.is_finite() || det.abs() < tolerance {
+                    return false;
+                }
+                det = 1.0 / det;
+
+                out.0 = b01 * det;
+                out.1 = (-a22 * a01 + a02 * a21) * det;
+                out.2 = (a12 * a01 - a02 * a11) * det;
+                out.3 = b11 * det;
+                out.4 = (a22 * a00 - a02 * a20) * det;
+                out.5 = (-a12 * a00 + a02 * a10) * det;
+                out.6 = b21 * det;
+                out.7 = (-a21 * a00 + a01 * a20) * det;
+                out.8 = (a11 * a00 - a01 * a10) * det;
+
+                true
+            }
+
+            pub fn adjoint(out: &mut $Matrix3, a: &$Matrix3) {
+                let a00 = a.0;
+                let a01 = a.1;
+                let a02 = a.2;
+                let a10 = a.3;
+                let a11 = a.4;
+                let a12 = a.5;
+                let a20 = a.6;
+                let a21 = a.7;
+                let a22 = a.8;
+
+                out.0 = a11 * a22 - a12 * a21;
+                out.1 = a02 * a21 - a01 * a22;
+                out.2 = a01 * a12 - a02 * a11;
+                out.3 = a12 * a20 - a10 * a22;
+                out.4 = a00 * a22 - a02 * a20;
+                out.5 = a02 * a10 - a00 * a12;
+                out.6 = a10 * a21 - a11 * a20;
+                out.7 = a01 * a20 - a00 * a21;
+                out.8 = a00 * a11 - a01 * a10;
+            }
+
+            pub fn determinant(a: &$Matrix3) -> $F {
+                let a00 = a.0;
+                let a01 = a.1;
+                let a02 = a.2;
+                let a10 = a.3;
+                let a11 = a.4;
+                let a12 = a.5;
+                let a20 = a.6;
+                let a21 = a.7;
+                let a22 = a.8;
+
+                a00 * (a22 * a11 - a12 * a21)
+                    + a01 * (-a22 * a10 + a12 * a20)
+                    + a02 * (a21 * a10 - a11 * a20)
+            }
+
+            pub fn multiply(out: &mut $Matrix3, a: &$Matrix3, b: &$Matrix3) {
+                let a00 = a.0;
+                let a01 = a.1;
+                let a02 = a.2;
+                let a10 = a.3;
+                let a11 = a.4;
+                let a12 = a.5;
+                let a20 = a.6;
+                let a21 = a.7;
+                let a22 = a.8;
+
+                let b00 = b.0;
+                let b01 = b.1;
+                let b02 = b.2;
+                let b10 = b.3;
+                let b11 = b.4;
+                let b12 = b.5;
+                let b20 = b.6;
+                let b21 = b.7;
+                let b22 = b.8;
+
+                out.0 = b00 * a00 + b01 * a10 + b02 * a20;
+                out.1 = b00 * a01 + b01 * a11 + b02 * a21;
+                out.2 = b00 * a02 + b01 * a12 + b02 * a22;
+
+                out.3 = b10 * a00 + b11 * a10 + b12 * a20;
+                out.4 = b10 * a01 + b11 * a11 + b12 * a21;
+                out.5 = b10 * a02 + b11 * a12 + b12 * a22;
+
+                out.6 = b20 * a00 + b21 * a10 + b22 * a20;
+                out.7 = b20 * a01 + b21 * a11 + b22 * a21;
+                out.8 = b20 * a02 + b21 * a12 + b22 * a22;
+            }
+
+            pub fn translate(out: &mut $Matrix3, a: &$Matrix3, v: &$Vector2) {
+                let a00 = a.0;
+                let a01 = a.1;
+                let a02 = a.2;
+                let a10 = a.3;
+                let a11 = a.4;
+                let a12 = a.5;
+                let a20 = a.6;
+                let a21 = a.7;
+                let a22 = a.8;
+                let x = v.0;
+                let y = v.1;
+
+                out.0 = a00;
+                out.1 = a01;
+                out.2 = a02;
+
+                out.3 = a10;
+                out.4 = a11;
+                out.5 = a12;
+
+                out.6 = x * a00 + y * a10 + a20;
+                out.7 = x * a01 + y * a11 + a21;
+                out.8 = x * a02 + y * a12 + a22;
+            }
+
+            pub fn rotate(out: &mut $Matrix3, a: &$Matrix3, rad: $F) {
+                let a00 = a.0;
+                let a01 = a.1;
+                let a02 = a.2;
+                let a10 = a.3;
+                let a11 = a.4;
+                let a12 = a.5;
+                let a20 = a.6;
+                let a21 = a.7;
+                let a22 = a.8;
+                let s = $F::sin(rad);
+                let c = $F::cos(rad);
+
+                out.0 = c * a00 + s * a10;
+                out.1 = c * a01 + s * a11;
+                out.2 = c * a02 + s * a12;
+
+                out.3 = c * a10 - s * a00;
+                out.4 = c * a11 - s * a01;
+                out.5 = c * a12 - s * a02;
+
+                out.6 = a20;
+                out.7 = a21;
+                out.8 = a22;
+            }
+
+            pub fn scale(out: &mut $Matrix3, a: &$Matrix3, v: &$Vector2) {
+                let x = v.0;
+                let y = v.1;
+
+                out.0 = x * a.0;
+                out.1 = x * a.1;
+                out.2 = x * a.2;
+
+                out.3 = y * a.3;
+                out.4 = y * a.4;
+                out.5 = y * a.5;
+
+                out.6 = a.6;
+                out.7 = a.7;
+                out.8 = a.8;
+            }
+
+            pub fn fromTranslation(out: &mut $Matrix3, v: &$Vector2) {
+                out.0 = 1.;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 0.;
+                out.4 = 1.;
+                out.5 = 0.;
+                out.6 = v.0;
+                out.7 = v.1;
+                out.8 = 1.;
+            }
+
+            pub fn fromRotation(out: &mut $Matrix3, rad: $F) {
+                let s = $F::sin(rad);
+                let c = $F::cos(rad);
+
+                out.0 = c;
+                out.1 = s;
+                out.2 = 0.;
+
+                out.3 = -s;
+                out.4 = c;
+                out.5 = 0.;
+
+                out.6 = 0.;
+                out.7 = 0.;
+                out.8 = 1.;
+            }
+
+            pub fn fromScaling(out: &mut $Matrix3, v: &$Vector2) {
+                out.0 = v.0;
+                out.1 = 0.;
+                out.2 = 0.;
+
+                out.3 = 0.;
+                out.4 = v.1;
+                out.5 = 0.;
+
+                out.6 = 0.;
+                out.7 = 0.;
+                out.8 = 1.;
+            }
+
+            pub fn fromMat2d(out: &mut $Matrix3, a: &$Matrix2d) {
+                out.0 = a.0;
+                out.1 = a.1;
+                out.2 = 0.;
+
+                out.3 = a.2;
+                out.4 = a.3;
+                out.5 = 0.;
+
+                out.6 = a.4;
+                out.7 = a.5;
+                out.8 = 1.;
+            }
+
+            pub fn fromQuat(out: &mut $Matrix3, q: &$Quaternion) {
+                let x = q.0;
+                let y = q.1;
+                let z = q.2;
+                let w = q.3;
+                let x2 = x + x;
+                let y2 = y + y;
+                let z2 = z + z;
+
+                let xx = x * x2;
+                let yx = y * x2;
+                let yy = y * y2;
+                let zx = z * x2;
+                let zy = z * y2;
+                let zz = z * z2;
+                let wx = w * x2;
+                let wy = w * y2;
+                let wz = w * z2;
+
+                out.0 = 1. - yy - zz;
+                out.3 = yx - wz;
+                out.6 = zx + wy;
+
+                out.1 = yx + wz;
+                out.4 = 1. - xx - zz;
+                out.7 = zy - wx;
+
+                out.2 = zx - wy;
+                out.5 = zy + wx;
+                out.8 = 1. - xx - yy;
+            }
+
+            pub fn fromEuler(
+                out: &mut $Matrix3,
+                x: $F,
+                y: $F,
+                z: $F,
+                order: EulerOrder,
+                unit: AngleUnit,
+            ) {
+                let q = &mut $Quaternion::create();
+                $Quaternion::fromEulerOrder(q, x, y, z, order, unit);
+                $Matrix3::fromQuat(out, q);
+            }
+
+            pub fn getEuler(out: &mut $Vector3, m: &$Matrix3, order: EulerOrder, unit: AngleUnit) {
+                // Scale is removed from the columns, angles are only read from the rotation.
+                let inv = |a: $F, b: $F, c: $F| {
+                    let len = $F::sqrt(a * a + b * b + c * c);
+                    if len > 0. {
+                        1. / len
+                    } else {
+                        0.
+                    }
+                };
+                let s = [inv(m.0, m.1, m.2), inv(m.3, m.4, m.5), inv(m.6, m.7, m.8)];
+                let m11 = m.0 * s[0];
+                let m21 = m.1 * s[0];
+                let m31 = m.2 * s[0];
+                let m12 = m.3 * s[1];
+                let m22 = m.4 * s[1];
+                let m32 = m.5 * s[1];
+                let m13 = m.6 * s[2];
+                let m23 = m.7 * s[2];
+                let m33 = m.8 * s[2];
+
+                // Past the threshold the first and last axes line up, the last angle is set to zero.
+                let locked = 0.9999999;
+                let (x, y, z);
+                match order {
+                    EulerOrder::XYZ | EulerOrder::ExtrinsicZYX => {
+                        y = $F::asin(m13.clamp(-1., 1.));
+                        if m13.abs() < locked {
+                            x = $F::atan2(-m23, m33);
+                            z = $F::atan2(-m12, m11);
+                        } else {
+                            x = $F::atan2(m32, m22);
+                            z = 0.;
+                        }
+                    }
+                    EulerOrder::XZY | EulerOrder::ExtrinsicYZX => {
+                        z = $F::asin(-m12.clamp(-1., 1.));
+                        if m12.abs() < locked {
+                            x = $F::atan2(m32, m22);
+                            y = $F::atan2(m13, m11);
+                        } else {
+                            x = $F::atan2(-m23, m33);
+                            y = 0.;
+                        }
+                    }
+                    EulerOrder::YXZ | EulerOrder::ExtrinsicZXY => {
+                        x = $F::asin(-m23.clamp(-1., 1.));
+                        if m23.abs() < locked {
+                            y = $F::atan2(m13, m33);
+                            z = $F::atan2(m21, m22);
+                        } else {
+                            y = $F::atan2(-m31, m11);
+                            z = 0.;
+                        }
+                    }
+                    EulerOrder::YZX | EulerOrder::ExtrinsicXZY => {
+                        z = $F::asin(m21.clamp(-1., 1.));
+                        if m21.abs() < locked {
+                            x = $F::atan2(-m23, m22);
+                            y = $F::atan2(-m31, m11);
+                        } else {
+                            x = 0.;
+                            y = $F::atan2(m13, m33);
+                        }
+                    }
+                    EulerOrder::ZXY | EulerOrder::ExtrinsicYXZ => {
+                        x = $F::asin(m32.clamp(-1., 1.));
+                        if m32.abs() < locked {
+                            y = $F::atan2(-m31, m33);
+                            z = $F::atan2(-m12, m22);
+                        } else {
+                            y = 0.;
+                            z = $F::atan2(m21, m11);
+                        }
+                    }
+                    EulerOrder::ZYX | EulerOrder::ExtrinsicXYZ => {
+                        y = $F::asin(-m31.clamp(-1., 1.));
+                        if m31.abs() < locked {
+                            x = $F::atan2(m32, m33);
+                            z = $F::atan2(m21, m11);
+                        } else {
+                            x = 0.;
+                            z = $F::atan2(-m12, m22);
+                        }
+                    }
+                }
+
+                let toUnit = match unit {
+                    AngleUnit::Radians => 1.,
+                    AngleUnit::Degrees => 180. / PI,
+                };
+                out.0 = x * toUnit;
+                out.1 = y * toUnit;
+                out.2 = z * toUnit;
+            }
+
+            pub fn normalFromMat4(out: &mut $Matrix3, a: &$Matrix4) {
+                let a00 = a.0;
+                let a01 = a.1;
+                let a02 = a.2;
+                let a03 = a.3;
+                let a10 = a.4;
+                let a11 = a.5;
+                let a12 = a.6;
+                let a13 = a.7;
+                let a20 = a.8;
+                let a21 = a.9;
+                let a22 = a.10;
+                let a23 = a.11;
+                let a30 = a.12;
+                let a31 = a.13;
+                let a32 = a.14;
+                let a33 = a.15;
+
+                let b00 = a00 * a11 - a01 * a10;
+                let b01 = a00 * a12 - a02 * a10;
+                let b02 = a00 * a13 - a03 * a10;
+                let b03 = a01 * a12 - a02 * a11;
+                let b04 = a01 * a13 - a03 * a11;
+                let b05 = a02 * a13 - a03 * a12;
+                let b06 = a20 * a31 - a21 * a30;
+                let b07 = a20 * a32 - a22 * a30;
+                let b08 = a20 * a33 - a23 * a30;
+                let b09 = a21 * a32 - a22 * a31;
+                let b10 = a21 * a33 - a23 * a31;
+                let b11 = a22 * a33 - a23 * a32;
+
+                // Calculate the determinant
+                let mut det = b00 * b11 - b01 * b10 + b02 * b09 + b03 * b08 - b04 * b07 + b05 * b06;
+
+                if det.abs() < getEpsilon() {
+                    return;
+                }
+                det = 1.0 / det;
+
+                out.0 = (a11 * b11 - a12 * b10 + a13 * b09) * det;
+                out.1 = (a12 * b08 - a10 * b11 - a13 * b07) * det;
+                out.2 = (a10 * b10 - a11 * b08 + a13 * b06) * det;
+
+                out.3 = (a02 * b10 - a01 * b11 - a03 * b09) * det;
+                out.4 = (a00 * b11 - a02 * b08 + a03 * b07) * det;
+                out.5 = (a01 * b08 - a00 * b10 - a03 * b06) * det;
+
+                out.6 = (a31 * b05 - a32 * b04 + a33 * b03) * det;
+                out.7 = (a32 * b02 - a30 * b05 - a33 * b01) * det;
+                out.8 = (a30 * b04 - a31 * b02 + a33 * b00) * det;
+            }
+
+            pub fn projection(out: &mut $Matrix3, width: $F, height: $F) {
+                out.0 = 2. / width;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 0.;
+                out.4 = -2. / height;
+                out.5 = 0.;
+                out.6 = -1.;
+                out.7 = 1.;
+                out.8 = 1.;
+            }
+
+            pub fn str(a: &$Matrix3) -> String {
+                a.to_string()
+            }
+
+            pub fn frob(a: &$Matrix3) -> $F {
+                kernels::length(a.array())
+            }
+
+            pub fn add(out: &mut $Matrix3, a: &$Matrix3, b: &$Matrix3) {
+                kernels::add(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn subtract(out: &mut $Matrix3, a: &$Matrix3, b: &$Matrix3) {
+                kernels::subtract(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn multiplyScalar(out: &mut $Matrix3, a: &$Matrix3, b: $F) {
+                kernels::scale(out.arrayMut(), a.array(), b);
+            }
+
+            pub fn multiplyScalarAndAdd(out: &mut $Matrix3, a: &$Matrix3, b: &$Matrix3, scale: $F) {
+                kernels::scaleAndAdd(out.arrayMut(), a.array(), b.array(), scale);
+            }
+
+            pub fn exactEquals(a: &$Matrix3, b: &$Matrix3) -> bool {
+                kernels::exactEquals(a.array(), b.array())
+            }
+
+            pub fn equals(a: &$Matrix3, b: &$Matrix3) -> bool {
+                match getEqualsMode() {
+                    EqualsMode::Epsilon => $Matrix3::equalsWithEpsilon(a, b, getEpsilon()),
+                    EqualsMode::Ulps => $Matrix3::equalsWithUlps(a, b, getMaxUlps()),
+                }
+            }
+
+            pub fn equalsWithEpsilon(a: &$Matrix3, b: &$Matrix3, epsilon: $F) -> bool {
+                kernels::equalsWithEpsilon(a.array(), b.array(), epsilon)
+            }
+
+            pub fn equalsWithUlps(a: &$Matrix3, b: &$Matrix3, maxUlps: u32) -> bool {
+                kernels::equalsWithUlps(a.array(), b.array(), maxUlps)
+            }
+
+            pub fn mul(out: &mut $Matrix3, a: &$Matrix3, b: &$Matrix3) {
+                $Matrix3::multiply(out, a, b);
+            }
+
+            pub fn sub(out: &mut $Matrix3, a: &$Matrix3, b: &$Matrix3) {
+                $Matrix3::subtract(out, a, b);
+            }
+        }
+
+        // Flat f32 buffers, such as the uniforms or the batch functions.
+        impl $Matrix3 {
+            pub fn fromSlice(a: &[$F]) -> $Matrix3 {
+                $Matrix3(a[0], a[1], a[2], a[3], a[4], a[5], a[6], a[7], a[8])
+            }
+
+            pub fn writeSlice(&self, out: &mut [$F]) {
+                out[..9].copy_from_slice(self.array());
+            }
+
+            pub fn asSlice(&self) -> &[$F] {
+                self.array()
+            }
+        }
 
-#[wasm_bindgen]
-impl Matrix3 {
-    #[wasm_bindgen(getter)]
-    pub fn elements(&self) -> Box<[f32]> {
-        Box::new([
-            self.0, self.1, self.2, self.3, self.4, self.5, self.6, self.7, self.8,
-        ])
-    }
-
-    pub fn create() -> Matrix3 {
-        Matrix3(1., 0., 0., 0., 1., 0., 0., 0., 1.)
-    }
-
-    pub fn fromMat4(out: &mut Matrix3, a: &Matrix4) {
-        out.0 = a.0;
-        out.1 = a.1;
-        out.2 = a.2;
-        out.3 = a.4;
-        out.4 = a.5;
-        out.5 = a.6;
-        out.6 = a.8;
-        out.7 = a.9;
-        out.8 = a.10;
-    }
-
-    pub fn clone(a: &Matrix3) -> Matrix3 {
-        Matrix3(a.0, a.1, a.2, a.3, a.4, a.5, a.6, a.7, a.8)
-    }
-
-    pub fn copy(out: &mut Matrix3, a: &Matrix3) {
-        out.0 = a.0;
-        out.1 = a.1;
-        out.2 = a.2;
-        out.3 = a.3;
-        out.4 = a.4;
-        out.5 = a.5;
-        out.6 = a.6;
-        out.7 = a.7;
-        out.8 = a.8;
-    }
-
-    pub fn fromValues(
-        m00: f32,
-        m01: f32,
-        m02: f32,
-        m10: f32,
-        m11: f32,
-        m12: f32,
-        m20: f32,
-        m21: f32,
-        m22: f32,
-    ) -> Matrix3 {
-        Matrix3(m00, m01, m02, m10, m11, m12, m20, m21, m22)
-    }
-
-    pub fn set(
-        out: &mut Matrix3,
-        m00: f32,
-        m01: f32,
-        m02: f32,
-        m10: f32,
-        m11: f32,
-        m12: f32,
-        m20: f32,
-        m21: f32,
-        m22: f32,
-    ) {
-        out.0 = m00;
-        out.1 = m01;
-        out.2 = m02;
-        out.3 = m10;
-        out.4 = m11;
-        out.5 = m12;
-        out.6 = m20;
-        out.7 = m21;
-        out.8 = m22;
-    }
-
-    pub fn identity(out: &mut Matrix3) {
-        out.0 = 1.;
-        out.1 = 0.;
-        out.2 = 0.;
-        out.3 = 0.;
-        out.4 = 1.;
-        out.5 = 0.;
-        out.6 = 0.;
-        out.7 = 0.;
-        out.8 = 1.;
-    }
-
-    pub fn transpose(out: &mut Matrix3, a: &Matrix3) {
-        // If we are transposing ourselves we can skip a few steps but have to cache some values
-        if (out as *const Matrix3) == (a as *const Matrix3) {
-            let a01 = a.1;
-            let a02 = a.2;
-            let a12 = a.5;
-            out.1 = a.3;
-            out.2 = a.6;
-            out.3 = a01;
-            out.5 = a.7;
-            out.6 = a02;
-            out.7 = a12;
-        } else {
-            out.0 = a.0;
-            out.1 = a.3;
-            out.2 = a.6;
-            out.3 = a.1;
-            out.4 = a.4;
-            out.5 = a.7;
-            out.6 = a.2;
-            out.7 = a.5;
-            out.8 = a.8;
+        // `#[repr(C)]` keeps the 9 elements contiguous.
+        unsafe impl Elements<$F, 9> for $Matrix3 {}
+
+        #[cfg(feature = "wasm")]
+        #[wasm_bindgen]
+        impl $Matrix3 {
+            // Aliases the elements in wasm memory without copying. The view is detached when the memory grows,
+            // so don't keep it across calls that may allocate.
+            pub fn view(&self) -> $FloatArray {
+                unsafe { $FloatArray::view(self.asSlice()) }
+            }
+
+            // Reads the first 9 elements of `a`, without allocating in wasm memory. Throws a `RangeError`
+            // when `a` is shorter.
+            pub fn $fromFloatArray(a: &$FloatArray) -> Result<$Matrix3, JsValue> {
+                check_length(9, a.length() as usize)?;
+                let mut elements = [0.; 9];
+                a.subarray(0, 9).copy_to(&mut elements);
+                Ok($Matrix3::fromSlice(&elements))
+            }
+
+            pub fn copyFromArray(out: &mut $Matrix3, a: &$FloatArray) -> Result<(), JsValue> {
+                *out = $Matrix3::$fromFloatArray(a)?;
+                Ok(())
+            }
         }
-    }
-
-    pub fn invert(out: &mut Matrix3, a: &Matrix3) {
-        let a00 = a.0;
-        let a01 = a.1;
-        let a02 = a.2;
-        let a10 = a.3;
-        let a11 = a.4;
-        let a12 = a.5;
-        let a20 = a.6;
-        let a21 = a.7;
-        let a22 = a.8;
-
-        let b01 = a22 * a11 - a12 * a21;
-        let b11 = -a22 * a10 + a12 * a20;
-        let b21 = a21 * a10 - a11 * a20;
-
-        // Calculate the determinant
-        let mut det = a00 * b01 + a01 * b11 + a02 * b21;
-
-        if det.abs() < EPSILON {
-            return;
+
+        impl PartialEq for $Matrix3 {
+            fn eq(&self, other: &$Matrix3) -> bool {
+                $Matrix3::exactEquals(self, other)
+            }
         }
-        det = 1.0 / det;
-
-        out.0 = b01 * det;
-        out.1 = (-a22 * a01 + a02 * a21) * det;
-        out.2 = (a12 * a01 - a02 * a11) * det;
-        out.3 = b11 * det;
-        out.4 = (a22 * a00 - a02 * a20) * det;
-        out.5 = (-a12 * a00 + a02 * a10) * det;
-        out.6 = b21 * det;
-        out.7 = (-a21 * a00 + a01 * a20) * det;
-        out.8 = (a11 * a00 - a01 * a10) * det;
-    }
-
-    pub fn adjoint(out: &mut Matrix3, a: &Matrix3) {
-        let a00 = a.0;
-        let a01 = a.1;
-        let a02 = a.2;
-        let a10 = a.3;
-        let a11 = a.4;
-        let a12 = a.5;
-        let a20 = a.6;
-        let a21 = a.7;
-        let a22 = a.8;
-
-        out.0 = (a11 * a22 - a12 * a21);
-        out.1 = (a02 * a21 - a01 * a22);
-        out.2 = (a01 * a12 - a02 * a11);
-        out.3 = (a12 * a20 - a10 * a22);
-        out.4 = (a00 * a22 - a02 * a20);
-        out.5 = (a02 * a10 - a00 * a12);
-        out.6 = (a10 * a21 - a11 * a20);
-        out.7 = (a01 * a20 - a00 * a21);
-        out.8 = (a00 * a11 - a01 * a10);
-    }
-
-    pub fn determinant(a: &Matrix3) -> f32 {
-        let a00 = a.0;
-        let a01 = a.1;
-        let a02 = a.2;
-        let a10 = a.3;
-        let a11 = a.4;
-        let a12 = a.5;
-        let a20 = a.6;
-        let a21 = a.7;
-        let a22 = a.8;
-
-        a00 * (a22 * a11 - a12 * a21)
-            + a01 * (-a22 * a10 + a12 * a20)
-            + a02 * (a21 * a10 - a11 * a20)
-    }
-
-    pub fn multiply(out: &mut Matrix3, a: &Matrix3, b: &Matrix3) {
-        let a00 = a.0;
-        let a01 = a.1;
-        let a02 = a.2;
-        let a10 = a.3;
-        let a11 = a.4;
-        let a12 = a.5;
-        let a20 = a.6;
-        let a21 = a.7;
-        let a22 = a.8;
-
-        let b00 = b.0;
-        let b01 = b.1;
-        let b02 = b.2;
-        let b10 = b.3;
-        let b11 = b.4;
-        let b12 = b.5;
-        let b20 = b.6;
-        let b21 = b.7;
-        let b22 = b.8;
-
-        out.0 = b00 * a00 + b01 * a10 + b02 * a20;
-        out.1 = b00 * a01 + b01 * a11 + b02 * a21;
-        out.2 = b00 * a02 + b01 * a12 + b02 * a22;
-
-        out.3 = b10 * a00 + b11 * a10 + b12 * a20;
-        out.4 = b10 * a01 + b11 * a11 + b12 * a21;
-        out.5 = b10 * a02 + b11 * a12 + b12 * a22;
-
-        out.6 = b20 * a00 + b21 * a10 + b22 * a20;
-        out.7 = b20 * a01 + b21 * a11 + b22 * a21;
-        out.8 = b20 * a02 + b21 * a12 + b22 * a22;
-    }
-
-    pub fn translate(out: &mut Matrix3, a: &Matrix3, v: &Vector2) {
-        let a00 = a.0;
-        let a01 = a.1;
-        let a02 = a.2;
-        let a10 = a.3;
-        let a11 = a.4;
-        let a12 = a.5;
-        let a20 = a.6;
-        let a21 = a.7;
-        let a22 = a.8;
-        let x = v.0;
-        let y = v.1;
-
-        out.0 = a00;
-        out.1 = a01;
-        out.2 = a02;
-
-        out.3 = a10;
-        out.4 = a11;
-        out.5 = a12;
-
-        out.6 = x * a00 + y * a10 + a20;
-        out.7 = x * a01 + y * a11 + a21;
-        out.8 = x * a02 + y * a12 + a22;
-    }
-
-    pub fn rotate(out: &mut Matrix3, a: &Matrix3, rad: f32) {
-        let a00 = a.0;
-        let a01 = a.1;
-        let a02 = a.2;
-        let a10 = a.3;
-        let a11 = a.4;
-        let a12 = a.5;
-        let a20 = a.6;
-        let a21 = a.7;
-        let a22 = a.8;
-        let s = f32::sin(rad);
-        let c = f32::cos(rad);
-
-        out.0 = c * a00 + s * a10;
-        out.1 = c * a01 + s * a11;
-        out.2 = c * a02 + s * a12;
-
-        out.3 = c * a10 - s * a00;
-        out.4 = c * a11 - s * a01;
-        out.5 = c * a12 - s * a02;
-
-        out.6 = a20;
-        out.7 = a21;
-        out.8 = a22;
-    }
-
-    pub fn scale(out: &mut Matrix3, a: &Matrix3, v: &Vector2) {
-        let x = v.0;
-        let y = v.1;
-
-        out.0 = x * a.0;
-        out.1 = x * a.1;
-        out.2 = x * a.2;
-
-        out.3 = y * a.3;
-        out.4 = y * a.4;
-        out.5 = y * a.5;
-
-        out.6 = a.6;
-        out.7 = a.7;
-        out.8 = a.8;
-    }
-
-    pub fn fromTranslation(out: &mut Matrix3, v: &Vector2) {
-        out.0 = 1.;
-        out.1 = 0.;
-        out.2 = 0.;
-        out.3 = 0.;
-        out.4 = 1.;
-        out.5 = 0.;
-        out.6 = v.0;
-        out.7 = v.1;
-        out.8 = 1.;
-    }
-
-    pub fn fromRotation(out: &mut Matrix3, rad: f32) {
-        let s = f32::sin(rad);
-        let c = f32::cos(rad);
-
-        out.0 = c;
-        out.1 = s;
-        out.2 = 0.;
-
-        out.3 = -s;
-        out.4 = c;
-        out.5 = 0.;
-
-        out.6 = 0.;
-        out.7 = 0.;
-        out.8 = 1.;
-    }
-
-    pub fn fromScaling(out: &mut Matrix3, v: &Vector2) {
-        out.0 = v.0;
-        out.1 = 0.;
-        out.2 = 0.;
-
-        out.3 = 0.;
-        out.4 = v.1;
-        out.5 = 0.;
-
-        out.6 = 0.;
-        out.7 = 0.;
-        out.8 = 1.;
-    }
-
-    pub fn fromMat2d(out: &mut Matrix3, a: &Matrix2d) {
-        out.0 = a.0;
-        out.1 = a.1;
-        out.2 = 0.;
-
-        out.3 = a.2;
-        out.4 = a.3;
-        out.5 = 0.;
-
-        out.6 = a.4;
-        out.7 = a.5;
-        out.8 = 1.;
-    }
-
-    pub fn fromQuat(out: &mut Matrix3, q: &Quaternion) {
-        let x = q.0;
-        let y = q.1;
-        let z = q.2;
-        let w = q.3;
-        let x2 = x + x;
-        let y2 = y + y;
-        let z2 = z + z;
-
-        let xx = x * x2;
-        let yx = y * x2;
-        let yy = y * y2;
-        let zx = z * x2;
-        let zy = z * y2;
-        let zz = z * z2;
-        let wx = w * x2;
-        let wy = w * y2;
-        let wz = w * z2;
-
-        out.0 = 1. - yy - zz;
-        out.3 = yx - wz;
-        out.6 = zx + wy;
-
-        out.1 = yx + wz;
-        out.4 = 1. - xx - zz;
-        out.7 = zy - wx;
-
-        out.2 = zx - wy;
-        out.5 = zy + wx;
-        out.8 = 1. - xx - yy;
-    }
-
-    pub fn normalFromMat4(out: &mut Matrix3, a: &Matrix4) {
-        let a00 = a.0;
-        let a01 = a.1;
-        let a02 = a.2;
-        let a03 = a.3;
-        let a10 = a.4;
-        let a11 = a.5;
-        let a12 = a.6;
-        let a13 = a.7;
-        let a20 = a.8;
-        let a21 = a.9;
-        let a22 = a.10;
-        let a23 = a.11;
-        let a30 = a.12;
-        let a31 = a.13;
-        let a32 = a.14;
-        let a33 = a.15;
-
-        let b00 = a00 * a11 - a01 * a10;
-        let b01 = a00 * a12 - a02 * a10;
-        let b02 = a00 * a13 - a03 * a10;
-        let b03 = a01 * a12 - a02 * a11;
-        let b04 = a01 * a13 - a03 * a11;
-        let b05 = a02 * a13 - a03 * a12;
-        let b06 = a20 * a31 - a21 * a30;
-        let b07 = a20 * a32 - a22 * a30;
-        let b08 = a20 * a33 - a23 * a30;
-        let b09 = a21 * a32 - a22 * a31;
-        let b10 = a21 * a33 - a23 * a31;
-        let b11 = a22 * a33 - a23 * a32;
-
-        // Calculate the determinant
-        let mut det = b00 * b11 - b01 * b10 + b02 * b09 + b03 * b08 - b04 * b07 + b05 * b06;
-
-        if det.abs() < EPSILON {
-            return;
+
+        impl ApproxEq for $Matrix3 {
+            fn approx_eq(&self, other: &$Matrix3) -> bool {
+                $Matrix3::equals(self, other)
+            }
+        }
+
+        impl Default for $Matrix3 {
+            fn default() -> $Matrix3 {
+                $Matrix3::create()
+            }
         }
-        det = 1.0 / det;
-
-        out.0 = (a11 * b11 - a12 * b10 + a13 * b09) * det;
-        out.1 = (a12 * b08 - a10 * b11 - a13 * b07) * det;
-        out.2 = (a10 * b10 - a11 * b08 + a13 * b06) * det;
-
-        out.3 = (a02 * b10 - a01 * b11 - a03 * b09) * det;
-        out.4 = (a00 * b11 - a02 * b08 + a03 * b07) * det;
-        out.5 = (a01 * b08 - a00 * b10 - a03 * b06) * det;
-
-        out.6 = (a31 * b05 - a32 * b04 + a33 * b03) * det;
-        out.7 = (a32 * b02 - a30 * b05 - a33 * b01) * det;
-        out.8 = (a30 * b04 - a31 * b02 + a33 * b00) * det;
-    }
-
-    pub fn projection(out: &mut Matrix3, width: f32, height: f32) {
-        out.0 = 2. / width;
-        out.1 = 0.;
-        out.2 = 0.;
-        out.3 = 0.;
-        out.4 = -2. / height;
-        out.5 = 0.;
-        out.6 = -1.;
-        out.7 = 1.;
-        out.8 = 1.;
-    }
-
-    // pub fn str(a: &Matrix3) {
-    //   return "mat3(" + a.0 + ", " + a.1 + ", " + a.2 + ", " +
-    //           a.3 + ", " + a.4 + ", " + a.5 + ", " +
-    //           a.6 + ", " + a.7 + ", " + a.8 + ")";
-    // }
-
-    pub fn frob(a: &Matrix3) -> f32 {
-        (a.0.powi(2)
-            + a.1.powi(2)
-            + a.2.powi(2)
-            + a.3.powi(2)
-            + a.4.powi(2)
-            + a.5.powi(2)
-            + a.6.powi(2)
-            + a.7.powi(2)
-            + a.8.powi(2))
-        .sqrt()
-    }
-
-    pub fn add(out: &mut Matrix3, a: &Matrix3, b: &Matrix3) {
-        out.0 = a.0 + b.0;
-        out.1 = a.1 + b.1;
-        out.2 = a.2 + b.2;
-        out.3 = a.3 + b.3;
-        out.4 = a.4 + b.4;
-        out.5 = a.5 + b.5;
-        out.6 = a.6 + b.6;
-        out.7 = a.7 + b.7;
-        out.8 = a.8 + b.8;
-    }
-
-    pub fn subtract(out: &mut Matrix3, a: &Matrix3, b: &Matrix3) {
-        out.0 = a.0 - b.0;
-        out.1 = a.1 - b.1;
-        out.2 = a.2 - b.2;
-        out.3 = a.3 - b.3;
-        out.4 = a.4 - b.4;
-        out.5 = a.5 - b.5;
-        out.6 = a.6 - b.6;
-        out.7 = a.7 - b.7;
-        out.8 = a.8 - b.8;
-    }
-
-    pub fn multiplyScalar(out: &mut Matrix3, a: &Matrix3, b: f32) {
-        out.0 = a.0 * b;
-        out.1 = a.1 * b;
-        out.2 = a.2 * b;
-        out.3 = a.3 * b;
-        out.4 = a.4 * b;
-        out.5 = a.5 * b;
-        out.6 = a.6 * b;
-        out.7 = a.7 * b;
-        out.8 = a.8 * b;
-    }
-
-    pub fn multiplyScalarAndAdd(out: &mut Matrix3, a: &Matrix3, b: &Matrix3, scale: f32) {
-        out.0 = a.0 + (b.0 * scale);
-        out.1 = a.1 + (b.1 * scale);
-        out.2 = a.2 + (b.2 * scale);
-        out.3 = a.3 + (b.3 * scale);
-        out.4 = a.4 + (b.4 * scale);
-        out.5 = a.5 + (b.5 * scale);
-        out.6 = a.6 + (b.6 * scale);
-        out.7 = a.7 + (b.7 * scale);
-        out.8 = a.8 + (b.8 * scale);
-    }
-
-    pub fn exactEquals(a: &Matrix3, b: &Matrix3) -> bool {
-        a.0 == b.0
-            && a.1 == b.1
-            && a.2 == b.2
-            && a.3 == b.3
-            && a.4 == b.4
-            && a.5 == b.5
-            && a.6 == b.6
-            && a.7 == b.7
-            && a.8 == b.8
-    }
-
-    pub fn equals(a: &Matrix3, b: &Matrix3) -> bool {
-        let a0 = a.0;
-        let a1 = a.1;
-        let a2 = a.2;
-        let a3 = a.3;
-        let a4 = a.4;
-        let a5 = a.5;
-        let a6 = a.6;
-        let a7 = a.7;
-        let a8 = a.8;
-        let b0 = b.0;
-        let b1 = b.1;
-        let b2 = b.2;
-        let b3 = b.3;
-        let b4 = b.4;
-        let b5 = b.5;
-        let b6 = b.6;
-        let b7 = b.7;
-        let b8 = b.8;
-        f32::abs(a0 - b0) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a0), f32::abs(b0)))
-            && f32::abs(a1 - b1) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a1), f32::abs(b1)))
-            && f32::abs(a2 - b2) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a2), f32::abs(b2)))
-            && f32::abs(a3 - b3) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a3), f32::abs(b3)))
-            && f32::abs(a4 - b4) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a4), f32::abs(b4)))
-            && f32::abs(a5 - b5) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a5), f32::abs(b5)))
-            && f32::abs(a6 - b6) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a6), f32::abs(b6)))
-            && f32::abs(a7 - b7) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a7), f32::abs(b7)))
-            && f32::abs(a8 - b8) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a8), f32::abs(b8)))
-    }
-
-    pub fn mul(out: &mut Matrix3, a: &Matrix3, b: &Matrix3) {
-        Matrix3::multiply(out, a, b);
-    }
-
-    pub fn sub(out: &mut Matrix3, a: &Matrix3, b: &Matrix3) {
-        Matrix3::subtract(out, a, b);
-    }
+
+        impl fmt::Display for $Matrix3 {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "mat3({}, {}, {}, {}, {}, {}, {}, {}, {})",
+                    self.0, self.1, self.2, self.3, self.4, self.5, self.6, self.7, self.8
+                )
+            }
+        }
+
+        impl FromStr for $Matrix3 {
+            type Err = ParseError;
+
+            fn from_str(s: &str) -> Result<$Matrix3, ParseError> {
+                let mut v = [0.; 9];
+                parse_values(s, "mat3", &mut v)?;
+                Ok($Matrix3(
+                    v[0], v[1], v[2], v[3], v[4], v[5], v[6], v[7], v[8],
+                ))
+            }
+        }
+
+        #[cfg(test)]
+        mod tests {
+            // Some expected values are copied from the gl-matrix specs at double precision.
+            #![allow(clippy::excessive_precision)]
+            use super::*;
+            use crate::spec_helper::*;
+            use std::$F::consts::PI;
+
+            fn setup() -> ($Matrix3, $Matrix3, $Matrix3) {
+                (
+                    $Matrix3::fromValues(0., 0., 0., 0., 0., 0., 0., 0., 0.),
+                    $Matrix3::fromValues(1., 0., 0., 0., 1., 0., 1., 2., 1.),
+                    $Matrix3::fromValues(1., 0., 0., 0., 1., 0., 3., 4., 1.),
+                )
+            }
+
+            #[test]
+            fn normalFromMat4() {
+                let (out, _, _) = &mut setup();
+                let matA = &mut $Matrix4::create();
+                $Matrix3::normalFromMat4(out, matA);
+                expect_equalish(&out.elements(), &[1., 0., 0., 0., 1., 0., 0., 0., 1.]);
+
+                $Matrix4::translate(
+                    matA,
+                    &$Matrix4::clone(matA),
+                    &$Vector3::fromValues(2., 4., 6.),
+                );
+                $Matrix4::rotateX(matA, &$Matrix4::clone(matA), PI / 2.);
+                $Matrix3::normalFromMat4(out, matA);
+                expect_equalish(&out.elements(), &[1., 0., 0., 0., 0., 1., 0., -1., 0.]);
+
+                $Matrix4::scale(
+                    matA,
+                    &$Matrix4::clone(matA),
+                    &$Vector3::fromValues(2., 3., 4.),
+                );
+                $Matrix3::normalFromMat4(out, matA);
+                expect_equalish(
+                    &out.elements(),
+                    &[0.5, 0., 0., 0., 0., 0.333333, 0., -0.25, 0.],
+                );
+            }
+
+            #[test]
+            fn fromQuat() {
+                let (out, _, _) = &mut setup();
+                let q = &$Quaternion::fromValues(0., -0.7071067811865475, 0., 0.7071067811865475);
+                $Matrix3::fromQuat(out, q);
+
+                let v = &mut $Vector3::create();
+                $Vector3::transformMat3(v, &$Vector3::fromValues(0., 0., -1.), out);
+                let v2 = &mut $Vector3::create();
+                $Vector3::transformQuat(v2, &$Vector3::fromValues(0., 0., -1.), q);
+                expect_equalish(&v.elements(), &v2.elements());
+                expect_equalish(&v.elements(), &[1., 0., 0.]);
+            }
+
+            #[test]
+            fn euler() {
+                let (out, _, _) = &mut setup();
+                let orders = [
+                    EulerOrder::XYZ,
+                    EulerOrder::XZY,
+                    EulerOrder::YXZ,
+                    EulerOrder::YZX,
+                    EulerOrder::ZXY,
+                    EulerOrder::ZYX,
+                    EulerOrder::ExtrinsicXYZ,
+                    EulerOrder::ExtrinsicZYX,
+                ];
+                let angles = &mut $Vector3::create();
+                for &order in orders.iter() {
+                    $Matrix3::fromEuler(out, 0.3, -0.5, 1.1, order, AngleUnit::Radians);
+                    $Matrix3::getEuler(angles, out, order, AngleUnit::Radians);
+                    expect_equalish(&angles.elements(), &[0.3, -0.5, 1.1]);
+                }
+
+                // Rotating about the fixed axes in reverse order is the same rotation.
+                let extrinsic = &mut $Matrix3::create();
+                $Matrix3::fromEuler(out, 0.3, -0.5, 1.1, EulerOrder::YXZ, AngleUnit::Radians);
+                $Matrix3::fromEuler(
+                    extrinsic,
+                    0.3,
+                    -0.5,
+                    1.1,
+                    EulerOrder::ExtrinsicZXY,
+                    AngleUnit::Radians,
+                );
+                expect_equalish(&out.elements(), &extrinsic.elements());
+
+                // At gimbal lock the last angle is folded into the first one.
+                $Matrix3::fromEuler(out, 20., 90., 30., EulerOrder::XYZ, AngleUnit::Degrees);
+                $Matrix3::getEuler(angles, out, EulerOrder::XYZ, AngleUnit::Degrees);
+                assert!($F::abs(angles.1 - 90.) < 0.1);
+                assert_eq!(angles.2, 0.);
+                let locked = &mut $Matrix3::create();
+                $Matrix3::fromEuler(
+                    locked,
+                    angles.0,
+                    angles.1,
+                    angles.2,
+                    EulerOrder::XYZ,
+                    AngleUnit::Degrees,
+                );
+                expect_equalish(&locked.elements(), &out.elements());
+            }
+
+            #[test]
+            fn fromMat4() {
+                let (out, _, _) = &mut setup();
+                $Matrix3::fromMat4(
+                    out,
+                    &$Matrix4::fromValues(
+                        1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                    ),
+                );
+                expect_equalish(&out.elements(), &[1., 2., 3., 5., 6., 7., 9., 10., 11.]);
+            }
+
+            #[test]
+            fn scale() {
+                let (out, matA, _) = &mut setup();
+                $Matrix3::scale(out, matA, &$Vector2::fromValues(2., 2.));
+                expect_equalish(&out.elements(), &[2., 0., 0., 0., 2., 0., 1., 2., 1.]);
+            }
+
+            #[test]
+            fn create() {
+                let result = $Matrix3::create();
+                expect_equalish(&result.elements(), &[1., 0., 0., 0., 1., 0., 0., 0., 1.]);
+            }
+
+            #[test]
+            fn clone() {
+                let (_, matA, _) = &mut setup();
+                let result = $Matrix3::clone(matA);
+                expect_equalish(&result.elements(), &matA.elements());
+            }
+
+            #[test]
+            fn copy() {
+                let (out, matA, _) = &mut setup();
+                $Matrix3::copy(out, matA);
+                expect_equalish(&out.elements(), &matA.elements());
+            }
+
+            #[test]
+            fn identity() {
+                let (out, _, _) = &mut setup();
+                $Matrix3::identity(out);
+                expect_equalish(&out.elements(), &[1., 0., 0., 0., 1., 0., 0., 0., 1.]);
+            }
+
+            #[test]
+            fn transpose() {
+                let (out, matA, _) = &mut setup();
+                $Matrix3::transpose(out, matA);
+                expect_equalish(&out.elements(), &[1., 0., 1., 0., 1., 2., 0., 0., 1.]);
+                expect_equalish(&matA.elements(), &[1., 0., 0., 0., 1., 0., 1., 2., 1.]);
+
+                $Matrix3::transpose(matA, &$Matrix3::clone(matA));
+                expect_equalish(&matA.elements(), &[1., 0., 1., 0., 1., 2., 0., 0., 1.]);
+            }
+
+            #[test]
+            fn invert() {
+                let (out, matA, _) = &mut setup();
+                assert!($Matrix3::invert(out, matA));
+                expect_equalish(&out.elements(), &[1., 0., 0., 0., 1., 0., -1., -2., 1.]);
+                expect_equalish(&matA.elements(), &[1., 0., 0., 0., 1., 0., 1., 2., 1.]);
+
+                assert!($Matrix3::invert(matA, &$Matrix3::clone(matA)));
+                expect_equalish(&matA.elements(), &[1., 0., 0., 0., 1., 0., -1., -2., 1.]);
+            }
+
+            #[test]
+            fn adjoint() {
+                let (out, matA, _) = &mut setup();
+                $Matrix3::adjoint(out, matA);
+                expect_equalish(&out.elements(), &[1., 0., 0., 0., 1., 0., -1., -2., 1.]);
+                expect_equalish(&matA.elements(), &[1., 0., 0., 0., 1., 0., 1., 2., 1.]);
+
+                $Matrix3::adjoint(matA, &$Matrix3::clone(matA));
+                expect_equalish(&matA.elements(), &[1., 0., 0., 0., 1., 0., -1., -2., 1.]);
+            }
+
+            #[test]
+            fn determinant() {
+                let (_, matA, _) = &mut setup();
+                assert_eq!($Matrix3::determinant(matA), 1.);
+            }
+
+            #[test]
+            fn multiply() {
+                let (out, matA, matB) = &mut setup();
+                $Matrix3::multiply(out, matA, matB);
+                expect_equalish(&out.elements(), &[1., 0., 0., 0., 1., 0., 4., 6., 1.]);
+                expect_equalish(&matA.elements(), &[1., 0., 0., 0., 1., 0., 1., 2., 1.]);
+                expect_equalish(&matB.elements(), &[1., 0., 0., 0., 1., 0., 3., 4., 1.]);
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix3::multiply(matA, &$Matrix3::clone(matA), matB);
+                expect_equalish(&matA.elements(), &[1., 0., 0., 0., 1., 0., 4., 6., 1.]);
+                expect_equalish(&matB.elements(), &[1., 0., 0., 0., 1., 0., 3., 4., 1.]);
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix3::multiply(matB, matA, &$Matrix3::clone(matB));
+                expect_equalish(&matB.elements(), &[1., 0., 0., 0., 1., 0., 4., 6., 1.]);
+                expect_equalish(&matA.elements(), &[1., 0., 0., 0., 1., 0., 1., 2., 1.]);
+            }
+
+            #[test]
+            fn frob() {
+                let (_, matA, _) = &mut setup();
+                assert_eq!(
+                    $Matrix3::frob(matA),
+                    $F::sqrt(
+                        $F::powi(1., 2)
+                            + $F::powi(0., 2)
+                            + $F::powi(0., 2)
+                            + $F::powi(0., 2)
+                            + $F::powi(1., 2)
+                            + $F::powi(0., 2)
+                            + $F::powi(1., 2)
+                            + $F::powi(2., 2)
+                            + $F::powi(1., 2)
+                    )
+                );
+            }
+
+            #[test]
+            fn add() {
+                let out = &mut $Matrix3::create();
+                let matA = &mut $Matrix3::fromValues(1., 2., 3., 4., 5., 6., 7., 8., 9.);
+                let matB = &mut $Matrix3::fromValues(10., 11., 12., 13., 14., 15., 16., 17., 18.);
+                $Matrix3::add(out, matA, matB);
+                expect_equalish(
+                    &out.elements(),
+                    &[11., 13., 15., 17., 19., 21., 23., 25., 27.],
+                );
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6., 7., 8., 9.]);
+                expect_equalish(
+                    &matB.elements(),
+                    &[10., 11., 12., 13., 14., 15., 16., 17., 18.],
+                );
+
+                $Matrix3::add(matA, &$Matrix3::clone(matA), matB);
+                expect_equalish(
+                    &matA.elements(),
+                    &[11., 13., 15., 17., 19., 21., 23., 25., 27.],
+                );
+                expect_equalish(
+                    &matB.elements(),
+                    &[10., 11., 12., 13., 14., 15., 16., 17., 18.],
+                );
+
+                let matA = &mut $Matrix3::fromValues(1., 2., 3., 4., 5., 6., 7., 8., 9.);
+                $Matrix3::add(matB, matA, &$Matrix3::clone(matB));
+                expect_equalish(
+                    &matB.elements(),
+                    &[11., 13., 15., 17., 19., 21., 23., 25., 27.],
+                );
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6., 7., 8., 9.]);
+            }
+
+            #[test]
+            fn subtract() {
+                let out = &mut $Matrix3::create();
+                let matA = &mut $Matrix3::fromValues(1., 2., 3., 4., 5., 6., 7., 8., 9.);
+                let matB = &mut $Matrix3::fromValues(10., 11., 12., 13., 14., 15., 16., 17., 18.);
+                $Matrix3::subtract(out, matA, matB);
+                expect_equalish(
+                    &out.elements(),
+                    &[-9., -9., -9., -9., -9., -9., -9., -9., -9.],
+                );
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6., 7., 8., 9.]);
+                expect_equalish(
+                    &matB.elements(),
+                    &[10., 11., 12., 13., 14., 15., 16., 17., 18.],
+                );
+
+                $Matrix3::subtract(matA, &$Matrix3::clone(matA), matB);
+                expect_equalish(
+                    &matA.elements(),
+                    &[-9., -9., -9., -9., -9., -9., -9., -9., -9.],
+                );
+                expect_equalish(
+                    &matB.elements(),
+                    &[10., 11., 12., 13., 14., 15., 16., 17., 18.],
+                );
+
+                let matA = &mut $Matrix3::fromValues(1., 2., 3., 4., 5., 6., 7., 8., 9.);
+                $Matrix3::subtract(matB, matA, &$Matrix3::clone(matB));
+                expect_equalish(
+                    &matB.elements(),
+                    &[-9., -9., -9., -9., -9., -9., -9., -9., -9.],
+                );
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6., 7., 8., 9.]);
+            }
+
+            #[test]
+            fn fromValues() {
+                let result = $Matrix3::fromValues(1., 2., 3., 4., 5., 6., 7., 8., 9.);
+                expect_equalish(&result.elements(), &[1., 2., 3., 4., 5., 6., 7., 8., 9.]);
+            }
+
+            #[test]
+            fn set() {
+                let (out, _, _) = &mut setup();
+                $Matrix3::set(out, 1., 2., 3., 4., 5., 6., 7., 8., 9.);
+                expect_equalish(&out.elements(), &[1., 2., 3., 4., 5., 6., 7., 8., 9.]);
+            }
+
+            #[test]
+            fn multiplyScalar() {
+                let out = &mut $Matrix3::create();
+                let matA = &mut $Matrix3::fromValues(1., 2., 3., 4., 5., 6., 7., 8., 9.);
+                $Matrix3::multiplyScalar(out, matA, 2.);
+                expect_equalish(&out.elements(), &[2., 4., 6., 8., 10., 12., 14., 16., 18.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6., 7., 8., 9.]);
+
+                $Matrix3::multiplyScalar(matA, &$Matrix3::clone(matA), 2.);
+                expect_equalish(&matA.elements(), &[2., 4., 6., 8., 10., 12., 14., 16., 18.]);
+            }
+
+            #[test]
+            fn multiplyScalarAndAdd() {
+                let out = &mut $Matrix3::create();
+                let matA = &mut $Matrix3::fromValues(1., 2., 3., 4., 5., 6., 7., 8., 9.);
+                let matB = &mut $Matrix3::fromValues(10., 11., 12., 13., 14., 15., 16., 17., 18.);
+                $Matrix3::multiplyScalarAndAdd(out, matA, matB, 0.5);
+                expect_equalish(
+                    &out.elements(),
+                    &[6., 7.5, 9., 10.5, 12., 13.5, 15., 16.5, 18.],
+                );
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6., 7., 8., 9.]);
+                expect_equalish(
+                    &matB.elements(),
+                    &[10., 11., 12., 13., 14., 15., 16., 17., 18.],
+                );
+
+                $Matrix3::multiplyScalarAndAdd(matA, &$Matrix3::clone(matA), matB, 0.5);
+                expect_equalish(
+                    &matA.elements(),
+                    &[6., 7.5, 9., 10.5, 12., 13.5, 15., 16.5, 18.],
+                );
+                expect_equalish(
+                    &matB.elements(),
+                    &[10., 11., 12., 13., 14., 15., 16., 17., 18.],
+                );
+
+                let matA = &mut $Matrix3::fromValues(1., 2., 3., 4., 5., 6., 7., 8., 9.);
+                $Matrix3::multiplyScalarAndAdd(matB, matA, &$Matrix3::clone(matB), 0.5);
+                expect_equalish(
+                    &matB.elements(),
+                    &[6., 7.5, 9., 10.5, 12., 13.5, 15., 16.5, 18.],
+                );
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6., 7., 8., 9.]);
+            }
+
+            #[test]
+            fn projection() {
+                let (out, _, _) = &mut setup();
+                $Matrix3::projection(out, 100., 200.);
+                expect_equalish(&out.elements(), &[0.02, 0., 0., 0., -0.01, 0., -1., 1., 1.]);
+            }
+
+            #[test]
+            fn exactEquals() {
+                let matA = &$Matrix3::fromValues(0., 1., 2., 3., 4., 5., 6., 7., 8.);
+                let matB = &$Matrix3::fromValues(0., 1., 2., 3., 4., 5., 6., 7., 8.);
+                let matC = &$Matrix3::fromValues(1., 2., 3., 4., 5., 6., 7., 8., 9.);
+                assert!($Matrix3::exactEquals(matA, matB));
+                assert!(!$Matrix3::exactEquals(matA, matC));
+                expect_equalish(&matA.elements(), &[0., 1., 2., 3., 4., 5., 6., 7., 8.]);
+                expect_equalish(&matB.elements(), &[0., 1., 2., 3., 4., 5., 6., 7., 8.]);
+            }
+
+            #[test]
+            fn equals() {
+                let matA = &$Matrix3::fromValues(0., 1., 2., 3., 4., 5., 6., 7., 8.);
+                let matB = &$Matrix3::fromValues(0., 1., 2., 3., 4., 5., 6., 7., 8.);
+                let matC = &$Matrix3::fromValues(1., 2., 3., 4., 5., 6., 7., 8., 9.);
+                let matD = &$Matrix3::fromValues(1e-16, 1., 2., 3., 4., 5., 6., 7., 8.);
+                assert!($Matrix3::equals(matA, matB));
+                assert!(!$Matrix3::equals(matA, matC));
+                assert!($Matrix3::equals(matA, matD));
+                expect_equalish(&matA.elements(), &[0., 1., 2., 3., 4., 5., 6., 7., 8.]);
+                expect_equalish(&matB.elements(), &[0., 1., 2., 3., 4., 5., 6., 7., 8.]);
+            }
+
+            #[test]
+            fn traits() {
+                let a = $Matrix3::fromValues(1., 2., 3., 4., 5., 6., 7., 8., 9.);
+                let b = a;
+                assert_eq!(a, b);
+                assert_ne!(a, $Matrix3::fromValues(1., 2., 3., 4., 5., 6., 7., 8., 10.));
+                assert_ne!(
+                    a,
+                    $Matrix3::fromValues(1., 2., 3., 4., 5., 6., 7., 8., 9.00001)
+                );
+                assert!(a.approx_eq(&$Matrix3::fromValues(
+                    1., 2., 3., 4., 5., 6., 7., 8., 9.00001
+                )));
+                assert!(!a.approx_eq(&$Matrix3::fromValues(1., 2., 3., 4., 5., 6., 7., 8., 10.)));
+                assert_eq!($Matrix3::default(), $Matrix3::create());
+                assert_eq!(
+                    format!("{:?}", $Matrix3::create()),
+                    concat!(
+                        stringify!($Matrix3),
+                        "(1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0)"
+                    )
+                );
+            }
+
+            #[test]
+            fn str() {
+                let a = $Matrix3::fromValues(1., 0., 0., 0., 1., 0., 1., 2., 1.);
+                assert_eq!($Matrix3::str(&a), "mat3(1, 0, 0, 0, 1, 0, 1, 2, 1)");
+                assert_eq!("mat3(1, 0, 0, 0, 1, 0, 1, 2, 1)".parse::<$Matrix3>(), Ok(a));
+            }
+
+            #[test]
+            fn tryInvert() {
+                let (out, _, _) = &mut setup();
+                let singular = &$Matrix3::fromValues(1., 2., 3., 2., 4., 6., 0., 0., 1.);
+                assert!(!$Matrix3::invert(out, singular));
+                // A zero tolerance still rejects a zero or NaN determinant.
+                assert!(!$Matrix3::tryInvert(out, singular, 0.));
+                assert!(!$Matrix3::tryInvert(
+                    out,
+                    &$Matrix3::fromValues($F::NAN, 0., 0., 0., 1., 0., 0., 0., 1.),
+                    0.
+                ));
+                expect_equalish(&out.elements(), &[0., 0., 0., 0., 0., 0., 0., 0., 0.]);
+
+                let small =
+                    &$Matrix3::fromValues(0.0078125, 0., 0., 0., 0.0078125, 0., 0., 0., 0.0078125);
+                assert!(!$Matrix3::tryInvert(out, small, 1.));
+                expect_equalish(&out.elements(), &[0., 0., 0., 0., 0., 0., 0., 0., 0.]);
+                assert!($Matrix3::tryInvert(out, small, 1e-12));
+                expect_equalish(&out.elements(), &[128., 0., 0., 0., 128., 0., 0., 0., 128.]);
+
+                $Matrix3::set(out, 0., 0., 0., 0., 0., 0., 0., 0., 0.);
+                assert!(!$Matrix3::invert(out, small));
+                expect_equalish(&out.elements(), &[0., 0., 0., 0., 0., 0., 0., 0., 0.]);
+            }
+
+            #[test]
+            fn equalsWith() {
+                let a = &$Matrix3::fromValues(1., 2., 3., 4., 5., 6., 7., 8., 9.);
+                let b = &$Matrix3::fromValues(1.001, 2., 3., 4., 5., 6., 7., 8., 9.);
+                let c = &$Matrix3::fromValues(
+                    $F::from_bits($F::to_bits(1.) + 2),
+                    2.,
+                    3.,
+                    4.,
+                    5.,
+                    6.,
+                    7.,
+                    8.,
+                    9.,
+                );
+                assert!(!$Matrix3::equalsWithEpsilon(a, b, 0.0001));
+                assert!($Matrix3::equalsWithEpsilon(a, b, 0.01));
+                assert!($Matrix3::equalsWithUlps(a, c, 2));
+                assert!(!$Matrix3::equalsWithUlps(a, c, 1));
+                assert!(!$Matrix3::equalsWithUlps(a, b, 4));
+            }
+
+            #[test]
+            fn slice() {
+                let a = $Matrix3::fromValues(1., 2., 3., 4., 5., 6., 7., 8., 9.);
+                assert_eq!(a.asSlice(), &a.elements()[..]);
+
+                let buffer = &mut [0.; 10];
+                a.writeSlice(&mut buffer[1..]);
+                assert_eq!($Matrix3::fromSlice(&buffer[1..]), a);
+            }
+        }
+    };
 }
+
+pub(crate) use define_matrix3;
+
+define_matrix3!(
+    f32,
+    Float32Array,
+    fromFloat32Array,
+    Matrix2,
+    Matrix2d,
+    Matrix3,
+    Matrix4,
+    Quaternion,
+    Quaternion2,
+    Vector2,
+    Vector3,
+    Vector4
+);
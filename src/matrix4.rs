@@ -4,1489 +4,3540 @@
  * @Link   : http://dtysky.moe
  * @Date   : 2019/2/7 下午9:53:03
  */
-use wasm_bindgen::prelude::*;
-
-use super::common::*;
-use super::quaternion::*;
-use super::quaternion2::*;
-use super::vector3::*;
-
-#[wasm_bindgen]
-pub struct Matrix4(
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-);
+// `Matrix4` here and `Matrix4d` in `double`. `$F` is the element type, `$FloatArray` the js array of it
+// that `$fromFloatArray` reads, and the other names are the types of the same precision.
+macro_rules! define_matrix4 {
+    (
+        $F:ident,
+        $FloatArray:ident,
+        $fromFloatArray:ident,
+        $Matrix2:ident,
+        $Matrix2d:ident,
+        $Matrix3:ident,
+        $Matrix4:ident,
+        $Quaternion:ident,
+        $Quaternion2:ident,
+        $Vector2:ident,
+        $Vector3:ident,
+        $Vector4:ident
+    ) => {
+        #[cfg(feature = "wasm")]
+        use js_sys::$FloatArray;
+        #[cfg(feature = "wasm")]
+        use wasm_bindgen::prelude::*;
+
+        use core::fmt;
+        use core::str::FromStr;
+
+        use super::common::*;
+        use super::matrix3::*;
+        use super::quaternion::*;
+        use super::quaternion2::*;
+        use super::vector3::*;
+        use super::vector4::*;
+        use crate::kernels::{self, Elements};
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        #[derive(Clone, Copy, Debug)]
+        #[repr(C)]
+        pub struct $Matrix4(
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+        );
 
-#[wasm_bindgen]
-impl Matrix4 {
-    #[wasm_bindgen(getter)]
-    pub fn elements(&self) -> Box<[f32]> {
-        Box::new([
-            self.0, self.1, self.2, self.3, self.4, self.5, self.6, self.7, self.8, self.9,
-            self.10, self.11, self.12, self.13, self.14, self.15,
-        ])
-    }
-
-    pub fn create() -> Matrix4 {
-        Matrix4(
-            1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1.,
-        )
-    }
-
-    pub fn clone(a: &Matrix4) -> Matrix4 {
-        Matrix4(
-            a.0, a.1, a.2, a.3, a.4, a.5, a.6, a.7, a.8, a.9, a.10, a.11, a.12, a.13, a.14, a.15,
-        )
-    }
-
-    pub fn copy(out: &mut Matrix4, a: &Matrix4) {
-        out.0 = a.0;
-        out.1 = a.1;
-        out.2 = a.2;
-        out.3 = a.3;
-        out.4 = a.4;
-        out.5 = a.5;
-        out.6 = a.6;
-        out.7 = a.7;
-        out.8 = a.8;
-        out.9 = a.9;
-        out.10 = a.10;
-        out.11 = a.11;
-        out.12 = a.12;
-        out.13 = a.13;
-        out.14 = a.14;
-        out.15 = a.15;
-    }
-
-    pub fn fromValues(
-        m00: f32,
-        m01: f32,
-        m02: f32,
-        m03: f32,
-        m10: f32,
-        m11: f32,
-        m12: f32,
-        m13: f32,
-        m20: f32,
-        m21: f32,
-        m22: f32,
-        m23: f32,
-        m30: f32,
-        m31: f32,
-        m32: f32,
-        m33: f32,
-    ) -> Matrix4 {
-        Matrix4(
-            m00, m01, m02, m03, m10, m11, m12, m13, m20, m21, m22, m23, m30, m31, m32, m33,
-        )
-    }
-
-    pub fn set(
-        out: &mut Matrix4,
-        m00: f32,
-        m01: f32,
-        m02: f32,
-        m03: f32,
-        m10: f32,
-        m11: f32,
-        m12: f32,
-        m13: f32,
-        m20: f32,
-        m21: f32,
-        m22: f32,
-        m23: f32,
-        m30: f32,
-        m31: f32,
-        m32: f32,
-        m33: f32,
-    ) {
-        out.0 = m00;
-        out.1 = m01;
-        out.2 = m02;
-        out.3 = m03;
-        out.4 = m10;
-        out.5 = m11;
-        out.6 = m12;
-        out.7 = m13;
-        out.8 = m20;
-        out.9 = m21;
-        out.10 = m22;
-        out.11 = m23;
-        out.12 = m30;
-        out.13 = m31;
-        out.14 = m32;
-        out.15 = m33;
-    }
-
-    pub fn identity(out: &mut Matrix4) {
-        out.0 = 1.;
-        out.1 = 0.;
-        out.2 = 0.;
-        out.3 = 0.;
-        out.4 = 0.;
-        out.5 = 1.;
-        out.6 = 0.;
-        out.7 = 0.;
-        out.8 = 0.;
-        out.9 = 0.;
-        out.10 = 1.;
-        out.11 = 0.;
-        out.12 = 0.;
-        out.13 = 0.;
-        out.14 = 0.;
-        out.15 = 1.;
-    }
-
-    pub fn transpose(out: &mut Matrix4, a: &Matrix4) {
-        // If we are transposing ourselves we can skip a few steps but have to cache some values
-        if (out as *const Matrix4) == (a as *const Matrix4) {
-            let a01 = a.1;
-            let a02 = a.2;
-            let a03 = a.3;
-            let a12 = a.6;
-            let a13 = a.7;
-            let a23 = a.11;
-
-            out.1 = a.4;
-            out.2 = a.8;
-            out.3 = a.12;
-            out.4 = a01;
-            out.6 = a.9;
-            out.7 = a.13;
-            out.8 = a02;
-            out.9 = a12;
-            out.11 = a.14;
-            out.12 = a03;
-            out.13 = a13;
-            out.14 = a23;
-        } else {
-            out.0 = a.0;
-            out.1 = a.4;
-            out.2 = a.8;
-            out.3 = a.12;
-            out.4 = a.1;
-            out.5 = a.5;
-            out.6 = a.9;
-            out.7 = a.13;
-            out.8 = a.2;
-            out.9 = a.6;
-            out.10 = a.10;
-            out.11 = a.14;
-            out.12 = a.3;
-            out.13 = a.7;
-            out.14 = a.11;
-            out.15 = a.15;
-        }
-    }
-
-    pub fn invert(out: &mut Matrix4, a: &Matrix4) {
-        let a00 = a.0;
-        let a01 = a.1;
-        let a02 = a.2;
-        let a03 = a.3;
-        let a10 = a.4;
-        let a11 = a.5;
-        let a12 = a.6;
-        let a13 = a.7;
-        let a20 = a.8;
-        let a21 = a.9;
-        let a22 = a.10;
-        let a23 = a.11;
-        let a30 = a.12;
-        let a31 = a.13;
-        let a32 = a.14;
-        let a33 = a.15;
-
-        let b00 = a00 * a11 - a01 * a10;
-        let b01 = a00 * a12 - a02 * a10;
-        let b02 = a00 * a13 - a03 * a10;
-        let b03 = a01 * a12 - a02 * a11;
-        let b04 = a01 * a13 - a03 * a11;
-        let b05 = a02 * a13 - a03 * a12;
-        let b06 = a20 * a31 - a21 * a30;
-        let b07 = a20 * a32 - a22 * a30;
-        let b08 = a20 * a33 - a23 * a30;
-        let b09 = a21 * a32 - a22 * a31;
-        let b10 = a21 * a33 - a23 * a31;
-        let b11 = a22 * a33 - a23 * a32;
-
-        // Calculate the determinant
-        let mut det = b00 * b11 - b01 * b10 + b02 * b09 + b03 * b08 - b04 * b07 + b05 * b06;
-
-        if det.abs() < EPSILON {
-            return;
-        }
-        det = 1.0 / det;
-
-        out.0 = (a11 * b11 - a12 * b10 + a13 * b09) * det;
-        out.1 = (a02 * b10 - a01 * b11 - a03 * b09) * det;
-        out.2 = (a31 * b05 - a32 * b04 + a33 * b03) * det;
-        out.3 = (a22 * b04 - a21 * b05 - a23 * b03) * det;
-        out.4 = (a12 * b08 - a10 * b11 - a13 * b07) * det;
-        out.5 = (a00 * b11 - a02 * b08 + a03 * b07) * det;
-        out.6 = (a32 * b02 - a30 * b05 - a33 * b01) * det;
-        out.7 = (a20 * b05 - a22 * b02 + a23 * b01) * det;
-        out.8 = (a10 * b10 - a11 * b08 + a13 * b06) * det;
-        out.9 = (a01 * b08 - a00 * b10 - a03 * b06) * det;
-        out.10 = (a30 * b04 - a31 * b02 + a33 * b00) * det;
-        out.11 = (a21 * b02 - a20 * b04 - a23 * b00) * det;
-        out.12 = (a11 * b07 - a10 * b09 - a12 * b06) * det;
-        out.13 = (a00 * b09 - a01 * b07 + a02 * b06) * det;
-        out.14 = (a31 * b01 - a30 * b03 - a32 * b00) * det;
-        out.15 = (a20 * b03 - a21 * b01 + a22 * b00) * det;
-    }
-
-    pub fn adjoint(out: &mut Matrix4, a: &Matrix4) {
-        let a00 = a.0;
-        let a01 = a.1;
-        let a02 = a.2;
-        let a03 = a.3;
-        let a10 = a.4;
-        let a11 = a.5;
-        let a12 = a.6;
-        let a13 = a.7;
-        let a20 = a.8;
-        let a21 = a.9;
-        let a22 = a.10;
-        let a23 = a.11;
-        let a30 = a.12;
-        let a31 = a.13;
-        let a32 = a.14;
-        let a33 = a.15;
-
-        out.0 = (a11 * (a22 * a33 - a23 * a32) - a21 * (a12 * a33 - a13 * a32)
-            + a31 * (a12 * a23 - a13 * a22));
-        out.1 = -(a01 * (a22 * a33 - a23 * a32) - a21 * (a02 * a33 - a03 * a32)
-            + a31 * (a02 * a23 - a03 * a22));
-        out.2 = (a01 * (a12 * a33 - a13 * a32) - a11 * (a02 * a33 - a03 * a32)
-            + a31 * (a02 * a13 - a03 * a12));
-        out.3 = -(a01 * (a12 * a23 - a13 * a22) - a11 * (a02 * a23 - a03 * a22)
-            + a21 * (a02 * a13 - a03 * a12));
-        out.4 = -(a10 * (a22 * a33 - a23 * a32) - a20 * (a12 * a33 - a13 * a32)
-            + a30 * (a12 * a23 - a13 * a22));
-        out.5 = (a00 * (a22 * a33 - a23 * a32) - a20 * (a02 * a33 - a03 * a32)
-            + a30 * (a02 * a23 - a03 * a22));
-        out.6 = -(a00 * (a12 * a33 - a13 * a32) - a10 * (a02 * a33 - a03 * a32)
-            + a30 * (a02 * a13 - a03 * a12));
-        out.7 = (a00 * (a12 * a23 - a13 * a22) - a10 * (a02 * a23 - a03 * a22)
-            + a20 * (a02 * a13 - a03 * a12));
-        out.8 = (a10 * (a21 * a33 - a23 * a31) - a20 * (a11 * a33 - a13 * a31)
-            + a30 * (a11 * a23 - a13 * a21));
-        out.9 = -(a00 * (a21 * a33 - a23 * a31) - a20 * (a01 * a33 - a03 * a31)
-            + a30 * (a01 * a23 - a03 * a21));
-        out.10 = (a00 * (a11 * a33 - a13 * a31) - a10 * (a01 * a33 - a03 * a31)
-            + a30 * (a01 * a13 - a03 * a11));
-        out.11 = -(a00 * (a11 * a23 - a13 * a21) - a10 * (a01 * a23 - a03 * a21)
-            + a20 * (a01 * a13 - a03 * a11));
-        out.12 = -(a10 * (a21 * a32 - a22 * a31) - a20 * (a11 * a32 - a12 * a31)
-            + a30 * (a11 * a22 - a12 * a21));
-        out.13 = (a00 * (a21 * a32 - a22 * a31) - a20 * (a01 * a32 - a02 * a31)
-            + a30 * (a01 * a22 - a02 * a21));
-        out.14 = -(a00 * (a11 * a32 - a12 * a31) - a10 * (a01 * a32 - a02 * a31)
-            + a30 * (a01 * a12 - a02 * a11));
-        out.15 = (a00 * (a11 * a22 - a12 * a21) - a10 * (a01 * a22 - a02 * a21)
-            + a20 * (a01 * a12 - a02 * a11));
-    }
-
-    pub fn determinant(a: &Matrix4) -> f32 {
-        let a00 = a.0;
-        let a01 = a.1;
-        let a02 = a.2;
-        let a03 = a.3;
-        let a10 = a.4;
-        let a11 = a.5;
-        let a12 = a.6;
-        let a13 = a.7;
-        let a20 = a.8;
-        let a21 = a.9;
-        let a22 = a.10;
-        let a23 = a.11;
-        let a30 = a.12;
-        let a31 = a.13;
-        let a32 = a.14;
-        let a33 = a.15;
-
-        let b00 = a00 * a11 - a01 * a10;
-        let b01 = a00 * a12 - a02 * a10;
-        let b02 = a00 * a13 - a03 * a10;
-        let b03 = a01 * a12 - a02 * a11;
-        let b04 = a01 * a13 - a03 * a11;
-        let b05 = a02 * a13 - a03 * a12;
-        let b06 = a20 * a31 - a21 * a30;
-        let b07 = a20 * a32 - a22 * a30;
-        let b08 = a20 * a33 - a23 * a30;
-        let b09 = a21 * a32 - a22 * a31;
-        let b10 = a21 * a33 - a23 * a31;
-        let b11 = a22 * a33 - a23 * a32;
-
-        // Calculate the determinant
-        b00 * b11 - b01 * b10 + b02 * b09 + b03 * b08 - b04 * b07 + b05 * b06
-    }
-
-    pub fn multiply(out: &mut Matrix4, a: &Matrix4, b: &Matrix4) {
-        let a00 = a.0;
-        let a01 = a.1;
-        let a02 = a.2;
-        let a03 = a.3;
-        let a10 = a.4;
-        let a11 = a.5;
-        let a12 = a.6;
-        let a13 = a.7;
-        let a20 = a.8;
-        let a21 = a.9;
-        let a22 = a.10;
-        let a23 = a.11;
-        let a30 = a.12;
-        let a31 = a.13;
-        let a32 = a.14;
-        let a33 = a.15;
-
-        // Cache only the current line of the second matrix
-        let mut b0 = b.0;
-        let mut b1 = b.1;
-        let mut b2 = b.2;
-        let mut b3 = b.3;
-        out.0 = b0 * a00 + b1 * a10 + b2 * a20 + b3 * a30;
-        out.1 = b0 * a01 + b1 * a11 + b2 * a21 + b3 * a31;
-        out.2 = b0 * a02 + b1 * a12 + b2 * a22 + b3 * a32;
-        out.3 = b0 * a03 + b1 * a13 + b2 * a23 + b3 * a33;
-
-        b0 = b.4;
-        b1 = b.5;
-        b2 = b.6;
-        b3 = b.7;
-        out.4 = b0 * a00 + b1 * a10 + b2 * a20 + b3 * a30;
-        out.5 = b0 * a01 + b1 * a11 + b2 * a21 + b3 * a31;
-        out.6 = b0 * a02 + b1 * a12 + b2 * a22 + b3 * a32;
-        out.7 = b0 * a03 + b1 * a13 + b2 * a23 + b3 * a33;
-
-        b0 = b.8;
-        b1 = b.9;
-        b2 = b.10;
-        b3 = b.11;
-        out.8 = b0 * a00 + b1 * a10 + b2 * a20 + b3 * a30;
-        out.9 = b0 * a01 + b1 * a11 + b2 * a21 + b3 * a31;
-        out.10 = b0 * a02 + b1 * a12 + b2 * a22 + b3 * a32;
-        out.11 = b0 * a03 + b1 * a13 + b2 * a23 + b3 * a33;
-
-        b0 = b.12;
-        b1 = b.13;
-        b2 = b.14;
-        b3 = b.15;
-        out.12 = b0 * a00 + b1 * a10 + b2 * a20 + b3 * a30;
-        out.13 = b0 * a01 + b1 * a11 + b2 * a21 + b3 * a31;
-        out.14 = b0 * a02 + b1 * a12 + b2 * a22 + b3 * a32;
-        out.15 = b0 * a03 + b1 * a13 + b2 * a23 + b3 * a33;
-    }
-
-    pub fn translate(out: &mut Matrix4, a: &Matrix4, v: &Vector3) {
-        let x = v.0;
-        let y = v.1;
-        let z = v.2;
-
-        if (out as *const Matrix4) == (a as *const Matrix4) {
-            out.12 = a.0 * x + a.4 * y + a.8 * z + a.12;
-            out.13 = a.1 * x + a.5 * y + a.9 * z + a.13;
-            out.14 = a.2 * x + a.6 * y + a.10 * z + a.14;
-            out.15 = a.3 * x + a.7 * y + a.11 * z + a.15;
-        } else {
-            let a00 = a.0;
-            let a01 = a.1;
-            let a02 = a.2;
-            let a03 = a.3;
-            let a10 = a.4;
-            let a11 = a.5;
-            let a12 = a.6;
-            let a13 = a.7;
-            let a20 = a.8;
-            let a21 = a.9;
-            let a22 = a.10;
-            let a23 = a.11;
-
-            out.0 = a00;
-            out.1 = a01;
-            out.2 = a02;
-            out.3 = a03;
-            out.4 = a10;
-            out.5 = a11;
-            out.6 = a12;
-            out.7 = a13;
-            out.8 = a20;
-            out.9 = a21;
-            out.10 = a22;
-            out.11 = a23;
-
-            out.12 = a00 * x + a10 * y + a20 * z + a.12;
-            out.13 = a01 * x + a11 * y + a21 * z + a.13;
-            out.14 = a02 * x + a12 * y + a22 * z + a.14;
-            out.15 = a03 * x + a13 * y + a23 * z + a.15;
-        }
-    }
-
-    pub fn scale(out: &mut Matrix4, a: &Matrix4, v: &Vector3) {
-        let x = v.0;
-        let y = v.1;
-        let z = v.2;
-
-        out.0 = a.0 * x;
-        out.1 = a.1 * x;
-        out.2 = a.2 * x;
-        out.3 = a.3 * x;
-        out.4 = a.4 * y;
-        out.5 = a.5 * y;
-        out.6 = a.6 * y;
-        out.7 = a.7 * y;
-        out.8 = a.8 * z;
-        out.9 = a.9 * z;
-        out.10 = a.10 * z;
-        out.11 = a.11 * z;
-        out.12 = a.12;
-        out.13 = a.13;
-        out.14 = a.14;
-        out.15 = a.15;
-    }
-
-    pub fn rotate(out: &mut Matrix4, a: &Matrix4, rad: f32, axis: &Vector3) {
-        let mut x = axis.0;
-        let mut y = axis.1;
-        let mut z = axis.2;
-        let mut len = (x.powi(2) + y.powi(2) + z.powi(2)).sqrt();
-
-        if len < EPSILON {
-            return;
-        }
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        impl $Matrix4 {
+            #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+            pub fn elements(&self) -> Box<[$F]> {
+                Box::new([
+                    self.0, self.1, self.2, self.3, self.4, self.5, self.6, self.7, self.8, self.9,
+                    self.10, self.11, self.12, self.13, self.14, self.15,
+                ])
+            }
 
-        let len = 1. / len;
-        x *= len;
-        y *= len;
-        z *= len;
-
-        let s = f32::sin(rad);
-        let c = f32::cos(rad);
-        let t = 1. - c;
-
-        let a00 = a.0;
-        let a01 = a.1;
-        let a02 = a.2;
-        let a03 = a.3;
-        let a10 = a.4;
-        let a11 = a.5;
-        let a12 = a.6;
-        let a13 = a.7;
-        let a20 = a.8;
-        let a21 = a.9;
-        let a22 = a.10;
-        let a23 = a.11;
-
-        // Construct the elements of the rotation matrix
-        let b00 = x * x * t + c;
-        let b01 = y * x * t + z * s;
-        let b02 = z * x * t - y * s;
-        let b10 = x * y * t - z * s;
-        let b11 = y * y * t + c;
-        let b12 = z * y * t + x * s;
-        let b20 = x * z * t + y * s;
-        let b21 = y * z * t - x * s;
-        let b22 = z * z * t + c;
-
-        // Perform rotation-specific matrix multiplication
-        out.0 = a00 * b00 + a10 * b01 + a20 * b02;
-        out.1 = a01 * b00 + a11 * b01 + a21 * b02;
-        out.2 = a02 * b00 + a12 * b01 + a22 * b02;
-        out.3 = a03 * b00 + a13 * b01 + a23 * b02;
-        out.4 = a00 * b10 + a10 * b11 + a20 * b12;
-        out.5 = a01 * b10 + a11 * b11 + a21 * b12;
-        out.6 = a02 * b10 + a12 * b11 + a22 * b12;
-        out.7 = a03 * b10 + a13 * b11 + a23 * b12;
-        out.8 = a00 * b20 + a10 * b21 + a20 * b22;
-        out.9 = a01 * b20 + a11 * b21 + a21 * b22;
-        out.10 = a02 * b20 + a12 * b21 + a22 * b22;
-        out.11 = a03 * b20 + a13 * b21 + a23 * b22;
-
-        if (out as *const Matrix4) != (a as *const Matrix4) {
-            // If the source and destination differ, copy the unchanged last row
-            out.12 = a.12;
-            out.13 = a.13;
-            out.14 = a.14;
-            out.15 = a.15;
-        }
-    }
-
-    pub fn rotateX(out: &mut Matrix4, a: &Matrix4, rad: f32) {
-        let s = f32::sin(rad);
-        let c = f32::cos(rad);
-        let a10 = a.4;
-        let a11 = a.5;
-        let a12 = a.6;
-        let a13 = a.7;
-        let a20 = a.8;
-        let a21 = a.9;
-        let a22 = a.10;
-        let a23 = a.11;
-        if (out as *const Matrix4) != (a as *const Matrix4) {
-            out.0 = a.0;
-            out.1 = a.1;
-            out.2 = a.2;
-            out.3 = a.3;
-            out.12 = a.12;
-            out.13 = a.13;
-            out.14 = a.14;
-            out.15 = a.15;
-        }
+            pub fn create() -> $Matrix4 {
+                $Matrix4(
+                    1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1.,
+                )
+            }
+
+            #[allow(clippy::should_implement_trait)]
+            pub fn clone(a: &$Matrix4) -> $Matrix4 {
+                $Matrix4(
+                    a.0, a.1, a.2, a.3, a.4, a.5, a.6, a.7, a.8, a.9, a.10, a.11, a.12, a.13, a.14,
+                    a.15,
+                )
+            }
+
+            pub fn copy(out: &mut $Matrix4, a: &$Matrix4) {
+                *out = *a;
+            }
+
+            #[allow(clippy::too_many_arguments)]
+            pub fn fromValues(
+                m00: $F,
+                m01: $F,
+                m02: $F,
+                m03: $F,
+                m10: $F,
+                m11: $F,
+                m12: $F,
+                m13: $F,
+                m20: $F,
+                m21: $F,
+                m22: $F,
+                m23: $F,
+                m30: $F,
+                m31: $F,
+                m32: $F,
+                m33: $F,
+            ) -> $Matrix4 {
+                $Matrix4(
+                    m00, m01, m02, m03, m10, m11, m12, m13, m20, m21, m22, m23, m30, m31, m32, m33,
+                )
+            }
+
+            #[allow(clippy::too_many_arguments)]
+            pub fn set(
+                out: &mut $Matrix4,
+                m00: $F,
+                m01: $F,
+                m02: $F,
+                m03: $F,
+                m10: $F,
+                m11: $F,
+                m12: $F,
+                m13: $F,
+                m20: $F,
+                m21: $F,
+                m22: $F,
+                m23: $F,
+                m30: $F,
+                m31: $F,
+                m32: $F,
+                m33: $F,
+            ) {
+                out.0 = m00;
+                out.1 = m01;
+                out.2 = m02;
+                out.3 = m03;
+                out.4 = m10;
+                out.5 = m11;
+                out.6 = m12;
+                out.7 = m13;
+                out.8 = m20;
+                out.9 = m21;
+                out.10 = m22;
+                out.11 = m23;
+                out.12 = m30;
+                out.13 = m31;
+                out.14 = m32;
+                out.15 = m33;
+            }
+
+            pub fn identity(out: &mut $Matrix4) {
+                out.0 = 1.;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 0.;
+                out.4 = 0.;
+                out.5 = 1.;
+                out.6 = 0.;
+                out.7 = 0.;
+                out.8 = 0.;
+                out.9 = 0.;
+                out.10 = 1.;
+                out.11 = 0.;
+                out.12 = 0.;
+                out.13 = 0.;
+                out.14 = 0.;
+                out.15 = 1.;
+            }
+
+            pub fn transpose(out: &mut $Matrix4, a: &$Matrix4) {
+                f32_only!(
+                    $F,
+                    if cfg!(feature = "simd") {
+                        crate::simd::transposeMat4(out, a);
+                        return;
+                    }
+                );
+
+                // If we are transposing ourselves we can skip a few steps but have to cache some values
+                if core::ptr::eq(out, a) {
+                    let a01 = a.1;
+                    let a02 = a.2;
+                    let a03 = a.3;
+                    let a12 = a.6;
+                    let a13 = a.7;
+                    let a23 = a.11;
+
+                    out.1 = a.4;
+                    out.2 = a.8;
+                    out.3 = a.12;
+                    out.4 = a01;
+                    out.6 = a.9;
+                    out.7 = a.13;
+                    out.8 = a02;
+                    out.9 = a12;
+                    out.11 = a.14;
+                    out.12 = a03;
+                    out.13 = a13;
+                    out.14 = a23;
+                } else {
+                    out.0 = a.0;
+                    out.1 = a.4;
+                    out.2 = a.8;
+                    out.3 = a.12;
+                    out.4 = a.1;
+                    out.5 = a.5;
+                    out.6 = a.9;
+                    out.7 = a.13;
+                    out.8 = a.2;
+                    out.9 = a.6;
+                    out.10 = a.10;
+                    out.11 = a.14;
+                    out.12 = a.3;
+                    out.13 = a.7;
+                    out.14 = a.11;
+                    out.15 = a.15;
+                }
+            }
+
+            // Returns false and leaves `out` untouched when the matrix is singular.
+            pub fn invert(out: &mut $Matrix4, a: &$Matrix4) -> bool {
+                $Matrix4::tryInvert(out, a, getEpsilon())
+            }
+
+            // Same as `invert`, but the matrix is singular when the absolute determinant is below `tolerance`,
+            // or zero or not finite.
+            pub fn tryInvert(
+                out: &mut $Matrix4,
+                a: &$Matrix4,
+                tolerance: $F,
+            ) -> bool {
+                f32_only!(
+                    $F,
+                    if cfg!(feature = "simd") {
+                        return crate::simd::invertMat4(out, a, tolerance);
+                    }
+                );
+
+                let a00 = a.0;
+                let a01 = a.1;
+                let a02 = a.2;
+                let a03 = a.3;
+                let a10 = a.4;
+                let a11 = a.5;
+                let a12 = a.6;
+                let a13 = a.7;
+                let a20 = a.8;
+                let a21 = a.9;
+                let a22 = a.10;
+                let a23 = a.11;
+                let a30 = a.12;
+                let a31 = a.13;
+                let a32 = a.14;
+                let a33 = a.15;
+
+                let b00 = a00 * a11 - a01 * a10;
+                let b01 = a00 * a12 - a02 * a10;
+                let b02 = a00 * a13 - a03 * a10;
+                let b03 = a01 * a12 - a02 * a11;
+                let b04 = a01 * a13 - a03 * a11;
+                let b05 = a02 * a13 - a03 * a12;
+                let b06 = a20 * a31 - a21 * a30;
+                let b07 = a20 * a32 - a22 * a30;
+                let b08 = a20 * a33 - a23 * a30;
+                let b09 = a21 * a32 - a22 * a31;
+                let b10 = a21 * a33 - a23 * a31;
+                let b11 = a22 * a33 - a23 * a32;
+
+                // Calculate the determinant
+                let mut det = b00 * b11 - b01 * b10 + b02 * b09 + b03 * b08 - b04 * b07 + b05 * b06;
+
+                if det == 0. || !det.is_finite() || det.abs() < tolerance {
+                    return false;
+                }
+                det = 1.0 / det;
+
+                out.0 = (a11 * b11 - a12 * b10 + a13 * b09) * det;
+                out.1 = (a02 * b10 - a01 * b11 - a03 * b09) * det;
+                out.2 = (a31 * b05 - a32 * b04 + a33 * b03) * det;
+                out.3 = (a22 * b04 - a21 * b05 - a23 * b03) * det;
+                out.4 = (a12 * b08 - a10 * b11 - a13 * b07) * det;
+                out.5 = (a00 * b11 - a02 * b08 + a03 * b07) * det;
+                out.6 = (a32 * b02 - a30 * b05 - a33 * b01) * det;
+                out.7 = (a20 * b05 - a22 * b02 + a23 * b01) * det;
+                out.8 = (a10 * b10 - a11 * b08 + a13 * b06) * det;
+                out.9 = (a01 * b08 - a00 * b10 - a03 * b06) * det;
+                out.10 = (a30 * b04 - a31 * b02 + a33 * b00) * det;
+                out.11 = (a21 * b02 - a20 * b04 - a23 * b00) * det;
+                out.12 = (a11 * b07 - a10 * b09 - a12 * b06) * det;
+                out.13 = (a00 * b09 - a01 * b07 + a02 * b06) * det;
+                out.14 = (a31 * b01 - a30 * b03 - a32 * b00) * det;
+                out.15 = (a20 * b03 - a21 * b01 + a22 * b00) * det;
+
+                true
+            }
 
-        // Perform axis-specific matrix multiplication
-        out.4 = a10 * c + a20 * s;
-        out.5 = a11 * c + a21 * s;
-        out.6 = a12 * c + a22 * s;
-        out.7 = a13 * c + a23 * s;
-        out.8 = a20 * c - a10 * s;
-        out.9 = a21 * c - a11 * s;
-        out.10 = a22 * c - a12 * s;
-        out.11 = a23 * c - a13 * s;
-    }
-
-    pub fn rotateY(out: &mut Matrix4, a: &Matrix4, rad: f32) {
-        let s = f32::sin(rad);
-        let c = f32::cos(rad);
-        let a00 = a.0;
-        let a01 = a.1;
-        let a02 = a.2;
-        let a03 = a.3;
-        let a20 = a.8;
-        let a21 = a.9;
-        let a22 = a.10;
-        let a23 = a.11;
-        if (out as *const Matrix4) != (a as *const Matrix4) {
-            out.4 = a.4;
-            out.5 = a.5;
-            out.6 = a.6;
-            out.7 = a.7;
-            out.12 = a.12;
-            out.13 = a.13;
-            out.14 = a.14;
-            out.15 = a.15;
+            pub fn adjoint(out: &mut $Matrix4, a: &$Matrix4) {
+                let a00 = a.0;
+                let a01 = a.1;
+                let a02 = a.2;
+                let a03 = a.3;
+                let a10 = a.4;
+                let a11 = a.5;
+                let a12 = a.6;
+                let a13 = a.7;
+                let a20 = a.8;
+                let a21 = a.9;
+                let a22 = a.10;
+                let a23 = a.11;
+                let a30 = a.12;
+                let a31 = a.13;
+                let a32 = a.14;
+                let a33 = a.15;
+
+                out.0 = a11 * (a22 * a33 - a23 * a32) - a21 * (a12 * a33 - a13 * a32)
+                    + a31 * (a12 * a23 - a13 * a22);
+                out.1 = -(a01 * (a22 * a33 - a23 * a32) - a21 * (a02 * a33 - a03 * a32)
+                    + a31 * (a02 * a23 - a03 * a22));
+                out.2 = a01 * (a12 * a33 - a13 * a32) - a11 * (a02 * a33 - a03 * a32)
+                    + a31 * (a02 * a13 - a03 * a12);
+                out.3 = -(a01 * (a12 * a23 - a13 * a22) - a11 * (a02 * a23 - a03 * a22)
+                    + a21 * (a02 * a13 - a03 * a12));
+                out.4 = -(a10 * (a22 * a33 - a23 * a32) - a20 * (a12 * a33 - a13 * a32)
+                    + a30 * (a12 * a23 - a13 * a22));
+                out.5 = a00 * (a22 * a33 - a23 * a32) - a20 * (a02 * a33 - a03 * a32)
+                    + a30 * (a02 * a23 - a03 * a22);
+                out.6 = -(a00 * (a12 * a33 - a13 * a32) - a10 * (a02 * a33 - a03 * a32)
+                    + a30 * (a02 * a13 - a03 * a12));
+                out.7 = a00 * (a12 * a23 - a13 * a22) - a10 * (a02 * a23 - a03 * a22)
+                    + a20 * (a02 * a13 - a03 * a12);
+                out.8 = a10 * (a21 * a33 - a23 * a31) - a20 * (a11 * a33 - a13 * a31)
+                    + a30 * (a11 * a23 - a13 * a21);
+                out.9 = -(a00 * (a21 * a33 - a23 * a31) - a20 * (a01 * a33 - a03 * a31)
+                    + a30 * (a01 * a23 - a03 * a21));
+                out.10 = a00 * (a11 * a33 - a13 * a31) - a10 * (a01 * a33 - a03 * a31)
+                    + a30 * (a01 * a13 - a03 * a11);
+                out.11 = -(a00 * (a11 * a23 - a13 * a21) - a10 * (a01 * a23 - a03 * a21)
+                    + a20 * (a01 * a13 - a03 * a11));
+                out.12 = -(a10 * (a21 * a32 - a22 * a31) - a20 * (a11 * a32 - a12 * a31)
+                    + a30 * (a11 * a22 - a12 * a21));
+                out.13 = a00 * (a21 * a32 - a22 * a31) - a20 * (a01 * a32 - a02 * a31)
+                    + a30 * (a01 * a22 - a02 * a21);
+                out.14 = -(a00 * (a11 * a32 - a12 * a31) - a10 * (a01 * a32 - a02 * a31)
+                    + a30 * (a01 * a12 - a02 * a11));
+                out.15 = a00 * (a11 * a22 - a12 * a21) - a10 * (a01 * a22 - a02 * a21)
+                    + a20 * (a01 * a12 - a02 * a11);
+            }
+
+            pub fn determinant(a: &$Matrix4) -> $F {
+                let a00 = a.0;
+                let a01 = a.1;
+                let a02 = a.2;
+                let a03 = a.3;
+                let a10 = a.4;
+                let a11 = a.5;
+                let a12 = a.6;
+                let a13 = a.7;
+                let a20 = a.8;
+                let a21 = a.9;
+                let a22 = a.10;
+                let a23 = a.11;
+                let a30 = a.12;
+                let a31 = a.13;
+                let a32 = a.14;
+                let a33 = a.15;
+
+                let b00 = a00 * a11 - a01 * a10;
+                let b01 = a00 * a12 - a02 * a10;
+                let b02 = a00 * a13 - a03 * a10;
+                let b03 = a01 * a12 - a02 * a11;
+                let b04 = a01 * a13 - a03 * a11;
+                let b05 = a02 * a13 - a03 * a12;
+                let b06 = a20 * a31 - a21 * a30;
+                let b07 = a20 * a32 - a22 * a30;
+                let b08 = a20 * a33 - a23 * a30;
+                let b09 = a21 * a32 - a22 * a31;
+                let b10 = a21 * a33 - a23 * a31;
+                let b11 = a22 * a33 - a23 * a32;
+
+                // Calculate the determinant
+                b00 * b11 - b01 * b10 + b02 * b09 + b03 * b08 - b04 * b07 + b05 * b06
+            }
+
+            pub fn multiply(
+                out: &mut $Matrix4,
+                a: &$Matrix4,
+                b: &$Matrix4,
+            ) {
+                f32_only!(
+                    $F,
+                    if cfg!(feature = "simd") {
+                        crate::simd::multiplyMat4(out, a, b);
+                        return;
+                    }
+                );
+
+                let a00 = a.0;
+                let a01 = a.1;
+                let a02 = a.2;
+                let a03 = a.3;
+                let a10 = a.4;
+                let a11 = a.5;
+                let a12 = a.6;
+                let a13 = a.7;
+                let a20 = a.8;
+                let a21 = a.9;
+                let a22 = a.10;
+                let a23 = a.11;
+                let a30 = a.12;
+                let a31 = a.13;
+                let a32 = a.14;
+                let a33 = a.15;
+
+                // Cache only the current line of the second matrix
+                let mut b0 = b.0;
+                let mut b1 = b.1;
+                let mut b2 = b.2;
+                let mut b3 = b.3;
+                out.0 = b0 * a00 + b1 * a10 + b2 * a20 + b3 * a30;
+                out.1 = b0 * a01 + b1 * a11 + b2 * a21 + b3 * a31;
+                out.2 = b0 * a02 + b1 * a12 + b2 * a22 + b3 * a32;
+                out.3 = b0 * a03 + b1 * a13 + b2 * a23 + b3 * a33;
+
+                b0 = b.4;
+                b1 = b.5;
+                b2 = b.6;
+                b3 = b.7;
+                out.4 = b0 * a00 + b1 * a10 + b2 * a20 + b3 * a30;
+                out.5 = b0 * a01 + b1 * a11 + b2 * a21 + b3 * a31;
+                out.6 = b0 * a02 + b1 * a12 + b2 * a22 + b3 * a32;
+                out.7 = b0 * a03 + b1 * a13 + b2 * a23 + b3 * a33;
+
+                b0 = b.8;
+                b1 = b.9;
+                b2 = b.10;
+                b3 = b.11;
+                out.8 = b0 * a00 + b1 * a10 + b2 * a20 + b3 * a30;
+                out.9 = b0 * a01 + b1 * a11 + b2 * a21 + b3 * a31;
+                out.10 = b0 * a02 + b1 * a12 + b2 * a22 + b3 * a32;
+                out.11 = b0 * a03 + b1 * a13 + b2 * a23 + b3 * a33;
+
+                b0 = b.12;
+                b1 = b.13;
+                b2 = b.14;
+                b3 = b.15;
+                out.12 = b0 * a00 + b1 * a10 + b2 * a20 + b3 * a30;
+                out.13 = b0 * a01 + b1 * a11 + b2 * a21 + b3 * a31;
+                out.14 = b0 * a02 + b1 * a12 + b2 * a22 + b3 * a32;
+                out.15 = b0 * a03 + b1 * a13 + b2 * a23 + b3 * a33;
+            }
+
+            pub fn translate(
+                out: &mut $Matrix4,
+                a: &$Matrix4,
+                v: &$Vector3,
+            ) {
+                let x = v.0;
+                let y = v.1;
+                let z = v.2;
+
+                if core::ptr::eq(out, a) {
+                    out.12 = a.0 * x + a.4 * y + a.8 * z + a.12;
+                    out.13 = a.1 * x + a.5 * y + a.9 * z + a.13;
+                    out.14 = a.2 * x + a.6 * y + a.10 * z + a.14;
+                    out.15 = a.3 * x + a.7 * y + a.11 * z + a.15;
+                } else {
+                    let a00 = a.0;
+                    let a01 = a.1;
+                    let a02 = a.2;
+                    let a03 = a.3;
+                    let a10 = a.4;
+                    let a11 = a.5;
+                    let a12 = a.6;
+                    let a13 = a.7;
+                    let a20 = a.8;
+                    let a21 = a.9;
+                    let a22 = a.10;
+                    let a23 = a.11;
+
+                    out.0 = a00;
+                    out.1 = a01;
+                    out.2 = a02;
+                    out.3 = a03;
+                    out.4 = a10;
+                    out.5 = a11;
+                    out.6 = a12;
+                    out.7 = a13;
+                    out.8 = a20;
+                    out.9 = a21;
+                    out.10 = a22;
+                    out.11 = a23;
+
+                    out.12 = a00 * x + a10 * y + a20 * z + a.12;
+                    out.13 = a01 * x + a11 * y + a21 * z + a.13;
+                    out.14 = a02 * x + a12 * y + a22 * z + a.14;
+                    out.15 = a03 * x + a13 * y + a23 * z + a.15;
+                }
+            }
+
+            pub fn scale(out: &mut $Matrix4, a: &$Matrix4, v: &$Vector3) {
+                let x = v.0;
+                let y = v.1;
+                let z = v.2;
+
+                out.0 = a.0 * x;
+                out.1 = a.1 * x;
+                out.2 = a.2 * x;
+                out.3 = a.3 * x;
+                out.4 = a.4 * y;
+                out.5 = a.5 * y;
+                out.6 = a.6 * y;
+                out.7 = a.7 * y;
+                out.8 = a.8 * z;
+                out.9 = a.9 * z;
+                out.10 = a.10 * z;
+                out.11 = a.11 * z;
+                out.12 = a.12;
+                out.13 = a.13;
+                out.14 = a.14;
+                out.15 = a.15;
+            }
+
+            pub fn rotate(
+                out: &mut $Matrix4,
+                a: &$Matrix4,
+                rad: $F,
+                axis: &$Vector3,
+            ) {
+                let mut x = axis.0;
+                let mut y = axis.1;
+                let mut z = axis.2;
+                let len = (x.powi(2) + y.powi(2) + z.powi(2)).sqrt();
+
+                if len < EPSILON {
+                    return;
+                }
+
+                let len = 1. / len;
+                x *= len;
+                y *= len;
+                z *= len;
+
+                let s = $F::sin(rad);
+                let c = $F::cos(rad);
+                let t = 1. - c;
+
+                let a00 = a.0;
+                let a01 = a.1;
+                let a02 = a.2;
+                let a03 = a.3;
+                let a10 = a.4;
+                let a11 = a.5;
+                let a12 = a.6;
+                let a13 = a.7;
+                let a20 = a.8;
+                let a21 = a.9;
+                let a22 = a.10;
+                let a23 = a.11;
+
+                // Construct the elements of the rotation matrix
+                let b00 = x * x * t + c;
+                let b01 = y * x * t + z * s;
+                let b02 = z * x * t - y * s;
+                let b10 = x * y * t - z * s;
+                let b11 = y * y * t + c;
+                let b12 = z * y * t + x * s;
+                let b20 = x * z * t + y * s;
+                let b21 = y * z * t - x * s;
+                let b22 = z * z * t + c;
+
+                // Perform rotation-specific matrix multiplication
+                out.0 = a00 * b00 + a10 * b01 + a20 * b02;
+                out.1 = a01 * b00 + a11 * b01 + a21 * b02;
+                out.2 = a02 * b00 + a12 * b01 + a22 * b02;
+                out.3 = a03 * b00 + a13 * b01 + a23 * b02;
+                out.4 = a00 * b10 + a10 * b11 + a20 * b12;
+                out.5 = a01 * b10 + a11 * b11 + a21 * b12;
+                out.6 = a02 * b10 + a12 * b11 + a22 * b12;
+                out.7 = a03 * b10 + a13 * b11 + a23 * b12;
+                out.8 = a00 * b20 + a10 * b21 + a20 * b22;
+                out.9 = a01 * b20 + a11 * b21 + a21 * b22;
+                out.10 = a02 * b20 + a12 * b21 + a22 * b22;
+                out.11 = a03 * b20 + a13 * b21 + a23 * b22;
+
+                if !core::ptr::eq(out, a) {
+                    // If the source and destination differ, copy the unchanged last row
+                    out.12 = a.12;
+                    out.13 = a.13;
+                    out.14 = a.14;
+                    out.15 = a.15;
+                }
+            }
+
+            pub fn rotateX(out: &mut $Matrix4, a: &$Matrix4, rad: $F) {
+                let s = $F::sin(rad);
+                let c = $F::cos(rad);
+                let a10 = a.4;
+                let a11 = a.5;
+                let a12 = a.6;
+                let a13 = a.7;
+                let a20 = a.8;
+                let a21 = a.9;
+                let a22 = a.10;
+                let a23 = a.11;
+                if !core::ptr::eq(out, a) {
+                    out.0 = a.0;
+                    out.1 = a.1;
+                    out.2 = a.2;
+                    out.3 = a.3;
+                    out.12 = a.12;
+                    out.13 = a.13;
+                    out.14 = a.14;
+                    out.15 = a.15;
+                }
+
+                // Perform axis-specific matrix multiplication
+                out.4 = a10 * c + a20 * s;
+                out.5 = a11 * c + a21 * s;
+                out.6 = a12 * c + a22 * s;
+                out.7 = a13 * c + a23 * s;
+                out.8 = a20 * c - a10 * s;
+                out.9 = a21 * c - a11 * s;
+                out.10 = a22 * c - a12 * s;
+                out.11 = a23 * c - a13 * s;
+            }
+
+            pub fn rotateY(out: &mut $Matrix4, a: &$Matrix4, rad: $F) {
+                let s = $F::sin(rad);
+                let c = $F::cos(rad);
+                let a00 = a.0;
+                let a01 = a.1;
+                let a02 = a.2;
+                let a03 = a.3;
+                let a20 = a.8;
+                let a21 = a.9;
+                let a22 = a.10;
+                let a23 = a.11;
+                if !core::ptr::eq(out, a) {
+                    out.4 = a.4;
+                    out.5 = a.5;
+                    out.6 = a.6;
+                    out.7 = a.7;
+                    out.12 = a.12;
+                    out.13 = a.13;
+                    out.14 = a.14;
+                    out.15 = a.15;
+                }
+
+                // Perform axis-specific matrix multiplication
+                out.0 = a00 * c - a20 * s;
+                out.1 = a01 * c - a21 * s;
+                out.2 = a02 * c - a22 * s;
+                out.3 = a03 * c - a23 * s;
+                out.8 = a00 * s + a20 * c;
+                out.9 = a01 * s + a21 * c;
+                out.10 = a02 * s + a22 * c;
+                out.11 = a03 * s + a23 * c;
+            }
+
+            pub fn rotateZ(out: &mut $Matrix4, a: &$Matrix4, rad: $F) {
+                let s = $F::sin(rad);
+                let c = $F::cos(rad);
+                let a00 = a.0;
+                let a01 = a.1;
+                let a02 = a.2;
+                let a03 = a.3;
+                let a10 = a.4;
+                let a11 = a.5;
+                let a12 = a.6;
+                let a13 = a.7;
+                if !core::ptr::eq(out, a) {
+                    out.8 = a.8;
+                    out.9 = a.9;
+                    out.10 = a.10;
+                    out.11 = a.11;
+                    out.12 = a.12;
+                    out.13 = a.13;
+                    out.14 = a.14;
+                    out.15 = a.15;
+                }
+
+                // Perform axis-specific matrix multiplication
+                out.0 = a00 * c + a10 * s;
+                out.1 = a01 * c + a11 * s;
+                out.2 = a02 * c + a12 * s;
+                out.3 = a03 * c + a13 * s;
+                out.4 = a10 * c - a00 * s;
+                out.5 = a11 * c - a01 * s;
+                out.6 = a12 * c - a02 * s;
+                out.7 = a13 * c - a03 * s;
+            }
+
+            pub fn fromTranslation(out: &mut $Matrix4, v: &$Vector3) {
+                out.0 = 1.;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 0.;
+                out.4 = 0.;
+                out.5 = 1.;
+                out.6 = 0.;
+                out.7 = 0.;
+                out.8 = 0.;
+                out.9 = 0.;
+                out.10 = 1.;
+                out.11 = 0.;
+                out.12 = v.0;
+                out.13 = v.1;
+                out.14 = v.2;
+                out.15 = 1.;
+            }
+
+            pub fn fromScaling(out: &mut $Matrix4, v: &$Vector3) {
+                out.0 = v.0;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 0.;
+                out.4 = 0.;
+                out.5 = v.1;
+                out.6 = 0.;
+                out.7 = 0.;
+                out.8 = 0.;
+                out.9 = 0.;
+                out.10 = v.2;
+                out.11 = 0.;
+                out.12 = 0.;
+                out.13 = 0.;
+                out.14 = 0.;
+                out.15 = 1.;
+            }
+
+            pub fn fromRotation(
+                out: &mut $Matrix4,
+                rad: $F,
+                axis: &$Vector3,
+            ) {
+                let mut x = axis.0;
+                let mut y = axis.1;
+                let mut z = axis.2;
+                let mut len = (x.powi(2) + y.powi(2) + z.powi(2)).sqrt();
+
+                if len < EPSILON {
+                    return;
+                }
+
+                len = 1. / len;
+                x *= len;
+                y *= len;
+                z *= len;
+
+                let s = $F::sin(rad);
+                let c = $F::cos(rad);
+                let t = 1. - c;
+
+                // Perform rotation-specific matrix multiplication
+                out.0 = x * x * t + c;
+                out.1 = y * x * t + z * s;
+                out.2 = z * x * t - y * s;
+                out.3 = 0.;
+                out.4 = x * y * t - z * s;
+                out.5 = y * y * t + c;
+                out.6 = z * y * t + x * s;
+                out.7 = 0.;
+                out.8 = x * z * t + y * s;
+                out.9 = y * z * t - x * s;
+                out.10 = z * z * t + c;
+                out.11 = 0.;
+                out.12 = 0.;
+                out.13 = 0.;
+                out.14 = 0.;
+                out.15 = 1.;
+            }
+
+            pub fn fromXRotation(out: &mut $Matrix4, rad: $F) {
+                let s = $F::sin(rad);
+                let c = $F::cos(rad);
+
+                // Perform axis-specific matrix multiplication
+                out.0 = 1.;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 0.;
+                out.4 = 0.;
+                out.5 = c;
+                out.6 = s;
+                out.7 = 0.;
+                out.8 = 0.;
+                out.9 = -s;
+                out.10 = c;
+                out.11 = 0.;
+                out.12 = 0.;
+                out.13 = 0.;
+                out.14 = 0.;
+                out.15 = 1.;
+            }
+
+            pub fn fromYRotation(out: &mut $Matrix4, rad: $F) {
+                let s = $F::sin(rad);
+                let c = $F::cos(rad);
+
+                // Perform axis-specific matrix multiplication
+                out.0 = c;
+                out.1 = 0.;
+                out.2 = -s;
+                out.3 = 0.;
+                out.4 = 0.;
+                out.5 = 1.;
+                out.6 = 0.;
+                out.7 = 0.;
+                out.8 = s;
+                out.9 = 0.;
+                out.10 = c;
+                out.11 = 0.;
+                out.12 = 0.;
+                out.13 = 0.;
+                out.14 = 0.;
+                out.15 = 1.;
+            }
+
+            pub fn fromZRotation(out: &mut $Matrix4, rad: $F) {
+                let s = $F::sin(rad);
+                let c = $F::cos(rad);
+
+                // Perform axis-specific matrix multiplication
+                out.0 = c;
+                out.1 = s;
+                out.2 = 0.;
+                out.3 = 0.;
+                out.4 = -s;
+                out.5 = c;
+                out.6 = 0.;
+                out.7 = 0.;
+                out.8 = 0.;
+                out.9 = 0.;
+                out.10 = 1.;
+                out.11 = 0.;
+                out.12 = 0.;
+                out.13 = 0.;
+                out.14 = 0.;
+                out.15 = 1.;
+            }
+
+            pub fn fromRotationTranslation(
+                out: &mut $Matrix4,
+                q: &$Quaternion,
+                v: &$Vector3,
+            ) {
+                // Quaternion math
+                let x = q.0;
+                let y = q.1;
+                let z = q.2;
+                let w = q.3;
+                let x2 = x + x;
+                let y2 = y + y;
+                let z2 = z + z;
+
+                let xx = x * x2;
+                let xy = x * y2;
+                let xz = x * z2;
+                let yy = y * y2;
+                let yz = y * z2;
+                let zz = z * z2;
+                let wx = w * x2;
+                let wy = w * y2;
+                let wz = w * z2;
+
+                out.0 = 1. - (yy + zz);
+                out.1 = xy + wz;
+                out.2 = xz - wy;
+                out.3 = 0.;
+                out.4 = xy - wz;
+                out.5 = 1. - (xx + zz);
+                out.6 = yz + wx;
+                out.7 = 0.;
+                out.8 = xz + wy;
+                out.9 = yz - wx;
+                out.10 = 1. - (xx + yy);
+                out.11 = 0.;
+                out.12 = v.0;
+                out.13 = v.1;
+                out.14 = v.2;
+                out.15 = 1.;
+            }
+
+            pub fn fromQuat2(out: &mut $Matrix4, a: &$Quaternion2) {
+                let translation = &mut $Vector3::create();
+                let bx = -a.0;
+                let by = -a.1;
+                let bz = -a.2;
+                let bw = a.3;
+                let ax = a.4;
+                let ay = a.5;
+                let az = a.6;
+                let aw = a.7;
+
+                let magnitude = bx * bx + by * by + bz * bz + bw * bw;
+                //Only scale if it makes sense
+                if magnitude > EPSILON {
+                    translation.0 = (ax * bw + aw * bx + ay * bz - az * by) * 2. / magnitude;
+                    translation.1 = (ay * bw + aw * by + az * bx - ax * bz) * 2. / magnitude;
+                    translation.2 = (az * bw + aw * bz + ax * by - ay * bx) * 2. / magnitude;
+                } else {
+                    translation.0 = (ax * bw + aw * bx + ay * bz - az * by) * 2.;
+                    translation.1 = (ay * bw + aw * by + az * bx - ax * bz) * 2.;
+                    translation.2 = (az * bw + aw * bz + ax * by - ay * bx) * 2.;
+                }
+                $Matrix4::fromRotationTranslation(
+                    out,
+                    &$Quaternion::fromValues(a.0, a.1, a.2, a.3),
+                    translation,
+                );
+            }
+
+            pub fn getTranslation(out: &mut $Vector3, mat: &$Matrix4) {
+                out.0 = mat.12;
+                out.1 = mat.13;
+                out.2 = mat.14;
+            }
+
+            pub fn getScaling(out: &mut $Vector3, mat: &$Matrix4) {
+                let m11 = mat.0;
+                let m12 = mat.1;
+                let m13 = mat.2;
+                let m21 = mat.4;
+                let m22 = mat.5;
+                let m23 = mat.6;
+                let m31 = mat.8;
+                let m32 = mat.9;
+                let m33 = mat.10;
+                out.0 = (m11.powi(2) + m12.powi(2) + m13.powi(2)).sqrt();
+                out.1 = (m21.powi(2) + m22.powi(2) + m23.powi(2)).sqrt();
+                out.2 = (m31.powi(2) + m32.powi(2) + m33.powi(2)).sqrt();
+            }
+
+            pub fn getRotation(out: &mut $Quaternion, mat: &$Matrix4) {
+                let scaling = &mut $Vector3::create();
+                $Matrix4::getScaling(scaling, mat);
+
+                let is1 = 1. / scaling.0;
+                let is2 = 1. / scaling.1;
+                let is3 = 1. / scaling.2;
+
+                let sm11 = mat.0 * is1;
+                let sm12 = mat.1 * is2;
+                let sm13 = mat.2 * is3;
+                let sm21 = mat.4 * is1;
+                let sm22 = mat.5 * is2;
+                let sm23 = mat.6 * is3;
+                let sm31 = mat.8 * is1;
+                let sm32 = mat.9 * is2;
+                let sm33 = mat.10 * is3;
+
+                let trace = sm11 + sm22 + sm33;
+
+                if trace > 0. {
+                    let S = $F::sqrt(trace + 1.0) * 2.;
+                    out.3 = 0.25 * S;
+                    out.0 = (sm23 - sm32) / S;
+                    out.1 = (sm31 - sm13) / S;
+                    out.2 = (sm12 - sm21) / S;
+                } else if (sm11 > sm22) && (sm11 > sm33) {
+                    let S = $F::sqrt(1.0 + sm11 - sm22 - sm33) * 2.;
+                    out.3 = (sm23 - sm32) / S;
+                    out.0 = 0.25 * S;
+                    out.1 = (sm12 + sm21) / S;
+                    out.2 = (sm31 + sm13) / S;
+                } else if sm22 > sm33 {
+                    let S = $F::sqrt(1.0 + sm22 - sm11 - sm33) * 2.;
+                    out.3 = (sm31 - sm13) / S;
+                    out.0 = (sm12 + sm21) / S;
+                    out.1 = 0.25 * S;
+                    out.2 = (sm23 + sm32) / S;
+                } else {
+                    let S = $F::sqrt(1.0 + sm33 - sm11 - sm22) * 2.;
+                    out.3 = (sm12 - sm21) / S;
+                    out.0 = (sm31 + sm13) / S;
+                    out.1 = (sm23 + sm32) / S;
+                    out.2 = 0.25 * S;
+                }
+            }
+
+            // Splits `m` into translation, rotation, scale, shear (xy, xz, yz) and the perspective row, as
+            // "unmatrix" of Graphics Gems II. A reflection gives negative scales on every axis, so the
+            // rotation stays proper. Returns false and leaves the outputs untouched when `m` or its upper 3x4
+            // part is singular, small but finite determinants decompose.
+            pub fn decompose(
+                m: &$Matrix4,
+                outT: &mut $Vector3,
+                outR: &mut $Quaternion,
+                outS: &mut $Vector3,
+                outShear: &mut $Vector3,
+                outPersp: &mut $Vector4,
+            ) -> bool {
+                if m.15 == 0. {
+                    return false;
+                }
+                let local = &mut $Matrix4::create();
+                $Matrix4::multiplyScalar(local, m, 1. / m.15);
+
+                // The perspective row solved against the upper 3x4 part.
+                let perspective = &mut $Matrix4::clone(local);
+                perspective.3 = 0.;
+                perspective.7 = 0.;
+                perspective.11 = 0.;
+                perspective.15 = 1.;
+                if $Matrix4::determinant(perspective) == 0. {
+                    return false;
+                }
+                let persp = &mut $Vector4::fromValues(0., 0., 0., 1.);
+                if local.3 != 0. || local.7 != 0. || local.11 != 0. {
+                    let inverse = &mut $Matrix4::create();
+                    if !$Matrix4::tryInvert(inverse, perspective, 0.) {
+                        return false;
+                    }
+                    $Matrix4::transpose(inverse, &$Matrix4::clone(inverse));
+                    let rhs = &$Vector4::fromValues(local.3, local.7, local.11, local.15);
+                    $Vector4::transformMat4(persp, rhs, inverse);
+                }
+
+                // Gram-Schmidt on the columns, the removed parts are the shear.
+                let x = &mut $Vector3::fromValues(local.0, local.1, local.2);
+                let y = &mut $Vector3::fromValues(local.4, local.5, local.6);
+                let z = &mut $Vector3::fromValues(local.8, local.9, local.10);
+                let scale = &mut $Vector3::create();
+                let shear = &mut $Vector3::create();
+
+                scale.0 = $Vector3::len(x);
+                $Vector3::scale(x, &$Vector3::clone(x), 1. / scale.0);
+
+                shear.0 = $Vector3::dot(x, y);
+                $Vector3::scaleAndAdd(y, &$Vector3::clone(y), x, -shear.0);
+                scale.1 = $Vector3::len(y);
+                $Vector3::scale(y, &$Vector3::clone(y), 1. / scale.1);
+                shear.0 /= scale.1;
+
+                shear.1 = $Vector3::dot(x, z);
+                $Vector3::scaleAndAdd(z, &$Vector3::clone(z), x, -shear.1);
+                shear.2 = $Vector3::dot(y, z);
+                $Vector3::scaleAndAdd(z, &$Vector3::clone(z), y, -shear.2);
+                scale.2 = $Vector3::len(z);
+                $Vector3::scale(z, &$Vector3::clone(z), 1. / scale.2);
+                shear.1 /= scale.2;
+                shear.2 /= scale.2;
+
+                let cross = &mut $Vector3::create();
+                $Vector3::cross(cross, y, z);
+                if $Vector3::dot(x, cross) < 0. {
+                    $Vector3::negate(scale, &$Vector3::clone(scale));
+                    $Vector3::negate(x, &$Vector3::clone(x));
+                    $Vector3::negate(y, &$Vector3::clone(y));
+                    $Vector3::negate(z, &$Vector3::clone(z));
+                }
+
+                let rotation =
+                    &$Matrix3::fromValues(x.0, x.1, x.2, y.0, y.1, y.2, z.0, z.1, z.2);
+                $Quaternion::fromMat3(outR, rotation);
+                $Quaternion::normalize(outR, &$Quaternion::clone(outR));
+                $Vector3::set(outT, local.12, local.13, local.14);
+                $Vector3::copy(outS, scale);
+                $Vector3::copy(outShear, shear);
+                $Vector4::copy(outPersp, persp);
+                true
+            }
+
+            pub fn fromRotationTranslationScale(
+                out: &mut $Matrix4,
+                q: &$Quaternion,
+                v: &$Vector3,
+                s: &$Vector3,
+            ) {
+                // Quaternion math
+                let x = q.0;
+                let y = q.1;
+                let z = q.2;
+                let w = q.3;
+                let x2 = x + x;
+                let y2 = y + y;
+                let z2 = z + z;
+
+                let xx = x * x2;
+                let xy = x * y2;
+                let xz = x * z2;
+                let yy = y * y2;
+                let yz = y * z2;
+                let zz = z * z2;
+                let wx = w * x2;
+                let wy = w * y2;
+                let wz = w * z2;
+                let sx = s.0;
+                let sy = s.1;
+                let sz = s.2;
+
+                out.0 = (1. - (yy + zz)) * sx;
+                out.1 = (xy + wz) * sx;
+                out.2 = (xz - wy) * sx;
+                out.3 = 0.;
+                out.4 = (xy - wz) * sy;
+                out.5 = (1. - (xx + zz)) * sy;
+                out.6 = (yz + wx) * sy;
+                out.7 = 0.;
+                out.8 = (xz + wy) * sz;
+                out.9 = (yz - wx) * sz;
+                out.10 = (1. - (xx + yy)) * sz;
+                out.11 = 0.;
+                out.12 = v.0;
+                out.13 = v.1;
+                out.14 = v.2;
+                out.15 = 1.;
+            }
+
+            pub fn fromRotationTranslationScaleOrigin(
+                out: &mut $Matrix4,
+                q: &$Quaternion,
+                v: &$Vector3,
+                s: &$Vector3,
+                o: &$Vector3,
+            ) {
+                // Quaternion math
+                let x = q.0;
+                let y = q.1;
+                let z = q.2;
+                let w = q.3;
+                let x2 = x + x;
+                let y2 = y + y;
+                let z2 = z + z;
+
+                let xx = x * x2;
+                let xy = x * y2;
+                let xz = x * z2;
+                let yy = y * y2;
+                let yz = y * z2;
+                let zz = z * z2;
+                let wx = w * x2;
+                let wy = w * y2;
+                let wz = w * z2;
+
+                let sx = s.0;
+                let sy = s.1;
+                let sz = s.2;
+
+                let ox = o.0;
+                let oy = o.1;
+                let oz = o.2;
+
+                let out0 = (1. - (yy + zz)) * sx;
+                let out1 = (xy + wz) * sx;
+                let out2 = (xz - wy) * sx;
+                let out4 = (xy - wz) * sy;
+                let out5 = (1. - (xx + zz)) * sy;
+                let out6 = (yz + wx) * sy;
+                let out8 = (xz + wy) * sz;
+                let out9 = (yz - wx) * sz;
+                let out10 = (1. - (xx + yy)) * sz;
+
+                out.0 = out0;
+                out.1 = out1;
+                out.2 = out2;
+                out.3 = 0.;
+                out.4 = out4;
+                out.5 = out5;
+                out.6 = out6;
+                out.7 = 0.;
+                out.8 = out8;
+                out.9 = out9;
+                out.10 = out10;
+                out.11 = 0.;
+                out.12 = v.0 + ox - (out0 * ox + out4 * oy + out8 * oz);
+                out.13 = v.1 + oy - (out1 * ox + out5 * oy + out9 * oz);
+                out.14 = v.2 + oz - (out2 * ox + out6 * oy + out10 * oz);
+                out.15 = 1.;
+            }
+
+            pub fn fromQuat(out: &mut $Matrix4, q: &$Quaternion) {
+                let x = q.0;
+                let y = q.1;
+                let z = q.2;
+                let w = q.3;
+                let x2 = x + x;
+                let y2 = y + y;
+                let z2 = z + z;
+
+                let xx = x * x2;
+                let yx = y * x2;
+                let yy = y * y2;
+                let zx = z * x2;
+                let zy = z * y2;
+                let zz = z * z2;
+                let wx = w * x2;
+                let wy = w * y2;
+                let wz = w * z2;
+
+                out.0 = 1. - yy - zz;
+                out.1 = yx + wz;
+                out.2 = zx - wy;
+                out.3 = 0.;
+
+                out.4 = yx - wz;
+                out.5 = 1. - xx - zz;
+                out.6 = zy + wx;
+                out.7 = 0.;
+
+                out.8 = zx + wy;
+                out.9 = zy - wx;
+                out.10 = 1. - xx - yy;
+                out.11 = 0.;
+
+                out.12 = 0.;
+                out.13 = 0.;
+                out.14 = 0.;
+                out.15 = 1.;
+            }
+
+            pub fn fromEuler(
+                out: &mut $Matrix4,
+                x: $F,
+                y: $F,
+                z: $F,
+                order: EulerOrder,
+                unit: AngleUnit,
+            ) {
+                let q = &mut $Quaternion::create();
+                $Quaternion::fromEulerOrder(q, x, y, z, order, unit);
+                $Matrix4::fromQuat(out, q);
+            }
+
+            pub fn getEuler(
+                out: &mut $Vector3,
+                m: &$Matrix4,
+                order: EulerOrder,
+                unit: AngleUnit,
+            ) {
+                let m3 = &mut $Matrix3::create();
+                $Matrix3::fromMat4(m3, m);
+                $Matrix3::getEuler(out, m3, order, unit);
+            }
+
+            pub fn frustum(
+                out: &mut $Matrix4,
+                left: $F,
+                right: $F,
+                bottom: $F,
+                top: $F,
+                near: $F,
+                far: $F,
+            ) {
+                let rl = 1. / (right - left);
+                let tb = 1. / (top - bottom);
+                let nf = 1. / (near - far);
+                out.0 = (near * 2.) * rl;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 0.;
+                out.4 = 0.;
+                out.5 = (near * 2.) * tb;
+                out.6 = 0.;
+                out.7 = 0.;
+                out.8 = (right + left) * rl;
+                out.9 = (top + bottom) * tb;
+                out.10 = (far + near) * nf;
+                out.11 = -1.;
+                out.12 = 0.;
+                out.13 = 0.;
+                out.14 = (far * near * 2.) * nf;
+                out.15 = 0.;
+            }
+
+            // Same as `frustum`, but maps depth to [0, 1] as WebGPU / Vulkan / D3D clip space.
+            pub fn frustumZO(
+                out: &mut $Matrix4,
+                left: $F,
+                right: $F,
+                bottom: $F,
+                top: $F,
+                near: $F,
+                far: $F,
+            ) {
+                let rl = 1. / (right - left);
+                let tb = 1. / (top - bottom);
+                let nf = 1. / (near - far);
+                out.0 = (near * 2.) * rl;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 0.;
+                out.4 = 0.;
+                out.5 = (near * 2.) * tb;
+                out.6 = 0.;
+                out.7 = 0.;
+                out.8 = (right + left) * rl;
+                out.9 = (top + bottom) * tb;
+                out.10 = far * nf;
+                out.11 = -1.;
+                out.12 = 0.;
+                out.13 = 0.;
+                out.14 = far * near * nf;
+                out.15 = 0.;
+            }
+
+            pub fn perspective(
+                out: &mut $Matrix4,
+                fovy: $F,
+                aspect: $F,
+                near: $F,
+                far: Option<$F>,
+            ) {
+                let f = 1.0 / $F::tan(fovy / 2.);
+                let nf;
+                out.0 = f / aspect;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 0.;
+                out.4 = 0.;
+                out.5 = f;
+                out.6 = 0.;
+                out.7 = 0.;
+                out.8 = 0.;
+                out.9 = 0.;
+                out.11 = -1.;
+                out.12 = 0.;
+                out.13 = 0.;
+                out.15 = 0.;
+                match far {
+                    Some(far) => {
+                        if far == INFINITY || far == NEG_INFINITY {
+                            out.10 = -1.;
+                            out.14 = -2. * near;
+                        } else {
+                            nf = 1. / (near - far);
+                            out.10 = (far + near) * nf;
+                            out.14 = (2. * far * near) * nf;
+                        }
+                    }
+                    None => {
+                        out.10 = -1.;
+                        out.14 = -2. * near;
+                    }
+                };
+            }
+
+            // Same as `perspective`, but maps depth to [0, 1] as WebGPU / Vulkan / D3D clip space.
+            pub fn perspectiveZO(
+                out: &mut $Matrix4,
+                fovy: $F,
+                aspect: $F,
+                near: $F,
+                far: Option<$F>,
+            ) {
+                let f = 1.0 / $F::tan(fovy / 2.);
+                let nf;
+                out.0 = f / aspect;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 0.;
+                out.4 = 0.;
+                out.5 = f;
+                out.6 = 0.;
+                out.7 = 0.;
+                out.8 = 0.;
+                out.9 = 0.;
+                out.11 = -1.;
+                out.12 = 0.;
+                out.13 = 0.;
+                out.15 = 0.;
+                match far {
+                    Some(far) if far != INFINITY && far != NEG_INFINITY => {
+                        nf = 1. / (near - far);
+                        out.10 = far * nf;
+                        out.14 = far * near * nf;
+                    }
+                    _ => {
+                        out.10 = -1.;
+                        out.14 = -near;
+                    }
+                };
+            }
+
+            // Reversed-Z with [0, 1] depth, near plane goes to 1 and far plane to 0.
+            // Without far or with an infinite one, it is the infinite reversed-Z projection.
+            pub fn perspectiveReversedZ(
+                out: &mut $Matrix4,
+                fovy: $F,
+                aspect: $F,
+                near: $F,
+                far: Option<$F>,
+            ) {
+                let f = 1.0 / $F::tan(fovy / 2.);
+                let nf;
+                out.0 = f / aspect;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 0.;
+                out.4 = 0.;
+                out.5 = f;
+                out.6 = 0.;
+                out.7 = 0.;
+                out.8 = 0.;
+                out.9 = 0.;
+                out.11 = -1.;
+                out.12 = 0.;
+                out.13 = 0.;
+                out.15 = 0.;
+                match far {
+                    Some(far) if far != INFINITY && far != NEG_INFINITY => {
+                        nf = 1. / (near - far);
+                        out.10 = -near * nf;
+                        out.14 = -far * near * nf;
+                    }
+                    _ => {
+                        out.10 = 0.;
+                        out.14 = near;
+                    }
+                };
+            }
+
+            pub fn perspectiveInfiniteReversedZ(
+                out: &mut $Matrix4,
+                fovy: $F,
+                aspect: $F,
+                near: $F,
+            ) {
+                $Matrix4::perspectiveReversedZ(out, fovy, aspect, near, None);
+            }
+
+            pub fn perspectiveFromFieldOfView(
+                out: &mut $Matrix4,
+                fov: &FieldOfView,
+                near: $F,
+                far: $F,
+            ) {
+                let upTan = $F::tan($F::from(fov.upDegrees) * PI / 180.0);
+                let downTan = $F::tan($F::from(fov.downDegrees) * PI / 180.0);
+                let leftTan = $F::tan($F::from(fov.leftDegrees) * PI / 180.0);
+                let rightTan = $F::tan($F::from(fov.rightDegrees) * PI / 180.0);
+                let xScale = 2.0 / (leftTan + rightTan);
+                let yScale = 2.0 / (upTan + downTan);
+
+                out.0 = xScale;
+                out.1 = 0.0;
+                out.2 = 0.0;
+                out.3 = 0.0;
+                out.4 = 0.0;
+                out.5 = yScale;
+                out.6 = 0.0;
+                out.7 = 0.0;
+                out.8 = -((leftTan - rightTan) * xScale * 0.5);
+                out.9 = (upTan - downTan) * yScale * 0.5;
+                out.10 = far / (near - far);
+                out.11 = -1.0;
+                out.12 = 0.0;
+                out.13 = 0.0;
+                out.14 = (far * near) / (near - far);
+                out.15 = 0.0;
+            }
+
+            pub fn ortho(
+                out: &mut $Matrix4,
+                left: $F,
+                right: $F,
+                bottom: $F,
+                top: $F,
+                near: $F,
+                far: $F,
+            ) {
+                let lr = 1. / (left - right);
+                let bt = 1. / (bottom - top);
+                let nf = 1. / (near - far);
+                out.0 = -2. * lr;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 0.;
+                out.4 = 0.;
+                out.5 = -2. * bt;
+                out.6 = 0.;
+                out.7 = 0.;
+                out.8 = 0.;
+                out.9 = 0.;
+                out.10 = 2. * nf;
+                out.11 = 0.;
+                out.12 = (left + right) * lr;
+                out.13 = (top + bottom) * bt;
+                out.14 = (far + near) * nf;
+                out.15 = 1.;
+            }
+
+            // Same as `ortho`, but maps depth to [0, 1] as WebGPU / Vulkan / D3D clip space.
+            pub fn orthoZO(
+                out: &mut $Matrix4,
+                left: $F,
+                right: $F,
+                bottom: $F,
+                top: $F,
+                near: $F,
+                far: $F,
+            ) {
+                let lr = 1. / (left - right);
+                let bt = 1. / (bottom - top);
+                let nf = 1. / (near - far);
+                out.0 = -2. * lr;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 0.;
+                out.4 = 0.;
+                out.5 = -2. * bt;
+                out.6 = 0.;
+                out.7 = 0.;
+                out.8 = 0.;
+                out.9 = 0.;
+                out.10 = nf;
+                out.11 = 0.;
+                out.12 = (left + right) * lr;
+                out.13 = (top + bottom) * bt;
+                out.14 = near * nf;
+                out.15 = 1.;
+            }
+
+            pub fn lookAt(
+                out: &mut $Matrix4,
+                eye: &$Vector3,
+                center: &$Vector3,
+                up: &$Vector3,
+            ) {
+                let eyex = eye.0;
+                let eyey = eye.1;
+                let eyez = eye.2;
+                let upx = up.0;
+                let upy = up.1;
+                let upz = up.2;
+                let centerx = center.0;
+                let centery = center.1;
+                let centerz = center.2;
+
+                if $F::abs(eyex - centerx) < EPSILON
+                    && $F::abs(eyey - centery) < EPSILON
+                    && $F::abs(eyez - centerz) < EPSILON
+                {
+                    $Matrix4::identity(out);
+                    return;
+                }
+
+                let mut z0 = eyex - centerx;
+                let mut z1 = eyey - centery;
+                let mut z2 = eyez - centerz;
+
+                let len = 1. / (z0.powi(2) + z1.powi(2) + z2.powi(2)).sqrt();
+                z0 *= len;
+                z1 *= len;
+                z2 *= len;
+
+                let mut x0 = upy * z2 - upz * z1;
+                let mut x1 = upz * z0 - upx * z2;
+                let mut x2 = upx * z1 - upy * z0;
+                let mut len = (x0.powi(2) + x1.powi(2) + x2.powi(2)).sqrt();
+                if len < EPSILON {
+                    x0 = 0.;
+                    x1 = 0.;
+                    x2 = 0.;
+                } else {
+                    len = 1. / len;
+                    x0 *= len;
+                    x1 *= len;
+                    x2 *= len;
+                }
+
+                let mut y0 = z1 * x2 - z2 * x1;
+                let mut y1 = z2 * x0 - z0 * x2;
+                let mut y2 = z0 * x1 - z1 * x0;
+
+                len = (y0.powi(2) + y1.powi(2) + y2.powi(2)).sqrt();
+                if len < EPSILON {
+                    y0 = 0.;
+                    y1 = 0.;
+                    y2 = 0.;
+                } else {
+                    len = 1. / len;
+                    y0 *= len;
+                    y1 *= len;
+                    y2 *= len;
+                }
+
+                out.0 = x0;
+                out.1 = y0;
+                out.2 = z0;
+                out.3 = 0.;
+                out.4 = x1;
+                out.5 = y1;
+                out.6 = z1;
+                out.7 = 0.;
+                out.8 = x2;
+                out.9 = y2;
+                out.10 = z2;
+                out.11 = 0.;
+                out.12 = -(x0 * eyex + x1 * eyey + x2 * eyez);
+                out.13 = -(y0 * eyex + y1 * eyey + y2 * eyez);
+                out.14 = -(z0 * eyex + z1 * eyey + z2 * eyez);
+                out.15 = 1.;
+            }
+
+            pub fn targetTo(
+                out: &mut $Matrix4,
+                eye: &$Vector3,
+                target: &$Vector3,
+                up: &$Vector3,
+            ) {
+                let eyex = eye.0;
+                let eyey = eye.1;
+                let eyez = eye.2;
+                let upx = up.0;
+                let upy = up.1;
+                let upz = up.2;
+
+                let mut z0 = eyex - target.0;
+                let mut z1 = eyey - target.1;
+                let mut z2 = eyez - target.2;
+
+                let mut len = z0 * z0 + z1 * z1 + z2 * z2;
+                if len > EPSILON {
+                    len = 1. / $F::sqrt(len);
+                    z0 *= len;
+                    z1 *= len;
+                    z2 *= len;
+                }
+
+                let mut x0 = upy * z2 - upz * z1;
+                let mut x1 = upz * z0 - upx * z2;
+                let mut x2 = upx * z1 - upy * z0;
+
+                len = x0 * x0 + x1 * x1 + x2 * x2;
+                if len > EPSILON {
+                    len = 1. / $F::sqrt(len);
+                    x0 *= len;
+                    x1 *= len;
+                    x2 *= len;
+                }
+
+                out.0 = x0;
+                out.1 = x1;
+                out.2 = x2;
+                out.3 = 0.;
+                out.4 = z1 * x2 - z2 * x1;
+                out.5 = z2 * x0 - z0 * x2;
+                out.6 = z0 * x1 - z1 * x0;
+                out.7 = 0.;
+                out.8 = z0;
+                out.9 = z1;
+                out.10 = z2;
+                out.11 = 0.;
+                out.12 = eyex;
+                out.13 = eyey;
+                out.14 = eyez;
+                out.15 = 1.;
+            }
+
+            pub fn str(a: &$Matrix4) -> String {
+                a.to_string()
+            }
+
+            pub fn frob(a: &$Matrix4) -> $F {
+                kernels::length(a.array())
+            }
+
+            pub fn add(out: &mut $Matrix4, a: &$Matrix4, b: &$Matrix4) {
+                kernels::add(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn subtract(
+                out: &mut $Matrix4,
+                a: &$Matrix4,
+                b: &$Matrix4,
+            ) {
+                kernels::subtract(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn multiplyScalar(
+                out: &mut $Matrix4,
+                a: &$Matrix4,
+                b: $F,
+            ) {
+                kernels::scale(out.arrayMut(), a.array(), b);
+            }
+
+            pub fn multiplyScalarAndAdd(
+                out: &mut $Matrix4,
+                a: &$Matrix4,
+                b: &$Matrix4,
+                scale: $F,
+            ) {
+                kernels::scaleAndAdd(out.arrayMut(), a.array(), b.array(), scale);
+            }
+
+            pub fn exactEquals(a: &$Matrix4, b: &$Matrix4) -> bool {
+                kernels::exactEquals(a.array(), b.array())
+            }
+
+            pub fn equals(a: &$Matrix4, b: &$Matrix4) -> bool {
+                match getEqualsMode() {
+                    EqualsMode::Epsilon => $Matrix4::equalsWithEpsilon(a, b, getEpsilon()),
+                    EqualsMode::Ulps => $Matrix4::equalsWithUlps(a, b, getMaxUlps()),
+                }
+            }
+
+            pub fn equalsWithEpsilon(
+                a: &$Matrix4,
+                b: &$Matrix4,
+                epsilon: $F,
+            ) -> bool {
+                kernels::equalsWithEpsilon(a.array(), b.array(), epsilon)
+            }
+
+            pub fn equalsWithUlps(
+                a: &$Matrix4,
+                b: &$Matrix4,
+                maxUlps: u32,
+            ) -> bool {
+                kernels::equalsWithUlps(a.array(), b.array(), maxUlps)
+            }
+
+            pub fn mul(out: &mut $Matrix4, a: &$Matrix4, b: &$Matrix4) {
+                $Matrix4::multiply(out, a, b);
+            }
+
+            pub fn sub(out: &mut $Matrix4, a: &$Matrix4, b: &$Matrix4) {
+                $Matrix4::subtract(out, a, b);
+            }
+
+            // Multiplies `count` pairs of matrices packed in `a` and `b`, 16 elements each.
+            pub fn multiplyArray(
+                out: &mut [$F],
+                a: &[$F],
+                b: &[$F],
+                count: usize,
+            ) {
+                let len = count * 16;
+                assert!(
+                    out.len() >= len && a.len() >= len && b.len() >= len,
+                    "the buffers are shorter than {} elements",
+                    len
+                );
+                let m = &mut $Matrix4::create();
+                for i in 0..count {
+                    let r = i * 16..i * 16 + 16;
+                    $Matrix4::multiply(
+                        m,
+                        &$Matrix4::fromSlice(&a[r.clone()]),
+                        &$Matrix4::fromSlice(&b[r.clone()]),
+                    );
+                    m.writeSlice(&mut out[r]);
+                }
+            }
         }
 
-        // Perform axis-specific matrix multiplication
-        out.0 = a00 * c - a20 * s;
-        out.1 = a01 * c - a21 * s;
-        out.2 = a02 * c - a22 * s;
-        out.3 = a03 * c - a23 * s;
-        out.8 = a00 * s + a20 * c;
-        out.9 = a01 * s + a21 * c;
-        out.10 = a02 * s + a22 * c;
-        out.11 = a03 * s + a23 * c;
-    }
-
-    pub fn rotateZ(out: &mut Matrix4, a: &Matrix4, rad: f32) {
-        let s = f32::sin(rad);
-        let c = f32::cos(rad);
-        let a00 = a.0;
-        let a01 = a.1;
-        let a02 = a.2;
-        let a03 = a.3;
-        let a10 = a.4;
-        let a11 = a.5;
-        let a12 = a.6;
-        let a13 = a.7;
-        if (out as *const Matrix4) != (a as *const Matrix4) {
-            out.8 = a.8;
-            out.9 = a.9;
-            out.10 = a.10;
-            out.11 = a.11;
-            out.12 = a.12;
-            out.13 = a.13;
-            out.14 = a.14;
-            out.15 = a.15;
+        f32_only! {
+            $F,
+            // Field of view in degrees, as the `VRFieldOfView` of WebVR / WebXR.
+            #[cfg_attr(feature = "wasm", wasm_bindgen)]
+            #[derive(Clone, Copy, Debug, PartialEq)]
+            pub struct FieldOfView {
+                pub upDegrees: f32,
+                pub downDegrees: f32,
+                pub leftDegrees: f32,
+                pub rightDegrees: f32,
+            }
+
+            #[cfg_attr(feature = "wasm", wasm_bindgen)]
+            impl FieldOfView {
+                pub fn fromValues(
+                    upDegrees: f32,
+                    downDegrees: f32,
+                    leftDegrees: f32,
+                    rightDegrees: f32,
+                ) -> FieldOfView {
+                    FieldOfView {
+                        upDegrees,
+                        downDegrees,
+                        leftDegrees,
+                        rightDegrees,
+                    }
+                }
+            }
+
+            // Any js object with the four `*Degrees` fields, such as the fov given by the WebXR device.
+            #[cfg(feature = "wasm")]
+            #[wasm_bindgen]
+            extern "C" {
+                pub type FieldOfViewLike;
+
+                #[wasm_bindgen(method, getter)]
+                pub fn upDegrees(this: &FieldOfViewLike) -> f32;
+
+                #[wasm_bindgen(method, getter)]
+                pub fn downDegrees(this: &FieldOfViewLike) -> f32;
+
+                #[wasm_bindgen(method, getter)]
+                pub fn leftDegrees(this: &FieldOfViewLike) -> f32;
+
+                #[wasm_bindgen(method, getter)]
+                pub fn rightDegrees(this: &FieldOfViewLike) -> f32;
+            }
         }
 
-        // Perform axis-specific matrix multiplication
-        out.0 = a00 * c + a10 * s;
-        out.1 = a01 * c + a11 * s;
-        out.2 = a02 * c + a12 * s;
-        out.3 = a03 * c + a13 * s;
-        out.4 = a10 * c - a00 * s;
-        out.5 = a11 * c - a01 * s;
-        out.6 = a12 * c - a02 * s;
-        out.7 = a13 * c - a03 * s;
-    }
-
-    pub fn fromTranslation(out: &mut Matrix4, v: &Vector3) {
-        out.0 = 1.;
-        out.1 = 0.;
-        out.2 = 0.;
-        out.3 = 0.;
-        out.4 = 0.;
-        out.5 = 1.;
-        out.6 = 0.;
-        out.7 = 0.;
-        out.8 = 0.;
-        out.9 = 0.;
-        out.10 = 1.;
-        out.11 = 0.;
-        out.12 = v.0;
-        out.13 = v.1;
-        out.14 = v.2;
-        out.15 = 1.;
-    }
-
-    pub fn fromScaling(out: &mut Matrix4, v: &Vector3) {
-        out.0 = v.0;
-        out.1 = 0.;
-        out.2 = 0.;
-        out.3 = 0.;
-        out.4 = 0.;
-        out.5 = v.1;
-        out.6 = 0.;
-        out.7 = 0.;
-        out.8 = 0.;
-        out.9 = 0.;
-        out.10 = v.2;
-        out.11 = 0.;
-        out.12 = 0.;
-        out.13 = 0.;
-        out.14 = 0.;
-        out.15 = 1.;
-    }
-
-    pub fn fromRotation(out: &mut Matrix4, rad: f32, axis: &Vector3) {
-        let mut x = axis.0;
-        let mut y = axis.1;
-        let mut z = axis.2;
-        let mut len = (x.powi(2) + y.powi(2) + z.powi(2)).sqrt();
-
-        if (len < EPSILON) {
-            return;
+        // `#[repr(C)]` keeps the 16 elements contiguous.
+        unsafe impl Elements<$F, 16> for $Matrix4 {}
+
+        #[cfg(feature = "wasm")]
+        #[wasm_bindgen]
+        impl $Matrix4 {
+            pub fn perspectiveFromFieldOfViewObject(
+                out: &mut $Matrix4,
+                fov: &FieldOfViewLike,
+                near: $F,
+                far: $F,
+            ) {
+                let fov = FieldOfView::fromValues(
+                    fov.upDegrees(),
+                    fov.downDegrees(),
+                    fov.leftDegrees(),
+                    fov.rightDegrees(),
+                );
+                $Matrix4::perspectiveFromFieldOfView(out, &fov, near, far);
+            }
         }
 
-        len = 1. / len;
-        x *= len;
-        y *= len;
-        z *= len;
-
-        let s = f32::sin(rad);
-        let c = f32::cos(rad);
-        let t = 1. - c;
-
-        // Perform rotation-specific matrix multiplication
-        out.0 = x * x * t + c;
-        out.1 = y * x * t + z * s;
-        out.2 = z * x * t - y * s;
-        out.3 = 0.;
-        out.4 = x * y * t - z * s;
-        out.5 = y * y * t + c;
-        out.6 = z * y * t + x * s;
-        out.7 = 0.;
-        out.8 = x * z * t + y * s;
-        out.9 = y * z * t - x * s;
-        out.10 = z * z * t + c;
-        out.11 = 0.;
-        out.12 = 0.;
-        out.13 = 0.;
-        out.14 = 0.;
-        out.15 = 1.;
-    }
-
-    pub fn fromXRotation(out: &mut Matrix4, rad: f32) {
-        let s = f32::sin(rad);
-        let c = f32::cos(rad);
-
-        // Perform axis-specific matrix multiplication
-        out.0 = 1.;
-        out.1 = 0.;
-        out.2 = 0.;
-        out.3 = 0.;
-        out.4 = 0.;
-        out.5 = c;
-        out.6 = s;
-        out.7 = 0.;
-        out.8 = 0.;
-        out.9 = -s;
-        out.10 = c;
-        out.11 = 0.;
-        out.12 = 0.;
-        out.13 = 0.;
-        out.14 = 0.;
-        out.15 = 1.;
-    }
-
-    pub fn fromYRotation(out: &mut Matrix4, rad: f32) {
-        let s = f32::sin(rad);
-        let c = f32::cos(rad);
-
-        // Perform axis-specific matrix multiplication
-        out.0 = c;
-        out.1 = 0.;
-        out.2 = -s;
-        out.3 = 0.;
-        out.4 = 0.;
-        out.5 = 1.;
-        out.6 = 0.;
-        out.7 = 0.;
-        out.8 = s;
-        out.9 = 0.;
-        out.10 = c;
-        out.11 = 0.;
-        out.12 = 0.;
-        out.13 = 0.;
-        out.14 = 0.;
-        out.15 = 1.;
-    }
-
-    pub fn fromZRotation(out: &mut Matrix4, rad: f32) {
-        let s = f32::sin(rad);
-        let c = f32::cos(rad);
-
-        // Perform axis-specific matrix multiplication
-        out.0 = c;
-        out.1 = s;
-        out.2 = 0.;
-        out.3 = 0.;
-        out.4 = -s;
-        out.5 = c;
-        out.6 = 0.;
-        out.7 = 0.;
-        out.8 = 0.;
-        out.9 = 0.;
-        out.10 = 1.;
-        out.11 = 0.;
-        out.12 = 0.;
-        out.13 = 0.;
-        out.14 = 0.;
-        out.15 = 1.;
-    }
-
-    pub fn fromRotationTranslation(out: &mut Matrix4, q: &Quaternion, v: &Vector3) {
-        // Quaternion math
-        let x = q.0;
-        let y = q.1;
-        let z = q.2;
-        let w = q.3;
-        let x2 = x + x;
-        let y2 = y + y;
-        let z2 = z + z;
-
-        let xx = x * x2;
-        let xy = x * y2;
-        let xz = x * z2;
-        let yy = y * y2;
-        let yz = y * z2;
-        let zz = z * z2;
-        let wx = w * x2;
-        let wy = w * y2;
-        let wz = w * z2;
-
-        out.0 = 1. - (yy + zz);
-        out.1 = xy + wz;
-        out.2 = xz - wy;
-        out.3 = 0.;
-        out.4 = xy - wz;
-        out.5 = 1. - (xx + zz);
-        out.6 = yz + wx;
-        out.7 = 0.;
-        out.8 = xz + wy;
-        out.9 = yz - wx;
-        out.10 = 1. - (xx + yy);
-        out.11 = 0.;
-        out.12 = v.0;
-        out.13 = v.1;
-        out.14 = v.2;
-        out.15 = 1.;
-    }
-
-    pub fn fromQuat2(out: &mut Matrix4, a: &Quaternion2) {
-        let translation = &mut Vector3::create();
-        let bx = -a.0;
-        let by = -a.1;
-        let bz = -a.2;
-        let bw = a.3;
-        let ax = a.4;
-        let ay = a.5;
-        let az = a.6;
-        let aw = a.7;
-
-        let magnitude = bx * bx + by * by + bz * bz + bw * bw;
-        //Only scale if it makes sense
-        if (magnitude > EPSILON) {
-            translation.0 = (ax * bw + aw * bx + ay * bz - az * by) * 2. / magnitude;
-            translation.1 = (ay * bw + aw * by + az * bx - ax * bz) * 2. / magnitude;
-            translation.2 = (az * bw + aw * bz + ax * by - ay * bx) * 2. / magnitude;
-        } else {
-            translation.0 = (ax * bw + aw * bx + ay * bz - az * by) * 2.;
-            translation.1 = (ay * bw + aw * by + az * bx - ax * bz) * 2.;
-            translation.2 = (az * bw + aw * bz + ax * by - ay * bx) * 2.;
+        // Flat f32 buffers, such as the uniforms or the batch functions.
+        impl $Matrix4 {
+            pub fn fromSlice(a: &[$F]) -> $Matrix4 {
+                $Matrix4(
+                    a[0], a[1], a[2], a[3], a[4], a[5], a[6], a[7], a[8], a[9], a[10], a[11],
+                    a[12], a[13], a[14], a[15],
+                )
+            }
+
+            pub fn writeSlice(&self, out: &mut [$F]) {
+                out[..16].copy_from_slice(self.array());
+            }
+
+            pub fn asSlice(&self) -> &[$F] {
+                self.array()
+            }
         }
-        Matrix4::fromRotationTranslation(
-            out,
-            &Quaternion::fromValues(a.0, a.1, a.2, a.3),
-            translation,
-        );
-    }
-
-    pub fn getTranslation(out: &mut Vector3, mat: &Matrix4) {
-        out.0 = mat.12;
-        out.1 = mat.13;
-        out.2 = mat.14;
-    }
-
-    pub fn getScaling(out: &mut Vector3, mat: &Matrix4) {
-        let m11 = mat.0;
-        let m12 = mat.1;
-        let m13 = mat.2;
-        let m21 = mat.4;
-        let m22 = mat.5;
-        let m23 = mat.6;
-        let m31 = mat.8;
-        let m32 = mat.9;
-        let m33 = mat.10;
-        out.0 = (m11.powi(2) + m12.powi(2) + m13.powi(2)).sqrt();
-        out.1 = (m21.powi(2) + m22.powi(2) + m23.powi(2)).sqrt();
-        out.2 = (m31.powi(2) + m32.powi(2) + m33.powi(2)).sqrt();
-    }
-
-    pub fn getRotation(out: &mut Quaternion, mat: &Matrix4) {
-        let scaling = &mut Vector3::create();
-        Matrix4::getScaling(scaling, mat);
-
-        let is1 = 1. / scaling.0;
-        let is2 = 1. / scaling.1;
-        let is3 = 1. / scaling.2;
-
-        let sm11 = mat.0 * is1;
-        let sm12 = mat.1 * is2;
-        let sm13 = mat.2 * is3;
-        let sm21 = mat.4 * is1;
-        let sm22 = mat.5 * is2;
-        let sm23 = mat.6 * is3;
-        let sm31 = mat.8 * is1;
-        let sm32 = mat.9 * is2;
-        let sm33 = mat.10 * is3;
-
-        let trace = sm11 + sm22 + sm33;
-
-        if (trace > 0.) {
-            let S = f32::sqrt(trace + 1.0) * 2.;
-            out.3 = 0.25 * S;
-            out.0 = (sm23 - sm32) / S;
-            out.1 = (sm31 - sm13) / S;
-            out.2 = (sm12 - sm21) / S;
-        } else if ((sm11 > sm22) && (sm11 > sm33)) {
-            let S = f32::sqrt(1.0 + sm11 - sm22 - sm33) * 2.;
-            out.3 = (sm23 - sm32) / S;
-            out.0 = 0.25 * S;
-            out.1 = (sm12 + sm21) / S;
-            out.2 = (sm31 + sm13) / S;
-        } else if (sm22 > sm33) {
-            let S = f32::sqrt(1.0 + sm22 - sm11 - sm33) * 2.;
-            out.3 = (sm31 - sm13) / S;
-            out.0 = (sm12 + sm21) / S;
-            out.1 = 0.25 * S;
-            out.2 = (sm23 + sm32) / S;
-        } else {
-            let S = f32::sqrt(1.0 + sm33 - sm11 - sm22) * 2.;
-            out.3 = (sm12 - sm21) / S;
-            out.0 = (sm31 + sm13) / S;
-            out.1 = (sm23 + sm32) / S;
-            out.2 = 0.25 * S;
+
+        #[cfg(feature = "wasm")]
+        #[wasm_bindgen]
+        impl $Matrix4 {
+            // Aliases the elements in wasm memory without copying. The view is detached when the memory grows,
+            // so don't keep it across calls that may allocate.
+            pub fn view(&self) -> $FloatArray {
+                unsafe { $FloatArray::view(self.asSlice()) }
+            }
+
+            // Reads the first 16 elements of `a`, without allocating in wasm memory. Throws a `RangeError`
+            // when `a` is shorter.
+            pub fn $fromFloatArray(a: &$FloatArray) -> Result<$Matrix4, JsValue> {
+                check_length(16, a.length() as usize)?;
+                let mut elements = [0.; 16];
+                a.subarray(0, 16).copy_to(&mut elements);
+                Ok($Matrix4::fromSlice(&elements))
+            }
+
+            pub fn copyFromArray(out: &mut $Matrix4, a: &$FloatArray) -> Result<(), JsValue> {
+                *out = $Matrix4::$fromFloatArray(a)?;
+                Ok(())
+            }
         }
-    }
-
-    pub fn fromRotationTranslationScale(
-        out: &mut Matrix4,
-        q: &Quaternion,
-        v: &Vector3,
-        s: &Vector3,
-    ) {
-        // Quaternion math
-        let x = q.0;
-        let y = q.1;
-        let z = q.2;
-        let w = q.3;
-        let x2 = x + x;
-        let y2 = y + y;
-        let z2 = z + z;
-
-        let xx = x * x2;
-        let xy = x * y2;
-        let xz = x * z2;
-        let yy = y * y2;
-        let yz = y * z2;
-        let zz = z * z2;
-        let wx = w * x2;
-        let wy = w * y2;
-        let wz = w * z2;
-        let sx = s.0;
-        let sy = s.1;
-        let sz = s.2;
-
-        out.0 = (1. - (yy + zz)) * sx;
-        out.1 = (xy + wz) * sx;
-        out.2 = (xz - wy) * sx;
-        out.3 = 0.;
-        out.4 = (xy - wz) * sy;
-        out.5 = (1. - (xx + zz)) * sy;
-        out.6 = (yz + wx) * sy;
-        out.7 = 0.;
-        out.8 = (xz + wy) * sz;
-        out.9 = (yz - wx) * sz;
-        out.10 = (1. - (xx + yy)) * sz;
-        out.11 = 0.;
-        out.12 = v.0;
-        out.13 = v.1;
-        out.14 = v.2;
-        out.15 = 1.;
-    }
-
-    pub fn fromRotationTranslationScaleOrigin(
-        out: &mut Matrix4,
-        q: &Quaternion,
-        v: &Vector3,
-        s: &Vector3,
-        o: &Vector3,
-    ) {
-        // Quaternion math
-        let x = q.0;
-        let y = q.1;
-        let z = q.2;
-        let w = q.3;
-        let x2 = x + x;
-        let y2 = y + y;
-        let z2 = z + z;
-
-        let xx = x * x2;
-        let xy = x * y2;
-        let xz = x * z2;
-        let yy = y * y2;
-        let yz = y * z2;
-        let zz = z * z2;
-        let wx = w * x2;
-        let wy = w * y2;
-        let wz = w * z2;
-
-        let sx = s.0;
-        let sy = s.1;
-        let sz = s.2;
-
-        let ox = o.0;
-        let oy = o.1;
-        let oz = o.2;
-
-        let out0 = (1. - (yy + zz)) * sx;
-        let out1 = (xy + wz) * sx;
-        let out2 = (xz - wy) * sx;
-        let out4 = (xy - wz) * sy;
-        let out5 = (1. - (xx + zz)) * sy;
-        let out6 = (yz + wx) * sy;
-        let out8 = (xz + wy) * sz;
-        let out9 = (yz - wx) * sz;
-        let out10 = (1. - (xx + yy)) * sz;
-
-        out.0 = out0;
-        out.1 = out1;
-        out.2 = out2;
-        out.3 = 0.;
-        out.4 = out4;
-        out.5 = out5;
-        out.6 = out6;
-        out.7 = 0.;
-        out.8 = out8;
-        out.9 = out9;
-        out.10 = out10;
-        out.11 = 0.;
-        out.12 = v.0 + ox - (out0 * ox + out4 * oy + out8 * oz);
-        out.13 = v.1 + oy - (out1 * ox + out5 * oy + out9 * oz);
-        out.14 = v.2 + oz - (out2 * ox + out6 * oy + out10 * oz);
-        out.15 = 1.;
-    }
-
-    pub fn fromQuat(out: &mut Matrix4, q: &Quaternion) {
-        let x = q.0;
-        let y = q.1;
-        let z = q.2;
-        let w = q.3;
-        let x2 = x + x;
-        let y2 = y + y;
-        let z2 = z + z;
-
-        let xx = x * x2;
-        let yx = y * x2;
-        let yy = y * y2;
-        let zx = z * x2;
-        let zy = z * y2;
-        let zz = z * z2;
-        let wx = w * x2;
-        let wy = w * y2;
-        let wz = w * z2;
-
-        out.0 = 1. - yy - zz;
-        out.1 = yx + wz;
-        out.2 = zx - wy;
-        out.3 = 0.;
-
-        out.4 = yx - wz;
-        out.5 = 1. - xx - zz;
-        out.6 = zy + wx;
-        out.7 = 0.;
-
-        out.8 = zx + wy;
-        out.9 = zy - wx;
-        out.10 = 1. - xx - yy;
-        out.11 = 0.;
-
-        out.12 = 0.;
-        out.13 = 0.;
-        out.14 = 0.;
-        out.15 = 1.;
-    }
-
-    pub fn frustum(
-        out: &mut Matrix4,
-        left: f32,
-        right: f32,
-        bottom: f32,
-        top: f32,
-        near: f32,
-        far: f32,
-    ) {
-        let rl = 1. / (right - left);
-        let tb = 1. / (top - bottom);
-        let nf = 1. / (near - far);
-        out.0 = (near * 2.) * rl;
-        out.1 = 0.;
-        out.2 = 0.;
-        out.3 = 0.;
-        out.4 = 0.;
-        out.5 = (near * 2.) * tb;
-        out.6 = 0.;
-        out.7 = 0.;
-        out.8 = (right + left) * rl;
-        out.9 = (top + bottom) * tb;
-        out.10 = (far + near) * nf;
-        out.11 = -1.;
-        out.12 = 0.;
-        out.13 = 0.;
-        out.14 = (far * near * 2.) * nf;
-        out.15 = 0.;
-    }
-
-    pub fn perspective(out: &mut Matrix4, fovy: f32, aspect: f32, near: f32, far: Option<f32>) {
-        let f = 1.0 / f32::tan(fovy / 2.);
-        let nf;
-        out.0 = f / aspect;
-        out.1 = 0.;
-        out.2 = 0.;
-        out.3 = 0.;
-        out.4 = 0.;
-        out.5 = f;
-        out.6 = 0.;
-        out.7 = 0.;
-        out.8 = 0.;
-        out.9 = 0.;
-        out.11 = -1.;
-        out.12 = 0.;
-        out.13 = 0.;
-        out.15 = 0.;
-        match far {
-            Some(far) => {
-                if far == INFINITY || far == NEG_INFINITY {
-                    out.10 = -1.;
-                    out.14 = -2. * near;
-                } else {
-                    nf = 1. / (near - far);
-                    out.10 = (far + near) * nf;
-                    out.14 = (2. * far * near) * nf;
-                }
-            },
-            None => {
-                out.10 = -1.;
-                out.14 = -2. * near;        
-            }
-        };
-    }
-
-    // pub fn perspectiveFromFieldOfView(out: &mut Matrix4, fov: f32, near: f32, far: f32) {
-    //   let upTan = f32::tan(fov.upDegrees * PI/180.0);
-    //   let downTan = f32::tan(fov.downDegrees * PI/180.0);
-    //   let leftTan = f32::tan(fov.leftDegrees * PI/180.0);
-    //   let rightTan = f32::tan(fov.rightDegrees * PI/180.0);
-    //   let xScale = 2.0 / (leftTan + rightTan);
-    //   let yScale = 2.0 / (upTan + downTan);
-
-    //   out.0 = xScale;
-    //   out.1 = 0.0;
-    //   out.2 = 0.0;
-    //   out.3 = 0.0;
-    //   out.4 = 0.0;
-    //   out.5 = yScale;
-    //   out.6 = 0.0;
-    //   out.7 = 0.0;
-    //   out.8 = -((leftTan - rightTan) * xScale * 0.5);
-    //   out.9 = ((upTan - downTan) * yScale * 0.5);
-    //   out.10 = far / (near - far);
-    //   out.11 = -1.0;
-    //   out.12 = 0.0;
-    //   out.13 = 0.0;
-    //   out.14 = (far * near) / (near - far);
-    //   out.15 = 0.0;
-    //   }
-
-    pub fn ortho(
-        out: &mut Matrix4,
-        left: f32,
-        right: f32,
-        bottom: f32,
-        top: f32,
-        near: f32,
-        far: f32,
-    ) {
-        let lr = 1. / (left - right);
-        let bt = 1. / (bottom - top);
-        let nf = 1. / (near - far);
-        out.0 = -2. * lr;
-        out.1 = 0.;
-        out.2 = 0.;
-        out.3 = 0.;
-        out.4 = 0.;
-        out.5 = -2. * bt;
-        out.6 = 0.;
-        out.7 = 0.;
-        out.8 = 0.;
-        out.9 = 0.;
-        out.10 = 2. * nf;
-        out.11 = 0.;
-        out.12 = (left + right) * lr;
-        out.13 = (top + bottom) * bt;
-        out.14 = (far + near) * nf;
-        out.15 = 1.;
-    }
-
-    pub fn lookAt(out: &mut Matrix4, eye: &Vector3, center: &Vector3, up: &Vector3) {
-        let eyex = eye.0;
-        let eyey = eye.1;
-        let eyez = eye.2;
-        let upx = up.0;
-        let upy = up.1;
-        let upz = up.2;
-        let centerx = center.0;
-        let centery = center.1;
-        let centerz = center.2;
-
-        if (f32::abs(eyex - centerx) < EPSILON
-            && f32::abs(eyey - centery) < EPSILON
-            && f32::abs(eyez - centerz) < EPSILON)
-        {
-            Matrix4::identity(out);
-            return;
+
+        impl PartialEq for $Matrix4 {
+            fn eq(&self, other: &$Matrix4) -> bool {
+                $Matrix4::exactEquals(self, other)
+            }
         }
 
-        let mut z0 = eyex - centerx;
-        let mut z1 = eyey - centery;
-        let mut z2 = eyez - centerz;
-
-        let mut len = 1. / (z0.powi(2) + z1.powi(2) + z2.powi(2)).sqrt();
-        z0 *= len;
-        z1 *= len;
-        z2 *= len;
-
-        let mut x0 = upy * z2 - upz * z1;
-        let mut x1 = upz * z0 - upx * z2;
-        let mut x2 = upx * z1 - upy * z0;
-        let mut len = (x0.powi(2) + x1.powi(2) + x2.powi(2)).sqrt();
-        if (len < EPSILON) {
-            x0 = 0.;
-            x1 = 0.;
-            x2 = 0.;
-        } else {
-            len = 1. / len;
-            x0 *= len;
-            x1 *= len;
-            x2 *= len;
+        impl ApproxEq for $Matrix4 {
+            fn approx_eq(&self, other: &$Matrix4) -> bool {
+                $Matrix4::equals(self, other)
+            }
         }
 
-        let mut y0 = z1 * x2 - z2 * x1;
-        let mut y1 = z2 * x0 - z0 * x2;
-        let mut y2 = z0 * x1 - z1 * x0;
-
-        len = (y0.powi(2) + y1.powi(2) + y2.powi(2)).sqrt();
-        if (len < EPSILON) {
-            y0 = 0.;
-            y1 = 0.;
-            y2 = 0.;
-        } else {
-            len = 1. / len;
-            y0 *= len;
-            y1 *= len;
-            y2 *= len;
+        impl Default for $Matrix4 {
+            fn default() -> $Matrix4 {
+                $Matrix4::create()
+            }
         }
 
-        out.0 = x0;
-        out.1 = y0;
-        out.2 = z0;
-        out.3 = 0.;
-        out.4 = x1;
-        out.5 = y1;
-        out.6 = z1;
-        out.7 = 0.;
-        out.8 = x2;
-        out.9 = y2;
-        out.10 = z2;
-        out.11 = 0.;
-        out.12 = -(x0 * eyex + x1 * eyey + x2 * eyez);
-        out.13 = -(y0 * eyex + y1 * eyey + y2 * eyez);
-        out.14 = -(z0 * eyex + z1 * eyey + z2 * eyez);
-        out.15 = 1.;
-    }
-
-    pub fn targetTo(out: &mut Matrix4, eye: &Vector3, target: &Vector3, up: &Vector3) {
-        let eyex = eye.0;
-        let eyey = eye.1;
-        let eyez = eye.2;
-        let upx = up.0;
-        let upy = up.1;
-        let upz = up.2;
-
-        let mut z0 = eyex - target.0;
-        let mut z1 = eyey - target.1;
-        let mut z2 = eyez - target.2;
-
-        let mut len = z0 * z0 + z1 * z1 + z2 * z2;
-        if (len > EPSILON) {
-            len = 1. / f32::sqrt(len);
-            z0 *= len;
-            z1 *= len;
-            z2 *= len;
+        impl fmt::Display for $Matrix4 {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "mat4({}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {})",
+                    self.0,
+                    self.1,
+                    self.2,
+                    self.3,
+                    self.4,
+                    self.5,
+                    self.6,
+                    self.7,
+                    self.8,
+                    self.9,
+                    self.10,
+                    self.11,
+                    self.12,
+                    self.13,
+                    self.14,
+                    self.15
+                )
+            }
         }
 
-        let mut x0 = upy * z2 - upz * z1;
-        let mut x1 = upz * z0 - upx * z2;
-        let mut x2 = upx * z1 - upy * z0;
+        impl FromStr for $Matrix4 {
+            type Err = ParseError;
 
-        len = x0 * x0 + x1 * x1 + x2 * x2;
-        if (len > EPSILON) {
-            len = 1. / f32::sqrt(len);
-            x0 *= len;
-            x1 *= len;
-            x2 *= len;
+            fn from_str(s: &str) -> Result<$Matrix4, ParseError> {
+                let mut v = [0.; 16];
+                parse_values(s, "mat4", &mut v)?;
+                Ok($Matrix4(
+                    v[0], v[1], v[2], v[3], v[4], v[5], v[6], v[7], v[8], v[9], v[10], v[11],
+                    v[12], v[13], v[14], v[15],
+                ))
+            }
         }
 
-        out.0 = x0;
-        out.1 = x1;
-        out.2 = x2;
-        out.3 = 0.;
-        out.4 = z1 * x2 - z2 * x1;
-        out.5 = z2 * x0 - z0 * x2;
-        out.6 = z0 * x1 - z1 * x0;
-        out.7 = 0.;
-        out.8 = z0;
-        out.9 = z1;
-        out.10 = z2;
-        out.11 = 0.;
-        out.12 = eyex;
-        out.13 = eyey;
-        out.14 = eyez;
-        out.15 = 1.;
-    }
-
-    // pub fn str(a: &Matrix4) {
-    //   return "mat4(" + a.0 + ", " + a.1 + ", " + a.2 + ", " + a.3 + ", " +
-    //           a.4 + ", " + a.5 + ", " + a.6 + ", " + a.7 + ", " +
-    //           a.8 + ", " + a.9 + ", " + a.10 + ", " + a.11 + ", " +
-    //           a.12 + ", " + a.13 + ", " + a.14 + ", " + a.15 + ")";
-    // }
-
-    pub fn frob(a: &Matrix4) -> f32 {
-        (a.0.powi(2)
-            + a.1.powi(2)
-            + a.2.powi(2)
-            + a.3.powi(2)
-            + a.4.powi(2)
-            + a.5.powi(2)
-            + a.6.powi(2)
-            + a.7.powi(2)
-            + a.8.powi(2)
-            + a.9.powi(2)
-            + a.10.powi(2)
-            + a.11.powi(2)
-            + a.12.powi(2)
-            + a.13.powi(2)
-            + a.14.powi(2)
-            + a.15.powi(2))
-        .sqrt()
-    }
-
-    pub fn add(out: &mut Matrix4, a: &Matrix4, b: &Matrix4) {
-        out.0 = a.0 + b.0;
-        out.1 = a.1 + b.1;
-        out.2 = a.2 + b.2;
-        out.3 = a.3 + b.3;
-        out.4 = a.4 + b.4;
-        out.5 = a.5 + b.5;
-        out.6 = a.6 + b.6;
-        out.7 = a.7 + b.7;
-        out.8 = a.8 + b.8;
-        out.9 = a.9 + b.9;
-        out.10 = a.10 + b.10;
-        out.11 = a.11 + b.11;
-        out.12 = a.12 + b.12;
-        out.13 = a.13 + b.13;
-        out.14 = a.14 + b.14;
-        out.15 = a.15 + b.15;
-    }
-
-    pub fn subtract(out: &mut Matrix4, a: &Matrix4, b: &Matrix4) {
-        out.0 = a.0 - b.0;
-        out.1 = a.1 - b.1;
-        out.2 = a.2 - b.2;
-        out.3 = a.3 - b.3;
-        out.4 = a.4 - b.4;
-        out.5 = a.5 - b.5;
-        out.6 = a.6 - b.6;
-        out.7 = a.7 - b.7;
-        out.8 = a.8 - b.8;
-        out.9 = a.9 - b.9;
-        out.10 = a.10 - b.10;
-        out.11 = a.11 - b.11;
-        out.12 = a.12 - b.12;
-        out.13 = a.13 - b.13;
-        out.14 = a.14 - b.14;
-        out.15 = a.15 - b.15;
-    }
-
-    pub fn multiplyScalar(out: &mut Matrix4, a: &Matrix4, b: f32) {
-        out.0 = a.0 * b;
-        out.1 = a.1 * b;
-        out.2 = a.2 * b;
-        out.3 = a.3 * b;
-        out.4 = a.4 * b;
-        out.5 = a.5 * b;
-        out.6 = a.6 * b;
-        out.7 = a.7 * b;
-        out.8 = a.8 * b;
-        out.9 = a.9 * b;
-        out.10 = a.10 * b;
-        out.11 = a.11 * b;
-        out.12 = a.12 * b;
-        out.13 = a.13 * b;
-        out.14 = a.14 * b;
-        out.15 = a.15 * b;
-    }
-
-    pub fn multiplyScalarAndAdd(out: &mut Matrix4, a: &Matrix4, b: &Matrix4, scale: f32) {
-        out.0 = a.0 + (b.0 * scale);
-        out.1 = a.1 + (b.1 * scale);
-        out.2 = a.2 + (b.2 * scale);
-        out.3 = a.3 + (b.3 * scale);
-        out.4 = a.4 + (b.4 * scale);
-        out.5 = a.5 + (b.5 * scale);
-        out.6 = a.6 + (b.6 * scale);
-        out.7 = a.7 + (b.7 * scale);
-        out.8 = a.8 + (b.8 * scale);
-        out.9 = a.9 + (b.9 * scale);
-        out.10 = a.10 + (b.10 * scale);
-        out.11 = a.11 + (b.11 * scale);
-        out.12 = a.12 + (b.12 * scale);
-        out.13 = a.13 + (b.13 * scale);
-        out.14 = a.14 + (b.14 * scale);
-        out.15 = a.15 + (b.15 * scale);
-    }
-
-    pub fn exactEquals(a: &Matrix4, b: &Matrix4) -> bool {
-        a.0 == b.0
-            && a.1 == b.1
-            && a.2 == b.2
-            && a.3 == b.3
-            && a.4 == b.4
-            && a.5 == b.5
-            && a.6 == b.6
-            && a.7 == b.7
-            && a.8 == b.8
-            && a.9 == b.9
-            && a.10 == b.10
-            && a.11 == b.11
-            && a.12 == b.12
-            && a.13 == b.13
-            && a.14 == b.14
-            && a.15 == b.15
-    }
-
-    pub fn equals(a: &Matrix4, b: &Matrix4) -> bool {
-        let a0 = a.0;
-        let a1 = a.1;
-        let a2 = a.2;
-        let a3 = a.3;
-        let a4 = a.4;
-        let a5 = a.5;
-        let a6 = a.6;
-        let a7 = a.7;
-        let a8 = a.8;
-        let a9 = a.9;
-        let a10 = a.10;
-        let a11 = a.11;
-        let a12 = a.12;
-        let a13 = a.13;
-        let a14 = a.14;
-        let a15 = a.15;
-
-        let b0 = b.0;
-        let b1 = b.1;
-        let b2 = b.2;
-        let b3 = b.3;
-        let b4 = b.4;
-        let b5 = b.5;
-        let b6 = b.6;
-        let b7 = b.7;
-        let b8 = b.8;
-        let b9 = b.9;
-        let b10 = b.10;
-        let b11 = b.11;
-        let b12 = b.12;
-        let b13 = b.13;
-        let b14 = b.14;
-        let b15 = b.15;
-
-        f32::abs(a0 - b0) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a0), f32::abs(b0)))
-            && f32::abs(a1 - b1) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a1), f32::abs(b1)))
-            && f32::abs(a2 - b2) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a2), f32::abs(b2)))
-            && f32::abs(a3 - b3) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a3), f32::abs(b3)))
-            && f32::abs(a4 - b4) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a4), f32::abs(b4)))
-            && f32::abs(a5 - b5) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a5), f32::abs(b5)))
-            && f32::abs(a6 - b6) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a6), f32::abs(b6)))
-            && f32::abs(a7 - b7) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a7), f32::abs(b7)))
-            && f32::abs(a8 - b8) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a8), f32::abs(b8)))
-            && f32::abs(a9 - b9) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a9), f32::abs(b9)))
-            && f32::abs(a10 - b10)
-                <= EPSILON * f32::max(1.0, f32::max(f32::abs(a10), f32::abs(b10)))
-            && f32::abs(a11 - b11)
-                <= EPSILON * f32::max(1.0, f32::max(f32::abs(a11), f32::abs(b11)))
-            && f32::abs(a12 - b12)
-                <= EPSILON * f32::max(1.0, f32::max(f32::abs(a12), f32::abs(b12)))
-            && f32::abs(a13 - b13)
-                <= EPSILON * f32::max(1.0, f32::max(f32::abs(a13), f32::abs(b13)))
-            && f32::abs(a14 - b14)
-                <= EPSILON * f32::max(1.0, f32::max(f32::abs(a14), f32::abs(b14)))
-            && f32::abs(a15 - b15)
-                <= EPSILON * f32::max(1.0, f32::max(f32::abs(a15), f32::abs(b15)))
-    }
-
-    pub fn mul(out: &mut Matrix4, a: &Matrix4, b: &Matrix4) {
-        Matrix4::multiply(out, a, b);
-    }
-
-    pub fn sub(out: &mut Matrix4, a: &Matrix4, b: &Matrix4) {
-        Matrix4::subtract(out, a, b);
-    }
+        #[cfg(test)]
+        mod tests {
+            // Some expected values are copied from the gl-matrix specs at double precision.
+            #![allow(clippy::excessive_precision)]
+            use super::*;
+            use crate::spec_helper::*;
+            use std::$F::consts::PI;
+
+            fn setup() -> ($Matrix4, $Matrix4, $Matrix4) {
+                (
+                    $Matrix4::fromValues(
+                        0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+                    ),
+                    $Matrix4::fromValues(
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+                    ),
+                    $Matrix4::fromValues(
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 4., 5., 6., 1.,
+                    ),
+                )
+            }
+
+            #[test]
+            fn create() {
+                let result = $Matrix4::create();
+                expect_equalish(
+                    &result.elements(),
+                    &$Matrix4::fromValues(
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1.,
+                    )
+                    .elements(),
+                );
+            }
+
+            #[test]
+            fn clone() {
+                let (_, matA, _) = &mut setup();
+                let result = $Matrix4::clone(matA);
+                expect_equalish(&result.elements(), &matA.elements());
+            }
+
+            #[test]
+            fn copy() {
+                let (out, matA, _) = &mut setup();
+                $Matrix4::copy(out, matA);
+                expect_equalish(&out.elements(), &matA.elements());
+            }
+
+            #[test]
+            fn identity() {
+                let (out, _, _) = &mut setup();
+                $Matrix4::identity(out);
+                expect_equalish(&out.elements(), &$Matrix4::create().elements());
+            }
+
+            #[test]
+            fn transpose() {
+                let (out, matA, _) = &mut setup();
+                $Matrix4::transpose(out, matA);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1., 0., 0., 1., 0., 1., 0., 2., 0., 0., 1., 3., 0., 0., 0., 1.,
+                    ],
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+                    ],
+                );
+
+                $Matrix4::transpose(matA, &$Matrix4::clone(matA));
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 0., 0., 1., 0., 1., 0., 2., 0., 0., 1., 3., 0., 0., 0., 1.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn invert() {
+                let (out, matA, _) = &mut setup();
+                assert!($Matrix4::invert(out, matA));
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., -1., -2., -3., 1.,
+                    ],
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+                    ],
+                );
+
+                assert!($Matrix4::invert(
+                    matA,
+                    &$Matrix4::clone(matA)
+                ));
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., -1., -2., -3., 1.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn adjoint() {
+                let (out, matA, _) = &mut setup();
+                $Matrix4::adjoint(out, matA);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., -1., -2., -3., 1.,
+                    ],
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+                    ],
+                );
+
+                $Matrix4::adjoint(matA, &$Matrix4::clone(matA));
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., -1., -2., -3., 1.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn determinant() {
+                let (_, matA, _) = &mut setup();
+                assert_eq!($Matrix4::determinant(matA), 1.);
+            }
+
+            #[test]
+            fn multiply() {
+                let (out, matA, matB) = &mut setup();
+                $Matrix4::multiply(out, matA, matB);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 5., 7., 9., 1.,
+                    ],
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+                    ],
+                );
+                expect_equalish(
+                    &matB.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 4., 5., 6., 1.,
+                    ],
+                );
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix4::multiply(matA, &$Matrix4::clone(matA), matB);
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 5., 7., 9., 1.,
+                    ],
+                );
+                expect_equalish(
+                    &matB.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 4., 5., 6., 1.,
+                    ],
+                );
+
+                let (_, matA, matB) = &mut setup();
+                $Matrix4::multiply(matB, matA, &$Matrix4::clone(matB));
+                expect_equalish(
+                    &matB.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 5., 7., 9., 1.,
+                    ],
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn translate() {
+                let (out, matA, _) = &mut setup();
+                $Matrix4::translate(out, matA, &$Vector3::fromValues(4., 5., 6.));
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 5., 7., 9., 1.,
+                    ],
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+                    ],
+                );
+
+                $Matrix4::translate(
+                    matA,
+                    &$Matrix4::clone(matA),
+                    &$Vector3::fromValues(4., 5., 6.),
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 5., 7., 9., 1.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn scale() {
+                let (out, matA, _) = &mut setup();
+                $Matrix4::scale(out, matA, &$Vector3::fromValues(4., 5., 6.));
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        4., 0., 0., 0., 0., 5., 0., 0., 0., 0., 6., 0., 1., 2., 3., 1.,
+                    ],
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+                    ],
+                );
+
+                $Matrix4::scale(
+                    matA,
+                    &$Matrix4::clone(matA),
+                    &$Vector3::fromValues(4., 5., 6.),
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        4., 0., 0., 0., 0., 5., 0., 0., 0., 0., 6., 0., 1., 2., 3., 1.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn rotate() {
+                let (out, matA, _) = &mut setup();
+                let rad = PI * 0.5;
+                let axis = &$Vector3::fromValues(1., 0., 0.);
+                $Matrix4::rotate(out, matA, rad, axis);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1.,
+                        0.,
+                        0.,
+                        0.,
+                        0.,
+                        $F::cos(rad),
+                        $F::sin(rad),
+                        0.,
+                        0.,
+                        -$F::sin(rad),
+                        $F::cos(rad),
+                        0.,
+                        1.,
+                        2.,
+                        3.,
+                        1.,
+                    ],
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+                    ],
+                );
+
+                $Matrix4::rotate(matA, &$Matrix4::clone(matA), rad, axis);
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1.,
+                        0.,
+                        0.,
+                        0.,
+                        0.,
+                        $F::cos(rad),
+                        $F::sin(rad),
+                        0.,
+                        0.,
+                        -$F::sin(rad),
+                        $F::cos(rad),
+                        0.,
+                        1.,
+                        2.,
+                        3.,
+                        1.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn rotateX() {
+                let (out, matA, _) = &mut setup();
+                let rad = PI * 0.5;
+                $Matrix4::rotateX(out, matA, rad);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1.,
+                        0.,
+                        0.,
+                        0.,
+                        0.,
+                        $F::cos(rad),
+                        $F::sin(rad),
+                        0.,
+                        0.,
+                        -$F::sin(rad),
+                        $F::cos(rad),
+                        0.,
+                        1.,
+                        2.,
+                        3.,
+                        1.,
+                    ],
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+                    ],
+                );
+
+                $Matrix4::rotateX(matA, &$Matrix4::clone(matA), rad);
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1.,
+                        0.,
+                        0.,
+                        0.,
+                        0.,
+                        $F::cos(rad),
+                        $F::sin(rad),
+                        0.,
+                        0.,
+                        -$F::sin(rad),
+                        $F::cos(rad),
+                        0.,
+                        1.,
+                        2.,
+                        3.,
+                        1.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn rotateY() {
+                let (out, matA, _) = &mut setup();
+                let rad = PI * 0.5;
+                $Matrix4::rotateY(out, matA, rad);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        $F::cos(rad),
+                        0.,
+                        -$F::sin(rad),
+                        0.,
+                        0.,
+                        1.,
+                        0.,
+                        0.,
+                        $F::sin(rad),
+                        0.,
+                        $F::cos(rad),
+                        0.,
+                        1.,
+                        2.,
+                        3.,
+                        1.,
+                    ],
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+                    ],
+                );
+
+                $Matrix4::rotateY(matA, &$Matrix4::clone(matA), rad);
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        $F::cos(rad),
+                        0.,
+                        -$F::sin(rad),
+                        0.,
+                        0.,
+                        1.,
+                        0.,
+                        0.,
+                        $F::sin(rad),
+                        0.,
+                        $F::cos(rad),
+                        0.,
+                        1.,
+                        2.,
+                        3.,
+                        1.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn rotateZ() {
+                let (out, matA, _) = &mut setup();
+                let rad = PI * 0.5;
+                $Matrix4::rotateZ(out, matA, rad);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        $F::cos(rad),
+                        $F::sin(rad),
+                        0.,
+                        0.,
+                        -$F::sin(rad),
+                        $F::cos(rad),
+                        0.,
+                        0.,
+                        0.,
+                        0.,
+                        1.,
+                        0.,
+                        1.,
+                        2.,
+                        3.,
+                        1.,
+                    ],
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+                    ],
+                );
+
+                $Matrix4::rotateZ(matA, &$Matrix4::clone(matA), rad);
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        $F::cos(rad),
+                        $F::sin(rad),
+                        0.,
+                        0.,
+                        -$F::sin(rad),
+                        $F::cos(rad),
+                        0.,
+                        0.,
+                        0.,
+                        0.,
+                        1.,
+                        0.,
+                        1.,
+                        2.,
+                        3.,
+                        1.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn getTranslation() {
+                let (_, _, matB) = &mut setup();
+                let out = &mut $Vector3::fromValues(1., 2., 3.);
+                $Matrix4::getTranslation(out, &$Matrix4::create());
+                expect_equalish(&out.elements(), &[0., 0., 0.]);
+
+                let out = &mut $Vector3::fromValues(1., 2., 3.);
+                $Matrix4::getTranslation(out, matB);
+                expect_equalish(&out.elements(), &[4., 5., 6.]);
+
+                let q = &mut $Quaternion::create();
+                let v = &$Vector3::fromValues(5., 6., 7.);
+                let mat = &mut $Matrix4::create();
+                $Quaternion::setAxisAngle(
+                    q,
+                    &$Vector3::fromValues(0.26726124, 0.534522474, 0.8017837),
+                    0.55,
+                );
+                $Matrix4::fromRotationTranslation(mat, q, v);
+                let result = &mut $Vector3::create();
+                $Matrix4::getTranslation(result, mat);
+                expect_equalish(&result.elements(), &[5., 6., 7.]);
+            }
+
+            #[test]
+            fn getScaling() {
+                let (_, matA, _) = &mut setup();
+                let out = &mut $Vector3::fromValues(1., 2., 3.);
+                $Matrix4::getScaling(out, &$Matrix4::create());
+                expect_equalish(&out.elements(), &[1., 1., 1.]);
+
+                let out = &mut $Vector3::fromValues(1., 2., 3.);
+                $Matrix4::fromScaling(matA, &$Vector3::fromValues(4., 5., 6.));
+                $Matrix4::getScaling(out, matA);
+                expect_equalish(&out.elements(), &[4., 5., 6.]);
+
+                let q = &mut $Quaternion::create();
+                let mat = &mut $Matrix4::create();
+                $Quaternion::setAxisAngle(
+                    q,
+                    &$Vector3::fromValues(1., 0., 0.),
+                    0.5,
+                );
+                $Matrix4::fromRotationTranslation(
+                    mat,
+                    q,
+                    &$Vector3::fromValues(5., 6., 7.),
+                );
+                let result = &mut $Vector3::fromValues(1., 2., 3.);
+                $Matrix4::getScaling(result, mat);
+                expect_equalish(&result.elements(), &[1., 1., 1.]);
+
+                let q = &mut $Quaternion::create();
+                let t = &$Vector3::fromValues(1., 2., 3.);
+                let s = &$Vector3::fromValues(5., 6., 7.);
+                $Quaternion::setAxisAngle(
+                    q,
+                    &$Vector3::fromValues(0., 1., 0.),
+                    0.7,
+                );
+                $Matrix4::fromRotationTranslationScale(mat, q, t, s);
+                let result = &mut $Vector3::fromValues(5., 6., 7.);
+                $Matrix4::getScaling(result, mat);
+                expect_equalish(&result.elements(), &[5., 6., 7.]);
+            }
+
+            #[test]
+            fn euler() {
+                let (out, _, _) = &mut setup();
+                $Matrix4::fromEuler(
+                    out,
+                    10.,
+                    20.,
+                    30.,
+                    EulerOrder::ZXY,
+                    AngleUnit::Degrees,
+                );
+                let q = &mut $Quaternion::create();
+                $Quaternion::fromEulerOrder(
+                    q,
+                    10.,
+                    20.,
+                    30.,
+                    EulerOrder::ZXY,
+                    AngleUnit::Degrees,
+                );
+                let expected = &mut $Matrix4::create();
+                $Matrix4::fromQuat(expected, q);
+                expect_equalish(&out.elements(), &expected.elements());
+
+                // Translation and scale don't change the angles.
+                $Matrix4::fromRotationTranslationScale(
+                    out,
+                    q,
+                    &$Vector3::fromValues(1., 2., 3.),
+                    &$Vector3::fromValues(2., 0.5, 3.),
+                );
+                let angles = &mut $Vector3::create();
+                $Matrix4::getEuler(angles, out, EulerOrder::ZXY, AngleUnit::Degrees);
+                expect_equalish(&angles.elements(), &[10., 20., 30.]);
+            }
+
+            #[test]
+            fn decompose() {
+                let t = &mut $Vector3::create();
+                let r = &mut $Quaternion::create();
+                let s = &mut $Vector3::create();
+                let shear = &mut $Vector3::create();
+                let persp = &mut $Vector4::create();
+
+                let q = &mut $Quaternion::create();
+                $Quaternion::setAxisAngle(
+                    q,
+                    &$Vector3::fromValues(1., 2., 3.),
+                    0.7,
+                );
+                $Quaternion::normalize(q, &$Quaternion::clone(q));
+                let m = &mut $Matrix4::create();
+                let translation = &$Vector3::fromValues(1., 2., 3.);
+                $Matrix4::fromRotationTranslationScale(
+                    m,
+                    q,
+                    translation,
+                    &$Vector3::fromValues(2., 3., 4.),
+                );
+                assert!($Matrix4::decompose(m, t, r, s, shear, persp));
+                expect_equalish(&t.elements(), &[1., 2., 3.]);
+                expect_equalish(&s.elements(), &[2., 3., 4.]);
+                expect_equalish(&shear.elements(), &[0., 0., 0.]);
+                expect_equalish(&persp.elements(), &[0., 0., 0., 1.]);
+                expect_equalish_scalar($F::abs($Quaternion::dot(r, q)), 1.);
+                let back = &mut $Matrix4::create();
+                $Matrix4::fromRotationTranslationScale(back, r, t, s);
+                expect_equalish(&back.elements(), &m.elements());
+
+                // A reflection keeps a proper rotation and negates the scale.
+                $Matrix4::fromRotationTranslationScale(
+                    m,
+                    q,
+                    translation,
+                    &$Vector3::fromValues(2., 3., -4.),
+                );
+                assert!($Matrix4::decompose(m, t, r, s, shear, persp));
+                expect_equalish(&s.elements(), &[-2., -3., -4.]);
+                expect_equalish_scalar($Quaternion::len(r), 1.);
+                $Matrix4::fromRotationTranslationScale(back, r, t, s);
+                expect_equalish(&back.elements(), &m.elements());
+
+                // Shear of y along x.
+                let sheared = &$Matrix4::fromValues(
+                    1., 0., 0., 0., 0.5, 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1.,
+                );
+                assert!($Matrix4::decompose(sheared, t, r, s, shear, persp));
+                expect_equalish(&shear.elements(), &[0.5, 0., 0.]);
+                expect_equalish(&s.elements(), &[1., 1., 1.]);
+                expect_equalish(&r.elements(), &[0., 0., 0., 1.]);
+
+                // The perspective row of a projection applied after a translation.
+                let projection = &$Matrix4::fromValues(
+                    1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., -0.5, 0., 0., 0., 1.,
+                );
+                $Matrix4::multiply(
+                    m,
+                    projection,
+                    &$Matrix4::fromValues(
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+                    ),
+                );
+                assert!($Matrix4::decompose(m, t, r, s, shear, persp));
+                // `m` divided by its last element is the perspective row applied after the affine part.
+                $Matrix4::fromRotationTranslationScale(back, r, t, s);
+                let rows = &$Matrix4::fromValues(
+                    1., 0., 0., persp.0, 0., 1., 0., persp.1, 0., 0., 1., persp.2, 0., 0., 0.,
+                    persp.3,
+                );
+                $Matrix4::multiply(back, rows, &$Matrix4::clone(back));
+                $Matrix4::multiplyScalar(m, &$Matrix4::clone(m), 1. / m.15);
+                expect_equalish(&back.elements(), &m.elements());
+
+                // Determinants below `getEpsilon()` still decompose, with or without a perspective row.
+                let tiny = &mut $Matrix4::create();
+                $Matrix4::fromScaling(tiny, &$Vector3::fromValues(0.04, 0.04, 0.04));
+                assert!($Matrix4::decompose(tiny, t, r, s, shear, persp));
+                expect_equalish(&s.elements(), &[0.04, 0.04, 0.04]);
+                tiny.11 = -0.5;
+                assert!($Matrix4::decompose(tiny, t, r, s, shear, persp));
+                expect_equalish(&s.elements(), &[0.04, 0.04, 0.04]);
+                expect_equalish(&persp.elements(), &[0., 0., -12.5, 1.]);
+                $Matrix4::fromRotationTranslationScale(back, r, t, s);
+                let rows = &$Matrix4::fromValues(
+                    1., 0., 0., persp.0, 0., 1., 0., persp.1, 0., 0., 1., persp.2, 0., 0., 0.,
+                    persp.3,
+                );
+                $Matrix4::multiply(back, rows, &$Matrix4::clone(back));
+                expect_equalish(&back.elements(), &tiny.elements());
+
+                // Singular matrices fail and keep the outputs.
+                $Vector3::set(t, 7., 7., 7.);
+                let flat = &$Matrix4::fromValues(
+                    1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 0., 0., 0., 0., 0., 1.,
+                );
+                assert!(!$Matrix4::decompose(flat, t, r, s, shear, persp));
+                assert!(!$Matrix4::decompose(
+                    &$Matrix4::fromValues(
+                        0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+                    ),
+                    t,
+                    r,
+                    s,
+                    shear,
+                    persp
+                ));
+                expect_equalish(&t.elements(), &[7., 7., 7.]);
+            }
+
+            #[test]
+            fn getRotation() {
+                let (_, _, matB) = &mut setup();
+                let out = &mut $Quaternion::fromValues(1., 2., 3., 4.);
+                $Matrix4::getRotation(out, &$Matrix4::create());
+                expect_equalish(&out.elements(), &$Quaternion::create().elements());
+
+                let out = &mut $Quaternion::fromValues(1., 2., 3., 4.);
+                $Matrix4::getRotation(out, matB);
+                expect_equalish(&out.elements(), &$Quaternion::create().elements());
+
+                let q = &mut $Quaternion::create();
+                let outVec = &$Vector3::fromValues(5., 6., 7.);
+                let testVec = &mut $Vector3::fromValues(1., 5., 2.);
+                let ang = 0.78972;
+                let mat = &mut $Matrix4::create();
+                $Vector3::normalize(testVec, &$Vector3::clone(testVec));
+                $Quaternion::setAxisAngle(q, testVec, ang);
+                $Matrix4::fromRotationTranslation(mat, q, outVec);
+                let result = &mut $Quaternion::fromValues(2., 3., 4., 6.);
+                $Matrix4::getRotation(result, mat);
+                let outaxis = &mut $Vector3::create();
+                let outangle = $Quaternion::getAxisAngle(outaxis, result);
+                expect_equalish(&outaxis.elements(), &testVec.elements());
+                expect_equalish_scalar(outangle, ang);
+            }
+
+            #[test]
+            fn frustum() {
+                let (out, _, _) = &mut setup();
+                $Matrix4::frustum(out, -1., 1., -1., 1., -1., 1.);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        -1., 0., 0., 0., 0., -1., 0., 0., 0., 0., 0., -1., 0., 0., 1., 0.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn perspective() {
+                let (out, _, _) = &mut setup();
+                $Matrix4::perspective(out, PI * 0.5, 1., 0., Some(1.));
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., -1., -1., 0., 0., 0., 0.,
+                    ],
+                );
+
+                $Matrix4::perspective(out, 45. * PI / 180., 640. / 480., 0.1, Some(200.));
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1.81066, 0., 0., 0., 0., 2.414213, 0., 0., 0., 0., -1.001, -1., 0., 0.,
+                        -0.2001, 0.,
+                    ],
+                );
+
+                $Matrix4::perspective(out, 45. * PI / 180., 640. / 480., 0.1, None);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1.81066, 0., 0., 0., 0., 2.414213, 0., 0., 0., 0., -1., -1., 0., 0., -0.2,
+                        0.,
+                    ],
+                );
+
+                $Matrix4::perspective(
+                    out,
+                    45. * PI / 180.,
+                    640. / 480.,
+                    0.1,
+                    Some(INFINITY),
+                );
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1.81066, 0., 0., 0., 0., 2.414213, 0., 0., 0., 0., -1., -1., 0., 0., -0.2,
+                        0.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn ortho() {
+                let (out, _, _) = &mut setup();
+                $Matrix4::ortho(out, -1., 1., -1., 1., -1., 1.);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., -1., 0., 0., 0., 0., 1.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn lookAt() {
+                let (out, _, _) = &mut setup();
+                let v = &mut $Vector3::create();
+                let view = &$Vector3::fromValues(0., -1., 0.);
+                let up = &$Vector3::fromValues(0., 0., -1.);
+                let right = &$Vector3::fromValues(1., 0., 0.);
+                $Matrix4::lookAt(out, &$Vector3::fromValues(0., 0., 0.), view, up);
+                $Vector3::transformMat4(v, view, out);
+                expect_equalish(&v.elements(), &[0., 0., -1.]);
+                $Vector3::transformMat4(v, up, out);
+                expect_equalish(&v.elements(), &[0., 1., 0.]);
+                $Vector3::transformMat4(v, right, out);
+                expect_equalish(&v.elements(), &[1., 0., 0.]);
+
+                // #74
+                $Matrix4::lookAt(
+                    out,
+                    &$Vector3::fromValues(0., 2., 0.),
+                    &$Vector3::fromValues(0., 0.6, 0.),
+                    &$Vector3::fromValues(0., 0., -1.),
+                );
+                $Vector3::transformMat4(v, &$Vector3::fromValues(0., 2., -1.), out);
+                expect_equalish(&v.elements(), &[0., 1., 0.]);
+                $Vector3::transformMat4(v, &$Vector3::fromValues(1., 2., 0.), out);
+                expect_equalish(&v.elements(), &[1., 0., 0.]);
+                $Vector3::transformMat4(v, &$Vector3::fromValues(0., 1., 0.), out);
+                expect_equalish(&v.elements(), &[0., 0., -1.]);
+
+                let eye = &$Vector3::fromValues(0., 0., 1.);
+                let center = &$Vector3::fromValues(0., 0., -1.);
+                let up = &$Vector3::fromValues(0., 1., 0.);
+                $Matrix4::lookAt(out, eye, center, up);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0., -1., 1.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn targetTo() {
+                let (out, _, _) = &mut setup();
+                let v = &mut $Vector3::create();
+                let scaling = &mut $Vector3::create();
+                let view = &$Vector3::fromValues(0., -1., 0.);
+                let up = &$Vector3::fromValues(0., 0., -1.);
+                let right = &$Vector3::fromValues(1., 0., 0.);
+                $Matrix4::targetTo(
+                    out,
+                    &$Vector3::fromValues(0., 0., 0.),
+                    view,
+                    up,
+                );
+                $Vector3::transformMat4(v, view, out);
+                expect_equalish(&v.elements(), &[0., 0., 1.]);
+                $Vector3::transformMat4(v, up, out);
+                expect_equalish(&v.elements(), &[0., -1., 0.]);
+                $Vector3::transformMat4(v, right, out);
+                expect_equalish(&v.elements(), &[1., 0., 0.]);
+                $Matrix4::getScaling(scaling, out);
+                expect_equalish(&scaling.elements(), &[1., 1., 1.]);
+
+                // #74
+                $Matrix4::targetTo(
+                    out,
+                    &$Vector3::fromValues(0., 2., 0.),
+                    &$Vector3::fromValues(0., 0.6, 0.),
+                    &$Vector3::fromValues(0., 0., -1.),
+                );
+                $Vector3::transformMat4(v, &$Vector3::fromValues(0., 2., -1.), out);
+                expect_equalish(&v.elements(), &[0., 1., -2.]);
+                $Vector3::transformMat4(v, &$Vector3::fromValues(1., 2., 0.), out);
+                expect_equalish(&v.elements(), &[1., 2., -2.]);
+                $Vector3::transformMat4(v, &$Vector3::fromValues(0., 1., 0.), out);
+                expect_equalish(&v.elements(), &[0., 2., -1.]);
+                $Matrix4::getScaling(scaling, out);
+                expect_equalish(&scaling.elements(), &[1., 1., 1.]);
+
+                $Matrix4::targetTo(
+                    out,
+                    &$Vector3::fromValues(0., 1., 0.),
+                    &$Vector3::fromValues(0., 0., 1.),
+                    &$Vector3::fromValues(0., 0., -1.),
+                );
+                $Matrix4::getScaling(scaling, out);
+                expect_equalish(&scaling.elements(), &[1., 1., 1.]);
+
+                let eye = &$Vector3::fromValues(0., 0., 1.);
+                let center = &$Vector3::fromValues(0., 0., -1.);
+                let up = &$Vector3::fromValues(0., 1., 0.);
+                $Matrix4::targetTo(out, eye, center, up);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0., 1., 1.,
+                    ],
+                );
+                $Matrix4::getScaling(scaling, out);
+                expect_equalish(&scaling.elements(), &[1., 1., 1.]);
+            }
+
+            #[test]
+            fn frob() {
+                let (_, matA, _) = &mut setup();
+                expect_equalish_scalar(
+                    $Matrix4::frob(matA),
+                    $F::sqrt(
+                        $F::powi(1., 2)
+                            + $F::powi(1., 2)
+                            + $F::powi(1., 2)
+                            + $F::powi(1., 2)
+                            + $F::powi(1., 2)
+                            + $F::powi(2., 2)
+                            + $F::powi(3., 2),
+                    ),
+                );
+            }
+
+            #[test]
+            fn add() {
+                let out = &mut $Matrix4::create();
+                let matA = &mut $Matrix4::fromValues(
+                    1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                );
+                let matB = &mut $Matrix4::fromValues(
+                    17., 18., 19., 20., 21., 22., 23., 24., 25., 26., 27., 28., 29., 30., 31., 32.,
+                );
+                $Matrix4::add(out, matA, matB);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        18., 20., 22., 24., 26., 28., 30., 32., 34., 36., 38., 40., 42., 44., 46.,
+                        48.,
+                    ],
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                    ],
+                );
+                expect_equalish(
+                    &matB.elements(),
+                    &[
+                        17., 18., 19., 20., 21., 22., 23., 24., 25., 26., 27., 28., 29., 30., 31.,
+                        32.,
+                    ],
+                );
+
+                $Matrix4::add(matA, &$Matrix4::clone(matA), matB);
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        18., 20., 22., 24., 26., 28., 30., 32., 34., 36., 38., 40., 42., 44., 46.,
+                        48.,
+                    ],
+                );
+                expect_equalish(
+                    &matB.elements(),
+                    &[
+                        17., 18., 19., 20., 21., 22., 23., 24., 25., 26., 27., 28., 29., 30., 31.,
+                        32.,
+                    ],
+                );
+
+                let matA = &mut $Matrix4::fromValues(
+                    1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                );
+                $Matrix4::add(matB, matA, &$Matrix4::clone(matB));
+                expect_equalish(
+                    &matB.elements(),
+                    &[
+                        18., 20., 22., 24., 26., 28., 30., 32., 34., 36., 38., 40., 42., 44., 46.,
+                        48.,
+                    ],
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn subtract() {
+                let out = &mut $Matrix4::create();
+                let matA = &mut $Matrix4::fromValues(
+                    1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                );
+                let matB = &mut $Matrix4::fromValues(
+                    17., 18., 19., 20., 21., 22., 23., 24., 25., 26., 27., 28., 29., 30., 31., 32.,
+                );
+                $Matrix4::subtract(out, matA, matB);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        -16., -16., -16., -16., -16., -16., -16., -16., -16., -16., -16., -16.,
+                        -16., -16., -16., -16.,
+                    ],
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                    ],
+                );
+                expect_equalish(
+                    &matB.elements(),
+                    &[
+                        17., 18., 19., 20., 21., 22., 23., 24., 25., 26., 27., 28., 29., 30., 31.,
+                        32.,
+                    ],
+                );
+
+                $Matrix4::subtract(matA, &$Matrix4::clone(matA), matB);
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        -16., -16., -16., -16., -16., -16., -16., -16., -16., -16., -16., -16.,
+                        -16., -16., -16., -16.,
+                    ],
+                );
+                expect_equalish(
+                    &matB.elements(),
+                    &[
+                        17., 18., 19., 20., 21., 22., 23., 24., 25., 26., 27., 28., 29., 30., 31.,
+                        32.,
+                    ],
+                );
+
+                let matA = &mut $Matrix4::fromValues(
+                    1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                );
+                $Matrix4::subtract(matB, matA, &$Matrix4::clone(matB));
+                expect_equalish(
+                    &matB.elements(),
+                    &[
+                        -16., -16., -16., -16., -16., -16., -16., -16., -16., -16., -16., -16.,
+                        -16., -16., -16., -16.,
+                    ],
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn fromValues() {
+                let result = $Matrix4::fromValues(
+                    1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                );
+                expect_equalish(
+                    &result.elements(),
+                    &[
+                        1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn set() {
+                let (out, _, _) = &mut setup();
+                $Matrix4::set(
+                    out, 1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                );
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn multiplyScalar() {
+                let out = &mut $Matrix4::create();
+                let matA = &mut $Matrix4::fromValues(
+                    1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                );
+                $Matrix4::multiplyScalar(out, matA, 2.);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        2., 4., 6., 8., 10., 12., 14., 16., 18., 20., 22., 24., 26., 28., 30., 32.,
+                    ],
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                    ],
+                );
+
+                $Matrix4::multiplyScalar(matA, &$Matrix4::clone(matA), 2.);
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        2., 4., 6., 8., 10., 12., 14., 16., 18., 20., 22., 24., 26., 28., 30., 32.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn multiplyScalarAndAdd() {
+                let out = &mut $Matrix4::create();
+                let matA = &mut $Matrix4::fromValues(
+                    1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                );
+                let matB = &mut $Matrix4::fromValues(
+                    17., 18., 19., 20., 21., 22., 23., 24., 25., 26., 27., 28., 29., 30., 31., 32.,
+                );
+                $Matrix4::multiplyScalarAndAdd(out, matA, matB, 0.5);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        9.5, 11., 12.5, 14., 15.5, 17., 18.5, 20., 21.5, 23., 24.5, 26., 27.5, 29.,
+                        30.5, 32.,
+                    ],
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                    ],
+                );
+                expect_equalish(
+                    &matB.elements(),
+                    &[
+                        17., 18., 19., 20., 21., 22., 23., 24., 25., 26., 27., 28., 29., 30., 31.,
+                        32.,
+                    ],
+                );
+
+                $Matrix4::multiplyScalarAndAdd(
+                    matA,
+                    &$Matrix4::clone(matA),
+                    matB,
+                    0.5,
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        9.5, 11., 12.5, 14., 15.5, 17., 18.5, 20., 21.5, 23., 24.5, 26., 27.5, 29.,
+                        30.5, 32.,
+                    ],
+                );
+                expect_equalish(
+                    &matB.elements(),
+                    &[
+                        17., 18., 19., 20., 21., 22., 23., 24., 25., 26., 27., 28., 29., 30., 31.,
+                        32.,
+                    ],
+                );
+
+                let matA = &mut $Matrix4::fromValues(
+                    1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                );
+                $Matrix4::multiplyScalarAndAdd(
+                    matB,
+                    matA,
+                    &$Matrix4::clone(matB),
+                    0.5,
+                );
+                expect_equalish(
+                    &matB.elements(),
+                    &[
+                        9.5, 11., 12.5, 14., 15.5, 17., 18.5, 20., 21.5, 23., 24.5, 26., 27.5, 29.,
+                        30.5, 32.,
+                    ],
+                );
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn exactEquals() {
+                let matA = &$Matrix4::fromValues(
+                    0., 1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15.,
+                );
+                let matB = &$Matrix4::fromValues(
+                    0., 1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15.,
+                );
+                let matC = &$Matrix4::fromValues(
+                    1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                );
+                assert!($Matrix4::exactEquals(matA, matB));
+                assert!(!$Matrix4::exactEquals(matA, matC));
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        0., 1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15.,
+                    ],
+                );
+                expect_equalish(
+                    &matB.elements(),
+                    &[
+                        0., 1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn equals() {
+                let matA = &$Matrix4::fromValues(
+                    0., 1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15.,
+                );
+                let matB = &$Matrix4::fromValues(
+                    0., 1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15.,
+                );
+                let matC = &$Matrix4::fromValues(
+                    1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                );
+                let matD = &$Matrix4::fromValues(
+                    1e-16, 1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15.,
+                );
+                assert!($Matrix4::equals(matA, matB));
+                assert!(!$Matrix4::equals(matA, matC));
+                assert!($Matrix4::equals(matA, matD));
+                expect_equalish(
+                    &matA.elements(),
+                    &[
+                        0., 1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15.,
+                    ],
+                );
+                expect_equalish(
+                    &matB.elements(),
+                    &[
+                        0., 1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn traits() {
+                let a = $Matrix4::fromValues(
+                    1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                );
+                let b = a;
+                assert_eq!(a, b);
+                assert_ne!(
+                    a,
+                    $Matrix4::fromValues(
+                        1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 17.
+                    )
+                );
+                assert_ne!(
+                    a,
+                    $Matrix4::fromValues(
+                        1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.00001
+                    )
+                );
+                assert!(a.approx_eq(&$Matrix4::fromValues(
+                    1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.00001
+                )));
+                assert!(!a.approx_eq(&$Matrix4::fromValues(
+                    1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 17.
+                )));
+                assert_eq!($Matrix4::default(), $Matrix4::create());
+                assert_eq!(
+                    format!("{:?}", $Matrix4::create()),
+                    concat!(
+                        stringify!($Matrix4),
+                        "(1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0)"
+                    )
+                );
+            }
+
+            #[test]
+            fn str() {
+                let a = $Matrix4::fromValues(
+                    1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+                );
+                assert_eq!(
+                    $Matrix4::str(&a),
+                    "mat4(1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 1, 2, 3, 1)"
+                );
+                assert_eq!(
+                    "mat4(1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 1, 2, 3, 1)"
+                        .parse::<$Matrix4>(),
+                    Ok(a)
+                );
+            }
+
+            #[test]
+            fn perspectiveFromFieldOfView() {
+                let (out, _, _) = &mut setup();
+                let fov = &FieldOfView::fromValues(45., 45., 45., 45.);
+                $Matrix4::perspectiveFromFieldOfView(out, fov, 0.1, 100.);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., -1.001001, -1., 0., 0., -0.1001001,
+                        0.,
+                    ],
+                );
+
+                let fov = &FieldOfView::fromValues(30., 40., 50., 20.);
+                $Matrix4::perspectiveFromFieldOfView(out, fov, 0.5, 200.);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1.2855752, 0., 0., 0., 0., 1.4119807, 0., 0., -0.5320889, -0.1847925,
+                        -1.0025063, -1., 0., 0., -0.5012531, 0.,
+                    ],
+                );
+            }
+
+            // Depth in clip space of the point on the view axis at distance `d`.
+            fn depth(m: &$Matrix4, d: $F) -> $F {
+                let v = &mut $Vector4::create();
+                $Vector4::transformMat4(
+                    v,
+                    &$Vector4::fromValues(0., 0., -d, 1.),
+                    m,
+                );
+                v.2 / v.3
+            }
+
+            #[test]
+            fn frustumZO() {
+                let (out, _, _) = &mut setup();
+                $Matrix4::frustumZO(out, -1., 1., -1., 1., -1., 1.);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        -1., 0., 0., 0., 0., -1., 0., 0., 0., 0., -0.5, -1., 0., 0., 0.5, 0.,
+                    ],
+                );
+
+                $Matrix4::frustumZO(out, -1., 1., -1., 1., 0.1, 100.);
+                expect_equalish_scalar(depth(out, 0.1), 0.);
+                expect_equalish_scalar(depth(out, 100.), 1.);
+            }
+
+            #[test]
+            fn perspectiveZO() {
+                let (out, _, _) = &mut setup();
+                $Matrix4::perspectiveZO(out, PI * 0.5, 1., 0., Some(1.));
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., -1., -1., 0., 0., 0., 0.,
+                    ],
+                );
+
+                $Matrix4::perspectiveZO(out, 45. * PI / 180., 640. / 480., 0.1, Some(200.));
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1.81066,
+                        0.,
+                        0.,
+                        0.,
+                        0.,
+                        2.414213,
+                        0.,
+                        0.,
+                        0.,
+                        0.,
+                        -1.0005003,
+                        -1.,
+                        0.,
+                        0.,
+                        -0.10005003,
+                        0.,
+                    ],
+                );
+                expect_equalish_scalar(depth(out, 0.1), 0.);
+                expect_equalish_scalar(depth(out, 200.), 1.);
+
+                $Matrix4::perspectiveZO(out, 45. * PI / 180., 640. / 480., 0.1, None);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1.81066, 0., 0., 0., 0., 2.414213, 0., 0., 0., 0., -1., -1., 0., 0., -0.1,
+                        0.,
+                    ],
+                );
+
+                let infinite = &mut $Matrix4::create();
+                $Matrix4::perspectiveZO(
+                    infinite,
+                    45. * PI / 180.,
+                    640. / 480.,
+                    0.1,
+                    Some(INFINITY),
+                );
+                expect_equalish(&infinite.elements(), &out.elements());
+            }
+
+            #[test]
+            fn perspectiveReversedZ() {
+                let (out, _, _) = &mut setup();
+                $Matrix4::perspectiveReversedZ(
+                    out,
+                    45. * PI / 180.,
+                    640. / 480.,
+                    0.1,
+                    Some(200.),
+                );
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1.81066, 0., 0., 0., 0., 2.414213, 0., 0., 0., 0., 0.00050025, -1., 0., 0.,
+                        0.10005003, 0.,
+                    ],
+                );
+                expect_equalish_scalar(depth(out, 0.1), 1.);
+                expect_equalish_scalar(depth(out, 200.), 0.);
+
+                $Matrix4::perspectiveReversedZ(
+                    out,
+                    45. * PI / 180.,
+                    640. / 480.,
+                    0.1,
+                    None,
+                );
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1.81066, 0., 0., 0., 0., 2.414213, 0., 0., 0., 0., 0., -1., 0., 0., 0.1, 0.,
+                    ],
+                );
+                expect_equalish_scalar(depth(out, 0.1), 1.);
+                expect_equalish_scalar(depth(out, 1e30), 0.);
+
+                let infinite = &mut $Matrix4::create();
+                $Matrix4::perspectiveReversedZ(
+                    infinite,
+                    45. * PI / 180.,
+                    640. / 480.,
+                    0.1,
+                    Some(INFINITY),
+                );
+                expect_equalish(&infinite.elements(), &out.elements());
+                $Matrix4::perspectiveInfiniteReversedZ(
+                    infinite,
+                    45. * PI / 180.,
+                    640. / 480.,
+                    0.1,
+                );
+                expect_equalish(&infinite.elements(), &out.elements());
+            }
+
+            #[test]
+            fn orthoZO() {
+                let (out, _, _) = &mut setup();
+                $Matrix4::orthoZO(out, -1., 1., -1., 1., -1., 1.);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., -0.5, 0., 0., 0., 0.5, 1.,
+                    ],
+                );
+
+                $Matrix4::orthoZO(out, -1., 1., -1., 1., 0.1, 100.);
+                expect_equalish_scalar(depth(out, 0.1), 0.);
+                expect_equalish_scalar(depth(out, 100.), 1.);
+            }
+
+            #[test]
+            fn tryInvert() {
+                let (out, _, _) = &mut setup();
+                let singular = &$Matrix4::fromValues(
+                    1., 2., 3., 4., 2., 4., 6., 8., 0., 0., 1., 0., 0., 0., 0., 1.,
+                );
+                assert!(!$Matrix4::invert(out, singular));
+                // A zero tolerance still rejects a zero or NaN determinant.
+                assert!(!$Matrix4::tryInvert(out, singular, 0.));
+                assert!(!$Matrix4::tryInvert(
+                    out,
+                    &$Matrix4::fromValues(
+                        $F::NAN,
+                        0.,
+                        0.,
+                        0.,
+                        0.,
+                        1.,
+                        0.,
+                        0.,
+                        0.,
+                        0.,
+                        1.,
+                        0.,
+                        0.,
+                        0.,
+                        0.,
+                        1.,
+                    ),
+                    0.
+                ));
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+                    ],
+                );
+
+                let small = &$Matrix4::fromValues(
+                    0.0078125, 0., 0., 0., 0., 0.0078125, 0., 0., 0., 0., 0.0078125, 0., 0., 0.,
+                    0., 1.,
+                );
+                assert!(!$Matrix4::tryInvert(out, small, 1.));
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+                    ],
+                );
+                assert!($Matrix4::tryInvert(out, small, 1e-12));
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        128., 0., 0., 0., 0., 128., 0., 0., 0., 0., 128., 0., 0., 0., 0., 1.,
+                    ],
+                );
+
+                $Matrix4::set(
+                    out, 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+                );
+                assert!(!$Matrix4::invert(out, small));
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+                    ],
+                );
+            }
+
+            #[test]
+            fn equalsWith() {
+                let a = &$Matrix4::fromValues(
+                    1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                );
+                let b = &$Matrix4::fromValues(
+                    1.001, 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                );
+                let c = &$Matrix4::fromValues(
+                    $F::from_bits($F::to_bits(1.) + 2),
+                    2.,
+                    3.,
+                    4.,
+                    5.,
+                    6.,
+                    7.,
+                    8.,
+                    9.,
+                    10.,
+                    11.,
+                    12.,
+                    13.,
+                    14.,
+                    15.,
+                    16.,
+                );
+                assert!(!$Matrix4::equalsWithEpsilon(a, b, 0.0001));
+                assert!($Matrix4::equalsWithEpsilon(a, b, 0.01));
+                assert!($Matrix4::equalsWithUlps(a, c, 2));
+                assert!(!$Matrix4::equalsWithUlps(a, c, 1));
+                assert!(!$Matrix4::equalsWithUlps(a, b, 4));
+            }
+
+            #[test]
+            fn multiplyArray() {
+                let (_, matA, matB) = &mut setup();
+                let a = &mut [0.; 32];
+                let b = &mut [0.; 32];
+                matA.writeSlice(&mut a[..16]);
+                matB.writeSlice(&mut a[16..]);
+                matB.writeSlice(&mut b[..16]);
+                matA.writeSlice(&mut b[16..]);
+                let out = &mut [0.; 32];
+                $Matrix4::multiplyArray(out, a, b, 2);
+
+                let expected = &mut $Matrix4::create();
+                $Matrix4::multiply(expected, matA, matB);
+                expect_equalish(&out[..16], &expected.elements());
+                $Matrix4::multiply(expected, matB, matA);
+                expect_equalish(&out[16..], &expected.elements());
+            }
+
+            #[test]
+            #[should_panic]
+            fn multiplyArrayShort() {
+                $Matrix4::multiplyArray(&mut [0.; 16], &[0.; 32], &[0.; 32], 2);
+            }
+
+            #[test]
+            fn slice() {
+                let a = $Matrix4::fromValues(
+                    1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+                );
+                assert_eq!(a.asSlice(), &a.elements()[..]);
+
+                let buffer = &mut [0.; 17];
+                a.writeSlice(&mut buffer[1..]);
+                assert_eq!($Matrix4::fromSlice(&buffer[1..]), a);
+            }
+        }
+    };
 }
+
+pub(crate) use define_matrix4;
+
+define_matrix4!(
+    f32,
+    Float32Array,
+    fromFloat32Array,
+    Matrix2,
+    Matrix2d,
+    Matrix3,
+    Matrix4,
+    Quaternion,
+    Quaternion2,
+    Vector2,
+    Vector3,
+    Vector4
+);
@@ -0,0 +1,453 @@
+/**
+ * @File   : ops.rs
+ * @Author : dtysky (dtysky@outlook.com)
+ * @Link   : http://dtysky.moe
+ * @Date   : 2019/2/7 下午9:53:03
+ */
+// Operator overloading for the native side, every operator delegates to the gl-matrix style kernel.
+use core::ops::{Add, AddAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign};
+
+// `lhs op rhs` for every combination of owned and borrowed operands.
+macro_rules! impl_binary_op {
+    ($Op:ident, $op:ident, $Lhs:ident, $Rhs:ident, $Out:ident, |$out:ident, $a:ident, $b:ident| $body:expr) => {
+        impl<'a, 'b> $Op<&'b $Rhs> for &'a $Lhs {
+            type Output = $Out;
+
+            fn $op(self, rhs: &'b $Rhs) -> $Out {
+                let mut result = $Out::create();
+                {
+                    let $out = &mut result;
+                    let $a = self;
+                    let $b = rhs;
+                    $body;
+                }
+                result
+            }
+        }
+
+        impl<'a> $Op<$Rhs> for &'a $Lhs {
+            type Output = $Out;
+
+            fn $op(self, rhs: $Rhs) -> $Out {
+                $Op::$op(self, &rhs)
+            }
+        }
+
+        impl<'b> $Op<&'b $Rhs> for $Lhs {
+            type Output = $Out;
+
+            fn $op(self, rhs: &'b $Rhs) -> $Out {
+                $Op::$op(&self, rhs)
+            }
+        }
+
+        impl $Op<$Rhs> for $Lhs {
+            type Output = $Out;
+
+            fn $op(self, rhs: $Rhs) -> $Out {
+                $Op::$op(&self, &rhs)
+            }
+        }
+    };
+}
+
+// `t * s`, `s * t` and `t *= s` with a scalar of the element type.
+macro_rules! impl_scalar_mul {
+    ($T:ident, $f:ident, $scale:ident) => {
+        impl<'a> Mul<$f> for &'a $T {
+            type Output = $T;
+
+            fn mul(self, rhs: $f) -> $T {
+                let mut result = $T::create();
+                $T::$scale(&mut result, self, rhs);
+                result
+            }
+        }
+
+        impl Mul<$f> for $T {
+            type Output = $T;
+
+            fn mul(self, rhs: $f) -> $T {
+                &self * rhs
+            }
+        }
+
+        impl<'a> Mul<&'a $T> for $f {
+            type Output = $T;
+
+            fn mul(self, rhs: &'a $T) -> $T {
+                rhs * self
+            }
+        }
+
+        impl Mul<$T> for $f {
+            type Output = $T;
+
+            fn mul(self, rhs: $T) -> $T {
+                &rhs * self
+            }
+        }
+
+        impl MulAssign<$f> for $T {
+            fn mul_assign(&mut self, rhs: $f) {
+                $T::$scale(self, &$T::clone(self), rhs);
+            }
+        }
+
+        impl<'a> Neg for &'a $T {
+            type Output = $T;
+
+            fn neg(self) -> $T {
+                self * -1.
+            }
+        }
+
+        impl Neg for $T {
+            type Output = $T;
+
+            fn neg(self) -> $T {
+                &self * -1.
+            }
+        }
+    };
+}
+
+// `t op= rhs`, the kernels support aliasing only through a clone, as everywhere else in this crate.
+macro_rules! impl_assign_op {
+    ($Op:ident, $op:ident, $T:ident, $Rhs:ident, $kernel:ident) => {
+        impl<'a> $Op<&'a $Rhs> for $T {
+            fn $op(&mut self, rhs: &'a $Rhs) {
+                $T::$kernel(self, &$T::clone(self), rhs);
+            }
+        }
+
+        impl $Op<$Rhs> for $T {
+            fn $op(&mut self, rhs: $Rhs) {
+                $T::$kernel(self, &$T::clone(self), &rhs);
+            }
+        }
+    };
+}
+
+macro_rules! impl_index {
+    ($T:ident, $f:ident, $len:expr, $($i:tt)*) => {
+        impl Index<usize> for $T {
+            type Output = $f;
+
+            fn index(&self, index: usize) -> &$f {
+                match index {
+                    $($i => &self.$i,)*
+                    _ => panic!(
+                        "index out of bounds: the len is {} but the index is {}",
+                        $len, index
+                    ),
+                }
+            }
+        }
+
+        impl IndexMut<usize> for $T {
+            fn index_mut(&mut self, index: usize) -> &mut $f {
+                match index {
+                    $($i => &mut self.$i,)*
+                    _ => panic!(
+                        "index out of bounds: the len is {} but the index is {}",
+                        $len, index
+                    ),
+                }
+            }
+        }
+    };
+}
+
+// Component-wise add, subtract and multiply, plus scalar scaling.
+macro_rules! impl_vector_ops {
+    ($T:ident, $f:ident) => {
+        impl_binary_op!(Add, add, $T, $T, $T, |out, a, b| $T::add(out, a, b));
+        impl_binary_op!(Sub, sub, $T, $T, $T, |out, a, b| $T::subtract(out, a, b));
+        impl_binary_op!(Mul, mul, $T, $T, $T, |out, a, b| $T::multiply(out, a, b));
+        impl_assign_op!(AddAssign, add_assign, $T, $T, add);
+        impl_assign_op!(SubAssign, sub_assign, $T, $T, subtract);
+        impl_assign_op!(MulAssign, mul_assign, $T, $T, multiply);
+        impl_scalar_mul!($T, $f, scale);
+    };
+}
+
+// Matrix add, subtract and product, plus scalar scaling.
+macro_rules! impl_matrix_ops {
+    ($T:ident, $f:ident) => {
+        impl_binary_op!(Add, add, $T, $T, $T, |out, a, b| $T::add(out, a, b));
+        impl_binary_op!(Sub, sub, $T, $T, $T, |out, a, b| $T::subtract(out, a, b));
+        impl_binary_op!(Mul, mul, $T, $T, $T, |out, a, b| $T::multiply(out, a, b));
+        impl_assign_op!(AddAssign, add_assign, $T, $T, add);
+        impl_assign_op!(SubAssign, sub_assign, $T, $T, subtract);
+        impl_assign_op!(MulAssign, mul_assign, $T, $T, multiply);
+        impl_scalar_mul!($T, $f, multiplyScalar);
+    };
+}
+
+// Quaternions have no subtract in gl-matrix, only sum and (hamilton / dual) product.
+macro_rules! impl_quaternion_ops {
+    ($T:ident, $f:ident) => {
+        impl_binary_op!(Add, add, $T, $T, $T, |out, a, b| $T::add(out, a, b));
+        impl_binary_op!(Mul, mul, $T, $T, $T, |out, a, b| $T::multiply(out, a, b));
+        impl_assign_op!(AddAssign, add_assign, $T, $T, add);
+        impl_assign_op!(MulAssign, mul_assign, $T, $T, multiply);
+        impl_scalar_mul!($T, $f, scale);
+    };
+}
+
+pub(crate) use {
+    impl_assign_op, impl_binary_op, impl_index, impl_matrix_ops, impl_quaternion_ops,
+    impl_scalar_mul, impl_vector_ops,
+};
+
+// The operators of the f32 and the f64 types, the names are the types of one precision as in
+// `define_vector3` and the others.
+macro_rules! define_ops {
+    (
+        $F:ident,
+        $FloatArray:ident,
+        $fromFloatArray:ident,
+        $Matrix2:ident,
+        $Matrix2d:ident,
+        $Matrix3:ident,
+        $Matrix4:ident,
+        $Quaternion:ident,
+        $Quaternion2:ident,
+        $Vector2:ident,
+        $Vector3:ident,
+        $Vector4:ident
+    ) => {
+        use super::matrix2::*;
+        use super::matrix2d::*;
+        use super::matrix3::*;
+        use super::matrix4::*;
+        use super::quaternion::*;
+        use super::quaternion2::*;
+        use super::vector2::*;
+        use super::vector3::*;
+        use super::vector4::*;
+
+        impl_vector_ops!($Vector2, $F);
+        impl_vector_ops!($Vector3, $F);
+        impl_vector_ops!($Vector4, $F);
+        impl_matrix_ops!($Matrix2, $F);
+        impl_matrix_ops!($Matrix2d, $F);
+        impl_matrix_ops!($Matrix3, $F);
+        impl_matrix_ops!($Matrix4, $F);
+        impl_quaternion_ops!($Quaternion, $F);
+        impl_quaternion_ops!($Quaternion2, $F);
+
+        // Transforming a vector, the matrix or quaternion always stands on the left.
+        impl_binary_op!(
+            Mul,
+            mul,
+            $Matrix2,
+            $Vector2,
+            $Vector2,
+            |out, m, v| { $Vector2::transformMat2(out, v, m) }
+        );
+        impl_binary_op!(
+            Mul,
+            mul,
+            $Matrix2d,
+            $Vector2,
+            $Vector2,
+            |out, m, v| { $Vector2::transformMat2d(out, v, m) }
+        );
+        impl_binary_op!(
+            Mul,
+            mul,
+            $Matrix3,
+            $Vector2,
+            $Vector2,
+            |out, m, v| { $Vector2::transformMat3(out, v, m) }
+        );
+        impl_binary_op!(
+            Mul,
+            mul,
+            $Matrix3,
+            $Vector3,
+            $Vector3,
+            |out, m, v| { $Vector3::transformMat3(out, v, m) }
+        );
+        impl_binary_op!(
+            Mul,
+            mul,
+            $Matrix4,
+            $Vector3,
+            $Vector3,
+            |out, m, v| { $Vector3::transformMat4(out, v, m) }
+        );
+        impl_binary_op!(
+            Mul,
+            mul,
+            $Matrix4,
+            $Vector4,
+            $Vector4,
+            |out, m, v| { $Vector4::transformMat4(out, v, m) }
+        );
+        impl_binary_op!(
+            Mul,
+            mul,
+            $Quaternion,
+            $Vector3,
+            $Vector3,
+            |out, q, v| { $Vector3::transformQuat(out, v, q) }
+        );
+        impl_binary_op!(
+            Mul,
+            mul,
+            $Quaternion,
+            $Vector4,
+            $Vector4,
+            |out, q, v| { $Vector4::transformQuat(out, v, q) }
+        );
+
+        impl_index!($Vector2, $F, 2, 0 1);
+        impl_index!($Vector3, $F, 3, 0 1 2);
+        impl_index!($Vector4, $F, 4, 0 1 2 3);
+        impl_index!($Matrix2, $F, 4, 0 1 2 3);
+        impl_index!($Matrix2d, $F, 6, 0 1 2 3 4 5);
+        impl_index!($Matrix3, $F, 9, 0 1 2 3 4 5 6 7 8);
+        impl_index!($Matrix4, $F, 16, 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15);
+        impl_index!($Quaternion, $F, 4, 0 1 2 3);
+        impl_index!($Quaternion2, $F, 8, 0 1 2 3 4 5 6 7);
+
+        #[cfg(test)]
+        mod tests {
+            use super::*;
+            use crate::spec_helper::*;
+            use std::$F::consts::PI;
+
+            #[test]
+            fn vector() {
+                let vecA = $Vector3::fromValues(1., 2., 3.);
+                let vecB = $Vector3::fromValues(4., 5., 6.);
+                expect_equalish(&(vecA + vecB).elements(), &[5., 7., 9.]);
+                expect_equalish(&(vecA - vecB).elements(), &[-3., -3., -3.]);
+                expect_equalish(&(vecA * vecB).elements(), &[4., 10., 18.]);
+                expect_equalish(&(&vecA * 2.).elements(), &[2., 4., 6.]);
+                expect_equalish(&(2. * &vecA).elements(), &[2., 4., 6.]);
+                expect_equalish(&(-&vecA).elements(), &[-1., -2., -3.]);
+                expect_equalish(&(vecA + vecB).elements(), &[5., 7., 9.]);
+            }
+
+            #[test]
+            fn vector_assign() {
+                let vecA = &mut $Vector2::fromValues(1., 2.);
+                *vecA += $Vector2::fromValues(3., 4.);
+                expect_equalish(&vecA.elements(), &[4., 6.]);
+                *vecA -= &$Vector2::fromValues(1., 1.);
+                expect_equalish(&vecA.elements(), &[3., 5.]);
+                *vecA *= $Vector2::fromValues(2., 3.);
+                expect_equalish(&vecA.elements(), &[6., 15.]);
+                *vecA *= 0.5;
+                expect_equalish(&vecA.elements(), &[3., 7.5]);
+            }
+
+            #[test]
+            fn matrix() {
+                let matA = $Matrix4::fromValues(
+                    1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+                );
+                let matB = $Matrix4::fromValues(
+                    1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 4., 5., 6., 1.,
+                );
+                let expected = &mut $Matrix4::create();
+                $Matrix4::multiply(expected, &matA, &matB);
+                expect_equalish(&(matA * matB).elements(), &expected.elements());
+
+                let matC = &mut $Matrix4::clone(&matA);
+                *matC *= &matB;
+                expect_equalish(&matC.elements(), &expected.elements());
+                *matC -= &matB;
+                *matC += matB;
+                expect_equalish(&matC.elements(), &expected.elements());
+
+                $Matrix4::multiplyScalar(expected, &matA, 2.);
+                expect_equalish(&(&matA * 2.).elements(), &expected.elements());
+                $Matrix4::multiplyScalar(expected, &matA, -1.);
+                expect_equalish(&(-matA).elements(), &expected.elements());
+            }
+
+            #[test]
+            fn transform() {
+                let matA = &mut $Matrix4::create();
+                $Matrix4::fromTranslation(matA, &$Vector3::fromValues(1., 2., 3.));
+                let vec4 = *matA * $Vector4::fromValues(1., 1., 1., 1.);
+                expect_equalish(&vec4.elements(), &[2., 3., 4., 1.]);
+                let vec3 = *matA * $Vector3::fromValues(1., 1., 1.);
+                expect_equalish(&vec3.elements(), &[2., 3., 4.]);
+
+                let quatA = &mut $Quaternion::create();
+                $Quaternion::rotateX(quatA, &$Quaternion::create(), PI / 2.);
+                let vec3 = *quatA * $Vector3::fromValues(0., 0., -1.);
+                expect_equalish(&vec3.elements(), &[0., 1., 0.]);
+
+                let matB = &$Matrix2::fromValues(1., 2., 3., 4.);
+                let vec2 = matB * $Vector2::fromValues(1., 2.);
+                expect_equalish(&vec2.elements(), &[7., 10.]);
+            }
+
+            #[test]
+            fn quaternion() {
+                let quatA = $Quaternion::fromValues(1., 2., 3., 4.);
+                let quatB = $Quaternion::fromValues(5., 6., 7., 8.);
+                expect_equalish(&(quatA * quatB).elements(), &[24., 48., 48., -6.]);
+                expect_equalish(&(quatA + quatB).elements(), &[6., 8., 10., 12.]);
+                expect_equalish(&(&quatA * 2.).elements(), &[2., 4., 6., 8.]);
+
+                let quat2A = $Quaternion2::fromValues(1., 2., 3., 4., 2., 5., 6., -2.);
+                let quat2B = $Quaternion2::fromValues(5., 6., 7., 8., 9., 8., 6., -4.);
+                let quat2C = &mut $Quaternion2::clone(&quat2A);
+                *quat2C *= quat2B;
+                expect_equalish(
+                    &quat2C.elements(),
+                    &[24., 48., 48., -6., 25., 89., 23., -157.],
+                );
+                expect_equalish(
+                    &(-quat2A).elements(),
+                    &[-1., -2., -3., -4., -2., -5., -6., 2.],
+                );
+            }
+
+            #[test]
+            fn index() {
+                let matA = &mut $Matrix3::create();
+                assert_eq!(matA[4], 1.);
+                matA[7] = 5.;
+                assert_eq!(matA.7, 5.);
+
+                let quat2A = &mut $Quaternion2::create();
+                quat2A[6] = 2.;
+                assert_eq!(quat2A[6], 2.);
+                assert_eq!(quat2A[3], 1.);
+            }
+
+            #[test]
+            #[should_panic(expected = "index out of bounds: the len is 3 but the index is 3")]
+            fn index_out_of_bounds() {
+                let vecA = $Vector3::create();
+                let _ = vecA[3];
+            }
+        }
+    };
+}
+
+pub(crate) use define_ops;
+
+define_ops!(
+    f32,
+    Float32Array,
+    fromFloat32Array,
+    Matrix2,
+    Matrix2d,
+    Matrix3,
+    Matrix4,
+    Quaternion,
+    Quaternion2,
+    Vector2,
+    Vector3,
+    Vector4
+);
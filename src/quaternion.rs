@@ -4,498 +4,1735 @@
  * @Link   : http://dtysky.moe
  * @Date   : 2019/2/7 下午9:53:03
  */
-use wasm_bindgen::prelude::*;
-
-use super::common::*;
-use super::matrix3::*;
-use super::vector3::*;
-
-#[wasm_bindgen]
-pub struct Quaternion(pub f32, pub f32, pub f32, pub f32);
-
-#[wasm_bindgen]
-impl Quaternion {
-    #[wasm_bindgen(getter)]
-    pub fn elements(&self) -> Box<[f32]> {
-        Box::new([self.0, self.1, self.2, self.3])
-    }
-
-    pub fn create() -> Quaternion {
-        Quaternion(0., 0., 0., 1.)
-    }
-
-    pub fn identity(out: &mut Quaternion) {
-        out.0 = 0.;
-        out.1 = 0.;
-        out.2 = 0.;
-        out.3 = 1.;
-    }
-
-    pub fn setAxisAngle(out: &mut Quaternion, axis: &Vector3, rad: f32) {
-        let rad = rad * 0.5;
-        let s = f32::sin(rad);
-        out.0 = s * axis.0;
-        out.1 = s * axis.1;
-        out.2 = s * axis.2;
-        out.3 = f32::cos(rad);
-    }
-
-    pub fn getAxisAngle(out_axis: &mut Vector3, q: &Quaternion) -> f32 {
-        let rad = f32::acos(q.3) * 2.0;
-        let s = f32::sin(rad / 2.0);
-        if (s > EPSILON) {
-            out_axis.0 = q.0 / s;
-            out_axis.1 = q.1 / s;
-            out_axis.2 = q.2 / s;
-        } else {
-            // If s is zero, return any axis (no rotation - axis does not matter)
-            out_axis.0 = 1.;
-            out_axis.1 = 0.;
-            out_axis.2 = 0.;
-        }
-        rad
-    }
-
-    pub fn multiply(out: &mut Quaternion, a: &Quaternion, b: &Quaternion) {
-        let ax = a.0;
-        let ay = a.1;
-        let az = a.2;
-        let aw = a.3;
-        let bx = b.0;
-        let by = b.1;
-        let bz = b.2;
-        let bw = b.3;
-
-        out.0 = ax * bw + aw * bx + ay * bz - az * by;
-        out.1 = ay * bw + aw * by + az * bx - ax * bz;
-        out.2 = az * bw + aw * bz + ax * by - ay * bx;
-        out.3 = aw * bw - ax * bx - ay * by - az * bz;
-    }
-
-    pub fn rotateX(out: &mut Quaternion, a: &Quaternion, rad: f32) {
-        let rad = rad * 0.5;
-
-        let ax = a.0;
-        let ay = a.1;
-        let az = a.2;
-        let aw = a.3;
-        let mut bx = f32::sin(rad);
-        let mut bw = f32::cos(rad);
-
-        if bx.abs() < EPSILON {
-            bx = 0.;
-        }
+// `Quaternion` here and `Quaterniond` in `double`. `$F` is the element type, `$FloatArray` the js array of it
+// that `$fromFloatArray` reads, and the other names are the types of the same precision.
+macro_rules! define_quaternion {
+    (
+        $F:ident,
+        $FloatArray:ident,
+        $fromFloatArray:ident,
+        $Matrix2:ident,
+        $Matrix2d:ident,
+        $Matrix3:ident,
+        $Matrix4:ident,
+        $Quaternion:ident,
+        $Quaternion2:ident,
+        $Vector2:ident,
+        $Vector3:ident,
+        $Vector4:ident
+    ) => {
+        #[cfg(feature = "wasm")]
+        use js_sys::$FloatArray;
+        #[cfg(feature = "wasm")]
+        use wasm_bindgen::prelude::*;
+
+        use core::fmt;
+        use core::str::FromStr;
+
+        use super::common::*;
+        use super::matrix3::*;
+        use super::vector3::*;
+        use crate::kernels::{self, Elements};
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        #[derive(Clone, Copy, Debug)]
+        #[repr(C)]
+        pub struct $Quaternion(pub $F, pub $F, pub $F, pub $F);
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        impl $Quaternion {
+            #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+            pub fn elements(&self) -> Box<[$F]> {
+                Box::new([self.0, self.1, self.2, self.3])
+            }
 
-        if bw.abs() < EPSILON {
-            bw = 0.;
-        }
+            pub fn create() -> $Quaternion {
+                $Quaternion(0., 0., 0., 1.)
+            }
+
+            pub fn identity(out: &mut $Quaternion) {
+                out.0 = 0.;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 1.;
+            }
+
+            pub fn setAxisAngle(out: &mut $Quaternion, axis: &$Vector3, rad: $F) {
+                let rad = rad * 0.5;
+                let s = $F::sin(rad);
+                out.0 = s * axis.0;
+                out.1 = s * axis.1;
+                out.2 = s * axis.2;
+                out.3 = $F::cos(rad);
+            }
+
+            pub fn getAxisAngle(out_axis: &mut $Vector3, q: &$Quaternion) -> $F {
+                let rad = $F::acos(q.3) * 2.0;
+                let s = $F::sin(rad / 2.0);
+                if s > EPSILON {
+                    out_axis.0 = q.0 / s;
+                    out_axis.1 = q.1 / s;
+                    out_axis.2 = q.2 / s;
+                } else {
+                    // If s is zero, return any axis (no rotation - axis does not matter)
+                    out_axis.0 = 1.;
+                    out_axis.1 = 0.;
+                    out_axis.2 = 0.;
+                }
+                rad
+            }
+
+            pub fn multiply(out: &mut $Quaternion, a: &$Quaternion, b: &$Quaternion) {
+                let ax = a.0;
+                let ay = a.1;
+                let az = a.2;
+                let aw = a.3;
+                let bx = b.0;
+                let by = b.1;
+                let bz = b.2;
+                let bw = b.3;
+
+                out.0 = ax * bw + aw * bx + ay * bz - az * by;
+                out.1 = ay * bw + aw * by + az * bx - ax * bz;
+                out.2 = az * bw + aw * bz + ax * by - ay * bx;
+                out.3 = aw * bw - ax * bx - ay * by - az * bz;
+            }
+
+            pub fn rotateX(out: &mut $Quaternion, a: &$Quaternion, rad: $F) {
+                let rad = rad * 0.5;
+
+                let ax = a.0;
+                let ay = a.1;
+                let az = a.2;
+                let aw = a.3;
+                let mut bx = $F::sin(rad);
+                let mut bw = $F::cos(rad);
+
+                if bx.abs() < EPSILON {
+                    bx = 0.;
+                }
+
+                if bw.abs() < EPSILON {
+                    bw = 0.;
+                }
+
+                out.0 = ax * bw + aw * bx;
+                out.1 = ay * bw + az * bx;
+                out.2 = az * bw - ay * bx;
+                out.3 = aw * bw - ax * bx;
+            }
+
+            pub fn rotateY(out: &mut $Quaternion, a: &$Quaternion, rad: $F) {
+                let rad = rad * 0.5;
+
+                let ax = a.0;
+                let ay = a.1;
+                let az = a.2;
+                let aw = a.3;
+                let mut by = $F::sin(rad);
+                let mut bw = $F::cos(rad);
+
+                if by.abs() < EPSILON {
+                    by = 0.;
+                }
+
+                if bw.abs() < EPSILON {
+                    bw = 0.;
+                }
+
+                out.0 = ax * bw - az * by;
+                out.1 = ay * bw + aw * by;
+                out.2 = az * bw + ax * by;
+                out.3 = aw * bw - ay * by;
+            }
+
+            pub fn rotateZ(out: &mut $Quaternion, a: &$Quaternion, rad: $F) {
+                let rad = rad * 0.5;
+
+                let ax = a.0;
+                let ay = a.1;
+                let az = a.2;
+                let aw = a.3;
+                let mut bz = $F::sin(rad);
+                let mut bw = $F::cos(rad);
+
+                if bz.abs() < EPSILON {
+                    bz = 0.;
+                }
+
+                if bw.abs() < EPSILON {
+                    bw = 0.;
+                }
+
+                out.0 = ax * bw + ay * bz;
+                out.1 = ay * bw - ax * bz;
+                out.2 = az * bw + aw * bz;
+                out.3 = aw * bw - az * bz;
+            }
+
+            pub fn calculateW(out: &mut $Quaternion, a: &$Quaternion) {
+                let x = a.0;
+                let y = a.1;
+                let z = a.2;
+
+                out.0 = x;
+                out.1 = y;
+                out.2 = z;
+                out.3 = $F::sqrt($F::abs(1.0 - x * x - y * y - z * z));
+            }
+
+            pub fn slerp(out: &mut $Quaternion, a: &$Quaternion, b: &$Quaternion, t: $F) {
+                f32_only!(
+                    $F,
+                    if cfg!(feature = "simd") {
+                        crate::simd::slerpQuat(out, a, b, t);
+                        return;
+                    }
+                );
+
+                // benchmarks:
+                //    http://jsperf.com/quaternion-slerp-implementations
+                let ax = a.0;
+                let ay = a.1;
+                let az = a.2;
+                let aw = a.3;
+                let mut bx = b.0;
+                let mut by = b.1;
+                let mut bz = b.2;
+                let mut bw = b.3;
+
+                // calc cosine
+                let mut cosom = ax * bx + ay * by + az * bz + aw * bw;
+
+                if cosom.abs() < EPSILON {
+                    cosom = 0.;
+                }
+
+                // adjust signs (if necessary)
+                if cosom < 0. {
+                    cosom = -cosom;
+                    bx = -bx;
+                    by = -by;
+                    bz = -bz;
+                    bw = -bw;
+                }
+
+                // calculate coefficients
+                let (scale0, scale1) = if (1.0 - cosom) > EPSILON {
+                    // standard case (slerp)
+                    let omega = $F::acos(cosom);
+                    let sinom = $F::sin(omega);
+                    (
+                        $F::sin((1.0 - t) * omega) / sinom,
+                        $F::sin(t * omega) / sinom,
+                    )
+                } else {
+                    // "from" and "to" quaternions are very close
+                    //  ... so we can do a linear interpolation
+                    (1.0 - t, t)
+                };
+
+                // calculate final values
+                out.0 = scale0 * ax + scale1 * bx;
+                out.1 = scale0 * ay + scale1 * by;
+                out.2 = scale0 * az + scale1 * bz;
+                out.3 = scale0 * aw + scale1 * bw;
+            }
+
+            pub fn random(out: &mut $Quaternion) {
+                // Implementation of http://planning.cs.uiuc.edu/node198.html
+                // TODO: Calling random 3 times is probably not the fastest solution
+                let u1 = RANDOM();
+                let u2 = RANDOM();
+                let u3 = RANDOM();
+
+                let sqrt1MinusU1 = $F::sqrt(1. - u1);
+                let sqrtU1 = $F::sqrt(u1);
+
+                out.0 = sqrt1MinusU1 * $F::sin(2.0 * PI * u2);
+                out.1 = sqrt1MinusU1 * $F::cos(2.0 * PI * u2);
+                out.2 = sqrtU1 * $F::sin(2.0 * PI * u3);
+                out.3 = sqrtU1 * $F::cos(2.0 * PI * u3);
+            }
+
+            pub fn invert(out: &mut $Quaternion, a: &$Quaternion) {
+                let a0 = a.0;
+                let a1 = a.1;
+                let a2 = a.2;
+                let a3 = a.3;
+                let dot = a0 * a0 + a1 * a1 + a2 * a2 + a3 * a3;
+                let invDot = if dot < EPSILON { 0. } else { 1. / dot };
+
+                // TODO: Would be faster to return [0,0,0,0] immediately if dot == 0
+
+                out.0 = -a0 * invDot;
+                out.1 = -a1 * invDot;
+                out.2 = -a2 * invDot;
+                out.3 = a3 * invDot;
+            }
+
+            pub fn conjugate(out: &mut $Quaternion, a: &$Quaternion) {
+                out.0 = -a.0;
+                out.1 = -a.1;
+                out.2 = -a.2;
+                out.3 = a.3;
+            }
+
+            pub fn fromMat3(out: &mut $Quaternion, m: &$Matrix3) {
+                // Algorithm in Ken Shoemake"s article in 1987 SIGGRAPH course notes
+                // article "Quaternion Calculus and Fast Animation".
+                let fTrace = m.0 + m.4 + m.8;
+
+                if fTrace > 0.0 {
+                    // |w| > 1/2, may as well choose w > 1/2
+                    let mut fRoot = $F::sqrt(fTrace + 1.0); // 2w
+                    out.3 = 0.5 * fRoot;
+                    fRoot = 0.5 / fRoot; // 1/(4w)
+                    out.0 = (m.5 - m.7) * fRoot;
+                    out.1 = (m.6 - m.2) * fRoot;
+                    out.2 = (m.1 - m.3) * fRoot;
+                } else {
+                    // |w| <= 1/2
+                    let mut i = 0;
+                    if m.4 > m.0 {
+                        i = 1;
+                    }
+                    let tmp = if i == 0 { m.0 } else { m.4 };
+                    if m.8 > tmp {
+                        i = 2;
+                    }
+
+                    match i {
+                        0 => {
+                            // i = 0, j = 1, k = 2
+                            let mut fRoot = $F::sqrt(m.0 - m.4 - m.8 + 1.0);
+                            out.0 = 0.5 * fRoot;
+                            fRoot = 0.5 / fRoot;
+                            out.3 = (m.5 - m.7) * fRoot;
+                            out.1 = (m.3 + m.1) * fRoot;
+                            out.2 = (m.6 + m.2) * fRoot;
+                        }
+                        1 => {
+                            // i = 1, j = 2, k = 0
+                            let mut fRoot = $F::sqrt(m.4 - m.8 - m.0 + 1.0);
+                            out.1 = 0.5 * fRoot;
+                            fRoot = 0.5 / fRoot;
+                            out.3 = (m.6 - m.2) * fRoot;
+                            out.2 = (m.7 + m.5) * fRoot;
+                            out.0 = (m.1 + m.3) * fRoot;
+                        }
+                        2 => {
+                            // i = 2, j = 0, k = 1
+                            let mut fRoot = $F::sqrt(m.8 - m.0 - m.4 + 1.0);
+                            out.2 = 0.5 * fRoot;
+                            fRoot = 0.5 / fRoot;
+                            out.3 = (m.1 - m.3) * fRoot;
+                            out.0 = (m.2 + m.6) * fRoot;
+                            out.1 = (m.5 + m.7) * fRoot;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            pub fn fromEuler(out: &mut $Quaternion, x: $F, y: $F, z: $F) {
+                let halfToRad = 0.5 * PI / 180.0;
+                let x = x * halfToRad;
+                let y = y * halfToRad;
+                let z = z * halfToRad;
+
+                let sx = $F::sin(x);
+                let cx = $F::cos(x);
+                let sy = $F::sin(y);
+                let cy = $F::cos(y);
+                let sz = $F::sin(z);
+                let cz = $F::cos(z);
+
+                out.0 = sx * cy * cz - cx * sy * sz;
+                out.1 = cx * sy * cz + sx * cy * sz;
+                out.2 = cx * cy * sz - sx * sy * cz;
+                out.3 = cx * cy * cz + sx * sy * sz;
+            }
+
+            pub fn fromEulerOrder(
+                out: &mut $Quaternion,
+                x: $F,
+                y: $F,
+                z: $F,
+                order: EulerOrder,
+                unit: AngleUnit,
+            ) {
+                let half = match unit {
+                    AngleUnit::Radians => 0.5,
+                    AngleUnit::Degrees => 0.5 * PI / 180.0,
+                };
+                let qx = $Quaternion($F::sin(x * half), 0., 0., $F::cos(x * half));
+                let qy = $Quaternion(0., $F::sin(y * half), 0., $F::cos(y * half));
+                let qz = $Quaternion(0., 0., $F::sin(z * half), $F::cos(z * half));
+
+                let (a, b, c) = match order {
+                    EulerOrder::XYZ | EulerOrder::ExtrinsicZYX => (qx, qy, qz),
+                    EulerOrder::XZY | EulerOrder::ExtrinsicYZX => (qx, qz, qy),
+                    EulerOrder::YXZ | EulerOrder::ExtrinsicZXY => (qy, qx, qz),
+                    EulerOrder::YZX | EulerOrder::ExtrinsicXZY => (qy, qz, qx),
+                    EulerOrder::ZXY | EulerOrder::ExtrinsicYXZ => (qz, qx, qy),
+                    EulerOrder::ZYX | EulerOrder::ExtrinsicXYZ => (qz, qy, qx),
+                };
+                $Quaternion::multiply(out, &a, &b);
+                $Quaternion::multiply(out, &$Quaternion::clone(out), &c);
+            }
+
+            pub fn toEuler(
+                out: &mut $Vector3,
+                q: &$Quaternion,
+                order: EulerOrder,
+                unit: AngleUnit,
+            ) {
+                let n = &mut $Quaternion::create();
+                $Quaternion::normalize(n, q);
+                let m = &mut $Matrix3::create();
+                $Matrix3::fromQuat(m, n);
+                $Matrix3::getEuler(out, m, order, unit);
+            }
+
+            pub fn str(a: &$Quaternion) -> String {
+                a.to_string()
+            }
+
+            #[allow(clippy::should_implement_trait)]
+            pub fn clone(a: &$Quaternion) -> $Quaternion {
+                $Quaternion(a.0, a.1, a.2, a.3)
+            }
+
+            pub fn fromValues(x: $F, y: $F, z: $F, w: $F) -> $Quaternion {
+                $Quaternion(x, y, z, w)
+            }
+
+            pub fn copy(out: &mut $Quaternion, a: &$Quaternion) {
+                *out = *a;
+            }
+
+            pub fn set(out: &mut $Quaternion, x: $F, y: $F, z: $F, w: $F) {
+                out.0 = x;
+                out.1 = y;
+                out.2 = z;
+                out.3 = w;
+            }
+
+            pub fn add(out: &mut $Quaternion, a: &$Quaternion, b: &$Quaternion) {
+                kernels::add(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn mul(out: &mut $Quaternion, a: &$Quaternion, b: &$Quaternion) {
+                $Quaternion::multiply(out, a, b);
+            }
+
+            pub fn scale(out: &mut $Quaternion, a: &$Quaternion, b: $F) {
+                kernels::scale(out.arrayMut(), a.array(), b);
+            }
+
+            pub fn dot(a: &$Quaternion, b: &$Quaternion) -> $F {
+                kernels::dot(a.array(), b.array())
+            }
+
+            pub fn lerp(out: &mut $Quaternion, a: &$Quaternion, b: &$Quaternion, t: $F) {
+                kernels::lerp(out.arrayMut(), a.array(), b.array(), t);
+            }
+
+            pub fn len(a: &$Quaternion) -> $F {
+                kernels::length(a.array())
+            }
+
+            pub fn squaredLength(a: &$Quaternion) -> $F {
+                kernels::squaredLength(a.array())
+            }
+
+            pub fn sqrLen(a: &$Quaternion) -> $F {
+                $Quaternion::squaredLength(a)
+            }
+
+            pub fn normalize(out: &mut $Quaternion, a: &$Quaternion) {
+                kernels::normalize(out.arrayMut(), a.array(), EPSILON);
+            }
+
+            pub fn exactEquals(a: &$Quaternion, b: &$Quaternion) -> bool {
+                kernels::exactEquals(a.array(), b.array())
+            }
+
+            pub fn equals(a: &$Quaternion, b: &$Quaternion) -> bool {
+                match getEqualsMode() {
+                    EqualsMode::Epsilon => $Quaternion::equalsWithEpsilon(a, b, getEpsilon()),
+                    EqualsMode::Ulps => $Quaternion::equalsWithUlps(a, b, getMaxUlps()),
+                }
+            }
+
+            pub fn equalsWithEpsilon(a: &$Quaternion, b: &$Quaternion, epsilon: $F) -> bool {
+                kernels::equalsWithEpsilon(a.array(), b.array(), epsilon)
+            }
+
+            pub fn equalsWithUlps(a: &$Quaternion, b: &$Quaternion, maxUlps: u32) -> bool {
+                kernels::equalsWithUlps(a.array(), b.array(), maxUlps)
+            }
+
+            pub fn rotationTo(out: &mut $Quaternion, a: &$Vector3, b: &$Vector3) {
+                let tmpvec3 = &mut $Vector3::create();
+                let xUnitVec3 = &$Vector3::fromValues(1., 0., 0.);
+                let yUnitVec3 = &$Vector3::fromValues(0., 1., 0.);
+
+                let dot = $Vector3::dot(a, b);
+                if dot < -0.999999 {
+                    $Vector3::cross(tmpvec3, xUnitVec3, a);
+                    if $Vector3::len(tmpvec3) < EPSILON {
+                        $Vector3::cross(tmpvec3, yUnitVec3, a);
+                    }
+                    $Vector3::normalize(tmpvec3, &$Vector3::clone(tmpvec3));
+                    $Quaternion::setAxisAngle(out, tmpvec3, PI);
+                } else if dot > 0.999999 {
+                    out.0 = 0.;
+                    out.1 = 0.;
+                    out.2 = 0.;
+                    out.3 = 1.;
+                } else {
+                    $Vector3::cross(tmpvec3, a, b);
+                    out.0 = tmpvec3.0;
+                    out.1 = tmpvec3.1;
+                    out.2 = tmpvec3.2;
+                    out.3 = 1. + dot;
+                    $Quaternion::normalize(out, &$Quaternion::clone(out));
+                }
+            }
+
+            pub fn sqlerp(
+                out: &mut $Quaternion,
+                a: &$Quaternion,
+                b: &$Quaternion,
+                c: &$Quaternion,
+                d: &$Quaternion,
+                t: $F,
+            ) {
+                let temp1 = &mut $Quaternion::create();
+                let temp2 = &mut $Quaternion::create();
+
+                $Quaternion::slerp(temp1, a, d, t);
+                $Quaternion::slerp(temp2, b, c, t);
+                $Quaternion::slerp(out, temp1, temp2, 2. * t * (1. - t));
+            }
+
+            pub fn exp(out: &mut $Quaternion, a: &$Quaternion) {
+                let x = a.0;
+                let y = a.1;
+                let z = a.2;
+                let r = $F::sqrt(x * x + y * y + z * z);
+                let et = $F::exp(a.3);
+                let s = if r > 0. { et * $F::sin(r) / r } else { 0. };
+
+                out.0 = x * s;
+                out.1 = y * s;
+                out.2 = z * s;
+                out.3 = et * $F::cos(r);
+            }
+
+            pub fn ln(out: &mut $Quaternion, a: &$Quaternion) {
+                let x = a.0;
+                let y = a.1;
+                let z = a.2;
+                let w = a.3;
+                let r = $F::sqrt(x * x + y * y + z * z);
+                let t = if r > 0. { $F::atan2(r, w) / r } else { 0. };
+
+                out.0 = x * t;
+                out.1 = y * t;
+                out.2 = z * t;
+                out.3 = 0.5 * $F::ln(x * x + y * y + z * z + w * w);
+            }
+
+            pub fn pow(out: &mut $Quaternion, a: &$Quaternion, b: $F) {
+                $Quaternion::ln(out, a);
+                $Quaternion::scale(out, &$Quaternion::clone(out), b);
+                $Quaternion::exp(out, &$Quaternion::clone(out));
+            }
+
+            // Inner control point of `q` for `squad`, `q * exp(-(ln(q^-1 * next) + ln(q^-1 * prev)) / 4)`.
+            pub fn squadControlPoint(
+                out: &mut $Quaternion,
+                prev: &$Quaternion,
+                q: &$Quaternion,
+                next: &$Quaternion,
+            ) {
+                let inv = &mut $Quaternion::create();
+                $Quaternion::invert(inv, q);
+                let toPrev = &mut $Quaternion::create();
+                let toNext = &mut $Quaternion::create();
+                // The neighbours are taken on the hemisphere of `q`.
+                let prev = &if $Quaternion::dot(prev, q) < 0. {
+                    -*prev
+                } else {
+                    *prev
+                };
+                let next = &if $Quaternion::dot(next, q) < 0. {
+                    -*next
+                } else {
+                    *next
+                };
+                $Quaternion::multiply(toPrev, inv, prev);
+                $Quaternion::multiply(toNext, inv, next);
+                $Quaternion::ln(toPrev, &$Quaternion::clone(toPrev));
+                $Quaternion::ln(toNext, &$Quaternion::clone(toNext));
+
+                $Quaternion::add(out, toPrev, toNext);
+                $Quaternion::scale(out, &$Quaternion::clone(out), -0.25);
+                $Quaternion::exp(out, &$Quaternion::clone(out));
+                $Quaternion::multiply(out, q, &$Quaternion::clone(out));
+            }
+
+            // Squad from `a` to `b`, the control points are computed from the neighbouring keys `prev` and `next`.
+            pub fn squad(
+                out: &mut $Quaternion,
+                prev: &$Quaternion,
+                a: &$Quaternion,
+                b: &$Quaternion,
+                next: &$Quaternion,
+                t: $F,
+            ) {
+                let prev = &if $Quaternion::dot(prev, a) < 0. {
+                    -*prev
+                } else {
+                    *prev
+                };
+                let b = &if $Quaternion::dot(b, a) < 0. { -*b } else { *b };
+                let next = &if $Quaternion::dot(next, b) < 0. {
+                    -*next
+                } else {
+                    *next
+                };
+                let ctrlA = &mut $Quaternion::create();
+                let ctrlB = &mut $Quaternion::create();
+                $Quaternion::squadControlPoint(ctrlA, prev, a, b);
+                $Quaternion::squadControlPoint(ctrlB, a, b, next);
+
+                // `sqlerp` without the hemisphere flips, which would jump by half a turn inside the segment
+                // when the keys are more than a quarter turn apart.
+                let temp1 = &mut $Quaternion::create();
+                let temp2 = &mut $Quaternion::create();
+                $Quaternion::slerpUnflipped(temp1, a, b, t);
+                $Quaternion::slerpUnflipped(temp2, ctrlA, ctrlB, t);
+                $Quaternion::slerpUnflipped(out, temp1, temp2, 2. * t * (1. - t));
+            }
+
+            // Evaluates the squad spline through the keys packed in `keys`, 4 elements each, at `t` from 0 to
+            // the last key index. The rotation is C1 continuous at the keys, as a Catmull-Rom spline.
+            pub fn spline(out: &mut $Quaternion, keys: &[$F], t: $F) {
+                let count = keys.len() / 4;
+                assert!(count > 0, "the spline has no keys");
+                let key = |i: usize| $Quaternion::fromSlice(&keys[i * 4..]);
+                if count == 1 {
+                    $Quaternion::copy(out, &key(0));
+                    return;
+                }
+
+                let t = t.clamp(0., (count - 1) as $F);
+                let i = (t.floor() as usize).min(count - 2);
+                $Quaternion::squad(
+                    out,
+                    &key(i.saturating_sub(1)),
+                    &key(i),
+                    &key(i + 1),
+                    &key((i + 2).min(count - 1)),
+                    t - i as $F,
+                );
+            }
+
+            // Splits `q` into `swing * twist`, the twist rotates about `axis` and the swing about an axis
+            // perpendicular to it. When `q` turns `axis` around by half a turn the twist is the identity.
+            pub fn swingTwist(
+                q: &$Quaternion,
+                axis: &$Vector3,
+                outSwing: &mut $Quaternion,
+                outTwist: &mut $Quaternion,
+            ) {
+                let a = &mut $Vector3::create();
+                $Vector3::normalize(a, axis);
+                let d = q.0 * a.0 + q.1 * a.1 + q.2 * a.2;
+                $Quaternion::set(outTwist, a.0 * d, a.1 * d, a.2 * d, q.3);
+                if $Quaternion::squaredLength(outTwist) < EPSILON * EPSILON {
+                    $Quaternion::identity(outTwist);
+                } else {
+                    $Quaternion::normalize(outTwist, &$Quaternion::clone(outTwist));
+                }
+
+                let inv = &mut $Quaternion::create();
+                $Quaternion::conjugate(inv, outTwist);
+                $Quaternion::multiply(outSwing, q, inv);
+            }
+
+            // Limits the twist of `q` about `axis` to the angles from `minAngle` to `maxAngle`, in -PI to PI.
+            pub fn clampTwist(
+                out: &mut $Quaternion,
+                q: &$Quaternion,
+                axis: &$Vector3,
+                minAngle: $F,
+                maxAngle: $F,
+            ) {
+                let a = &mut $Vector3::create();
+                $Vector3::normalize(a, axis);
+                let swing = &mut $Quaternion::create();
+                let twist = &mut $Quaternion::create();
+                $Quaternion::swingTwist(q, a, swing, twist);
+
+                let mut rad =
+                    2. * $F::atan2(twist.0 * a.0 + twist.1 * a.1 + twist.2 * a.2, twist.3);
+                if rad > PI {
+                    rad -= 2. * PI;
+                } else if rad < -PI {
+                    rad += 2. * PI;
+                }
+                $Quaternion::setAxisAngle(twist, a, rad.max(minAngle).min(maxAngle));
+                $Quaternion::multiply(out, swing, twist);
+            }
+
+            // Limits the swing of `q` away from `axis` to an elliptical cone, keeping the twist. The swing
+            // angle is at most `limitAngle` about `limitAxis`, which is made perpendicular to `axis`, and
+            // `limitAnglePerp` about `axis x limitAxis`. Swings out of the cone are scaled back to its edge, a
+            // limit of zero locks the swing about its axis.
+            pub fn clampSwing(
+                out: &mut $Quaternion,
+                q: &$Quaternion,
+                axis: &$Vector3,
+                limitAxis: &$Vector3,
+                limitAngle: $F,
+                limitAnglePerp: $F,
+            ) {
+                let a = &mut $Vector3::create();
+                $Vector3::normalize(a, axis);
+                let u = &mut $Vector3::create();
+                $Vector3::scaleAndAdd(u, limitAxis, a, -$Vector3::dot(limitAxis, a));
+                $Vector3::normalize(u, &$Vector3::clone(u));
+                let v = &mut $Vector3::create();
+                $Vector3::cross(v, a, u);
+
+                let swing = &mut $Quaternion::create();
+                let twist = &mut $Quaternion::create();
+                $Quaternion::swingTwist(q, a, swing, twist);
+                let swing = &mut if swing.3 < 0. { -*swing } else { *swing };
+
+                // The swing as a rotation vector in the plane of `u` and `v`.
+                $Quaternion::ln(swing, &$Quaternion::clone(swing));
+                let log = &$Vector3::fromValues(swing.0, swing.1, swing.2);
+                let mut x = 2. * $Vector3::dot(log, u);
+                let mut y = 2. * $Vector3::dot(log, v);
+                if limitAngle <= 0. {
+                    x = 0.;
+                }
+                if limitAnglePerp <= 0. {
+                    y = 0.;
+                }
+                let rx = if x == 0. { 0. } else { x / limitAngle };
+                let ry = if y == 0. { 0. } else { y / limitAnglePerp };
+                let r = rx * rx + ry * ry;
+                if r > 1. {
+                    let s = 1. / $F::sqrt(r);
+                    x *= s;
+                    y *= s;
+                }
+
+                $Quaternion::set(
+                    swing,
+                    (u.0 * x + v.0 * y) * 0.5,
+                    (u.1 * x + v.1 * y) * 0.5,
+                    (u.2 * x + v.2 * y) * 0.5,
+                    0.,
+                );
+                $Quaternion::exp(swing, &$Quaternion::clone(swing));
+                $Quaternion::multiply(out, swing, twist);
+            }
+
+            pub fn setAxes(
+                out: &mut $Quaternion,
+                view: &$Vector3,
+                right: &$Vector3,
+                up: &$Vector3,
+            ) {
+                let matr = &mut $Matrix3::create();
 
-        out.0 = ax * bw + aw * bx;
-        out.1 = ay * bw + az * bx;
-        out.2 = az * bw - ay * bx;
-        out.3 = aw * bw - ax * bx;
-    }
+                matr.0 = right.0;
+                matr.3 = right.1;
+                matr.6 = right.2;
 
-    pub fn rotateY(out: &mut Quaternion, a: &Quaternion, rad: f32) {
-        let rad = rad * 0.5;
+                matr.1 = up.0;
+                matr.4 = up.1;
+                matr.7 = up.2;
 
-        let ax = a.0;
-        let ay = a.1;
-        let az = a.2;
-        let aw = a.3;
-        let mut by = f32::sin(rad);
-        let mut bw = f32::cos(rad);
+                matr.2 = -view.0;
+                matr.5 = -view.1;
+                matr.8 = -view.2;
 
-        if by.abs() < EPSILON {
-            by = 0.;
+                $Quaternion::fromMat3(out, matr);
+                $Quaternion::normalize(out, &$Quaternion::clone(out));
+            }
+
+            // Slerps `count` pairs of quaternions packed in `a` and `b`, 4 elements each, by the same `t`.
+            pub fn slerpArray(out: &mut [$F], a: &[$F], b: &[$F], t: $F, count: usize) {
+                let len = count * 4;
+                assert!(
+                    out.len() >= len && a.len() >= len && b.len() >= len,
+                    "the buffers are shorter than {} elements",
+                    len
+                );
+                let q = &mut $Quaternion::create();
+                for i in 0..count {
+                    let r = i * 4..i * 4 + 4;
+                    $Quaternion::slerp(
+                        q,
+                        &$Quaternion::fromSlice(&a[r.clone()]),
+                        &$Quaternion::fromSlice(&b[r.clone()]),
+                        t,
+                    );
+                    q.writeSlice(&mut out[r]);
+                }
+            }
         }
 
-        if bw.abs() < EPSILON {
-            bw = 0.;
+        impl $Quaternion {
+            // Normalized `slerp` along the arc from `a` to `b` as given, even when it is the longer one.
+            fn slerpUnflipped(out: &mut $Quaternion, a: &$Quaternion, b: &$Quaternion, t: $F) {
+                let cosom = $Quaternion::dot(a, b).clamp(-1., 1.);
+                let (scale0, scale1) = if 1. - $F::abs(cosom) > EPSILON {
+                    let omega = $F::acos(cosom);
+                    let sinom = $F::sin(omega);
+                    (
+                        $F::sin((1. - t) * omega) / sinom,
+                        $F::sin(t * omega) / sinom,
+                    )
+                } else {
+                    (1. - t, t)
+                };
+                $Quaternion::set(
+                    out,
+                    scale0 * a.0 + scale1 * b.0,
+                    scale0 * a.1 + scale1 * b.1,
+                    scale0 * a.2 + scale1 * b.2,
+                    scale0 * a.3 + scale1 * b.3,
+                );
+                $Quaternion::normalize(out, &$Quaternion::clone(out));
+            }
         }
 
-        out.0 = ax * bw - az * by;
-        out.1 = ay * bw + aw * by;
-        out.2 = az * bw + ax * by;
-        out.3 = aw * bw - ay * by;
-    }
+        // Flat f32 buffers, such as the uniforms or the batch functions.
+        impl $Quaternion {
+            pub fn fromSlice(a: &[$F]) -> $Quaternion {
+                $Quaternion(a[0], a[1], a[2], a[3])
+            }
+
+            pub fn writeSlice(&self, out: &mut [$F]) {
+                out[..4].copy_from_slice(self.array());
+            }
+
+            pub fn asSlice(&self) -> &[$F] {
+                self.array()
+            }
+        }
 
-    pub fn rotateZ(out: &mut Quaternion, a: &Quaternion, rad: f32) {
-        let rad = rad * 0.5;
+        // `#[repr(C)]` keeps the 4 elements contiguous.
+        unsafe impl Elements<$F, 4> for $Quaternion {}
 
-        let ax = a.0;
-        let ay = a.1;
-        let az = a.2;
-        let aw = a.3;
-        let mut bz = f32::sin(rad);
-        let mut bw = f32::cos(rad);
+        #[cfg(feature = "wasm")]
+        #[wasm_bindgen]
+        impl $Quaternion {
+            // Aliases the elements in wasm memory without copying. The view is detached when the memory grows,
+            // so don't keep it across calls that may allocate.
+            pub fn view(&self) -> $FloatArray {
+                unsafe { $FloatArray::view(self.asSlice()) }
+            }
 
-        if bz.abs() < EPSILON {
-            bz = 0.;
+            // Reads the first 4 elements of `a`, without allocating in wasm memory. Throws a `RangeError`
+            // when `a` is shorter.
+            pub fn $fromFloatArray(a: &$FloatArray) -> Result<$Quaternion, JsValue> {
+                check_length(4, a.length() as usize)?;
+                let mut elements = [0.; 4];
+                a.subarray(0, 4).copy_to(&mut elements);
+                Ok($Quaternion::fromSlice(&elements))
+            }
+
+            pub fn copyFromArray(out: &mut $Quaternion, a: &$FloatArray) -> Result<(), JsValue> {
+                *out = $Quaternion::$fromFloatArray(a)?;
+                Ok(())
+            }
+        }
+
+        impl PartialEq for $Quaternion {
+            fn eq(&self, other: &$Quaternion) -> bool {
+                $Quaternion::exactEquals(self, other)
+            }
         }
 
-        if bw.abs() < EPSILON {
-            bw = 0.;
+        impl ApproxEq for $Quaternion {
+            fn approx_eq(&self, other: &$Quaternion) -> bool {
+                $Quaternion::equals(self, other)
+            }
         }
 
-        out.0 = ax * bw + ay * bz;
-        out.1 = ay * bw - ax * bz;
-        out.2 = az * bw + aw * bz;
-        out.3 = aw * bw - az * bz;
-    }
-
-    pub fn calculateW(out: &mut Quaternion, a: &Quaternion) {
-        let x = a.0;
-        let y = a.1;
-        let z = a.2;
-
-        out.0 = x;
-        out.1 = y;
-        out.2 = z;
-        out.3 = f32::sqrt(f32::abs(1.0 - x * x - y * y - z * z));
-    }
-
-    pub fn slerp(out: &mut Quaternion, a: &Quaternion, b: &Quaternion, t: f32) {
-        // benchmarks:
-        //    http://jsperf.com/quaternion-slerp-implementations
-        let ax = a.0;
-        let ay = a.1;
-        let az = a.2;
-        let aw = a.3;
-        let mut bx = b.0;
-        let mut by = b.1;
-        let mut bz = b.2;
-        let mut bw = b.3;
-
-        let mut omega = 0.;
-        let mut sinom = 0.;
-        let mut scale0 = 0.;
-        let mut scale1 = 0.;
-        // calc cosine
-        let mut cosom = ax * bx + ay * by + az * bz + aw * bw;
-
-        if cosom.abs() < EPSILON {
-            cosom = 0.;
+        impl Default for $Quaternion {
+            fn default() -> $Quaternion {
+                $Quaternion::create()
+            }
         }
 
-        // adjust signs (if necessary)
-        if cosom < 0. {
-            cosom = -cosom;
-            bx = -bx;
-            by = -by;
-            bz = -bz;
-            bw = -bw;
+        impl fmt::Display for $Quaternion {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "quat({}, {}, {}, {})", self.0, self.1, self.2, self.3)
+            }
         }
 
-        // calculate coefficients
-        if (1.0 - cosom) > EPSILON {
-            // standard case (slerp)
-            omega = f32::acos(cosom);
-            sinom = f32::sin(omega);
-            scale0 = f32::sin((1.0 - t) * omega) / sinom;
-            scale1 = f32::sin(t * omega) / sinom;
-        } else {
-            // "from" and "to" quaternions are very close
-            //  ... so we can do a linear interpolation
-            scale0 = 1.0 - t;
-            scale1 = t;
+        impl FromStr for $Quaternion {
+            type Err = ParseError;
+
+            fn from_str(s: &str) -> Result<$Quaternion, ParseError> {
+                let mut v = [0.; 4];
+                parse_values(s, "quat", &mut v)?;
+                Ok($Quaternion(v[0], v[1], v[2], v[3]))
+            }
         }
 
-        // calculate final values
-        out.0 = scale0 * ax + scale1 * bx;
-        out.1 = scale0 * ay + scale1 * by;
-        out.2 = scale0 * az + scale1 * bz;
-        out.3 = scale0 * aw + scale1 * bw;
-    }
-
-    pub fn random(out: &mut Quaternion) {
-        // Implementation of http://planning.cs.uiuc.edu/node198.html
-        // TODO: Calling random 3 times is probably not the fastest solution
-        let u1 = RANDOM();
-        let u2 = RANDOM();
-        let u3 = RANDOM();
-
-        let sqrt1MinusU1 = f32::sqrt(1. - u1);
-        let sqrtU1 = f32::sqrt(u1);
-
-        out.0 = sqrt1MinusU1 * f32::sin(2.0 * PI * u2);
-        out.1 = sqrt1MinusU1 * f32::cos(2.0 * PI * u2);
-        out.2 = sqrtU1 * f32::sin(2.0 * PI * u3);
-        out.3 = sqrtU1 * f32::cos(2.0 * PI * u3);
-    }
-
-    pub fn invert(out: &mut Quaternion, a: &Quaternion) {
-        let a0 = a.0;
-        let a1 = a.1;
-        let a2 = a.2;
-        let a3 = a.3;
-        let dot = a0 * a0 + a1 * a1 + a2 * a2 + a3 * a3;
-        let invDot = if dot < EPSILON { 0. } else { 1. / dot };
-
-        // TODO: Would be faster to return [0,0,0,0] immediately if dot == 0
-
-        out.0 = -a0 * invDot;
-        out.1 = -a1 * invDot;
-        out.2 = -a2 * invDot;
-        out.3 = a3 * invDot;
-    }
-
-    pub fn conjugate(out: &mut Quaternion, a: &Quaternion) {
-        out.0 = -a.0;
-        out.1 = -a.1;
-        out.2 = -a.2;
-        out.3 = a.3;
-    }
-
-    pub fn fromMat3(out: &mut Quaternion, m: &Matrix3) {
-        // Algorithm in Ken Shoemake"s article in 1987 SIGGRAPH course notes
-        // article "Quaternion Calculus and Fast Animation".
-        let fTrace = m.0 + m.4 + m.8;
-
-        if (fTrace > 0.0) {
-            // |w| > 1/2, may as well choose w > 1/2
-            let mut fRoot = f32::sqrt(fTrace + 1.0); // 2w
-            out.3 = 0.5 * fRoot;
-            fRoot = 0.5 / fRoot; // 1/(4w)
-            out.0 = (m.5 - m.7) * fRoot;
-            out.1 = (m.6 - m.2) * fRoot;
-            out.2 = (m.1 - m.3) * fRoot;
-        } else {
-            // |w| <= 1/2
-            let mut i = 0;
-            if (m.4 > m.0) {
-                i = 1;
-            }
-            let tmp = if i == 0 { m.0 } else { m.4 };
-            if (m.8 > tmp) {
-                i = 2;
-            }
-
-            match i {
-                0 => {
-                    // i = 0, j = 1, k = 2
-                    let mut fRoot = f32::sqrt(m.0 - m.4 - m.8 + 1.0);
-                    out.0 = 0.5 * fRoot;
-                    fRoot = 0.5 / fRoot;
-                    out.3 = (m.5 - m.7) * fRoot;
-                    out.1 = (m.3 + m.1) * fRoot;
-                    out.2 = (m.6 + m.2) * fRoot;
+        #[cfg(test)]
+        mod tests {
+            use super::super::matrix4::*;
+            use super::*;
+            use crate::spec_helper::*;
+            use std::$F::consts::{FRAC_1_SQRT_2, PI};
+
+            fn setup() -> ($Quaternion, $Quaternion, $Quaternion) {
+                (
+                    $Quaternion::fromValues(0., 0., 0., 0.),
+                    $Quaternion::fromValues(1., 2., 3., 4.),
+                    $Quaternion::fromValues(5., 6., 7., 8.),
+                )
+            }
+
+            #[test]
+            fn slerp() {
+                let (out, quatA, _) = &mut setup();
+                $Quaternion::slerp(
+                    out,
+                    &$Quaternion::fromValues(0., 0., 0., 1.),
+                    &$Quaternion::fromValues(0., 1., 0., 0.),
+                    0.5,
+                );
+                expect_equalish(&out.elements(), &[0., FRAC_1_SQRT_2, 0., FRAC_1_SQRT_2]);
+
+                $Quaternion::slerp(
+                    out,
+                    &$Quaternion::fromValues(0., 0., 0., 1.),
+                    &$Quaternion::fromValues(0., 0., 0., 1.),
+                    0.5,
+                );
+                expect_equalish(&out.elements(), &[0., 0., 0., 1.]);
+
+                $Quaternion::rotateX(quatA, &$Quaternion::fromValues(1., 0., 0., 0.), PI);
+                $Quaternion::slerp(out, &$Quaternion::fromValues(1., 0., 0., 0.), quatA, 1.);
+                expect_equalish(&out.elements(), &[0., 0., 0., -1.]);
+
+                $Quaternion::slerp(
+                    out,
+                    &$Quaternion::fromValues(1., 0., 0., 0.),
+                    &$Quaternion::fromValues(-1., 0., 0., 0.),
+                    0.5,
+                );
+                expect_equalish(&out.elements(), &[1., 0., 0., 0.]);
+
+                let (out, quatA, quatB) = &mut setup();
+                $Quaternion::slerp(out, quatA, quatB, 0.5);
+                expect_equalish(&out.elements(), &[3., 4., 5., 6.]);
+                expect_equalish(&quatA.elements(), &[1., 2., 3., 4.]);
+                expect_equalish(&quatB.elements(), &[5., 6., 7., 8.]);
+
+                $Quaternion::slerp(quatA, &$Quaternion::clone(quatA), quatB, 0.5);
+                expect_equalish(&quatA.elements(), &[3., 4., 5., 6.]);
+                expect_equalish(&quatB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, quatA, quatB) = &mut setup();
+                $Quaternion::slerp(quatB, quatA, &$Quaternion::clone(quatB), 0.5);
+                expect_equalish(&quatB.elements(), &[3., 4., 5., 6.]);
+                expect_equalish(&quatA.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn rotateX() {
+                let (out, _, _) = &mut setup();
+                let vec = &mut $Vector3::create();
+                $Quaternion::rotateX(out, &$Quaternion::create(), PI / 2.);
+                $Vector3::transformQuat(vec, &$Vector3::fromValues(0., 0., -1.), out);
+                expect_equalish(&vec.elements(), &[0., 1., 0.]);
+            }
+
+            #[test]
+            fn rotateY() {
+                let (out, _, _) = &mut setup();
+                let vec = &mut $Vector3::create();
+                $Quaternion::rotateY(out, &$Quaternion::create(), PI / 2.);
+                $Vector3::transformQuat(vec, &$Vector3::fromValues(0., 0., -1.), out);
+                expect_equalish(&vec.elements(), &[-1., 0., 0.]);
+            }
+
+            #[test]
+            fn rotateZ() {
+                let (out, _, _) = &mut setup();
+                let vec = &mut $Vector3::create();
+                $Quaternion::rotateZ(out, &$Quaternion::create(), PI / 2.);
+                $Vector3::transformQuat(vec, &$Vector3::fromValues(0., 1., 0.), out);
+                expect_equalish(&vec.elements(), &[-1., 0., 0.]);
+            }
+
+            // Normal matrix of a view looking along `center` with `up`, as in the spec.
+            fn normal_matrix(center: &$Vector3, up: &$Vector3) -> $Matrix3 {
+                let matr = &mut $Matrix3::create();
+                let m = &mut $Matrix4::create();
+                $Matrix4::lookAt(m, &$Vector3::fromValues(0., 0., 0.), center, up);
+                $Matrix3::fromMat4(matr, m);
+                $Matrix3::invert(matr, &$Matrix3::clone(matr));
+                $Matrix3::transpose(matr, &$Matrix3::clone(matr));
+                $Matrix3::clone(matr)
+            }
+
+            #[test]
+            fn fromMat3() {
+                let (out, _, _) = &mut setup();
+                let v = &mut $Vector3::create();
+                let matr = &$Matrix3::fromValues(1., 0., 0., 0., 0., -1., 0., 1., 0.);
+                $Quaternion::fromMat3(out, matr);
+                expect_equalish(&out.elements(), &[-FRAC_1_SQRT_2, 0., 0., FRAC_1_SQRT_2]);
+                $Vector3::transformQuat(v, &$Vector3::fromValues(0., 1., 0.), out);
+                expect_equalish(&v.elements(), &[0., 0., -1.]);
+
+                let matr = &normal_matrix(
+                    &$Vector3::fromValues(0., 0., 1.),
+                    &$Vector3::fromValues(0., 1., 0.),
+                );
+                $Quaternion::fromMat3(out, matr);
+                $Quaternion::normalize(out, &$Quaternion::clone(out));
+                $Vector3::transformQuat(v, &$Vector3::fromValues(3., 2., -1.), out);
+                expect_equalish(&v.elements(), &[-3., 2., 1.]);
+
+                let matr = &normal_matrix(
+                    &$Vector3::fromValues(-1., 0., 0.),
+                    &$Vector3::fromValues(0., -1., 0.),
+                );
+                $Quaternion::fromMat3(out, matr);
+                $Quaternion::normalize(out, &$Quaternion::clone(out));
+                $Vector3::transformQuat(v, &$Vector3::fromValues(3., 2., -1.), out);
+                expect_equalish(&v.elements(), &[-1., -2., 3.]);
+
+                let matr = &normal_matrix(
+                    &$Vector3::fromValues(0., 0., -1.),
+                    &$Vector3::fromValues(0., -1., 0.),
+                );
+                $Quaternion::fromMat3(out, matr);
+                $Quaternion::normalize(out, &$Quaternion::clone(out));
+                $Vector3::transformQuat(v, &$Vector3::fromValues(3., 2., -1.), out);
+                let v2 = &mut $Vector3::create();
+                $Vector3::transformMat3(v2, &$Vector3::fromValues(3., 2., -1.), matr);
+                expect_equalish(&v.elements(), &v2.elements());
+            }
+
+            #[test]
+            fn fromEuler() {
+                let (out, _, _) = &mut setup();
+                $Quaternion::fromEuler(out, -90., 0., 0.);
+                expect_equalish(&out.elements(), &[-FRAC_1_SQRT_2, 0., 0., FRAC_1_SQRT_2]);
+
+                let v = &mut $Vector3::create();
+                $Vector3::transformQuat(v, &$Vector3::fromValues(0., 1., 0.), out);
+                expect_equalish(&v.elements(), &[0., 0., -1.]);
+            }
+
+            #[test]
+            fn fromEulerOrder() {
+                let (out, _, _) = &mut setup();
+                let expected = &mut $Quaternion::create();
+                $Quaternion::fromEuler(expected, 10., -20., 30.);
+                $Quaternion::fromEulerOrder(
+                    out,
+                    10.,
+                    -20.,
+                    30.,
+                    EulerOrder::ZYX,
+                    AngleUnit::Degrees,
+                );
+                expect_equalish(&out.elements(), &expected.elements());
+
+                $Quaternion::fromEulerOrder(
+                    out,
+                    PI / 18.,
+                    -PI / 9.,
+                    PI / 6.,
+                    EulerOrder::ExtrinsicXYZ,
+                    AngleUnit::Radians,
+                );
+                expect_equalish(&out.elements(), &expected.elements());
+
+                // Yaw then pitch: the pitch is about the yawed x axis.
+                $Quaternion::fromEulerOrder(out, 45., 90., 0., EulerOrder::YXZ, AngleUnit::Degrees);
+                let v = &mut $Vector3::create();
+                $Vector3::transformQuat(v, &$Vector3::fromValues(0., 0., -1.), out);
+                expect_equalish(&v.elements(), &[-FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.]);
+            }
+
+            #[test]
+            fn toEuler() {
+                let (out, _, _) = &mut setup();
+                let angles = &mut $Vector3::create();
+                $Quaternion::fromEulerOrder(
+                    out,
+                    0.8,
+                    -0.5,
+                    1.1,
+                    EulerOrder::YZX,
+                    AngleUnit::Radians,
+                );
+                $Quaternion::toEuler(angles, out, EulerOrder::YZX, AngleUnit::Radians);
+                expect_equalish(&angles.elements(), &[0.8, -0.5, 1.1]);
+
+                // The scale of the quaternion is ignored.
+                $Quaternion::scale(out, &$Quaternion::clone(out), 2.);
+                $Quaternion::toEuler(angles, out, EulerOrder::YZX, AngleUnit::Radians);
+                expect_equalish(&angles.elements(), &[0.8, -0.5, 1.1]);
+
+                $Quaternion::toEuler(angles, out, EulerOrder::XYZ, AngleUnit::Radians);
+                let q = &mut $Quaternion::create();
+                $Quaternion::fromEulerOrder(
+                    q,
+                    angles.0,
+                    angles.1,
+                    angles.2,
+                    EulerOrder::XYZ,
+                    AngleUnit::Radians,
+                );
+                $Quaternion::normalize(out, &$Quaternion::clone(out));
+                expect_equalish(&q.elements(), &out.elements());
+            }
+
+            #[test]
+            fn expLn() {
+                let (out, quatA, _) = &mut setup();
+                $Quaternion::ln(out, quatA);
+                $Quaternion::exp(out, &$Quaternion::clone(out));
+                expect_equalish(&out.elements(), &quatA.elements());
+
+                $Quaternion::setAxisAngle(quatA, &$Vector3::fromValues(0., 0., 1.), 1.2);
+                $Quaternion::ln(out, quatA);
+                expect_equalish(&out.elements(), &[0., 0., 0.6, 0.]);
+
+                $Quaternion::identity(quatA);
+                $Quaternion::ln(out, quatA);
+                expect_equalish(&out.elements(), &[0., 0., 0., 0.]);
+                $Quaternion::exp(out, &$Quaternion::clone(out));
+                expect_equalish(&out.elements(), &[0., 0., 0., 1.]);
+            }
+
+            #[test]
+            fn pow() {
+                let (out, quatA, _) = &mut setup();
+                $Quaternion::setAxisAngle(quatA, &$Vector3::fromValues(1., 0., 0.), 1.2);
+                $Quaternion::pow(out, quatA, 0.5);
+                let expected = &mut $Quaternion::create();
+                $Quaternion::setAxisAngle(expected, &$Vector3::fromValues(1., 0., 0.), 0.6);
+                expect_equalish(&out.elements(), &expected.elements());
+
+                $Quaternion::pow(out, quatA, 0.);
+                expect_equalish(&out.elements(), &[0., 0., 0., 1.]);
+            }
+
+            #[test]
+            fn squad() {
+                let (out, _, _) = &mut setup();
+                let axis = &$Vector3::fromValues(0., 1., 0.);
+                let keys = &mut [
+                    $Quaternion::create(),
+                    $Quaternion::create(),
+                    $Quaternion::create(),
+                    $Quaternion::create(),
+                ];
+                for (i, key) in keys.iter_mut().enumerate() {
+                    $Quaternion::setAxisAngle(key, axis, i as $F * 0.5);
+                }
+
+                // Evenly spaced keys about one axis keep the control points on the keys.
+                $Quaternion::squadControlPoint(out, &keys[0], &keys[1], &keys[2]);
+                expect_equalish(&out.elements(), &keys[1].elements());
+
+                let expected = &mut $Quaternion::create();
+                $Quaternion::squad(out, &keys[0], &keys[1], &keys[2], &keys[3], 0.3);
+                $Quaternion::slerp(expected, &keys[1], &keys[2], 0.3);
+                expect_equalish(&out.elements(), &expected.elements());
+
+                // Neighbours on the other hemisphere are the same rotations.
+                let flipped = $Quaternion::clone(&keys[3]);
+                $Quaternion::scale(&mut keys[3], &flipped, -1.);
+                $Quaternion::squad(out, &keys[0], &keys[1], &keys[2], &keys[3], 0.3);
+                expect_equalish(&out.elements(), &expected.elements());
+            }
+
+            #[test]
+            fn spline() {
+                let (out, q, _) = &mut setup();
+                let keys = &mut [0.; 16];
+                $Quaternion::setAxisAngle(q, &$Vector3::fromValues(0., 1., 0.), 0.5);
+                q.writeSlice(&mut keys[4..]);
+                $Quaternion::setAxisAngle(q, &$Vector3::fromValues(1., 0., 0.), 1.);
+                q.writeSlice(&mut keys[8..]);
+                $Quaternion::setAxisAngle(q, &$Vector3::fromValues(0., 0., 1.), -0.8);
+                q.writeSlice(&mut keys[12..]);
+                keys[3] = 1.;
+
+                for i in 0..4 {
+                    $Quaternion::spline(out, keys, i as $F);
+                    expect_equalish(&out.elements(), &keys[i * 4..i * 4 + 4]);
                 }
-                1 => {
-                    // i = 1, j = 2, k = 0
-                    let mut fRoot = f32::sqrt(m.4 - m.8 - m.0 + 1.0);
-                    out.1 = 0.5 * fRoot;
-                    fRoot = 0.5 / fRoot;
-                    out.3 = (m.6 - m.2) * fRoot;
-                    out.2 = (m.7 + m.5) * fRoot;
-                    out.0 = (m.1 + m.3) * fRoot;
+                $Quaternion::spline(out, keys, 5.);
+                expect_equalish(&out.elements(), &keys[12..]);
+
+                // The rotation has the same velocity on both sides of an inner key.
+                let h = 0.001;
+                let before = &mut $Quaternion::create();
+                let after = &mut $Quaternion::create();
+                for &t in [1., 2.].iter() {
+                    $Quaternion::spline(before, keys, t - h);
+                    $Quaternion::spline(q, keys, t);
+                    $Quaternion::spline(after, keys, t + h);
+                    for i in 0..4 {
+                        let left = q.asSlice()[i] - before.asSlice()[i];
+                        let right = after.asSlice()[i] - q.asSlice()[i];
+                        assert!($F::abs(left - right) < 1e-5, "{} {}", left, right);
+                    }
                 }
-                2 => {
-                    // i = 2, j = 0, k = 1
-                    let mut fRoot = f32::sqrt(m.8 - m.0 - m.4 + 1.0);
-                    out.2 = 0.5 * fRoot;
-                    fRoot = 0.5 / fRoot;
-                    out.3 = (m.1 - m.3) * fRoot;
-                    out.0 = (m.2 + m.6) * fRoot;
-                    out.1 = (m.5 + m.7) * fRoot;
+
+                $Quaternion::spline(out, &keys[4..8], 0.7);
+                expect_equalish(&out.elements(), &keys[4..8]);
+
+                // Keys a half turn apart don't make the rotation jump inside a segment.
+                let axisY = &$Vector3::fromValues(0., 1., 0.);
+                $Quaternion::identity(q);
+                q.writeSlice(&mut keys[..]);
+                $Quaternion::setAxisAngle(q, axisY, PI / 2.);
+                q.writeSlice(&mut keys[4..]);
+                $Quaternion::setAxisAngle(q, axisY, PI);
+                q.writeSlice(&mut keys[8..]);
+                $Quaternion::setAxisAngle(q, &$Vector3::fromValues(1., 0., 0.), PI / 2.);
+                q.writeSlice(&mut keys[12..]);
+                let previous = &mut $Quaternion::create();
+                let steps = 3000;
+                for i in 0..=steps {
+                    $Quaternion::copy(previous, out);
+                    $Quaternion::spline(out, keys, 3. * i as $F / steps as $F);
+                    if i > 0 {
+                        let cos = $F::abs($Quaternion::dot(previous, out)).min(1.);
+                        let angle = 2. * $F::acos(cos);
+                        assert!(angle < 0.01, "{} at {}", angle, i);
+                    }
                 }
-                _ => {}
+            }
+
+            #[test]
+            fn swingTwist() {
+                let (out, q, _) = &mut setup();
+                let axis = &$Vector3::fromValues(0., 1., 0.);
+                let swing = &mut $Quaternion::create();
+                let twist = &mut $Quaternion::create();
+                $Quaternion::setAxisAngle(twist, axis, 0.7);
+                $Quaternion::setAxisAngle(swing, &$Vector3::fromValues(0.6, 0., 0.8), 0.4);
+                $Quaternion::multiply(q, swing, twist);
+
+                let outSwing = &mut $Quaternion::create();
+                let outTwist = &mut $Quaternion::create();
+                $Quaternion::swingTwist(q, &$Vector3::fromValues(0., 2., 0.), outSwing, outTwist);
+                expect_equalish(&outSwing.elements(), &swing.elements());
+                expect_equalish(&outTwist.elements(), &twist.elements());
+                $Quaternion::multiply(out, outSwing, outTwist);
+                expect_equalish(&out.elements(), &q.elements());
+
+                // Half a turn away from the axis leaves no twist.
+                $Quaternion::setAxisAngle(q, &$Vector3::fromValues(1., 0., 0.), PI);
+                $Quaternion::swingTwist(q, axis, outSwing, outTwist);
+                expect_equalish(&outTwist.elements(), &[0., 0., 0., 1.]);
+                expect_equalish(&outSwing.elements(), &q.elements());
+            }
+
+            #[test]
+            fn clampTwist() {
+                let (out, q, _) = &mut setup();
+                let axis = &$Vector3::fromValues(0., 0., 1.);
+                let swing = &mut $Quaternion::create();
+                let twist = &mut $Quaternion::create();
+                $Quaternion::setAxisAngle(swing, &$Vector3::fromValues(1., 0., 0.), 0.3);
+
+                $Quaternion::setAxisAngle(twist, axis, 1.2);
+                $Quaternion::multiply(q, swing, twist);
+                $Quaternion::clampTwist(out, q, axis, -0.5, 0.5);
+                let expected = &mut $Quaternion::create();
+                $Quaternion::setAxisAngle(twist, axis, 0.5);
+                $Quaternion::multiply(expected, swing, twist);
+                expect_equalish(&out.elements(), &expected.elements());
+
+                $Quaternion::setAxisAngle(twist, axis, -2.5);
+                $Quaternion::multiply(q, swing, twist);
+                $Quaternion::clampTwist(out, q, axis, -0.5, 0.5);
+                $Quaternion::setAxisAngle(twist, axis, -0.5);
+                $Quaternion::multiply(expected, swing, twist);
+                expect_equalish(&out.elements(), &expected.elements());
+
+                $Quaternion::clampTwist(out, q, axis, -3., 3.);
+                expect_equalish(&out.elements(), &q.elements());
+            }
+
+            #[test]
+            fn clampSwing() {
+                let (out, q, _) = &mut setup();
+                let axis = &$Vector3::fromValues(0., 1., 0.);
+                let limitAxis = &$Vector3::fromValues(1., 0.5, 0.);
+                let swing = &mut $Quaternion::create();
+                let twist = &mut $Quaternion::create();
+                let expected = &mut $Quaternion::create();
+                $Quaternion::setAxisAngle(twist, axis, 0.4);
+
+                // About the limit axis the swing stops at `limitAngle`, about the other one at `limitAnglePerp`.
+                $Quaternion::setAxisAngle(swing, &$Vector3::fromValues(1., 0., 0.), 1.);
+                $Quaternion::multiply(q, swing, twist);
+                $Quaternion::clampSwing(out, q, axis, limitAxis, 0.5, 0.2);
+                $Quaternion::setAxisAngle(swing, &$Vector3::fromValues(1., 0., 0.), 0.5);
+                $Quaternion::multiply(expected, swing, twist);
+                expect_equalish(&out.elements(), &expected.elements());
+
+                $Quaternion::setAxisAngle(swing, &$Vector3::fromValues(0., 0., -1.), 1.);
+                $Quaternion::multiply(q, swing, twist);
+                $Quaternion::clampSwing(out, q, axis, limitAxis, 0.5, 0.2);
+                $Quaternion::setAxisAngle(swing, &$Vector3::fromValues(0., 0., -1.), 0.2);
+                $Quaternion::multiply(expected, swing, twist);
+                expect_equalish(&out.elements(), &expected.elements());
+
+                // Inside the cone nothing changes.
+                $Quaternion::setAxisAngle(swing, &$Vector3::fromValues(0.6, 0., 0.8), 0.15);
+                $Quaternion::multiply(q, swing, twist);
+                $Quaternion::clampSwing(out, q, axis, limitAxis, 0.5, 0.2);
+                expect_equalish(&out.elements(), &q.elements());
+
+                // A diagonal swing lands on the edge of the ellipse.
+                $Quaternion::setAxisAngle(
+                    swing,
+                    &$Vector3::fromValues(FRAC_1_SQRT_2, 0., FRAC_1_SQRT_2),
+                    1.,
+                );
+                $Quaternion::multiply(q, swing, twist);
+                $Quaternion::clampSwing(out, q, axis, limitAxis, 0.5, 0.2);
+                let outSwing = &mut $Quaternion::create();
+                $Quaternion::swingTwist(out, axis, outSwing, twist);
+                let swingAxis = &mut $Vector3::create();
+                let rad = $Quaternion::getAxisAngle(swingAxis, outSwing);
+                let x = rad * swingAxis.0 / 0.5;
+                let z = rad * swingAxis.2 / 0.2;
+                expect_equalish_scalar(x * x + z * z, 1.);
+                expect_equalish_scalar(swingAxis.0, swingAxis.2);
+
+                // A zero limit locks that axis, the other one is still clamped.
+                $Quaternion::setAxisAngle(
+                    swing,
+                    &$Vector3::fromValues(FRAC_1_SQRT_2, 0., FRAC_1_SQRT_2),
+                    1.,
+                );
+                $Quaternion::multiply(q, swing, twist);
+                $Quaternion::clampSwing(out, q, axis, limitAxis, 0., 0.2);
+                $Quaternion::setAxisAngle(twist, axis, 0.4);
+                $Quaternion::setAxisAngle(swing, &$Vector3::fromValues(0., 0., 1.), 0.2);
+                $Quaternion::multiply(expected, swing, twist);
+                expect_equalish(&out.elements(), &expected.elements());
+
+                $Quaternion::clampSwing(out, q, axis, limitAxis, 0., 0.);
+                expect_equalish(&out.elements(), &twist.elements());
+            }
+
+            #[test]
+            fn setAxes() {
+                let (out, _, _) = &mut setup();
+                let r = &mut $Vector3::create();
+                let view = &$Vector3::fromValues(-1., 0., 0.);
+                let up = &$Vector3::fromValues(0., 1., 0.);
+                let right = &$Vector3::fromValues(0., 0., -1.);
+                $Quaternion::setAxes(out, view, right, up);
+                $Vector3::transformQuat(r, &$Vector3::fromValues(0., 0., -1.), out);
+                expect_equalish(&r.elements(), &[1., 0., 0.]);
+                $Vector3::transformQuat(r, &$Vector3::fromValues(1., 0., 0.), out);
+                expect_equalish(&r.elements(), &[0., 0., 1.]);
+
+                let view = &$Vector3::fromValues(0., 0., -1.);
+                let up = &$Vector3::fromValues(0., 1., 0.);
+                let right = &$Vector3::fromValues(1., 0., 0.);
+                $Quaternion::setAxes(out, view, right, up);
+                expect_equalish(&out.elements(), &[0., 0., 0., 1.]);
+            }
+
+            #[test]
+            fn rotationTo() {
+                let (out, _, _) = &mut setup();
+                let r = &mut $Vector3::create();
+                $Quaternion::rotationTo(
+                    out,
+                    &$Vector3::fromValues(0., 1., 0.),
+                    &$Vector3::fromValues(1., 0., 0.),
+                );
+                expect_equalish(&out.elements(), &[0., 0., -FRAC_1_SQRT_2, FRAC_1_SQRT_2]);
+
+                for (a, b) in [
+                    ([0., 1., 0.], [0., 1., 0.]),
+                    ([1., 0., 0.], [-1., 0., 0.]),
+                    ([0., 1., 0.], [0., -1., 0.]),
+                    ([0., 0., 1.], [0., 0., -1.]),
+                ]
+                .iter()
+                {
+                    let a = &$Vector3::fromValues(a[0], a[1], a[2]);
+                    $Quaternion::rotationTo(out, a, &$Vector3::fromValues(b[0], b[1], b[2]));
+                    $Vector3::transformQuat(r, a, out);
+                    expect_equalish(&r.elements(), b);
+                }
+            }
+
+            #[test]
+            fn create() {
+                let result = $Quaternion::create();
+                expect_equalish(&result.elements(), &[0., 0., 0., 1.]);
+            }
+
+            #[test]
+            fn clone() {
+                let (_, quatA, _) = &mut setup();
+                let result = $Quaternion::clone(quatA);
+                expect_equalish(&result.elements(), &quatA.elements());
+            }
+
+            #[test]
+            fn fromValues() {
+                let result = $Quaternion::fromValues(1., 2., 3., 4.);
+                expect_equalish(&result.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn copy() {
+                let (out, quatA, _) = &mut setup();
+                $Quaternion::copy(out, quatA);
+                expect_equalish(&out.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn set() {
+                let (out, _, _) = &mut setup();
+                $Quaternion::set(out, 1., 2., 3., 4.);
+                expect_equalish(&out.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn identity() {
+                let (out, _, _) = &mut setup();
+                $Quaternion::identity(out);
+                expect_equalish(&out.elements(), &[0., 0., 0., 1.]);
+            }
+
+            #[test]
+            fn setAxisAngle() {
+                let (out, _, _) = &mut setup();
+                $Quaternion::setAxisAngle(out, &$Vector3::fromValues(1., 0., 0.), PI * 0.5);
+                expect_equalish(&out.elements(), &[FRAC_1_SQRT_2, 0., 0., FRAC_1_SQRT_2]);
+            }
+
+            #[test]
+            fn getAxisAngle() {
+                let (out, quatA, quatB) = &mut setup();
+                let vec = &mut $Vector3::create();
+                $Quaternion::setAxisAngle(out, &$Vector3::fromValues(0., 1., 0.), 0.);
+                let deg90 = $Quaternion::getAxisAngle(vec, out);
+                expect_equalish_scalar(deg90 % (PI * 2.), 0.);
+
+                $Quaternion::setAxisAngle(out, &$Vector3::fromValues(1., 0., 0.), 0.7778);
+                let deg90 = $Quaternion::getAxisAngle(vec, out);
+                expect_equalish_scalar(deg90, 0.7778);
+                expect_equalish(&vec.elements(), &[1., 0., 0.]);
+
+                $Quaternion::setAxisAngle(out, &$Vector3::fromValues(0., 1., 0.), 0.879546);
+                let deg90 = $Quaternion::getAxisAngle(vec, out);
+                expect_equalish_scalar(deg90, 0.879546);
+                expect_equalish(&vec.elements(), &[0., 1., 0.]);
+
+                $Quaternion::setAxisAngle(out, &$Vector3::fromValues(0., 0., 1.), 0.123456);
+                let deg90 = $Quaternion::getAxisAngle(vec, out);
+                expect_equalish_scalar(deg90, 0.123456);
+                expect_equalish(&vec.elements(), &[0., 0., 1.]);
+
+                $Quaternion::setAxisAngle(
+                    out,
+                    &$Vector3::fromValues(FRAC_1_SQRT_2, 0., FRAC_1_SQRT_2),
+                    PI * 0.5,
+                );
+                let deg90 = $Quaternion::getAxisAngle(vec, out);
+                expect_equalish(&vec.elements(), &[FRAC_1_SQRT_2, 0., FRAC_1_SQRT_2]);
+                expect_equalish_scalar(deg90, PI * 0.5);
+
+                $Quaternion::setAxisAngle(
+                    quatA,
+                    &$Vector3::fromValues(0.65538555, 0.49153915, 0.57346237),
+                    8.8888,
+                );
+                let deg90 = $Quaternion::getAxisAngle(vec, quatA);
+                $Quaternion::setAxisAngle(quatB, vec, deg90);
+                assert!(deg90 > 0.);
+                assert!(deg90 < PI * 2.);
+                expect_equalish(&quatA.elements(), &quatB.elements());
+            }
+
+            #[test]
+            fn add() {
+                let (out, quatA, quatB) = &mut setup();
+                $Quaternion::add(out, quatA, quatB);
+                expect_equalish(&out.elements(), &[6., 8., 10., 12.]);
+                expect_equalish(&quatA.elements(), &[1., 2., 3., 4.]);
+                expect_equalish(&quatB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, quatA, quatB) = &mut setup();
+                $Quaternion::add(quatA, &$Quaternion::clone(quatA), quatB);
+                expect_equalish(&quatA.elements(), &[6., 8., 10., 12.]);
+                expect_equalish(&quatB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, quatA, quatB) = &mut setup();
+                $Quaternion::add(quatB, quatA, &$Quaternion::clone(quatB));
+                expect_equalish(&quatB.elements(), &[6., 8., 10., 12.]);
+                expect_equalish(&quatA.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn multiply() {
+                let (out, quatA, quatB) = &mut setup();
+                $Quaternion::multiply(out, quatA, quatB);
+                expect_equalish(&out.elements(), &[24., 48., 48., -6.]);
+                expect_equalish(&quatA.elements(), &[1., 2., 3., 4.]);
+                expect_equalish(&quatB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, quatA, quatB) = &mut setup();
+                $Quaternion::multiply(quatA, &$Quaternion::clone(quatA), quatB);
+                expect_equalish(&quatA.elements(), &[24., 48., 48., -6.]);
+                expect_equalish(&quatB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, quatA, quatB) = &mut setup();
+                $Quaternion::multiply(quatB, quatA, &$Quaternion::clone(quatB));
+                expect_equalish(&quatB.elements(), &[24., 48., 48., -6.]);
+                expect_equalish(&quatA.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn scale() {
+                let (out, quatA, _) = &mut setup();
+                $Quaternion::scale(out, quatA, 2.);
+                expect_equalish(&out.elements(), &[2., 4., 6., 8.]);
+                expect_equalish(&quatA.elements(), &[1., 2., 3., 4.]);
+
+                $Quaternion::scale(quatA, &$Quaternion::clone(quatA), 2.);
+                expect_equalish(&quatA.elements(), &[2., 4., 6., 8.]);
+            }
+
+            #[test]
+            fn len() {
+                let (_, quatA, _) = &mut setup();
+                expect_equalish_scalar($Quaternion::len(quatA), 5.477225);
+            }
+
+            #[test]
+            fn squaredLength() {
+                let (_, quatA, _) = &mut setup();
+                assert_eq!($Quaternion::squaredLength(quatA), 30.);
+            }
+
+            #[test]
+            fn normalize() {
+                let out = &mut $Quaternion::create();
+                let quatA = &mut $Quaternion::fromValues(5., 0., 0., 0.);
+                $Quaternion::normalize(out, quatA);
+                expect_equalish(&out.elements(), &[1., 0., 0., 0.]);
+                expect_equalish(&quatA.elements(), &[5., 0., 0., 0.]);
+
+                $Quaternion::normalize(quatA, &$Quaternion::clone(quatA));
+                expect_equalish(&quatA.elements(), &[1., 0., 0., 0.]);
+            }
+
+            #[test]
+            fn lerp() {
+                let (out, quatA, quatB) = &mut setup();
+                $Quaternion::lerp(out, quatA, quatB, 0.5);
+                expect_equalish(&out.elements(), &[3., 4., 5., 6.]);
+                expect_equalish(&quatA.elements(), &[1., 2., 3., 4.]);
+                expect_equalish(&quatB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, quatA, quatB) = &mut setup();
+                $Quaternion::lerp(quatA, &$Quaternion::clone(quatA), quatB, 0.5);
+                expect_equalish(&quatA.elements(), &[3., 4., 5., 6.]);
+                expect_equalish(&quatB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, quatA, quatB) = &mut setup();
+                $Quaternion::lerp(quatB, quatA, &$Quaternion::clone(quatB), 0.5);
+                expect_equalish(&quatB.elements(), &[3., 4., 5., 6.]);
+                expect_equalish(&quatA.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn random() {
+                let _lock = lock_random();
+                let (out, _, _) = &mut setup();
+                $Quaternion::random(out);
+                let copy = $Quaternion::clone(out);
+                $Quaternion::normalize(out, &$Quaternion::clone(out));
+                expect_equalish(&out.elements(), &copy.elements());
+            }
+
+            #[test]
+            fn invert() {
+                let (out, quatA, _) = &mut setup();
+                $Quaternion::invert(out, quatA);
+                expect_equalish(&out.elements(), &[-0.033333, -0.066666, -0.1, 0.133333]);
+                expect_equalish(&quatA.elements(), &[1., 2., 3., 4.]);
+
+                $Quaternion::invert(quatA, &$Quaternion::clone(quatA));
+                expect_equalish(&quatA.elements(), &[-0.033333, -0.066666, -0.1, 0.133333]);
+            }
+
+            #[test]
+            fn conjugate() {
+                let (out, quatA, _) = &mut setup();
+                $Quaternion::conjugate(out, quatA);
+                expect_equalish(&out.elements(), &[-1., -2., -3., 4.]);
+                expect_equalish(&quatA.elements(), &[1., 2., 3., 4.]);
+
+                $Quaternion::conjugate(quatA, &$Quaternion::clone(quatA));
+                expect_equalish(&quatA.elements(), &[-1., -2., -3., 4.]);
+            }
+
+            #[test]
+            fn exactEquals() {
+                let quatA = &$Quaternion::fromValues(0., 1., 2., 3.);
+                let quatB = &$Quaternion::fromValues(0., 1., 2., 3.);
+                let quatC = &$Quaternion::fromValues(1., 2., 3., 4.);
+                assert!($Quaternion::exactEquals(quatA, quatB));
+                assert!(!$Quaternion::exactEquals(quatA, quatC));
+                expect_equalish(&quatA.elements(), &[0., 1., 2., 3.]);
+                expect_equalish(&quatB.elements(), &[0., 1., 2., 3.]);
+            }
+
+            #[test]
+            fn equals() {
+                let quatA = &$Quaternion::fromValues(0., 1., 2., 3.);
+                let quatB = &$Quaternion::fromValues(0., 1., 2., 3.);
+                let quatC = &$Quaternion::fromValues(1., 2., 3., 4.);
+                let quatD = &$Quaternion::fromValues(1e-16, 1., 2., 3.);
+                assert!($Quaternion::equals(quatA, quatB));
+                assert!(!$Quaternion::equals(quatA, quatC));
+                assert!($Quaternion::equals(quatA, quatD));
+                expect_equalish(&quatA.elements(), &[0., 1., 2., 3.]);
+                expect_equalish(&quatB.elements(), &[0., 1., 2., 3.]);
+            }
+
+            #[test]
+            fn traits() {
+                let a = $Quaternion::fromValues(1., 2., 3., 4.);
+                let b = a;
+                assert_eq!(a, b);
+                assert_ne!(a, $Quaternion::fromValues(1., 2., 3., 5.));
+                assert_ne!(a, $Quaternion::fromValues(1., 2., 3., 4.00001));
+                assert!(a.approx_eq(&$Quaternion::fromValues(1., 2., 3., 4.00001)));
+                assert!(!a.approx_eq(&$Quaternion::fromValues(1., 2., 3., 5.)));
+                assert_eq!($Quaternion::default(), $Quaternion::create());
+                assert_eq!(
+                    format!("{:?}", $Quaternion::create()),
+                    concat!(stringify!($Quaternion), "(0.0, 0.0, 0.0, 1.0)")
+                );
+            }
+
+            #[test]
+            fn str() {
+                let a = $Quaternion::fromValues(1., 2., 3., 4.);
+                assert_eq!($Quaternion::str(&a), "quat(1, 2, 3, 4)");
+                assert_eq!("quat(1, 2, 3, 4)".parse::<$Quaternion>(), Ok(a));
+            }
+
+            #[test]
+            fn equalsWith() {
+                let a = &$Quaternion::fromValues(1., 2., 3., 4.);
+                let b = &$Quaternion::fromValues(1.001, 2., 3., 4.);
+                let c = &$Quaternion::fromValues($F::from_bits($F::to_bits(1.) + 2), 2., 3., 4.);
+                assert!(!$Quaternion::equalsWithEpsilon(a, b, 0.0001));
+                assert!($Quaternion::equalsWithEpsilon(a, b, 0.01));
+                assert!($Quaternion::equalsWithUlps(a, c, 2));
+                assert!(!$Quaternion::equalsWithUlps(a, c, 1));
+                assert!(!$Quaternion::equalsWithUlps(a, b, 4));
+            }
+
+            #[test]
+            fn setSeed() {
+                let _lock = lock_random();
+                let (out, _, _) = &mut setup();
+                crate::common::setSeed(42);
+                $Quaternion::random(out);
+                let first = $Quaternion::clone(out);
+                $Quaternion::random(out);
+                assert_ne!(out, &first);
+
+                crate::common::setSeed(42);
+                $Quaternion::random(out);
+                assert_eq!(out, &first);
+            }
+
+            #[test]
+            fn slerpArray() {
+                let a = [0., 0., 0., 1., 0., 0., 0., 1.];
+                let b = [0., 1., 0., 0., 0., FRAC_1_SQRT_2, 0., FRAC_1_SQRT_2];
+                let out = &mut [0.; 8];
+                $Quaternion::slerpArray(out, &a, &b, 0.5, 2);
+
+                let expected = &mut $Quaternion::create();
+                $Quaternion::slerp(
+                    expected,
+                    &$Quaternion::fromValues(0., 0., 0., 1.),
+                    &$Quaternion::fromValues(0., 1., 0., 0.),
+                    0.5,
+                );
+                expect_equalish(&out[..4], &expected.elements());
+                $Quaternion::slerp(
+                    expected,
+                    &$Quaternion::fromValues(0., 0., 0., 1.),
+                    &$Quaternion::fromValues(0., FRAC_1_SQRT_2, 0., FRAC_1_SQRT_2),
+                    0.5,
+                );
+                expect_equalish(&out[4..], &expected.elements());
+            }
+
+            #[test]
+            fn slice() {
+                let a = $Quaternion::fromValues(1., 2., 3., 4.);
+                assert_eq!(a.asSlice(), &a.elements()[..]);
+
+                let buffer = &mut [0.; 5];
+                a.writeSlice(&mut buffer[1..]);
+                assert_eq!($Quaternion::fromSlice(&buffer[1..]), a);
             }
         }
-    }
-
-    pub fn fromEuler(out: &mut Quaternion, x: f32, y: f32, z: f32) {
-        let halfToRad = 0.5 * PI / 180.0;
-        let x = x * halfToRad;
-        let y = y * halfToRad;
-        let z = z * halfToRad;
-
-        let sx = f32::sin(x);
-        let cx = f32::cos(x);
-        let sy = f32::sin(y);
-        let cy = f32::cos(y);
-        let sz = f32::sin(z);
-        let cz = f32::cos(z);
-
-        out.0 = sx * cy * cz - cx * sy * sz;
-        out.1 = cx * sy * cz + sx * cy * sz;
-        out.2 = cx * cy * sz - sx * sy * cz;
-        out.3 = cx * cy * cz + sx * sy * sz;
-    }
-
-    // pub fn str(a: &Quaternion) {
-    //   return "quat(" + a.0 + ", " + a.1 + ", " + a.2 + ", " + a.3 + ")";
-    // }
-
-    pub fn clone(a: &Quaternion) -> Quaternion {
-        Quaternion(a.0, a.1, a.2, a.3)
-    }
-
-    pub fn fromValues(x: f32, y: f32, z: f32, w: f32) -> Quaternion {
-        Quaternion(x, y, z, w)
-    }
-
-    pub fn copy(out: &mut Quaternion, a: &Quaternion) {
-        out.0 = a.0;
-        out.1 = a.1;
-        out.2 = a.2;
-        out.3 = a.3;
-    }
-
-    pub fn set(out: &mut Quaternion, x: f32, y: f32, z: f32, w: f32) {
-        out.0 = x;
-        out.1 = y;
-        out.2 = z;
-        out.3 = w;
-    }
-
-    pub fn add(out: &mut Quaternion, a: &Quaternion, b: &Quaternion) {
-        out.0 = a.0 + b.0;
-        out.1 = a.1 + b.1;
-        out.2 = a.2 + b.2;
-        out.3 = a.3 + b.3;
-    }
-
-    pub fn mul(out: &mut Quaternion, a: &Quaternion, b: &Quaternion) {
-        Quaternion::multiply(out, a, b);
-    }
-
-    pub fn scale(out: &mut Quaternion, a: &Quaternion, b: f32) {
-        out.0 = a.0 * b;
-        out.1 = a.1 * b;
-        out.2 = a.2 * b;
-        out.3 = a.3 * b;
-    }
-
-    pub fn dot(a: &Quaternion, b: &Quaternion) -> f32 {
-        a.0 * b.0 + a.1 * b.1 + a.2 * b.2 + a.3 * b.3
-    }
-
-    pub fn lerp(out: &mut Quaternion, a: &Quaternion, b: &Quaternion, t: f32) {
-        let ax = a.0;
-        let ay = a.1;
-        let az = a.2;
-        let aw = a.3;
-        out.0 = ax + t * (b.0 - ax);
-        out.1 = ay + t * (b.1 - ay);
-        out.2 = az + t * (b.2 - az);
-        out.3 = aw + t * (b.3 - aw);
-    }
-
-    pub fn len(a: &Quaternion) -> f32 {
-        let x = a.0;
-        let y = a.1;
-        let z = a.2;
-        let w = a.3;
-        (x.powi(2) + y.powi(2) + z.powi(2) + w.powi(2)).sqrt()
-    }
-
-    pub fn squaredLength(a: &Quaternion) -> f32 {
-        let x = a.0;
-        let y = a.1;
-        let z = a.2;
-        let w = a.3;
-        x * x + y * y + z * z + w * w
-    }
-
-    pub fn sqrLen(a: &Quaternion) -> f32 {
-        Quaternion::squaredLength(a)
-    }
-
-    pub fn normalize(out: &mut Quaternion, a: &Quaternion) {
-        let x = a.0;
-        let y = a.1;
-        let z = a.2;
-        let w = a.3;
-        let mut len = x * x + y * y + z * z + w * w;
-        if (len > EPSILON) {
-            len = 1. / f32::sqrt(len);
-        }
-        out.0 = x * len;
-        out.1 = y * len;
-        out.2 = z * len;
-        out.3 = w * len;
-    }
-
-    pub fn exactEquals(a: &Quaternion, b: &Quaternion) -> bool {
-        a.0 == b.0 && a.1 == b.1 && a.2 == b.2 && a.3 == b.3
-    }
-
-    pub fn equals(a: &Quaternion, b: &Quaternion) -> bool {
-        let a0 = a.0;
-        let a1 = a.1;
-        let a2 = a.2;
-        let a3 = a.3;
-        let b0 = b.0;
-        let b1 = b.1;
-        let b2 = b.2;
-        let b3 = b.3;
-        f32::abs(a0 - b0) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a0), f32::abs(b0)))
-            && f32::abs(a1 - b1) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a1), f32::abs(b1)))
-            && f32::abs(a2 - b2) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a2), f32::abs(b2)))
-            && f32::abs(a3 - b3) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a3), f32::abs(b3)))
-    }
-
-    pub fn rotationTo(out: &mut Quaternion, a: &Vector3, b: &Vector3) {
-        let tmpvec3 = &mut Vector3::create();
-        let xUnitVec3 = &Vector3::fromValues(1., 0., 0.);
-        let yUnitVec3 = &Vector3::fromValues(0., 1., 0.);
-
-        let dot = Vector3::dot(a, b);
-        if (dot < -0.999999) {
-            Vector3::cross(tmpvec3, xUnitVec3, a);
-            if (Vector3::len(tmpvec3) < EPSILON) {
-                Vector3::cross(tmpvec3, yUnitVec3, a);
-            }
-            Vector3::normalize(tmpvec3, &Vector3::clone(tmpvec3));
-            Quaternion::setAxisAngle(out, tmpvec3, PI);
-        } else if (dot > 0.999999) {
-            out.0 = 0.;
-            out.1 = 0.;
-            out.2 = 0.;
-            out.3 = 1.;
-        } else {
-            Vector3::cross(tmpvec3, a, b);
-            out.0 = tmpvec3.0;
-            out.1 = tmpvec3.1;
-            out.2 = tmpvec3.2;
-            out.3 = 1. + dot;
-            Quaternion::normalize(out, &Quaternion::clone(out));
-        }
-    }
-
-    pub fn sqlerp(
-        out: &mut Quaternion,
-        a: &Quaternion,
-        b: &Quaternion,
-        c: &Quaternion,
-        d: &Quaternion,
-        t: f32,
-    ) {
-        let temp1 = &mut Quaternion::create();
-        let temp2 = &mut Quaternion::create();
-
-        Quaternion::slerp(temp1, a, d, t);
-        Quaternion::slerp(temp2, b, c, t);
-        Quaternion::slerp(out, temp1, temp2, 2. * t * (1. - t));
-    }
-
-    pub fn setAxes(out: &mut Quaternion, view: &Vector3, right: &Vector3, up: &Vector3) {
-        let matr = &mut Matrix3::create();
-
-        matr.0 = right.0;
-        matr.3 = right.1;
-        matr.6 = right.2;
-
-        matr.1 = up.0;
-        matr.4 = up.1;
-        matr.7 = up.2;
-
-        matr.2 = -view.0;
-        matr.5 = -view.1;
-        matr.8 = -view.2;
-
-        Quaternion::fromMat3(out, matr);
-        Quaternion::normalize(out, &Quaternion::clone(out));
-    }
+    };
 }
+
+pub(crate) use define_quaternion;
+
+define_quaternion!(
+    f32,
+    Float32Array,
+    fromFloat32Array,
+    Matrix2,
+    Matrix2d,
+    Matrix3,
+    Matrix4,
+    Quaternion,
+    Quaternion2,
+    Vector2,
+    Vector3,
+    Vector4
+);
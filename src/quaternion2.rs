@@ -4,598 +4,1676 @@
  * @Link   : http://dtysky.moe
  * @Date   : 2019/2/7 下午9:53:03
  */
-use wasm_bindgen::prelude::*;
-
-use super::common::*;
-use super::matrix4::*;
-use super::quaternion::*;
-use super::vector3::*;
-
-#[wasm_bindgen]
-pub struct Quaternion2(
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-);
+// `Quaternion2` here and `Quaternion2d` in `double`. `$F` is the element type, `$FloatArray` the js array of it
+// that `$fromFloatArray` reads, and the other names are the types of the same precision.
+macro_rules! define_quaternion2 {
+    (
+        $F:ident,
+        $FloatArray:ident,
+        $fromFloatArray:ident,
+        $Matrix2:ident,
+        $Matrix2d:ident,
+        $Matrix3:ident,
+        $Matrix4:ident,
+        $Quaternion:ident,
+        $Quaternion2:ident,
+        $Vector2:ident,
+        $Vector3:ident,
+        $Vector4:ident
+    ) => {
+        #[cfg(feature = "wasm")]
+        use js_sys::$FloatArray;
+        #[cfg(feature = "wasm")]
+        use wasm_bindgen::prelude::*;
+
+        use core::fmt;
+        use core::str::FromStr;
+
+        use super::common::*;
+        use super::matrix4::*;
+        use super::quaternion::*;
+        use super::vector2::*;
+        use super::vector3::*;
+        use crate::kernels::{self, Elements};
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        #[derive(Clone, Copy, Debug)]
+        #[repr(C)]
+        pub struct $Quaternion2(
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+            pub $F,
+        );
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        impl $Quaternion2 {
+            #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+            pub fn elements(&self) -> Box<[$F]> {
+                Box::new([
+                    self.0, self.1, self.2, self.3, self.4, self.5, self.6, self.7,
+                ])
+            }
+
+            pub fn create() -> $Quaternion2 {
+                $Quaternion2(0., 0., 0., 1., 0., 0., 0., 0.)
+            }
+
+            #[allow(clippy::should_implement_trait)]
+            pub fn clone(a: &$Quaternion2) -> $Quaternion2 {
+                $Quaternion2(a.0, a.1, a.2, a.3, a.4, a.5, a.6, a.7)
+            }
+
+            #[allow(clippy::too_many_arguments)]
+            pub fn fromValues(
+                x1: $F,
+                y1: $F,
+                z1: $F,
+                w1: $F,
+                x2: $F,
+                y2: $F,
+                z2: $F,
+                w2: $F,
+            ) -> $Quaternion2 {
+                $Quaternion2(x1, y1, z1, w1, x2, y2, z2, w2)
+            }
+
+            pub fn fromRotationTranslationValues(
+                x1: $F,
+                y1: $F,
+                z1: $F,
+                w1: $F,
+                x2: $F,
+                y2: $F,
+                z2: $F,
+            ) -> $Quaternion2 {
+                let ax = x2 * 0.5;
+                let ay = y2 * 0.5;
+                let az = z2 * 0.5;
+
+                $Quaternion2(
+                    x1,
+                    y1,
+                    z1,
+                    w1,
+                    ax * w1 + ay * z1 - az * y1,
+                    ay * w1 + az * x1 - ax * z1,
+                    az * w1 + ax * y1 - ay * x1,
+                    -ax * x1 - ay * y1 - az * z1,
+                )
+            }
+
+            pub fn fromRotationTranslation(out: &mut $Quaternion2, q: &$Quaternion, t: &$Vector3) {
+                let ax = t.0 * 0.5;
+                let ay = t.1 * 0.5;
+                let az = t.2 * 0.5;
+                let bx = q.0;
+                let by = q.1;
+                let bz = q.2;
+                let bw = q.3;
+                out.0 = bx;
+                out.1 = by;
+                out.2 = bz;
+                out.3 = bw;
+                out.4 = ax * bw + ay * bz - az * by;
+                out.5 = ay * bw + az * bx - ax * bz;
+                out.6 = az * bw + ax * by - ay * bx;
+                out.7 = -ax * bx - ay * by - az * bz;
+            }
+
+            pub fn fromTranslation(out: &mut $Quaternion2, t: &$Vector3) {
+                out.0 = 0.;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 1.;
+                out.4 = t.0 * 0.5;
+                out.5 = t.1 * 0.5;
+                out.6 = t.2 * 0.5;
+                out.7 = 0.;
+            }
+
+            pub fn fromRotation(out: &mut $Quaternion2, q: &$Quaternion) {
+                out.0 = q.0;
+                out.1 = q.1;
+                out.2 = q.2;
+                out.3 = q.3;
+                out.4 = 0.;
+                out.5 = 0.;
+                out.6 = 0.;
+                out.7 = 0.;
+            }
+
+            pub fn fromMat4(out: &mut $Quaternion2, a: &$Matrix4) {
+                //TODO Optimize this
+                let outer = &mut $Quaternion::create();
+                $Matrix4::getRotation(outer, a);
+                let t = &mut $Vector3::create();
+                $Matrix4::getTranslation(t, a);
+                $Quaternion2::fromRotationTranslation(out, outer, t);
+            }
+
+            pub fn copy(out: &mut $Quaternion2, a: &$Quaternion2) {
+                *out = *a;
+            }
+
+            pub fn identity(out: &mut $Quaternion2) {
+                out.0 = 0.;
+                out.1 = 0.;
+                out.2 = 0.;
+                out.3 = 1.;
+                out.4 = 0.;
+                out.5 = 0.;
+                out.6 = 0.;
+                out.7 = 0.;
+            }
+
+            #[allow(clippy::too_many_arguments)]
+            pub fn set(
+                out: &mut $Quaternion2,
+                x1: $F,
+                y1: $F,
+                z1: $F,
+                w1: $F,
+                x2: $F,
+                y2: $F,
+                z2: $F,
+                w2: $F,
+            ) {
+                out.0 = x1;
+                out.1 = y1;
+                out.2 = z1;
+                out.3 = w1;
+
+                out.4 = x2;
+                out.5 = y2;
+                out.6 = z2;
+                out.7 = w2;
+            }
+
+            pub fn getReal(out: &mut $Quaternion, a: &$Quaternion2) {
+                out.0 = a.0;
+                out.1 = a.1;
+                out.2 = a.2;
+                out.3 = a.3;
+            }
+
+            pub fn getDual(out: &mut $Quaternion, a: &$Quaternion2) {
+                out.0 = a.4;
+                out.1 = a.5;
+                out.2 = a.6;
+                out.3 = a.7;
+            }
+
+            pub fn setReal(out: &mut $Quaternion2, a: &$Quaternion) {
+                out.0 = a.0;
+                out.1 = a.1;
+                out.2 = a.2;
+                out.3 = a.3;
+            }
+
+            pub fn setDual(out: &mut $Quaternion2, q: &$Quaternion) {
+                out.4 = q.0;
+                out.5 = q.1;
+                out.6 = q.2;
+                out.7 = q.3;
+            }
+
+            pub fn getTranslation(out: &mut $Vector3, a: &$Quaternion2) {
+                let ax = a.4;
+                let ay = a.5;
+                let az = a.6;
+                let aw = a.7;
+                let bx = -a.0;
+                let by = -a.1;
+                let bz = -a.2;
+                let bw = a.3;
+                out.0 = (ax * bw + aw * bx + ay * bz - az * by) * 2.;
+                out.1 = (ay * bw + aw * by + az * bx - ax * bz) * 2.;
+                out.2 = (az * bw + aw * bz + ax * by - ay * bx) * 2.;
+            }
+
+            // Screw parameters of the unit dual quaternion `a`: the motion rotates by `outAnglePitch.0` about
+            // the line of direction `outAxis` and moment `outMoment`, and translates along it by `outAnglePitch.1`.
+            // A pure translation has an angle and a moment of zero. The pitch and the moment come from the
+            // translation so that they stay exact for small rotations, whose axis is far from the origin.
+            pub fn toScrew(
+                outAxis: &mut $Vector3,
+                outMoment: &mut $Vector3,
+                outAnglePitch: &mut $Vector2,
+                a: &$Quaternion2,
+            ) {
+                let s = $F::sqrt(a.0 * a.0 + a.1 * a.1 + a.2 * a.2);
+                let t = &mut $Vector3::create();
+                $Quaternion2::getTranslation(t, a);
+
+                if s > 0. {
+                    outAnglePitch.0 = 2. * $F::atan2(s, a.3);
+                    $Vector3::set(outAxis, a.0 / s, a.1 / s, a.2 / s);
+                    let pitch = $Vector3::dot(t, outAxis);
+                    // moment = (t x axis + cot(angle / 2) * (t - pitch * axis)) / 2
+                    let cot = a.3 / s;
+                    $Vector3::cross(outMoment, t, outAxis);
+                    $Vector3::scaleAndAdd(outMoment, &$Vector3::clone(outMoment), t, cot);
+                    $Vector3::scaleAndAdd(
+                        outMoment,
+                        &$Vector3::clone(outMoment),
+                        outAxis,
+                        -pitch * cot,
+                    );
+                    $Vector3::scale(outMoment, &$Vector3::clone(outMoment), 0.5);
+                    outAnglePitch.1 = pitch;
+                } else {
+                    // A whole turn keeps the sign of the real part.
+                    outAnglePitch.0 = if a.3 < 0. { 2. * PI } else { 0. };
+                    let pitch = $Vector3::len(t);
+                    if pitch > 0. {
+                        $Vector3::scale(outAxis, t, 1. / pitch);
+                    } else {
+                        $Vector3::set(outAxis, 1., 0., 0.);
+                    }
+                    $Vector3::set(outMoment, 0., 0., 0.);
+                    outAnglePitch.1 = pitch;
+                }
+            }
+
+            // `axis` is a unit vector and `moment` is perpendicular to it, as from `toScrew`.
+            pub fn fromScrew(
+                out: &mut $Quaternion2,
+                axis: &$Vector3,
+                moment: &$Vector3,
+                angle: $F,
+                pitch: $F,
+            ) {
+                let half = angle * 0.5;
+                let s = $F::sin(half);
+                let c = $F::cos(half);
+                let w = 0.5 * pitch * c;
+                out.0 = axis.0 * s;
+                out.1 = axis.1 * s;
+                out.2 = axis.2 * s;
+                out.3 = c;
+                out.4 = axis.0 * w + moment.0 * s;
+                out.5 = axis.1 * w + moment.1 * s;
+                out.6 = axis.2 * w + moment.2 * s;
+                out.7 = -0.5 * pitch * s;
+            }
+
+            pub fn ln(out: &mut $Quaternion2, a: &$Quaternion2) {
+                let axis = &mut $Vector3::create();
+                let moment = &mut $Vector3::create();
+                let anglePitch = &mut $Vector2::create();
+                $Quaternion2::toScrew(axis, moment, anglePitch, a);
+
+                let half = anglePitch.0 * 0.5;
+                let w = anglePitch.1 * 0.5;
+                out.0 = axis.0 * half;
+                out.1 = axis.1 * half;
+                out.2 = axis.2 * half;
+                out.3 = 0.;
+                out.4 = axis.0 * w + moment.0 * half;
+                out.5 = axis.1 * w + moment.1 * half;
+                out.6 = axis.2 * w + moment.2 * half;
+                out.7 = 0.;
+            }
+
+            // Exponential of a dual quaternion with zero scalar parts, as from `ln`.
+            pub fn exp(out: &mut $Quaternion2, a: &$Quaternion2) {
+                let half = $F::sqrt(a.0 * a.0 + a.1 * a.1 + a.2 * a.2);
+                if half > 0. {
+                    let axis = &$Vector3::fromValues(a.0 / half, a.1 / half, a.2 / half);
+                    let w = a.4 * axis.0 + a.5 * axis.1 + a.6 * axis.2;
+                    let moment = &$Vector3::fromValues(
+                        (a.4 - axis.0 * w) / half,
+                        (a.5 - axis.1 * w) / half,
+                        (a.6 - axis.2 * w) / half,
+                    );
+                    $Quaternion2::fromScrew(out, axis, moment, 2. * half, 2. * w);
+                } else {
+                    $Quaternion2::set(out, 0., 0., 0., 1., a.4, a.5, a.6, 0.);
+                }
+            }
+
+            pub fn pow(out: &mut $Quaternion2, a: &$Quaternion2, b: $F) {
+                $Quaternion2::ln(out, a);
+                $Quaternion2::scale(out, &$Quaternion2::clone(out), b);
+                $Quaternion2::exp(out, &$Quaternion2::clone(out));
+            }
+
+            // Screw linear interpolation of unit dual quaternions, the motion from `a` to `b` at a constant
+            // speed along the screw, by the shortest path.
+            pub fn sclerp(out: &mut $Quaternion2, a: &$Quaternion2, b: &$Quaternion2, t: $F) {
+                let diff = &mut $Quaternion2::create();
+                $Quaternion2::conjugate(diff, a);
+                if $Quaternion2::dot(a, b) < 0. {
+                    $Quaternion2::multiply(diff, &$Quaternion2::clone(diff), &-*b);
+                } else {
+                    $Quaternion2::multiply(diff, &$Quaternion2::clone(diff), b);
+                }
+                $Quaternion2::pow(diff, &$Quaternion2::clone(diff), t);
+                $Quaternion2::multiply(out, a, diff);
+            }
+
+            pub fn translate(out: &mut $Quaternion2, a: &$Quaternion2, v: &$Vector3) {
+                let ax1 = a.0;
+                let ay1 = a.1;
+                let az1 = a.2;
+                let aw1 = a.3;
+                let bx1 = v.0 * 0.5;
+                let by1 = v.1 * 0.5;
+                let bz1 = v.2 * 0.5;
+                let ax2 = a.4;
+                let ay2 = a.5;
+                let az2 = a.6;
+                let aw2 = a.7;
+                out.0 = ax1;
+                out.1 = ay1;
+                out.2 = az1;
+                out.3 = aw1;
+                out.4 = aw1 * bx1 + ay1 * bz1 - az1 * by1 + ax2;
+                out.5 = aw1 * by1 + az1 * bx1 - ax1 * bz1 + ay2;
+                out.6 = aw1 * bz1 + ax1 * by1 - ay1 * bx1 + az2;
+                out.7 = -ax1 * bx1 - ay1 * by1 - az1 * bz1 + aw2;
+            }
+
+            pub fn rotateX(out: &mut $Quaternion2, a: &$Quaternion2, rad: $F) {
+                let mut bx = -a.0;
+                let mut by = -a.1;
+                let mut bz = -a.2;
+                let mut bw = a.3;
+                let ax = a.4;
+                let ay = a.5;
+                let az = a.6;
+                let aw = a.7;
+                let ax1 = ax * bw + aw * bx + ay * bz - az * by;
+                let ay1 = ay * bw + aw * by + az * bx - ax * bz;
+                let az1 = az * bw + aw * bz + ax * by - ay * bx;
+                let aw1 = aw * bw - ax * bx - ay * by - az * bz;
+                let o = &mut $Quaternion::fromValues(out.0, out.1, out.2, out.3);
+                $Quaternion::rotateX(o, &$Quaternion::fromValues(a.0, a.1, a.2, a.3), rad);
+                bx = o.0;
+                by = o.1;
+                bz = o.2;
+                bw = o.3;
+                out.0 = bx;
+                out.1 = by;
+                out.2 = bz;
+                out.3 = bw;
+                out.4 = ax1 * bw + aw1 * bx + ay1 * bz - az1 * by;
+                out.5 = ay1 * bw + aw1 * by + az1 * bx - ax1 * bz;
+                out.6 = az1 * bw + aw1 * bz + ax1 * by - ay1 * bx;
+                out.7 = aw1 * bw - ax1 * bx - ay1 * by - az1 * bz;
+            }
+
+            pub fn rotateY(out: &mut $Quaternion2, a: &$Quaternion2, rad: $F) {
+                let mut bx = -a.0;
+                let mut by = -a.1;
+                let mut bz = -a.2;
+                let mut bw = a.3;
+                let ax = a.4;
+                let ay = a.5;
+                let az = a.6;
+                let aw = a.7;
+                let ax1 = ax * bw + aw * bx + ay * bz - az * by;
+                let ay1 = ay * bw + aw * by + az * bx - ax * bz;
+                let az1 = az * bw + aw * bz + ax * by - ay * bx;
+                let aw1 = aw * bw - ax * bx - ay * by - az * bz;
+                let o = &mut $Quaternion::fromValues(out.0, out.1, out.2, out.3);
+                $Quaternion::rotateY(o, &$Quaternion::fromValues(a.0, a.1, a.2, a.3), rad);
+                bx = o.0;
+                by = o.1;
+                bz = o.2;
+                bw = o.3;
+                out.0 = bx;
+                out.1 = by;
+                out.2 = bz;
+                out.3 = bw;
+                out.4 = ax1 * bw + aw1 * bx + ay1 * bz - az1 * by;
+                out.5 = ay1 * bw + aw1 * by + az1 * bx - ax1 * bz;
+                out.6 = az1 * bw + aw1 * bz + ax1 * by - ay1 * bx;
+                out.7 = aw1 * bw - ax1 * bx - ay1 * by - az1 * bz;
+            }
+
+            pub fn rotateZ(out: &mut $Quaternion2, a: &$Quaternion2, rad: $F) {
+                let mut bx = -a.0;
+                let mut by = -a.1;
+                let mut bz = -a.2;
+                let mut bw = a.3;
+                let ax = a.4;
+                let ay = a.5;
+                let az = a.6;
+                let aw = a.7;
+                let ax1 = ax * bw + aw * bx + ay * bz - az * by;
+                let ay1 = ay * bw + aw * by + az * bx - ax * bz;
+                let az1 = az * bw + aw * bz + ax * by - ay * bx;
+                let aw1 = aw * bw - ax * bx - ay * by - az * bz;
+                let o = &mut $Quaternion::fromValues(out.0, out.1, out.2, out.3);
+                $Quaternion::rotateZ(o, &$Quaternion::fromValues(a.0, a.1, a.2, a.3), rad);
+                bx = o.0;
+                by = o.1;
+                bz = o.2;
+                bw = o.3;
+                out.0 = bx;
+                out.1 = by;
+                out.2 = bz;
+                out.3 = bw;
+                out.4 = ax1 * bw + aw1 * bx + ay1 * bz - az1 * by;
+                out.5 = ay1 * bw + aw1 * by + az1 * bx - ax1 * bz;
+                out.6 = az1 * bw + aw1 * bz + ax1 * by - ay1 * bx;
+                out.7 = aw1 * bw - ax1 * bx - ay1 * by - az1 * bz;
+            }
+
+            pub fn rotateByQuatAppend(out: &mut $Quaternion2, a: &$Quaternion2, q: &$Quaternion) {
+                let qx = q.0;
+                let qy = q.1;
+                let qz = q.2;
+                let qw = q.3;
+                let mut ax = a.0;
+                let mut ay = a.1;
+                let mut az = a.2;
+                let mut aw = a.3;
+
+                out.0 = ax * qw + aw * qx + ay * qz - az * qy;
+                out.1 = ay * qw + aw * qy + az * qx - ax * qz;
+                out.2 = az * qw + aw * qz + ax * qy - ay * qx;
+                out.3 = aw * qw - ax * qx - ay * qy - az * qz;
+                ax = a.4;
+                ay = a.5;
+                az = a.6;
+                aw = a.7;
+                out.4 = ax * qw + aw * qx + ay * qz - az * qy;
+                out.5 = ay * qw + aw * qy + az * qx - ax * qz;
+                out.6 = az * qw + aw * qz + ax * qy - ay * qx;
+                out.7 = aw * qw - ax * qx - ay * qy - az * qz;
+            }
+
+            pub fn rotateByQuatPrepend(out: &mut $Quaternion2, q: &$Quaternion, a: &$Quaternion2) {
+                let qx = q.0;
+                let qy = q.1;
+                let qz = q.2;
+                let qw = q.3;
+                let mut bx = a.0;
+                let mut by = a.1;
+                let mut bz = a.2;
+                let mut bw = a.3;
+
+                out.0 = qx * bw + qw * bx + qy * bz - qz * by;
+                out.1 = qy * bw + qw * by + qz * bx - qx * bz;
+                out.2 = qz * bw + qw * bz + qx * by - qy * bx;
+                out.3 = qw * bw - qx * bx - qy * by - qz * bz;
+                bx = a.4;
+                by = a.5;
+                bz = a.6;
+                bw = a.7;
+                out.4 = qx * bw + qw * bx + qy * bz - qz * by;
+                out.5 = qy * bw + qw * by + qz * bx - qx * bz;
+                out.6 = qz * bw + qw * bz + qx * by - qy * bx;
+                out.7 = qw * bw - qx * bx - qy * by - qz * bz;
+            }
+
+            pub fn rotateAroundAxis(
+                out: &mut $Quaternion2,
+                a: &$Quaternion2,
+                axis: &$Vector3,
+                rad: $F,
+            ) {
+                //Special case for rad = 0
+                if $F::abs(rad) < EPSILON {
+                    $Quaternion2::copy(out, a);
+                    return;
+                }
+                let axisLength = (axis.0.powi(2) + axis.1.powi(2) + axis.2.powi(2)).sqrt();
+
+                let rad = rad * 0.5;
+                let s = $F::sin(rad);
+                let bx = s * axis.0 / axisLength;
+                let by = s * axis.1 / axisLength;
+                let bz = s * axis.2 / axisLength;
+                let bw = $F::cos(rad);
+
+                let ax1 = a.0;
+                let ay1 = a.1;
+                let az1 = a.2;
+                let aw1 = a.3;
+                out.0 = ax1 * bw + aw1 * bx + ay1 * bz - az1 * by;
+                out.1 = ay1 * bw + aw1 * by + az1 * bx - ax1 * bz;
+                out.2 = az1 * bw + aw1 * bz + ax1 * by - ay1 * bx;
+                out.3 = aw1 * bw - ax1 * bx - ay1 * by - az1 * bz;
+
+                let ax = a.4;
+                let ay = a.5;
+                let az = a.6;
+                let aw = a.7;
+                out.4 = ax * bw + aw * bx + ay * bz - az * by;
+                out.5 = ay * bw + aw * by + az * bx - ax * bz;
+                out.6 = az * bw + aw * bz + ax * by - ay * bx;
+                out.7 = aw * bw - ax * bx - ay * by - az * bz;
+            }
+
+            pub fn add(out: &mut $Quaternion2, a: &$Quaternion2, b: &$Quaternion2) {
+                kernels::add(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn multiply(out: &mut $Quaternion2, a: &$Quaternion2, b: &$Quaternion2) {
+                let ax0 = a.0;
+                let ay0 = a.1;
+                let az0 = a.2;
+                let aw0 = a.3;
+                let bx1 = b.4;
+                let by1 = b.5;
+                let bz1 = b.6;
+                let bw1 = b.7;
+                let ax1 = a.4;
+                let ay1 = a.5;
+                let az1 = a.6;
+                let aw1 = a.7;
+                let bx0 = b.0;
+                let by0 = b.1;
+                let bz0 = b.2;
+                let bw0 = b.3;
+                out.0 = ax0 * bw0 + aw0 * bx0 + ay0 * bz0 - az0 * by0;
+                out.1 = ay0 * bw0 + aw0 * by0 + az0 * bx0 - ax0 * bz0;
+                out.2 = az0 * bw0 + aw0 * bz0 + ax0 * by0 - ay0 * bx0;
+                out.3 = aw0 * bw0 - ax0 * bx0 - ay0 * by0 - az0 * bz0;
+                out.4 = ax0 * bw1 + aw0 * bx1 + ay0 * bz1 - az0 * by1
+                    + ax1 * bw0
+                    + aw1 * bx0
+                    + ay1 * bz0
+                    - az1 * by0;
+                out.5 = ay0 * bw1 + aw0 * by1 + az0 * bx1 - ax0 * bz1
+                    + ay1 * bw0
+                    + aw1 * by0
+                    + az1 * bx0
+                    - ax1 * bz0;
+                out.6 = az0 * bw1 + aw0 * bz1 + ax0 * by1 - ay0 * bx1
+                    + az1 * bw0
+                    + aw1 * bz0
+                    + ax1 * by0
+                    - ay1 * bx0;
+                out.7 = aw0 * bw1 - ax0 * bx1 - ay0 * by1 - az0 * bz1 + aw1 * bw0
+                    - ax1 * bx0
+                    - ay1 * by0
+                    - az1 * bz0;
+            }
+
+            pub fn mul(out: &mut $Quaternion2, a: &$Quaternion2, b: &$Quaternion2) {
+                $Quaternion2::multiply(out, a, b);
+            }
 
-#[wasm_bindgen]
-impl Quaternion2 {
-    #[wasm_bindgen(getter)]
-    pub fn elements(&self) -> Box<[f32]> {
-        Box::new([
-            self.0, self.1, self.2, self.3, self.4, self.5, self.6, self.7,
-        ])
-    }
-
-    pub fn create() -> Quaternion2 {
-        Quaternion2(0., 0., 0., 1., 0., 0., 0., 0.)
-    }
-
-    pub fn clone(a: &Quaternion2) -> Quaternion2 {
-        Quaternion2(a.0, a.1, a.2, a.3, a.4, a.5, a.6, a.7)
-    }
-
-    pub fn fromValues(
-        x1: f32,
-        y1: f32,
-        z1: f32,
-        w1: f32,
-        x2: f32,
-        y2: f32,
-        z2: f32,
-        w2: f32,
-    ) -> Quaternion2 {
-        Quaternion2(x1, y1, z1, w1, x2, y2, z2, w2)
-    }
-
-    pub fn fromRotationTranslationValues(
-        x1: f32,
-        y1: f32,
-        z1: f32,
-        w1: f32,
-        x2: f32,
-        y2: f32,
-        z2: f32,
-    ) -> Quaternion2 {
-        let ax = x2 * 0.5;
-        let ay = y2 * 0.5;
-        let az = z2 * 0.5;
-
-        Quaternion2(
-            x1,
-            y1,
-            z1,
-            w1,
-            ax * w1 + ay * z1 - az * y1,
-            ay * w1 + az * x1 - ax * z1,
-            az * w1 + ax * y1 - ay * x1,
-            -ax * x1 - ay * y1 - az * z1,
-        )
-    }
-
-    pub fn fromRotationTranslation(out: &mut Quaternion2, q: &Quaternion, t: &Vector3) {
-        let ax = t.0 * 0.5;
-        let ay = t.1 * 0.5;
-        let az = t.2 * 0.5;
-        let bx = q.0;
-        let by = q.1;
-        let bz = q.2;
-        let bw = q.3;
-        out.0 = bx;
-        out.1 = by;
-        out.2 = bz;
-        out.3 = bw;
-        out.4 = ax * bw + ay * bz - az * by;
-        out.5 = ay * bw + az * bx - ax * bz;
-        out.6 = az * bw + ax * by - ay * bx;
-        out.7 = -ax * bx - ay * by - az * bz;
-    }
-
-    pub fn fromTranslation(out: &mut Quaternion2, t: &Vector3) {
-        out.0 = 0.;
-        out.1 = 0.;
-        out.2 = 0.;
-        out.3 = 1.;
-        out.4 = t.0 * 0.5;
-        out.5 = t.1 * 0.5;
-        out.6 = t.2 * 0.5;
-        out.7 = 0.;
-    }
-
-    pub fn fromRotation(out: &mut Quaternion2, q: &Quaternion) {
-        out.0 = q.0;
-        out.1 = q.1;
-        out.2 = q.2;
-        out.3 = q.3;
-        out.4 = 0.;
-        out.5 = 0.;
-        out.6 = 0.;
-        out.7 = 0.;
-    }
-
-    pub fn fromMat4(out: &mut Quaternion2, a: &Matrix4) {
-        //TODO Optimize this
-        let outer = &mut Quaternion::create();
-        Matrix4::getRotation(outer, a);
-        let t = &mut Vector3::create();
-        Matrix4::getTranslation(t, a);
-        Quaternion2::fromRotationTranslation(out, outer, t);
-    }
-
-    pub fn copy(out: &mut Quaternion2, a: &Quaternion2) {
-        out.0 = a.0;
-        out.1 = a.1;
-        out.2 = a.2;
-        out.3 = a.3;
-        out.4 = a.4;
-        out.5 = a.5;
-        out.6 = a.6;
-        out.7 = a.7;
-    }
-
-    pub fn identity(out: &mut Quaternion2) {
-        out.0 = 0.;
-        out.1 = 0.;
-        out.2 = 0.;
-        out.3 = 1.;
-        out.4 = 0.;
-        out.5 = 0.;
-        out.6 = 0.;
-        out.7 = 0.;
-    }
-
-    pub fn set(
-        out: &mut Quaternion2,
-        x1: f32,
-        y1: f32,
-        z1: f32,
-        w1: f32,
-        x2: f32,
-        y2: f32,
-        z2: f32,
-        w2: f32,
-    ) {
-        out.0 = x1;
-        out.1 = y1;
-        out.2 = z1;
-        out.3 = w1;
-
-        out.4 = x2;
-        out.5 = y2;
-        out.6 = z2;
-        out.7 = w2;
-    }
-
-    pub fn getReal(out: &mut Quaternion, a: &Quaternion2) {
-        out.0 = a.0;
-        out.1 = a.1;
-        out.2 = a.2;
-        out.3 = a.3;
-    }
-
-    pub fn getDual(out: &mut Quaternion, a: &Quaternion2) {
-        out.0 = a.4;
-        out.1 = a.5;
-        out.2 = a.6;
-        out.3 = a.7;
-    }
-
-    pub fn setReal(out: &mut Quaternion2, a: &Quaternion) {
-        out.0 = a.0;
-        out.1 = a.1;
-        out.2 = a.2;
-        out.3 = a.3;
-    }
-
-    pub fn setDual(out: &mut Quaternion2, q: &Quaternion) {
-        out.4 = q.0;
-        out.5 = q.1;
-        out.6 = q.2;
-        out.7 = q.3;
-    }
-
-    pub fn getTranslation(out: &mut Vector3, a: &Quaternion2) {
-        let ax = a.4;
-        let ay = a.5;
-        let az = a.6;
-        let aw = a.7;
-        let bx = -a.0;
-        let by = -a.1;
-        let bz = -a.2;
-        let bw = a.3;
-        out.0 = (ax * bw + aw * bx + ay * bz - az * by) * 2.;
-        out.1 = (ay * bw + aw * by + az * bx - ax * bz) * 2.;
-        out.2 = (az * bw + aw * bz + ax * by - ay * bx) * 2.;
-    }
-
-    pub fn translate(out: &mut Quaternion2, a: &Quaternion2, v: &Vector3) {
-        let ax1 = a.0;
-        let ay1 = a.1;
-        let az1 = a.2;
-        let aw1 = a.3;
-        let bx1 = v.0 * 0.5;
-        let by1 = v.1 * 0.5;
-        let bz1 = v.2 * 0.5;
-        let ax2 = a.4;
-        let ay2 = a.5;
-        let az2 = a.6;
-        let aw2 = a.7;
-        out.0 = ax1;
-        out.1 = ay1;
-        out.2 = az1;
-        out.3 = aw1;
-        out.4 = aw1 * bx1 + ay1 * bz1 - az1 * by1 + ax2;
-        out.5 = aw1 * by1 + az1 * bx1 - ax1 * bz1 + ay2;
-        out.6 = aw1 * bz1 + ax1 * by1 - ay1 * bx1 + az2;
-        out.7 = -ax1 * bx1 - ay1 * by1 - az1 * bz1 + aw2;
-    }
-
-    pub fn rotateX(out: &mut Quaternion2, a: &Quaternion2, rad: f32) {
-        let mut bx = -a.0;
-        let mut by = -a.1;
-        let mut bz = -a.2;
-        let mut bw = a.3;
-        let ax = a.4;
-        let ay = a.5;
-        let az = a.6;
-        let aw = a.7;
-        let ax1 = ax * bw + aw * bx + ay * bz - az * by;
-        let ay1 = ay * bw + aw * by + az * bx - ax * bz;
-        let az1 = az * bw + aw * bz + ax * by - ay * bx;
-        let aw1 = aw * bw - ax * bx - ay * by - az * bz;
-        let o = &mut Quaternion::fromValues(out.0, out.1, out.2, out.3);
-        Quaternion::rotateX(o, &Quaternion::fromValues(a.0, a.1, a.2, a.3), rad);
-        bx = o.0;
-        by = o.1;
-        bz = o.2;
-        bw = o.3;
-        out.0 = bx;
-        out.1 = by;
-        out.2 = bz;
-        out.3 = bw;
-        out.4 = ax1 * bw + aw1 * bx + ay1 * bz - az1 * by;
-        out.5 = ay1 * bw + aw1 * by + az1 * bx - ax1 * bz;
-        out.6 = az1 * bw + aw1 * bz + ax1 * by - ay1 * bx;
-        out.7 = aw1 * bw - ax1 * bx - ay1 * by - az1 * bz;
-    }
-
-    pub fn rotateY(out: &mut Quaternion2, a: &Quaternion2, rad: f32) {
-        let mut bx = -a.0;
-        let mut by = -a.1;
-        let mut bz = -a.2;
-        let mut bw = a.3;
-        let ax = a.4;
-        let ay = a.5;
-        let az = a.6;
-        let aw = a.7;
-        let ax1 = ax * bw + aw * bx + ay * bz - az * by;
-        let ay1 = ay * bw + aw * by + az * bx - ax * bz;
-        let az1 = az * bw + aw * bz + ax * by - ay * bx;
-        let aw1 = aw * bw - ax * bx - ay * by - az * bz;
-        let o = &mut Quaternion::fromValues(out.0, out.1, out.2, out.3);
-        Quaternion::rotateY(o, &Quaternion::fromValues(a.0, a.1, a.2, a.3), rad);
-        bx = o.0;
-        by = o.1;
-        bz = o.2;
-        bw = o.3;
-        out.0 = bx;
-        out.1 = by;
-        out.2 = bz;
-        out.3 = bw;
-        out.4 = ax1 * bw + aw1 * bx + ay1 * bz - az1 * by;
-        out.5 = ay1 * bw + aw1 * by + az1 * bx - ax1 * bz;
-        out.6 = az1 * bw + aw1 * bz + ax1 * by - ay1 * bx;
-        out.7 = aw1 * bw - ax1 * bx - ay1 * by - az1 * bz;
-    }
-
-    pub fn rotateZ(out: &mut Quaternion2, a: &Quaternion2, rad: f32) {
-        let mut bx = -a.0;
-        let mut by = -a.1;
-        let mut bz = -a.2;
-        let mut bw = a.3;
-        let ax = a.4;
-        let ay = a.5;
-        let az = a.6;
-        let aw = a.7;
-        let ax1 = ax * bw + aw * bx + ay * bz - az * by;
-        let ay1 = ay * bw + aw * by + az * bx - ax * bz;
-        let az1 = az * bw + aw * bz + ax * by - ay * bx;
-        let aw1 = aw * bw - ax * bx - ay * by - az * bz;
-        let o = &mut Quaternion::fromValues(out.0, out.1, out.2, out.3);
-        Quaternion::rotateZ(o, &Quaternion::fromValues(a.0, a.1, a.2, a.3), rad);
-        bx = o.0;
-        by = o.1;
-        bz = o.2;
-        bw = o.3;
-        out.0 = bx;
-        out.1 = by;
-        out.2 = bz;
-        out.3 = bw;
-        out.4 = ax1 * bw + aw1 * bx + ay1 * bz - az1 * by;
-        out.5 = ay1 * bw + aw1 * by + az1 * bx - ax1 * bz;
-        out.6 = az1 * bw + aw1 * bz + ax1 * by - ay1 * bx;
-        out.7 = aw1 * bw - ax1 * bx - ay1 * by - az1 * bz;
-    }
-
-    pub fn rotateByQuatAppend(out: &mut Quaternion2, a: &Quaternion2, q: &Quaternion) {
-        let qx = q.0;
-        let qy = q.1;
-        let qz = q.2;
-        let qw = q.3;
-        let mut ax = a.0;
-        let mut ay = a.1;
-        let mut az = a.2;
-        let mut aw = a.3;
-
-        out.0 = ax * qw + aw * qx + ay * qz - az * qy;
-        out.1 = ay * qw + aw * qy + az * qx - ax * qz;
-        out.2 = az * qw + aw * qz + ax * qy - ay * qx;
-        out.3 = aw * qw - ax * qx - ay * qy - az * qz;
-        ax = a.4;
-        ay = a.5;
-        az = a.6;
-        aw = a.7;
-        out.4 = ax * qw + aw * qx + ay * qz - az * qy;
-        out.5 = ay * qw + aw * qy + az * qx - ax * qz;
-        out.6 = az * qw + aw * qz + ax * qy - ay * qx;
-        out.7 = aw * qw - ax * qx - ay * qy - az * qz;
-    }
-
-    pub fn rotateByQuatPrepend(out: &mut Quaternion2, q: &Quaternion, a: &Quaternion2) {
-        let qx = q.0;
-        let qy = q.1;
-        let qz = q.2;
-        let qw = q.3;
-        let mut bx = a.0;
-        let mut by = a.1;
-        let mut bz = a.2;
-        let mut bw = a.3;
-
-        out.0 = qx * bw + qw * bx + qy * bz - qz * by;
-        out.1 = qy * bw + qw * by + qz * bx - qx * bz;
-        out.2 = qz * bw + qw * bz + qx * by - qy * bx;
-        out.3 = qw * bw - qx * bx - qy * by - qz * bz;
-        bx = a.4;
-        by = a.5;
-        bz = a.6;
-        bw = a.7;
-        out.4 = qx * bw + qw * bx + qy * bz - qz * by;
-        out.5 = qy * bw + qw * by + qz * bx - qx * bz;
-        out.6 = qz * bw + qw * bz + qx * by - qy * bx;
-        out.7 = qw * bw - qx * bx - qy * by - qz * bz;
-    }
-
-    pub fn rotateAroundAxis(out: &mut Quaternion2, a: &Quaternion2, axis: &Vector3, rad: f32) {
-        //Special case for rad = 0
-        if (f32::abs(rad) < EPSILON) {
-            Quaternion2::copy(out, a);
-            return;
+            pub fn scale(out: &mut $Quaternion2, a: &$Quaternion2, b: $F) {
+                kernels::scale(out.arrayMut(), a.array(), b);
+            }
+
+            pub fn dot(a: &$Quaternion2, b: &$Quaternion2) -> $F {
+                a.0 * b.0 + a.1 * b.1 + a.2 * b.2 + a.3 * b.3
+            }
+
+            pub fn lerp(out: &mut $Quaternion2, a: &$Quaternion2, b: &$Quaternion2, t: $F) {
+                let mt = 1. - t;
+                let mut t = t;
+                if $Quaternion2::dot(a, b) < EPSILON {
+                    t = -t;
+                }
+
+                out.0 = a.0 * mt + b.0 * t;
+                out.1 = a.1 * mt + b.1 * t;
+                out.2 = a.2 * mt + b.2 * t;
+                out.3 = a.3 * mt + b.3 * t;
+                out.4 = a.4 * mt + b.4 * t;
+                out.5 = a.5 * mt + b.5 * t;
+                out.6 = a.6 * mt + b.6 * t;
+                out.7 = a.7 * mt + b.7 * t;
+            }
+
+            pub fn invert(out: &mut $Quaternion2, a: &$Quaternion2) {
+                let sqlen = $Quaternion2::squaredLength(a);
+                out.0 = -a.0 / sqlen;
+                out.1 = -a.1 / sqlen;
+                out.2 = -a.2 / sqlen;
+                out.3 = a.3 / sqlen;
+                out.4 = -a.4 / sqlen;
+                out.5 = -a.5 / sqlen;
+                out.6 = -a.6 / sqlen;
+                out.7 = a.7 / sqlen;
+            }
+
+            pub fn conjugate(out: &mut $Quaternion2, a: &$Quaternion2) {
+                out.0 = -a.0;
+                out.1 = -a.1;
+                out.2 = -a.2;
+                out.3 = a.3;
+                out.4 = -a.4;
+                out.5 = -a.5;
+                out.6 = -a.6;
+                out.7 = a.7;
+            }
+
+            pub fn len(a: &$Quaternion2) -> $F {
+                let x = a.0;
+                let y = a.1;
+                let z = a.2;
+                let w = a.3;
+                (x.powi(2) + y.powi(2) + z.powi(2) + w.powi(2)).sqrt()
+            }
+
+            pub fn squaredLength(a: &$Quaternion2) -> $F {
+                let x = a.0;
+                let y = a.1;
+                let z = a.2;
+                let w = a.3;
+                x * x + y * y + z * z + w * w
+            }
+
+            pub fn sqrLen(a: &$Quaternion2) -> $F {
+                $Quaternion2::squaredLength(a)
+            }
+
+            pub fn normalize(out: &mut $Quaternion2, a: &$Quaternion2) {
+                let mut magnitude = $Quaternion2::squaredLength(a);
+                if magnitude > EPSILON {
+                    magnitude = $F::sqrt(magnitude);
+
+                    let a0 = a.0 / magnitude;
+                    let a1 = a.1 / magnitude;
+                    let a2 = a.2 / magnitude;
+                    let a3 = a.3 / magnitude;
+
+                    let b0 = a.4;
+                    let b1 = a.5;
+                    let b2 = a.6;
+                    let b3 = a.7;
+
+                    let a_dot_b = (a0 * b0) + (a1 * b1) + (a2 * b2) + (a3 * b3);
+
+                    out.0 = a0;
+                    out.1 = a1;
+                    out.2 = a2;
+                    out.3 = a3;
+
+                    out.4 = (b0 - (a0 * a_dot_b)) / magnitude;
+                    out.5 = (b1 - (a1 * a_dot_b)) / magnitude;
+                    out.6 = (b2 - (a2 * a_dot_b)) / magnitude;
+                    out.7 = (b3 - (a3 * a_dot_b)) / magnitude;
+                }
+            }
+
+            pub fn str(a: &$Quaternion2) -> String {
+                a.to_string()
+            }
+
+            pub fn exactEquals(a: &$Quaternion2, b: &$Quaternion2) -> bool {
+                kernels::exactEquals(a.array(), b.array())
+            }
+
+            pub fn equals(a: &$Quaternion2, b: &$Quaternion2) -> bool {
+                match getEqualsMode() {
+                    EqualsMode::Epsilon => $Quaternion2::equalsWithEpsilon(a, b, getEpsilon()),
+                    EqualsMode::Ulps => $Quaternion2::equalsWithUlps(a, b, getMaxUlps()),
+                }
+            }
+
+            pub fn equalsWithEpsilon(a: &$Quaternion2, b: &$Quaternion2, epsilon: $F) -> bool {
+                kernels::equalsWithEpsilon(a.array(), b.array(), epsilon)
+            }
+
+            pub fn equalsWithUlps(a: &$Quaternion2, b: &$Quaternion2, maxUlps: u32) -> bool {
+                kernels::equalsWithUlps(a.array(), b.array(), maxUlps)
+            }
+
+            // Dual quaternion linear blending of the unit dual quaternions packed in `a`, 8 elements each, by
+            // `weights`, as for skinning. They are taken on the hemisphere of the first one.
+            pub fn blendArray(out: &mut $Quaternion2, a: &[$F], weights: &[$F]) {
+                let len = weights.len() * 8;
+                assert!(
+                    a.len() >= len,
+                    "the buffer is shorter than {} elements",
+                    len
+                );
+                if weights.is_empty() {
+                    $Quaternion2::identity(out);
+                    return;
+                }
+
+                let pivot = $Quaternion2::fromSlice(a);
+                let sum = &mut $Quaternion2(0., 0., 0., 0., 0., 0., 0., 0.);
+                for (i, &weight) in weights.iter().enumerate() {
+                    let q = &$Quaternion2::fromSlice(&a[i * 8..]);
+                    let weight = if $Quaternion2::dot(q, &pivot) < 0. {
+                        -weight
+                    } else {
+                        weight
+                    };
+                    let prev = $Quaternion2::clone(sum);
+                    kernels::scaleAndAdd(sum.arrayMut(), prev.array(), q.array(), weight);
+                }
+                $Quaternion2::normalize(out, sum);
+            }
         }
-        let axisLength = (axis.0.powi(2) + axis.1.powi(2) + axis.2.powi(2)).sqrt();
-
-        let rad = rad * 0.5;
-        let s = f32::sin(rad);
-        let bx = s * axis.0 / axisLength;
-        let by = s * axis.1 / axisLength;
-        let bz = s * axis.2 / axisLength;
-        let bw = f32::cos(rad);
-
-        let ax1 = a.0;
-        let ay1 = a.1;
-        let az1 = a.2;
-        let aw1 = a.3;
-        out.0 = ax1 * bw + aw1 * bx + ay1 * bz - az1 * by;
-        out.1 = ay1 * bw + aw1 * by + az1 * bx - ax1 * bz;
-        out.2 = az1 * bw + aw1 * bz + ax1 * by - ay1 * bx;
-        out.3 = aw1 * bw - ax1 * bx - ay1 * by - az1 * bz;
-
-        let ax = a.4;
-        let ay = a.5;
-        let az = a.6;
-        let aw = a.7;
-        out.4 = ax * bw + aw * bx + ay * bz - az * by;
-        out.5 = ay * bw + aw * by + az * bx - ax * bz;
-        out.6 = az * bw + aw * bz + ax * by - ay * bx;
-        out.7 = aw * bw - ax * bx - ay * by - az * bz;
-    }
-
-    pub fn add(out: &mut Quaternion2, a: &Quaternion2, b: &Quaternion2) {
-        out.0 = a.0 + b.0;
-        out.1 = a.1 + b.1;
-        out.2 = a.2 + b.2;
-        out.3 = a.3 + b.3;
-        out.4 = a.4 + b.4;
-        out.5 = a.5 + b.5;
-        out.6 = a.6 + b.6;
-        out.7 = a.7 + b.7;
-    }
-
-    pub fn multiply(out: &mut Quaternion2, a: &Quaternion2, b: &Quaternion2) {
-        let ax0 = a.0;
-        let ay0 = a.1;
-        let az0 = a.2;
-        let aw0 = a.3;
-        let bx1 = b.4;
-        let by1 = b.5;
-        let bz1 = b.6;
-        let bw1 = b.7;
-        let ax1 = a.4;
-        let ay1 = a.5;
-        let az1 = a.6;
-        let aw1 = a.7;
-        let bx0 = b.0;
-        let by0 = b.1;
-        let bz0 = b.2;
-        let bw0 = b.3;
-        out.0 = ax0 * bw0 + aw0 * bx0 + ay0 * bz0 - az0 * by0;
-        out.1 = ay0 * bw0 + aw0 * by0 + az0 * bx0 - ax0 * bz0;
-        out.2 = az0 * bw0 + aw0 * bz0 + ax0 * by0 - ay0 * bx0;
-        out.3 = aw0 * bw0 - ax0 * bx0 - ay0 * by0 - az0 * bz0;
-        out.4 = ax0 * bw1 + aw0 * bx1 + ay0 * bz1 - az0 * by1 + ax1 * bw0 + aw1 * bx0 + ay1 * bz0
-            - az1 * by0;
-        out.5 = ay0 * bw1 + aw0 * by1 + az0 * bx1 - ax0 * bz1 + ay1 * bw0 + aw1 * by0 + az1 * bx0
-            - ax1 * bz0;
-        out.6 = az0 * bw1 + aw0 * bz1 + ax0 * by1 - ay0 * bx1 + az1 * bw0 + aw1 * bz0 + ax1 * by0
-            - ay1 * bx0;
-        out.7 = aw0 * bw1 - ax0 * bx1 - ay0 * by1 - az0 * bz1 + aw1 * bw0
-            - ax1 * bx0
-            - ay1 * by0
-            - az1 * bz0;
-    }
-
-    pub fn mul(out: &mut Quaternion2, a: &Quaternion2, b: &Quaternion2) {
-        Quaternion2::multiply(out, a, b);
-    }
-
-    pub fn scale(out: &mut Quaternion2, a: &Quaternion2, b: f32) {
-        out.0 = a.0 * b;
-        out.1 = a.1 * b;
-        out.2 = a.2 * b;
-        out.3 = a.3 * b;
-        out.4 = a.4 * b;
-        out.5 = a.5 * b;
-        out.6 = a.6 * b;
-        out.7 = a.7 * b;
-    }
-
-    pub fn dot(a: &Quaternion2, b: &Quaternion2) -> f32 {
-        a.0 * b.0 + a.1 * b.1 + a.2 * b.2 + a.3 * b.3
-    }
-
-    pub fn lerp(out: &mut Quaternion2, a: &Quaternion2, b: &Quaternion2, t: f32) {
-        let mt = 1. - t;
-        let mut t = t;
-        if (Quaternion2::dot(a, b) < EPSILON) {
-            t = -t;
+
+        // Flat f32 buffers, such as the uniforms or the batch functions.
+        impl $Quaternion2 {
+            pub fn fromSlice(a: &[$F]) -> $Quaternion2 {
+                $Quaternion2(a[0], a[1], a[2], a[3], a[4], a[5], a[6], a[7])
+            }
+
+            pub fn writeSlice(&self, out: &mut [$F]) {
+                out[..8].copy_from_slice(self.array());
+            }
+
+            pub fn asSlice(&self) -> &[$F] {
+                self.array()
+            }
+        }
+
+        // `#[repr(C)]` keeps the 8 elements contiguous.
+        unsafe impl Elements<$F, 8> for $Quaternion2 {}
+
+        #[cfg(feature = "wasm")]
+        #[wasm_bindgen]
+        impl $Quaternion2 {
+            // Aliases the elements in wasm memory without copying. The view is detached when the memory grows,
+            // so don't keep it across calls that may allocate.
+            pub fn view(&self) -> $FloatArray {
+                unsafe { $FloatArray::view(self.asSlice()) }
+            }
+
+            // Reads the first 8 elements of `a`, without allocating in wasm memory. Throws a `RangeError`
+            // when `a` is shorter.
+            pub fn $fromFloatArray(a: &$FloatArray) -> Result<$Quaternion2, JsValue> {
+                check_length(8, a.length() as usize)?;
+                let mut elements = [0.; 8];
+                a.subarray(0, 8).copy_to(&mut elements);
+                Ok($Quaternion2::fromSlice(&elements))
+            }
+
+            pub fn copyFromArray(out: &mut $Quaternion2, a: &$FloatArray) -> Result<(), JsValue> {
+                *out = $Quaternion2::$fromFloatArray(a)?;
+                Ok(())
+            }
+        }
+
+        impl PartialEq for $Quaternion2 {
+            fn eq(&self, other: &$Quaternion2) -> bool {
+                $Quaternion2::exactEquals(self, other)
+            }
+        }
+
+        impl ApproxEq for $Quaternion2 {
+            fn approx_eq(&self, other: &$Quaternion2) -> bool {
+                $Quaternion2::equals(self, other)
+            }
+        }
+
+        impl Default for $Quaternion2 {
+            fn default() -> $Quaternion2 {
+                $Quaternion2::create()
+            }
+        }
+
+        impl fmt::Display for $Quaternion2 {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "quat2({}, {}, {}, {}, {}, {}, {}, {})",
+                    self.0, self.1, self.2, self.3, self.4, self.5, self.6, self.7
+                )
+            }
         }
 
-        out.0 = a.0 * mt + b.0 * t;
-        out.1 = a.1 * mt + b.1 * t;
-        out.2 = a.2 * mt + b.2 * t;
-        out.3 = a.3 * mt + b.3 * t;
-        out.4 = a.4 * mt + b.4 * t;
-        out.5 = a.5 * mt + b.5 * t;
-        out.6 = a.6 * mt + b.6 * t;
-        out.7 = a.7 * mt + b.7 * t;
-    }
-
-    pub fn invert(out: &mut Quaternion2, a: &Quaternion2) {
-        let sqlen = Quaternion2::squaredLength(a);
-        out.0 = -a.0 / sqlen;
-        out.1 = -a.1 / sqlen;
-        out.2 = -a.2 / sqlen;
-        out.3 = a.3 / sqlen;
-        out.4 = -a.4 / sqlen;
-        out.5 = -a.5 / sqlen;
-        out.6 = -a.6 / sqlen;
-        out.7 = a.7 / sqlen;
-    }
-
-    pub fn conjugate(out: &mut Quaternion2, a: &Quaternion2) {
-        out.0 = -a.0;
-        out.1 = -a.1;
-        out.2 = -a.2;
-        out.3 = a.3;
-        out.4 = -a.4;
-        out.5 = -a.5;
-        out.6 = -a.6;
-        out.7 = a.7;
-    }
-
-    pub fn len(a: &Quaternion2) -> f32 {
-        let x = a.0;
-        let y = a.1;
-        let z = a.2;
-        let w = a.3;
-        (x.powi(2) + y.powi(2) + z.powi(2) + w.powi(2)).sqrt()
-    }
-
-    pub fn squaredLength(a: &Quaternion2) -> f32 {
-        let x = a.0;
-        let y = a.1;
-        let z = a.2;
-        let w = a.3;
-        x * x + y * y + z * z + w * w
-    }
-
-    pub fn sqrLen(a: &Quaternion2) -> f32 {
-        Quaternion2::squaredLength(a)
-    }
-
-    pub fn normalize(out: &mut Quaternion2, a: &Quaternion2) {
-        let mut magnitude = Quaternion2::squaredLength(a);
-        if (magnitude > EPSILON) {
-            magnitude = f32::sqrt(magnitude);
-
-            let a0 = a.0 / magnitude;
-            let a1 = a.1 / magnitude;
-            let a2 = a.2 / magnitude;
-            let a3 = a.3 / magnitude;
-
-            let b0 = a.4;
-            let b1 = a.5;
-            let b2 = a.6;
-            let b3 = a.7;
-
-            let a_dot_b = (a0 * b0) + (a1 * b1) + (a2 * b2) + (a3 * b3);
-
-            out.0 = a0;
-            out.1 = a1;
-            out.2 = a2;
-            out.3 = a3;
-
-            out.4 = (b0 - (a0 * a_dot_b)) / magnitude;
-            out.5 = (b1 - (a1 * a_dot_b)) / magnitude;
-            out.6 = (b2 - (a2 * a_dot_b)) / magnitude;
-            out.7 = (b3 - (a3 * a_dot_b)) / magnitude;
+        impl FromStr for $Quaternion2 {
+            type Err = ParseError;
+
+            fn from_str(s: &str) -> Result<$Quaternion2, ParseError> {
+                let mut v = [0.; 8];
+                parse_values(s, "quat2", &mut v)?;
+                Ok($Quaternion2(v[0], v[1], v[2], v[3], v[4], v[5], v[6], v[7]))
+            }
         }
-    }
-
-    // pub fn str(a: &Quaternion2) {
-    //   return "quat2(" + a.0 + ", " + a.1 + ", " + a.2 + ", " + a.3 + ", " +
-    //     a.4 + ", " + a.5 + ", " + a.6 + ", " + a.7 + ")";
-    // }
-
-    pub fn exactEquals(a: &Quaternion2, b: &Quaternion2) -> bool {
-        a.0 == b.0
-            && a.1 == b.1
-            && a.2 == b.2
-            && a.3 == b.3
-            && a.4 == b.4
-            && a.5 == b.5
-            && a.6 == b.6
-            && a.7 == b.7
-    }
-
-    pub fn equals(a: &Quaternion2, b: &Quaternion2) -> bool {
-        let a0 = a.0;
-        let a1 = a.1;
-        let a2 = a.2;
-        let a3 = a.3;
-        let a4 = a.4;
-        let a5 = a.5;
-        let a6 = a.6;
-        let a7 = a.7;
-        let b0 = b.0;
-        let b1 = b.1;
-        let b2 = b.2;
-        let b3 = b.3;
-        let b4 = b.4;
-        let b5 = b.5;
-        let b6 = b.6;
-        let b7 = b.7;
-        f32::abs(a0 - b0) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a0), f32::abs(b0)))
-            && f32::abs(a1 - b1) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a1), f32::abs(b1)))
-            && f32::abs(a2 - b2) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a2), f32::abs(b2)))
-            && f32::abs(a3 - b3) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a3), f32::abs(b3)))
-            && f32::abs(a4 - b4) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a4), f32::abs(b4)))
-            && f32::abs(a5 - b5) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a5), f32::abs(b5)))
-            && f32::abs(a6 - b6) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a6), f32::abs(b6)))
-            && f32::abs(a7 - b7) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a7), f32::abs(b7)))
-    }
+
+        #[cfg(test)]
+        mod tests {
+            // Some expected values are copied from the gl-matrix specs at double precision.
+            #![allow(clippy::excessive_precision)]
+            use super::*;
+            use crate::spec_helper::*;
+
+            fn setup() -> ($Quaternion2, $Quaternion2, $Quaternion2) {
+                (
+                    $Quaternion2::fromValues(0., 0., 0., 0., 0., 0., 0., 0.),
+                    $Quaternion2::fromValues(1., 2., 3., 4., 2., 5., 6., -2.),
+                    $Quaternion2::fromValues(5., 6., 7., 8., 9., 8., 6., -4.),
+                )
+            }
+
+            // The spec rebuilds quat2A as a normalized rigid transform before comparing against Matrix4.
+            fn rigid(q: &$Quaternion, t: &$Vector3) -> ($Quaternion2, $Matrix4) {
+                let quat2A = &mut $Quaternion2::create();
+                let matrixA = &mut $Matrix4::create();
+                $Quaternion2::fromRotationTranslation(quat2A, q, t);
+                $Quaternion2::normalize(quat2A, &$Quaternion2::clone(quat2A));
+                $Matrix4::fromQuat2(matrixA, quat2A);
+                ($Quaternion2::clone(quat2A), $Matrix4::clone(matrixA))
+            }
+
+            #[test]
+            fn translate() {
+                let (out, _, _) = &mut setup();
+                let vec = &$Vector3::fromValues(1., 1., -1.);
+                let (quat2A, matrixA) = &mut rigid(
+                    &$Quaternion::fromValues(1., 2., 3., 4.),
+                    &$Vector3::fromValues(-5., 4., 10.),
+                );
+                let quat2B = &$Quaternion2::clone(quat2A);
+                let matOut = &mut $Matrix4::create();
+                let quatOut = &mut $Quaternion2::create();
+                $Quaternion2::translate(out, quat2A, vec);
+                $Matrix4::translate(matOut, matrixA, vec);
+                $Quaternion2::fromMat4(quatOut, matOut);
+                expect_equalish_quat2(&out.elements(), &quatOut.elements(), None);
+                expect_equalish_quat2(&quat2A.elements(), &quat2B.elements(), None);
+
+                $Quaternion2::translate(quat2A, &$Quaternion2::clone(quat2A), vec);
+                expect_equalish_quat2(&quat2A.elements(), &quatOut.elements(), None);
+            }
+
+            #[test]
+            fn rotateAroundAxis() {
+                let (out, _, quat2B) = &mut setup();
+                let ax = &$Vector3::fromValues(1., 4., 2.);
+                let (quat2A, matrixA) = &mut rigid(
+                    &$Quaternion::fromValues(1., 2., 3., 4.),
+                    &$Vector3::fromValues(-5., 4., 10.),
+                );
+                let matOut = &mut $Matrix4::create();
+                $Quaternion2::rotateAroundAxis(out, quat2A, ax, 5.);
+                $Matrix4::rotate(matOut, matrixA, 5., ax);
+                $Quaternion2::fromMat4(quat2B, matOut);
+                expect_equalish_quat2(&out.elements(), &quat2B.elements(), None);
+                expect_equalish_quat2(
+                    &quat2A.elements(),
+                    &[
+                        0.18257418583505536,
+                        0.3651483716701107,
+                        0.5477225575051661,
+                        0.7302967433402214,
+                        -2.556038601690775,
+                        3.742770809618635,
+                        2.37346441585572,
+                        -3.0124740662784135,
+                    ],
+                    None,
+                );
+
+                $Quaternion2::rotateAroundAxis(quat2A, &$Quaternion2::clone(quat2A), ax, 5.);
+                expect_equalish_quat2(&quat2A.elements(), &quat2B.elements(), None);
+            }
+
+            #[test]
+            fn rotateByQuatAppend() {
+                let (out, quat2A, _) = &mut setup();
+                let correctResult = &mut $Quaternion2::create();
+                let rotationQuat = &$Quaternion2::fromValues(2., 5., 2., -10., 0., 0., 0., 0.);
+                $Quaternion2::multiply(correctResult, quat2A, rotationQuat);
+
+                $Quaternion2::rotateByQuatAppend(
+                    out,
+                    quat2A,
+                    &$Quaternion::fromValues(2., 5., 2., -10.),
+                );
+                expect_equalish_quat2(&out.elements(), &correctResult.elements(), None);
+                expect_equalish(&quat2A.elements(), &[1., 2., 3., 4., 2., 5., 6., -2.]);
+
+                $Quaternion2::rotateByQuatAppend(
+                    quat2A,
+                    &$Quaternion2::clone(quat2A),
+                    &$Quaternion::fromValues(2., 5., 2., -10.),
+                );
+                expect_equalish_quat2(&quat2A.elements(), &correctResult.elements(), None);
+            }
+
+            #[test]
+            fn rotateByQuatPrepend() {
+                let (out, quat2A, _) = &mut setup();
+                let outQuat = &mut $Quaternion::fromValues(0., 0., 0., 0.);
+                let correctResult = &mut $Quaternion2::create();
+                let rotationQuat = &$Quaternion2::fromValues(2., 5., 2., -10., 0., 0., 0., 0.);
+                $Quaternion2::multiply(correctResult, rotationQuat, quat2A);
+
+                $Quaternion2::getReal(outQuat, rotationQuat);
+                $Quaternion2::rotateByQuatPrepend(out, outQuat, quat2A);
+                expect_equalish_quat2(&out.elements(), &correctResult.elements(), None);
+                expect_equalish(&quat2A.elements(), &[1., 2., 3., 4., 2., 5., 6., -2.]);
+
+                $Quaternion2::rotateByQuatPrepend(quat2A, outQuat, &$Quaternion2::clone(quat2A));
+                expect_equalish_quat2(&quat2A.elements(), &correctResult.elements(), None);
+            }
+
+            #[test]
+            fn rotateX() {
+                let (out, _, _) = &mut setup();
+                let (quat2A, matrixA) = &mut rigid(
+                    &$Quaternion::fromValues(1., 2., 3., 4.),
+                    &$Vector3::fromValues(-5., 4., 10.),
+                );
+                let quat2B = &$Quaternion2::clone(quat2A);
+                let matOut = &mut $Matrix4::create();
+                let quatOut = &mut $Quaternion2::create();
+                $Quaternion2::rotateX(out, quat2A, 5.);
+                $Matrix4::rotateX(matOut, matrixA, 5.);
+                $Quaternion2::fromMat4(quatOut, matOut);
+                expect_equalish_quat2(&out.elements(), &quatOut.elements(), None);
+                expect_equalish_quat2(&quat2A.elements(), &quat2B.elements(), None);
+
+                $Quaternion2::rotateX(quat2A, &$Quaternion2::clone(quat2A), 5.);
+                expect_equalish_quat2(&quat2A.elements(), &quatOut.elements(), None);
+            }
+
+            #[test]
+            fn rotateY() {
+                let (out, _, _) = &mut setup();
+                let (quat2A, matrixA) = &mut rigid(
+                    &$Quaternion::fromValues(1., 2., 3., 4.),
+                    &$Vector3::fromValues(5., 4., -10.),
+                );
+                let quat2B = &$Quaternion2::clone(quat2A);
+                let matOut = &mut $Matrix4::create();
+                let quatOut = &mut $Quaternion2::create();
+                $Quaternion2::rotateY(out, quat2A, -2.);
+                $Matrix4::rotateY(matOut, matrixA, -2.);
+                $Quaternion2::fromMat4(quatOut, matOut);
+                expect_equalish_quat2(&out.elements(), &quatOut.elements(), None);
+                expect_equalish_quat2(&quat2A.elements(), &quat2B.elements(), None);
+
+                $Quaternion2::rotateY(quat2A, &$Quaternion2::clone(quat2A), -2.);
+                expect_equalish_quat2(&quat2A.elements(), &quatOut.elements(), None);
+            }
+
+            #[test]
+            fn rotateZ() {
+                let (out, _, _) = &mut setup();
+                let (quat2A, matrixA) = &mut rigid(
+                    &$Quaternion::fromValues(1., 0., 3., -4.),
+                    &$Vector3::fromValues(0., -4., -10.),
+                );
+                let quat2B = &$Quaternion2::clone(quat2A);
+                let matOut = &mut $Matrix4::create();
+                let quatOut = &mut $Quaternion2::create();
+                $Quaternion2::rotateZ(out, quat2A, 1.);
+                $Matrix4::rotateZ(matOut, matrixA, 1.);
+                $Quaternion2::fromMat4(quatOut, matOut);
+                expect_equalish_quat2(&out.elements(), &quatOut.elements(), None);
+                expect_equalish_quat2(&quat2A.elements(), &quat2B.elements(), None);
+
+                $Quaternion2::rotateZ(quat2A, &$Quaternion2::clone(quat2A), 1.);
+                expect_equalish_quat2(&quat2A.elements(), &quatOut.elements(), None);
+            }
+
+            #[test]
+            fn fromMat4() {
+                let (out, quat2A, _) = &mut setup();
+                let matOut = &mut $Matrix4::create();
+                let rotationQuat = &mut $Quaternion::create();
+                $Quaternion::normalize(rotationQuat, &$Quaternion::fromValues(1., 2., 3., 4.));
+                $Quaternion2::fromRotationTranslation(
+                    quat2A,
+                    rotationQuat,
+                    &$Vector3::fromValues(1., -5., 3.),
+                );
+                $Matrix4::fromQuat2(matOut, quat2A);
+                $Quaternion2::fromMat4(out, matOut);
+                expect_equalish_quat2(&quat2A.elements(), &out.elements(), None);
+            }
+
+            #[test]
+            fn create() {
+                let result = $Quaternion2::create();
+                expect_equalish_quat2(&result.elements(), &[0., 0., 0., 1., 0., 0., 0., 0.], None);
+            }
+
+            #[test]
+            fn clone() {
+                let (_, quat2A, _) = &mut setup();
+                let result = $Quaternion2::clone(quat2A);
+                expect_equalish_quat2(&result.elements(), &quat2A.elements(), None);
+            }
+
+            #[test]
+            fn fromValues() {
+                let result = $Quaternion2::fromValues(1., 2., 3., 4., 5., 7., 8., -2.);
+                expect_equalish_quat2(&result.elements(), &[1., 2., 3., 4., 5., 7., 8., -2.], None);
+            }
+
+            #[test]
+            fn copy() {
+                let (out, quat2A, _) = &mut setup();
+                $Quaternion2::copy(out, quat2A);
+                expect_equalish_quat2(&out.elements(), &[1., 2., 3., 4., 2., 5., 6., -2.], None);
+            }
+
+            #[test]
+            fn set() {
+                let (out, _, _) = &mut setup();
+                $Quaternion2::set(out, 1., 2., 3., 4., 2., 5., 6., -2.);
+                expect_equalish_quat2(&out.elements(), &[1., 2., 3., 4., 2., 5., 6., -2.], None);
+            }
+
+            #[test]
+            fn identity() {
+                let (out, _, _) = &mut setup();
+                $Quaternion2::identity(out);
+                expect_equalish_quat2(&out.elements(), &[0., 0., 0., 1., 0., 0., 0., 0.], None);
+            }
+
+            #[test]
+            fn add() {
+                let (out, quat2A, quat2B) = &mut setup();
+                $Quaternion2::add(out, quat2A, quat2B);
+                expect_equalish(&out.elements(), &[6., 8., 10., 12., 11., 13., 12., -6.]);
+                expect_equalish(&quat2A.elements(), &[1., 2., 3., 4., 2., 5., 6., -2.]);
+                expect_equalish(&quat2B.elements(), &[5., 6., 7., 8., 9., 8., 6., -4.]);
+
+                let (_, quat2A, quat2B) = &mut setup();
+                $Quaternion2::add(quat2A, &$Quaternion2::clone(quat2A), quat2B);
+                expect_equalish(&quat2A.elements(), &[6., 8., 10., 12., 11., 13., 12., -6.]);
+                expect_equalish(&quat2B.elements(), &[5., 6., 7., 8., 9., 8., 6., -4.]);
+
+                let (_, quat2A, quat2B) = &mut setup();
+                $Quaternion2::add(quat2B, quat2A, &$Quaternion2::clone(quat2B));
+                expect_equalish(&quat2B.elements(), &[6., 8., 10., 12., 11., 13., 12., -6.]);
+                expect_equalish(&quat2A.elements(), &[1., 2., 3., 4., 2., 5., 6., -2.]);
+            }
+
+            #[test]
+            fn multiply() {
+                let (out, quat2A, quat2B) = &mut setup();
+                $Quaternion2::multiply(out, quat2A, quat2B);
+                expect_equalish(&out.elements(), &[24., 48., 48., -6., 25., 89., 23., -157.]);
+                expect_equalish(&quat2A.elements(), &[1., 2., 3., 4., 2., 5., 6., -2.]);
+                expect_equalish(&quat2B.elements(), &[5., 6., 7., 8., 9., 8., 6., -4.]);
+
+                let (_, quat2A, quat2B) = &mut setup();
+                $Quaternion2::multiply(quat2A, &$Quaternion2::clone(quat2A), quat2B);
+                expect_equalish(
+                    &quat2A.elements(),
+                    &[24., 48., 48., -6., 25., 89., 23., -157.],
+                );
+                expect_equalish(&quat2B.elements(), &[5., 6., 7., 8., 9., 8., 6., -4.]);
+
+                let (_, quat2A, quat2B) = &mut setup();
+                $Quaternion2::multiply(quat2B, quat2A, &$Quaternion2::clone(quat2B));
+                expect_equalish(
+                    &quat2B.elements(),
+                    &[24., 48., 48., -6., 25., 89., 23., -157.],
+                );
+                expect_equalish(&quat2A.elements(), &[1., 2., 3., 4., 2., 5., 6., -2.]);
+
+                let (out, _, _) = &mut setup();
+                let (quat2A, matrixA) = &rigid(
+                    &$Quaternion::fromValues(1., 2., 3., 4.),
+                    &$Vector3::fromValues(-5., 4., 10.),
+                );
+                let (quat2B, matrixB) = &rigid(
+                    &$Quaternion::fromValues(5., 6., 7., 8.),
+                    &$Vector3::fromValues(9., 8., 6.),
+                );
+                let testQuat = &mut $Quaternion2::create();
+                $Quaternion2::fromMat4(testQuat, matrixA);
+                expect_equalish_quat2(&testQuat.elements(), &quat2A.elements(), None);
+                $Quaternion2::fromMat4(testQuat, matrixB);
+                expect_equalish_quat2(&testQuat.elements(), &quat2B.elements(), None);
+
+                let matOut = &mut $Matrix4::create();
+                let quatOut = &mut $Quaternion2::create();
+                $Quaternion2::multiply(out, quat2A, quat2B);
+                $Matrix4::mul(matOut, matrixA, matrixB);
+                $Quaternion2::fromMat4(quatOut, matOut);
+                expect_equalish_quat2(&out.elements(), &quatOut.elements(), None);
+            }
+
+            #[test]
+            fn scale() {
+                let (out, quat2A, _) = &mut setup();
+                $Quaternion2::scale(out, quat2A, 2.);
+                expect_equalish(&out.elements(), &[2., 4., 6., 8., 4., 10., 12., -4.]);
+                expect_equalish(&quat2A.elements(), &[1., 2., 3., 4., 2., 5., 6., -2.]);
+
+                $Quaternion2::scale(quat2A, &$Quaternion2::clone(quat2A), 2.);
+                expect_equalish(&quat2A.elements(), &[2., 4., 6., 8., 4., 10., 12., -4.]);
+            }
+
+            #[test]
+            fn len() {
+                let (_, quat2A, _) = &mut setup();
+                expect_equalish_scalar($Quaternion2::len(quat2A), 5.477225);
+            }
+
+            #[test]
+            fn squaredLength() {
+                let (_, quat2A, _) = &mut setup();
+                assert_eq!($Quaternion2::squaredLength(quat2A), 30.);
+            }
+
+            #[test]
+            fn fromRotation() {
+                let (out, _, _) = &mut setup();
+                $Quaternion2::fromRotation(out, &$Quaternion::fromValues(1., 2., 3., 4.));
+                expect_equalish_quat2(&out.elements(), &[1., 2., 3., 4., 0., 0., 0., 0.], None);
+            }
+
+            #[test]
+            fn fromTranslation() {
+                let (out, _, _) = &mut setup();
+                $Quaternion2::fromTranslation(out, &$Vector3::fromValues(1., 2., 3.));
+                expect_equalish_quat2(&out.elements(), &[0., 0., 0., 1., 0.5, 1., 1.5, 0.], None);
+            }
+
+            #[test]
+            fn fromRotationTranslation() {
+                let (out, _, _) = &mut setup();
+                let vec = &$Vector3::fromValues(1., 2., 3.);
+                $Quaternion2::fromRotationTranslation(
+                    out,
+                    &$Quaternion::fromValues(1., 2., 3., 4.),
+                    vec,
+                );
+                expect_equalish_quat2(&out.elements(), &[1., 2., 3., 4., 2., 4., 6., -7.], None);
+
+                let t = &mut $Vector3::fromValues(0., 0., 0.);
+                $Quaternion2::normalize(out, &$Quaternion2::clone(out));
+                $Quaternion2::getTranslation(t, out);
+                expect_equalish(&t.elements(), &[1., 2., 3.]);
+            }
+
+            #[test]
+            fn fromRotationTranslationValues() {
+                let result =
+                    &mut $Quaternion2::fromRotationTranslationValues(1., 2., 3., 4., 1., 2., 3.);
+                expect_equalish_quat2(&result.elements(), &[1., 2., 3., 4., 2., 4., 6., -7.], None);
+
+                let t = &mut $Vector3::fromValues(0., 0., 0.);
+                $Quaternion2::normalize(result, &$Quaternion2::clone(result));
+                $Quaternion2::getTranslation(t, result);
+                expect_equalish(&t.elements(), &[1., 2., 3.]);
+            }
+
+            #[test]
+            fn getTranslation() {
+                let (out, _, _) = &mut setup();
+                let outVec = &mut $Vector3::fromValues(0., 0., 0.);
+                $Quaternion2::fromTranslation(out, &$Vector3::fromValues(1., 2., 3.));
+                $Quaternion2::getTranslation(outVec, out);
+                expect_equalish(&outVec.elements(), &[1., 2., 3.]);
+                $Quaternion2::normalize(out, &$Quaternion2::clone(out));
+                $Quaternion2::getTranslation(outVec, out);
+                expect_equalish(&outVec.elements(), &[1., 2., 3.]);
+
+                $Quaternion2::fromRotationTranslation(
+                    out,
+                    &$Quaternion::fromValues(2., 4., 6., 2.),
+                    &$Vector3::fromValues(1., 2., 3.),
+                );
+                $Quaternion2::getTranslation(outVec, out);
+                expect_not_equalish(&outVec.elements(), &[1., 2., 3.]);
+                $Quaternion2::normalize(out, &$Quaternion2::clone(out));
+                $Quaternion2::getTranslation(outVec, out);
+                expect_equalish(&outVec.elements(), &[1., 2., 3.]);
+            }
+
+            #[test]
+            fn normalize() {
+                let (out, quat2A, _) = &mut setup();
+                $Quaternion2::normalize(out, quat2A);
+                expect_equalish_quat2(
+                    &out.elements(),
+                    &[
+                        1. / 5.4772255,
+                        2. / 5.4772255,
+                        3. / 5.4772255,
+                        4. / 5.4772255,
+                        0.231260,
+                        0.6450954,
+                        0.693781,
+                        -0.9006993,
+                    ],
+                    None,
+                );
+
+                let quat2A = &mut $Quaternion2::fromValues(5., 0., 0., 0., 0., 0., 0., 0.);
+                $Quaternion2::normalize(out, quat2A);
+                expect_equalish_quat2(&out.elements(), &[1., 0., 0., 0., 0., 0., 0., 0.], None);
+                expect_equalish_quat2(&quat2A.elements(), &[5., 0., 0., 0., 0., 0., 0., 0.], None);
+                $Quaternion2::normalize(quat2A, &$Quaternion2::clone(quat2A));
+                expect_equalish_quat2(&quat2A.elements(), &[1., 0., 0., 0., 0., 0., 0., 0.], None);
+
+                $Quaternion2::set(out, 5., 0., 0., 0., 1., 2., 3., 5.);
+                $Quaternion2::normalize(out, &$Quaternion2::clone(out));
+                expect_equalish_quat2(&out.elements(), &[1., 0., 0., 0., 0., 0.4, 0.6, 1.], None);
+            }
+
+            #[test]
+            fn lerp() {
+                let (out, quat2A, quat2B) = &mut setup();
+                $Quaternion2::lerp(out, quat2A, quat2B, 0.7);
+                expect_equalish_quat2(
+                    &out.elements(),
+                    &[3.8, 4.8, 5.8, 6.8, 6.9, 7.1, 6.0, -3.4],
+                    None,
+                );
+                expect_equalish_quat2(&quat2A.elements(), &[1., 2., 3., 4., 2., 5., 6., -2.], None);
+                expect_equalish_quat2(&quat2B.elements(), &[5., 6., 7., 8., 9., 8., 6., -4.], None);
+
+                $Quaternion2::lerp(quat2A, &$Quaternion2::clone(quat2A), quat2B, 0.5);
+                expect_equalish_quat2(
+                    &quat2A.elements(),
+                    &[3., 4., 5., 6., 5.5, 6.5, 6., -3.],
+                    None,
+                );
+                expect_equalish_quat2(&quat2B.elements(), &[5., 6., 7., 8., 9., 8., 6., -4.], None);
+
+                let (_, quat2A, quat2B) = &mut setup();
+                $Quaternion2::lerp(quat2B, quat2A, &$Quaternion2::clone(quat2B), 0.5);
+                expect_equalish_quat2(
+                    &quat2B.elements(),
+                    &[3., 4., 5., 6., 5.5, 6.5, 6., -3.],
+                    None,
+                );
+                expect_equalish_quat2(&quat2A.elements(), &[1., 2., 3., 4., 2., 5., 6., -2.], None);
+
+                $Quaternion2::lerp(
+                    out,
+                    &$Quaternion2::fromValues(1., 2., 3., -4., 2., 5., 6., -2.),
+                    &$Quaternion2::fromValues(5., -6., 7., 8., 9., 8., 6., -4.),
+                    0.4,
+                );
+                expect_equalish_quat2(
+                    &out.elements(),
+                    &[-1.4, 3.6, -1., -5.6, -2.4, -0.2, 1.2, 0.4],
+                    None,
+                );
+            }
+
+            // A quarter turn about the z axis through (1, 0, 0), moving 2 along it.
+            fn screw() -> $Quaternion2 {
+                let q = &mut $Quaternion::create();
+                $Quaternion::setAxisAngle(q, &$Vector3::fromValues(0., 0., 1.), PI / 2.);
+                let out = &mut $Quaternion2::create();
+                $Quaternion2::fromRotationTranslation(out, q, &$Vector3::fromValues(1., -1., 2.));
+                $Quaternion2::clone(out)
+            }
+
+            #[test]
+            fn toScrew() {
+                let axis = &mut $Vector3::create();
+                let moment = &mut $Vector3::create();
+                let anglePitch = &mut $Vector2::create();
+                $Quaternion2::toScrew(axis, moment, anglePitch, &screw());
+                expect_equalish(&axis.elements(), &[0., 0., 1.]);
+                expect_equalish(&moment.elements(), &[0., -1., 0.]);
+                expect_equalish(&anglePitch.elements(), &[PI / 2., 2.]);
+
+                let out = &mut $Quaternion2::create();
+                $Quaternion2::fromScrew(out, axis, moment, anglePitch.0, anglePitch.1);
+                expect_equalish_quat2(&out.elements(), &screw().elements(), None);
+
+                $Quaternion2::fromTranslation(out, &$Vector3::fromValues(0., 3., 4.));
+                $Quaternion2::toScrew(axis, moment, anglePitch, out);
+                expect_equalish(&axis.elements(), &[0., 0.6, 0.8]);
+                expect_equalish(&moment.elements(), &[0., 0., 0.]);
+                expect_equalish(&anglePitch.elements(), &[0., 5.]);
+
+                // A small rotation about a far axis, the round trip keeps it.
+                let q = &mut $Quaternion::create();
+                $Quaternion::setAxisAngle(q, &$Vector3::fromValues(1., 0., 0.), 1e-4);
+                let small = &mut $Quaternion2::create();
+                $Quaternion2::fromRotationTranslation(small, q, &$Vector3::fromValues(0., 3., 0.));
+                $Quaternion2::toScrew(axis, moment, anglePitch, small);
+                expect_equalish(&axis.elements(), &[1., 0., 0.]);
+                expect_equalish_scalar(anglePitch.0 * 1e4, 1.);
+                expect_equalish_scalar(anglePitch.1, 0.);
+                $Quaternion2::fromScrew(out, axis, moment, anglePitch.0, anglePitch.1);
+                expect_equalish_quat2(&out.elements(), &small.elements(), None);
+                expect_equalish_scalar(out.0 / small.0, 1.);
+            }
+
+            // `b` rotates by far less than `EPSILON` about y and translates by (1, 2, 3).
+            fn smallRotation() -> $Quaternion2 {
+                let q = &mut $Quaternion::create();
+                $Quaternion::setAxisAngle(q, &$Vector3::fromValues(0., 1., 0.), 1e-4);
+                let out = &mut $Quaternion2::create();
+                $Quaternion2::fromRotationTranslation(out, q, &$Vector3::fromValues(1., 2., 3.));
+                $Quaternion2::clone(out)
+            }
+
+            #[test]
+            fn expLn() {
+                let (out, _, _) = &mut setup();
+                $Quaternion2::ln(out, &screw());
+                expect_equalish_quat2(
+                    &out.elements(),
+                    &[0., 0., PI / 4., 0., 0., -PI / 4., 1., 0.],
+                    None,
+                );
+                $Quaternion2::exp(out, &$Quaternion2::clone(out));
+                expect_equalish_quat2(&out.elements(), &screw().elements(), None);
+
+                $Quaternion2::fromTranslation(out, &$Vector3::fromValues(1., 2., 3.));
+                $Quaternion2::ln(out, &$Quaternion2::clone(out));
+                $Quaternion2::exp(out, &$Quaternion2::clone(out));
+                let t = &mut $Vector3::create();
+                $Quaternion2::getTranslation(t, out);
+                expect_equalish(&t.elements(), &[1., 2., 3.]);
+            }
+
+            #[test]
+            fn pow() {
+                let (out, _, _) = &mut setup();
+                let half = &mut $Quaternion2::create();
+                $Quaternion2::pow(half, &screw(), 0.5);
+                $Quaternion2::multiply(out, half, half);
+                expect_equalish_quat2(&out.elements(), &screw().elements(), None);
+
+                let axis = &mut $Vector3::create();
+                let moment = &mut $Vector3::create();
+                let anglePitch = &mut $Vector2::create();
+                $Quaternion2::toScrew(axis, moment, anglePitch, half);
+                expect_equalish(&moment.elements(), &[0., -1., 0.]);
+                expect_equalish(&anglePitch.elements(), &[PI / 4., 1.]);
+
+                $Quaternion2::pow(out, &screw(), 0.);
+                expect_equalish_quat2(&out.elements(), &$Quaternion2::create().elements(), None);
+
+                let small = smallRotation();
+                $Quaternion2::pow(half, &small, 0.5);
+                $Quaternion2::multiply(out, half, half);
+                expect_equalish_quat2(&out.elements(), &small.elements(), None);
+                expect_equalish_scalar(half.1 / small.1, 0.5);
+            }
+
+            #[test]
+            fn sclerp() {
+                let (out, quat2A, _) = &mut setup();
+                let q = &mut $Quaternion::create();
+                $Quaternion::setAxisAngle(q, &$Vector3::fromValues(1., 0., 0.), 0.3);
+                $Quaternion2::fromRotationTranslation(quat2A, q, &$Vector3::fromValues(1., 2., 3.));
+                let quat2B = &mut $Quaternion2::create();
+                $Quaternion2::multiply(quat2B, quat2A, &screw());
+
+                $Quaternion2::sclerp(out, quat2A, quat2B, 0.);
+                expect_equalish_quat2(&out.elements(), &quat2A.elements(), None);
+                $Quaternion2::sclerp(out, quat2A, quat2B, 1.);
+                expect_equalish_quat2(&out.elements(), &quat2B.elements(), None);
+
+                let expected = &mut $Quaternion2::create();
+                $Quaternion2::pow(expected, &screw(), 0.5);
+                $Quaternion2::multiply(expected, quat2A, &$Quaternion2::clone(expected));
+                $Quaternion2::sclerp(out, quat2A, quat2B, 0.5);
+                expect_equalish_quat2(&out.elements(), &expected.elements(), None);
+
+                // The same transform on the other hemisphere takes the same path.
+                $Quaternion2::scale(quat2B, &$Quaternion2::clone(quat2B), -1.);
+                $Quaternion2::sclerp(out, quat2A, quat2B, 0.5);
+                expect_equalish_quat2(&out.elements(), &expected.elements(), None);
+
+                let small = smallRotation();
+                $Quaternion2::sclerp(out, &$Quaternion2::create(), &small, 1.);
+                expect_equalish_quat2(&out.elements(), &small.elements(), None);
+                expect_equalish_scalar(out.1 / small.1, 1.);
+                $Quaternion2::multiply(quat2B, quat2A, &small);
+                $Quaternion2::sclerp(out, quat2A, quat2B, 1.);
+                expect_equalish_quat2(&out.elements(), &quat2B.elements(), None);
+            }
+
+            #[test]
+            fn blendArray() {
+                let (out, quat2A, quat2B) = &mut setup();
+                let q = &mut $Quaternion::create();
+                $Quaternion::setAxisAngle(q, &$Vector3::fromValues(0., 1., 0.), 0.6);
+                $Quaternion2::fromRotationTranslation(quat2A, q, &$Vector3::fromValues(2., 0., 0.));
+                $Quaternion::setAxisAngle(q, &$Vector3::fromValues(0., 1., 0.), -0.2);
+                $Quaternion2::fromRotationTranslation(quat2B, q, &$Vector3::fromValues(0., 0., 4.));
+                let a = &mut [0.; 16];
+                quat2A.writeSlice(&mut a[..]);
+                quat2B.writeSlice(&mut a[8..]);
+
+                $Quaternion2::blendArray(out, a, &[1., 0.]);
+                expect_equalish_quat2(&out.elements(), &quat2A.elements(), None);
+
+                // About the same axis the blend halves the angle, translations are blended on the screw.
+                $Quaternion2::blendArray(out, a, &[0.5, 0.5]);
+                let r = &mut $Quaternion::create();
+                $Quaternion2::getReal(r, out);
+                $Quaternion::setAxisAngle(q, &$Vector3::fromValues(0., 1., 0.), 0.2);
+                expect_equalish(&r.elements(), &q.elements());
+                let t = &mut $Vector3::create();
+                $Quaternion2::getTranslation(t, out);
+                expect_equalish(&t.elements(), &[1.405420, 0., 2.202710]);
+
+                let flipped = &mut [0.; 16];
+                flipped.copy_from_slice(a);
+                for e in flipped[8..].iter_mut() {
+                    *e = -*e;
+                }
+                let expected = $Quaternion2::clone(out);
+                $Quaternion2::blendArray(out, flipped, &[0.5, 0.5]);
+                expect_equalish_quat2(&out.elements(), &expected.elements(), None);
+
+                // With the same rotation the translations are blended linearly.
+                $Quaternion2::fromTranslation(quat2A, &$Vector3::fromValues(2., 0., 0.));
+                $Quaternion2::fromTranslation(quat2B, &$Vector3::fromValues(0., 0., 4.));
+                quat2A.writeSlice(&mut a[..]);
+                quat2B.writeSlice(&mut a[8..]);
+                $Quaternion2::blendArray(out, a, &[0.25, 0.75]);
+                $Quaternion2::getTranslation(t, out);
+                expect_equalish(&t.elements(), &[0.5, 0., 3.]);
+
+                $Quaternion2::blendArray(out, &[], &[]);
+                expect_equalish_quat2(&out.elements(), &$Quaternion2::create().elements(), None);
+            }
+
+            #[test]
+            fn dot() {
+                let (_, quat2A, quat2B) = &mut setup();
+                expect_equalish_scalar($Quaternion2::dot(quat2A, quat2B), 70.);
+            }
+
+            #[test]
+            fn invert() {
+                let (out, quat2A, _) = &mut setup();
+                $Quaternion2::invert(out, quat2A);
+                expect_equalish(
+                    &out.elements(),
+                    &[
+                        -0.0333333333,
+                        -0.06666666666,
+                        -0.1,
+                        0.13333333333,
+                        -2. / 30.,
+                        -5. / 30.,
+                        -6. / 30.,
+                        -2. / 30.,
+                    ],
+                );
+                expect_equalish(&quat2A.elements(), &[1., 2., 3., 4., 2., 5., 6., -2.]);
+
+                $Quaternion2::invert(quat2A, &$Quaternion2::clone(quat2A));
+                expect_equalish(
+                    &quat2A.elements(),
+                    &[
+                        -0.0333333333,
+                        -0.06666666666,
+                        -0.1,
+                        0.13333333333,
+                        -2. / 30.,
+                        -5. / 30.,
+                        -6. / 30.,
+                        -2. / 30.,
+                    ],
+                );
+
+                let outQuat = &mut $Quaternion::fromValues(0., 0., 0., 0.);
+                let inverted = &mut $Quaternion::create();
+                $Quaternion::invert(inverted, &$Quaternion::fromValues(1., 2., 3., 4.));
+                $Quaternion2::getReal(outQuat, out);
+                expect_equalish(&outQuat.elements(), &inverted.elements());
+            }
+
+            #[test]
+            fn getReal() {
+                let (_, quat2A, _) = &mut setup();
+                let outQuat = &mut $Quaternion::fromValues(0., 0., 0., 0.);
+                $Quaternion2::getReal(outQuat, quat2A);
+                expect_equalish(&outQuat.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn getDual() {
+                let (_, quat2A, _) = &mut setup();
+                let outQuat = &mut $Quaternion::fromValues(0., 0., 0., 0.);
+                $Quaternion2::getDual(outQuat, quat2A);
+                expect_equalish(&outQuat.elements(), &[2., 5., 6., -2.]);
+            }
+
+            #[test]
+            fn setReal() {
+                let (_, quat2A, _) = &mut setup();
+                $Quaternion2::setReal(quat2A, &$Quaternion::fromValues(4., 6., 8., -100.));
+                expect_equalish_quat2(
+                    &quat2A.elements(),
+                    &[4., 6., 8., -100., 2., 5., 6., -2.],
+                    None,
+                );
+            }
+
+            #[test]
+            fn setDual() {
+                let (_, quat2A, _) = &mut setup();
+                $Quaternion2::setDual(quat2A, &$Quaternion::fromValues(4.3, 6., 8., -100.));
+                expect_equalish_quat2(
+                    &quat2A.elements(),
+                    &[1., 2., 3., 4., 4.3, 6., 8., -100.],
+                    None,
+                );
+            }
+
+            #[test]
+            fn conjugate() {
+                let (out, quat2A, _) = &mut setup();
+                $Quaternion2::conjugate(out, quat2A);
+                expect_equalish(&out.elements(), &[-1., -2., -3., 4., -2., -5., -6., -2.]);
+                expect_equalish(&quat2A.elements(), &[1., 2., 3., 4., 2., 5., 6., -2.]);
+
+                $Quaternion2::conjugate(quat2A, &$Quaternion2::clone(quat2A));
+                expect_equalish(&quat2A.elements(), &[-1., -2., -3., 4., -2., -5., -6., -2.]);
+            }
+
+            #[test]
+            fn exactEquals() {
+                let quat2A = &$Quaternion2::fromValues(0., 1., 2., 3., 4., 5., 6., 7.);
+                let quat2B = &$Quaternion2::fromValues(0., 1., 2., 3., 4., 5., 6., 7.);
+                let quat2C = &$Quaternion2::fromValues(1., 2., 3., 4., 5., 6., 7., 8.);
+                assert!($Quaternion2::exactEquals(quat2A, quat2B));
+                assert!(!$Quaternion2::exactEquals(quat2A, quat2C));
+                expect_equalish(&quat2A.elements(), &[0., 1., 2., 3., 4., 5., 6., 7.]);
+                expect_equalish(&quat2B.elements(), &[0., 1., 2., 3., 4., 5., 6., 7.]);
+            }
+
+            #[test]
+            fn equals() {
+                let quat2A = &$Quaternion2::fromValues(0., 1., 2., 3., 4., 5., 6., 7.);
+                let quat2B = &$Quaternion2::fromValues(0., 1., 2., 3., 4., 5., 6., 7.);
+                let quat2C = &$Quaternion2::fromValues(1., 2., 3., 4., 5., 6., 7., 8.);
+                let quat2D = &$Quaternion2::fromValues(1e-16, 1., 2., 3., 4., 5., 6., 7.);
+                assert!($Quaternion2::equals(quat2A, quat2B));
+                assert!(!$Quaternion2::equals(quat2A, quat2C));
+                assert!($Quaternion2::equals(quat2A, quat2D));
+                expect_equalish(&quat2A.elements(), &[0., 1., 2., 3., 4., 5., 6., 7.]);
+                expect_equalish(&quat2B.elements(), &[0., 1., 2., 3., 4., 5., 6., 7.]);
+            }
+
+            #[test]
+            fn traits() {
+                let a = $Quaternion2::fromValues(1., 2., 3., 4., 5., 6., 7., 8.);
+                let b = a;
+                assert_eq!(a, b);
+                assert_ne!(a, $Quaternion2::fromValues(1., 2., 3., 4., 5., 6., 7., 9.));
+                assert_ne!(
+                    a,
+                    $Quaternion2::fromValues(1., 2., 3., 4., 5., 6., 7., 8.00001)
+                );
+                assert!(a.approx_eq(&$Quaternion2::fromValues(
+                    1., 2., 3., 4., 5., 6., 7., 8.00001
+                )));
+                assert!(!a.approx_eq(&$Quaternion2::fromValues(1., 2., 3., 4., 5., 6., 7., 9.)));
+                assert_eq!($Quaternion2::default(), $Quaternion2::create());
+                assert_eq!(
+                    format!("{:?}", $Quaternion2::create()),
+                    concat!(
+                        stringify!($Quaternion2),
+                        "(0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0)"
+                    )
+                );
+            }
+
+            #[test]
+            fn str() {
+                let a = $Quaternion2::fromValues(1., 2., 3., 4., 2., 5., 6., -2.);
+                assert_eq!($Quaternion2::str(&a), "quat2(1, 2, 3, 4, 2, 5, 6, -2)");
+                assert_eq!(
+                    "quat2(1, 2, 3, 4, 2, 5, 6, -2)".parse::<$Quaternion2>(),
+                    Ok(a)
+                );
+            }
+
+            #[test]
+            fn equalsWith() {
+                let a = &$Quaternion2::fromValues(1., 2., 3., 4., 5., 6., 7., 8.);
+                let b = &$Quaternion2::fromValues(1.001, 2., 3., 4., 5., 6., 7., 8.);
+                let c = &$Quaternion2::fromValues(
+                    $F::from_bits($F::to_bits(1.) + 2),
+                    2.,
+                    3.,
+                    4.,
+                    5.,
+                    6.,
+                    7.,
+                    8.,
+                );
+                assert!(!$Quaternion2::equalsWithEpsilon(a, b, 0.0001));
+                assert!($Quaternion2::equalsWithEpsilon(a, b, 0.01));
+                assert!($Quaternion2::equalsWithUlps(a, c, 2));
+                assert!(!$Quaternion2::equalsWithUlps(a, c, 1));
+                assert!(!$Quaternion2::equalsWithUlps(a, b, 4));
+            }
+
+            #[test]
+            fn slice() {
+                let a = $Quaternion2::fromValues(1., 2., 3., 4., 5., 6., 7., 8.);
+                assert_eq!(a.asSlice(), &a.elements()[..]);
+
+                let buffer = &mut [0.; 9];
+                a.writeSlice(&mut buffer[1..]);
+                assert_eq!($Quaternion2::fromSlice(&buffer[1..]), a);
+            }
+        }
+    };
 }
+
+pub(crate) use define_quaternion2;
+
+define_quaternion2!(
+    f32,
+    Float32Array,
+    fromFloat32Array,
+    Matrix2,
+    Matrix2d,
+    Matrix3,
+    Matrix4,
+    Quaternion,
+    Quaternion2,
+    Vector2,
+    Vector3,
+    Vector4
+);
@@ -0,0 +1,274 @@
+/**
+ * @File   : serialization.rs
+ * @Author : dtysky (dtysky@outlook.com)
+ * @Link   : http://dtysky.moe
+ * @Date   : 2019/2/7 下午9:53:03
+ */
+// Serde support of the `serde` feature. Every type is its flat array of elements as in gl-matrix,
+// such as `[1, 0, 0, 0, 0, 1, ...]` for a column-major `Matrix4`. `named` is the `{x, y, z}` form.
+use core::fmt;
+use core::marker::PhantomData;
+
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{SerializeStruct, Serializer};
+use serde::{Deserialize, Serialize};
+
+use super::double::matrix2::*;
+use super::double::matrix2d::*;
+use super::double::matrix3::*;
+use super::double::matrix4::*;
+use super::double::quaternion::*;
+use super::double::quaternion2::*;
+use super::double::vector2::*;
+use super::double::vector3::*;
+use super::double::vector4::*;
+use super::kernels::Elements;
+use super::matrix2::*;
+use super::matrix2d::*;
+use super::matrix3::*;
+use super::matrix4::*;
+use super::quaternion::*;
+use super::quaternion2::*;
+use super::vector2::*;
+use super::vector3::*;
+use super::vector4::*;
+
+macro_rules! impl_array {
+    ($($t:ident: [$f:ident; $n:expr];)*) => {$(
+        impl Serialize for $t {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.array().serialize(serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $t {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$t, D::Error> {
+                let elements = <[$f; $n]>::deserialize(deserializer)?;
+                Ok($t::fromSlice(&elements))
+            }
+        }
+    )*};
+}
+
+impl_array! {
+    Vector2: [f32; 2];
+    Vector3: [f32; 3];
+    Vector4: [f32; 4];
+    Quaternion: [f32; 4];
+    Quaternion2: [f32; 8];
+    Matrix2: [f32; 4];
+    Matrix2d: [f32; 6];
+    Matrix3: [f32; 9];
+    Matrix4: [f32; 16];
+    Vector2d: [f64; 2];
+    Vector3d: [f64; 3];
+    Vector4d: [f64; 4];
+    Quaterniond: [f64; 4];
+    Quaternion2d: [f64; 8];
+    Matrix2x2d: [f64; 4];
+    Matrix2x3d: [f64; 6];
+    Matrix3d: [f64; 9];
+    Matrix4d: [f64; 16];
+}
+
+// Vectors and quaternions as structs of `x`, `y`, `z` and `w`, for a field of a serde type:
+//
+// #[serde(with = "gl_matrix_wasm::serialization::named")]
+// position: Vector3,
+pub mod named {
+    use super::*;
+
+    pub trait Named: Sized {
+        type Scalar: Copy + Default;
+        const NAME: &'static str;
+        const FIELDS: &'static [&'static str];
+
+        fn field(&self, index: usize) -> Self::Scalar;
+        fn fromFields(fields: &[Self::Scalar]) -> Self;
+    }
+
+    macro_rules! impl_named {
+        ($($t:ident: $f:ident, [$($field:expr),*];)*) => {$(
+            impl Named for $t {
+                type Scalar = $f;
+                const NAME: &'static str = stringify!($t);
+                const FIELDS: &'static [&'static str] = &[$($field),*];
+
+                fn field(&self, index: usize) -> $f {
+                    self.array()[index]
+                }
+
+                fn fromFields(fields: &[$f]) -> $t {
+                    $t::fromSlice(fields)
+                }
+            }
+        )*};
+    }
+
+    impl_named! {
+        Vector2: f32, ["x", "y"];
+        Vector3: f32, ["x", "y", "z"];
+        Vector4: f32, ["x", "y", "z", "w"];
+        Quaternion: f32, ["x", "y", "z", "w"];
+        Vector2d: f64, ["x", "y"];
+        Vector3d: f64, ["x", "y", "z"];
+        Vector4d: f64, ["x", "y", "z", "w"];
+        Quaterniond: f64, ["x", "y", "z", "w"];
+    }
+
+    pub fn serialize<V, S>(v: &V, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        V: Named,
+        V::Scalar: Serialize,
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct(V::NAME, V::FIELDS.len())?;
+        for (i, name) in V::FIELDS.iter().enumerate() {
+            s.serialize_field(name, &v.field(i))?;
+        }
+        s.end()
+    }
+
+    pub fn deserialize<'de, V, D>(deserializer: D) -> Result<V, D::Error>
+    where
+        V: Named,
+        V::Scalar: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct(V::NAME, V::FIELDS, NamedVisitor(PhantomData))
+    }
+
+    struct NamedVisitor<V>(PhantomData<V>);
+
+    impl<'de, V> Visitor<'de> for NamedVisitor<V>
+    where
+        V: Named,
+        V::Scalar: Deserialize<'de>,
+    {
+        type Value = V;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "struct {}", V::NAME)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<V, A::Error> {
+            let mut fields = [V::Scalar::default(); 4];
+            for (i, field) in fields.iter_mut().take(V::FIELDS.len()).enumerate() {
+                *field = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+            }
+            Ok(V::fromFields(&fields))
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<V, A::Error> {
+            let mut fields = [None; 4];
+            while let Some(i) = map.next_key_seed(FieldSeed(V::FIELDS))? {
+                if fields[i].is_some() {
+                    return Err(de::Error::duplicate_field(V::FIELDS[i]));
+                }
+                fields[i] = Some(map.next_value()?);
+            }
+            let mut values = [V::Scalar::default(); 4];
+            for (i, name) in V::FIELDS.iter().enumerate() {
+                values[i] = fields[i].ok_or_else(|| de::Error::missing_field(name))?;
+            }
+            Ok(V::fromFields(&values))
+        }
+    }
+
+    // Index of a field name in `FIELDS`.
+    struct FieldSeed(&'static [&'static str]);
+
+    impl<'de> DeserializeSeed<'de> for FieldSeed {
+        type Value = usize;
+
+        fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<usize, D::Error> {
+            deserializer.deserialize_identifier(self)
+        }
+    }
+
+    impl<'de> Visitor<'de> for FieldSeed {
+        type Value = usize;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "one of {:?}", self.0)
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<usize, E> {
+            match (v as usize) < self.0.len() {
+                true => Ok(v as usize),
+                false => Err(de::Error::invalid_value(de::Unexpected::Unsigned(v), &self)),
+            }
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<usize, E> {
+            self.0
+                .iter()
+                .position(|name| *name == v)
+                .ok_or_else(|| de::Error::unknown_field(v, self.0))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toNamed<V: named::Named>(v: &V) -> String
+    where
+        V::Scalar: Serialize,
+    {
+        let mut out = Vec::new();
+        named::serialize(v, &mut serde_json::Serializer::new(&mut out)).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn fromNamed<V: named::Named>(s: &str) -> serde_json::Result<V>
+    where
+        for<'de> V::Scalar: Deserialize<'de>,
+    {
+        named::deserialize(&mut serde_json::Deserializer::from_str(s))
+    }
+
+    #[test]
+    fn array() {
+        let m = &mut Matrix4::create();
+        Matrix4::fromTranslation(m, &Vector3::fromValues(1., 2., 3.));
+        let json = serde_json::to_string(m).unwrap();
+        assert_eq!(
+            json,
+            "[1.0,0.0,0.0,0.0,0.0,1.0,0.0,0.0,0.0,0.0,1.0,0.0,1.0,2.0,3.0,1.0]"
+        );
+        assert_eq!(serde_json::from_str::<Matrix4>(&json).unwrap(), *m);
+
+        let q = Quaternion2::fromValues(1., 2., 3., 4., 5., 6., 7., 8.);
+        let json = serde_json::to_string(&q).unwrap();
+        assert_eq!(json, "[1.0,2.0,3.0,4.0,5.0,6.0,7.0,8.0]");
+        assert_eq!(serde_json::from_str::<Quaternion2>(&json).unwrap(), q);
+
+        let v = Vector3d::fromValues(0.1, 0.2, 0.3);
+        let json = serde_json::to_string(&v).unwrap();
+        assert_eq!(json, "[0.1,0.2,0.3]");
+        assert_eq!(serde_json::from_str::<Vector3d>(&json).unwrap(), v);
+
+        assert!(serde_json::from_str::<Vector3>("[1, 2]").is_err());
+        assert!(serde_json::from_str::<Vector3>("[1, 2, 3, 4]").is_err());
+    }
+
+    #[test]
+    fn named() {
+        let v = Vector3::fromValues(1., 2., 3.);
+        assert_eq!(toNamed(&v), r#"{"x":1.0,"y":2.0,"z":3.0}"#);
+        assert_eq!(fromNamed::<Vector3>(r#"{"z":3,"x":1,"y":2}"#).unwrap(), v);
+        assert_eq!(fromNamed::<Vector3>("[1, 2, 3]").unwrap(), v);
+
+        let q = Quaterniond::fromValues(0., 0., 0., 1.);
+        assert_eq!(toNamed(&q), r#"{"x":0.0,"y":0.0,"z":0.0,"w":1.0}"#);
+        assert_eq!(fromNamed::<Quaterniond>(&toNamed(&q)).unwrap(), q);
+
+        assert!(fromNamed::<Vector2>(r#"{"x":1}"#).is_err());
+        assert!(fromNamed::<Vector2>(r#"{"x":1,"y":2,"z":3}"#).is_err());
+        assert!(fromNamed::<Vector2>(r#"{"x":1,"x":2}"#).is_err());
+    }
+}
@@ -0,0 +1,538 @@
+/**
+ * @File   : simd.rs
+ * @Author : dtysky (dtysky@outlook.com)
+ * @Link   : http://dtysky.moe
+ * @Date   : 2019/2/7 下午9:53:03
+ */
+// Kernels of the `simd` feature. They use wasm32 v128 when the target enables `simd128`, and a plain
+// `[f32; 4]` otherwise, so the same code runs natively in tests. Operations are done in the same order
+// as the scalar functions to give the same results.
+use core::ops::{Add, Div, Mul, Sub};
+
+use super::common::*;
+use super::matrix4::*;
+use super::quaternion::*;
+use super::vector3::*;
+use super::vector4::*;
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+use core::arch::wasm32::*;
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[derive(Clone, Copy)]
+pub(crate) struct F32x4(v128);
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+impl F32x4 {
+    #[inline(always)]
+    pub fn new(a: f32, b: f32, c: f32, d: f32) -> F32x4 {
+        F32x4(f32x4(a, b, c, d))
+    }
+
+    #[inline(always)]
+    pub fn splat(a: f32) -> F32x4 {
+        F32x4(f32x4_splat(a))
+    }
+
+    #[inline(always)]
+    pub fn toArray(self) -> [f32; 4] {
+        [
+            f32x4_extract_lane::<0>(self.0),
+            f32x4_extract_lane::<1>(self.0),
+            f32x4_extract_lane::<2>(self.0),
+            f32x4_extract_lane::<3>(self.0),
+        ]
+    }
+
+    // Lanes of `a` are 0..4 and lanes of `b` are 4..8.
+    #[inline(always)]
+    pub fn shuffle<const I0: usize, const I1: usize, const I2: usize, const I3: usize>(
+        a: F32x4,
+        b: F32x4,
+    ) -> F32x4 {
+        F32x4(i32x4_shuffle::<I0, I1, I2, I3>(a.0, b.0))
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+macro_rules! impl_lanes_op {
+    ($Op:ident, $op:ident, $intrinsic:ident) => {
+        impl $Op for F32x4 {
+            type Output = F32x4;
+
+            #[inline(always)]
+            fn $op(self, b: F32x4) -> F32x4 {
+                F32x4($intrinsic(self.0, b.0))
+            }
+        }
+    };
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+impl_lanes_op!(Add, add, f32x4_add);
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+impl_lanes_op!(Sub, sub, f32x4_sub);
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+impl_lanes_op!(Mul, mul, f32x4_mul);
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+impl_lanes_op!(Div, div, f32x4_div);
+
+// Scalar fallback.
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+#[derive(Clone, Copy)]
+pub(crate) struct F32x4([f32; 4]);
+
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+impl F32x4 {
+    #[inline(always)]
+    pub fn new(a: f32, b: f32, c: f32, d: f32) -> F32x4 {
+        F32x4([a, b, c, d])
+    }
+
+    #[inline(always)]
+    pub fn splat(a: f32) -> F32x4 {
+        F32x4([a; 4])
+    }
+
+    #[inline(always)]
+    pub fn toArray(self) -> [f32; 4] {
+        self.0
+    }
+
+    #[inline(always)]
+    pub fn shuffle<const I0: usize, const I1: usize, const I2: usize, const I3: usize>(
+        a: F32x4,
+        b: F32x4,
+    ) -> F32x4 {
+        let lanes = [
+            a.0[0], a.0[1], a.0[2], a.0[3], b.0[0], b.0[1], b.0[2], b.0[3],
+        ];
+        F32x4([lanes[I0], lanes[I1], lanes[I2], lanes[I3]])
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+macro_rules! impl_lanes_op {
+    ($Op:ident, $op:ident) => {
+        impl $Op for F32x4 {
+            type Output = F32x4;
+
+            #[inline(always)]
+            fn $op(self, b: F32x4) -> F32x4 {
+                let a = self.0;
+                let b = b.0;
+                F32x4([
+                    a[0].$op(b[0]),
+                    a[1].$op(b[1]),
+                    a[2].$op(b[2]),
+                    a[3].$op(b[3]),
+                ])
+            }
+        }
+    };
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+impl_lanes_op!(Add, add);
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+impl_lanes_op!(Sub, sub);
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+impl_lanes_op!(Mul, mul);
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+impl_lanes_op!(Div, div);
+
+#[inline(always)]
+fn columns(m: &Matrix4) -> [F32x4; 4] {
+    [
+        F32x4::new(m.0, m.1, m.2, m.3),
+        F32x4::new(m.4, m.5, m.6, m.7),
+        F32x4::new(m.8, m.9, m.10, m.11),
+        F32x4::new(m.12, m.13, m.14, m.15),
+    ]
+}
+
+#[inline(always)]
+fn store(out: &mut Matrix4, c: [F32x4; 4]) {
+    let [c0, c1, c2, c3] = [
+        c[0].toArray(),
+        c[1].toArray(),
+        c[2].toArray(),
+        c[3].toArray(),
+    ];
+    *out = Matrix4(
+        c0[0], c0[1], c0[2], c0[3], c1[0], c1[1], c1[2], c1[3], c2[0], c2[1], c2[2], c2[3], c3[0],
+        c3[1], c3[2], c3[3],
+    );
+}
+
+pub(crate) fn multiplyMat4(out: &mut Matrix4, a: &Matrix4, b: &Matrix4) {
+    let [a0, a1, a2, a3] = columns(a);
+    let mut c = [F32x4::splat(0.); 4];
+    for (i, b) in columns(b).iter().enumerate() {
+        let [b0, b1, b2, b3] = b.toArray();
+        c[i] = a0 * F32x4::splat(b0)
+            + a1 * F32x4::splat(b1)
+            + a2 * F32x4::splat(b2)
+            + a3 * F32x4::splat(b3);
+    }
+    store(out, c);
+}
+
+pub(crate) fn transposeMat4(out: &mut Matrix4, a: &Matrix4) {
+    let [r0, r1, r2, r3] = columns(a);
+    let t0 = F32x4::shuffle::<0, 4, 1, 5>(r0, r1);
+    let t1 = F32x4::shuffle::<0, 4, 1, 5>(r2, r3);
+    let t2 = F32x4::shuffle::<2, 6, 3, 7>(r0, r1);
+    let t3 = F32x4::shuffle::<2, 6, 3, 7>(r2, r3);
+    store(
+        out,
+        [
+            F32x4::shuffle::<0, 1, 4, 5>(t0, t1),
+            F32x4::shuffle::<2, 3, 6, 7>(t0, t1),
+            F32x4::shuffle::<0, 1, 4, 5>(t2, t3),
+            F32x4::shuffle::<2, 3, 6, 7>(t2, t3),
+        ],
+    );
+}
+
+pub(crate) fn invertMat4(out: &mut Matrix4, a: &Matrix4, tolerance: f32) -> bool {
+    let [a00, a01, a02, a03] = [a.0, a.1, a.2, a.3];
+    let [a10, a11, a12, a13] = [a.4, a.5, a.6, a.7];
+    let [a20, a21, a22, a23] = [a.8, a.9, a.10, a.11];
+    let [a30, a31, a32, a33] = [a.12, a.13, a.14, a.15];
+
+    // The 2x2 determinants b00..b11 of the scalar version, four at a time.
+    let [b00, b01, b02, b03] = (F32x4::new(a00, a00, a00, a01) * F32x4::new(a11, a12, a13, a12)
+        - F32x4::new(a01, a02, a03, a02) * F32x4::new(a10, a10, a10, a11))
+    .toArray();
+    let [b04, b05, b06, b07] = (F32x4::new(a01, a02, a20, a20) * F32x4::new(a13, a13, a31, a32)
+        - F32x4::new(a03, a03, a21, a22) * F32x4::new(a11, a12, a30, a30))
+    .toArray();
+    let [b08, b09, b10, b11] = (F32x4::new(a20, a21, a21, a22) * F32x4::new(a33, a32, a33, a33)
+        - F32x4::new(a23, a22, a23, a23) * F32x4::new(a30, a31, a31, a32))
+    .toArray();
+
+    let mut det = b00 * b11 - b01 * b10 + b02 * b09 + b03 * b08 - b04 * b07 + b05 * b06;
+
+    if det == 0. || !det.is_finite() || det.abs() < tolerance {
+        return false;
+    }
+    det = 1.0 / det;
+    let det = F32x4::splat(det);
+
+    // Each column is `p * q - r * s + t * u`, `t` is negated in the lanes which subtract.
+    let column =
+        |p: F32x4, q: F32x4, r: F32x4, s: F32x4, t: F32x4, u: F32x4| (p * q - r * s + t * u) * det;
+    store(
+        out,
+        [
+            column(
+                F32x4::new(a11, a02, a31, a22),
+                F32x4::new(b11, b10, b05, b04),
+                F32x4::new(a12, a01, a32, a21),
+                F32x4::new(b10, b11, b04, b05),
+                F32x4::new(a13, -a03, a33, -a23),
+                F32x4::new(b09, b09, b03, b03),
+            ),
+            column(
+                F32x4::new(a12, a00, a32, a20),
+                F32x4::new(b08, b11, b02, b05),
+                F32x4::new(a10, a02, a30, a22),
+                F32x4::new(b11, b08, b05, b02),
+                F32x4::new(-a13, a03, -a33, a23),
+                F32x4::new(b07, b07, b01, b01),
+            ),
+            column(
+                F32x4::new(a10, a01, a30, a21),
+                F32x4::new(b10, b08, b04, b02),
+                F32x4::new(a11, a00, a31, a20),
+                F32x4::new(b08, b10, b02, b04),
+                F32x4::new(a13, -a03, a33, -a23),
+                F32x4::new(b06, b06, b00, b00),
+            ),
+            column(
+                F32x4::new(a11, a00, a31, a20),
+                F32x4::new(b07, b09, b01, b03),
+                F32x4::new(a10, a01, a30, a21),
+                F32x4::new(b09, b07, b03, b01),
+                F32x4::new(-a12, a02, -a32, a22),
+                F32x4::new(b06, b06, b00, b00),
+            ),
+        ],
+    );
+
+    true
+}
+
+pub(crate) fn transformVec4Mat4(out: &mut Vector4, a: &Vector4, m: &Matrix4) {
+    let [m0, m1, m2, m3] = columns(m);
+    let [x, y, z, w] = (m0 * F32x4::splat(a.0)
+        + m1 * F32x4::splat(a.1)
+        + m2 * F32x4::splat(a.2)
+        + m3 * F32x4::splat(a.3))
+    .toArray();
+    *out = Vector4(x, y, z, w);
+}
+
+pub(crate) fn transformVec3Mat4(out: &mut Vector3, a: &Vector3, m: &Matrix4) {
+    let [m0, m1, m2, m3] = columns(m);
+    let v = m0 * F32x4::splat(a.0) + m1 * F32x4::splat(a.1) + m2 * F32x4::splat(a.2) + m3;
+    let mut w = v.toArray()[3];
+
+    if w.abs() < EPSILON {
+        w = 1.0;
+    }
+    let [x, y, z, _] = (v / F32x4::splat(w)).toArray();
+    *out = Vector3(x, y, z);
+}
+
+pub(crate) fn slerpQuat(out: &mut Quaternion, a: &Quaternion, b: &Quaternion, t: f32) {
+    let mut cosom = a.0 * b.0 + a.1 * b.1 + a.2 * b.2 + a.3 * b.3;
+
+    if cosom.abs() < EPSILON {
+        cosom = 0.;
+    }
+
+    // `b` is negated through the sign of `scale1`.
+    let sign = if cosom < 0. { -1. } else { 1. };
+    cosom *= sign;
+
+    let (scale0, scale1) = if (1.0 - cosom) > EPSILON {
+        let omega = f32::acos(cosom);
+        let sinom = f32::sin(omega);
+        (
+            f32::sin((1.0 - t) * omega) / sinom,
+            f32::sin(t * omega) / sinom,
+        )
+    } else {
+        (1.0 - t, t)
+    };
+
+    let [x, y, z, w] = (F32x4::splat(scale0) * F32x4::new(a.0, a.1, a.2, a.3)
+        + F32x4::splat(scale1 * sign) * F32x4::new(b.0, b.1, b.2, b.3))
+    .toArray();
+    *out = Quaternion(x, y, z, w);
+}
+
+// Natively these run the fallback lanes, against the scalar functions when the `simd` feature is off.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spec_helper::*;
+    use std::f32::consts::FRAC_1_SQRT_2;
+
+    // With the `simd` feature the functions of the types dispatch here, so the kernels are checked
+    // against the spec values and these scalar references, written in the same order of operations.
+    fn refMultiply(a: &Matrix4, b: &Matrix4) -> Matrix4 {
+        let (a, b) = (a.asSlice(), b.asSlice());
+        let e = &mut [0.; 16];
+        for c in 0..4 {
+            for r in 0..4 {
+                e[c * 4 + r] = b[c * 4] * a[r]
+                    + b[c * 4 + 1] * a[4 + r]
+                    + b[c * 4 + 2] * a[8 + r]
+                    + b[c * 4 + 3] * a[12 + r];
+            }
+        }
+        Matrix4::fromSlice(e)
+    }
+
+    fn refTranspose(a: &Matrix4) -> Matrix4 {
+        let a = a.asSlice();
+        let e = &mut [0.; 16];
+        for c in 0..4 {
+            for r in 0..4 {
+                e[c * 4 + r] = a[r * 4 + c];
+            }
+        }
+        Matrix4::fromSlice(e)
+    }
+
+    fn refTransform(m: &Matrix4, v: &[f32; 4]) -> [f32; 4] {
+        let m = m.asSlice();
+        let mut out = [0.; 4];
+        for (r, o) in out.iter_mut().enumerate() {
+            *o = m[r] * v[0] + m[4 + r] * v[1] + m[8 + r] * v[2] + m[12 + r] * v[3];
+        }
+        out
+    }
+
+    fn randomMat4(rng: &mut Pcg32) -> Matrix4 {
+        let e = &mut [0.; 16];
+        for v in e.iter_mut() {
+            *v = rng.nextF32() * 20. - 10.;
+        }
+        Matrix4::fromSlice(e)
+    }
+
+    #[test]
+    fn multiply() {
+        let out = &mut Matrix4::create();
+        multiplyMat4(
+            out,
+            &Matrix4::fromValues(
+                1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+            ),
+            &Matrix4::fromValues(
+                1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 4., 5., 6., 1.,
+            ),
+        );
+        expect_equalish(
+            &out.elements(),
+            &[
+                1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 5., 7., 9., 1.,
+            ],
+        );
+
+        let rng = &mut Pcg32::new(1);
+        for _ in 0..100 {
+            let (a, b) = (randomMat4(rng), randomMat4(rng));
+            multiplyMat4(out, &a, &b);
+            assert_eq!(out, &refMultiply(&a, &b));
+        }
+    }
+
+    #[test]
+    fn transpose() {
+        let out = &mut Matrix4::create();
+        transposeMat4(
+            out,
+            &Matrix4::fromValues(
+                1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+            ),
+        );
+        expect_equalish(
+            &out.elements(),
+            &[
+                1., 0., 0., 1., 0., 1., 0., 2., 0., 0., 1., 3., 0., 0., 0., 1.,
+            ],
+        );
+
+        let rng = &mut Pcg32::new(2);
+        for _ in 0..100 {
+            let a = randomMat4(rng);
+            transposeMat4(out, &a);
+            assert_eq!(out, &refTranspose(&a));
+        }
+    }
+
+    #[test]
+    fn invert() {
+        let out = &mut Matrix4::create();
+        assert!(invertMat4(
+            out,
+            &Matrix4::fromValues(1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,),
+            0.0001
+        ));
+        expect_equalish(
+            &out.elements(),
+            &[
+                1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., -1., -2., -3., 1.,
+            ],
+        );
+
+        // Well conditioned transforms give the identity back.
+        let rng = &mut Pcg32::new(3);
+        let (m, q) = (&mut Matrix4::create(), &mut Quaternion::create());
+        for _ in 0..100 {
+            Quaternion::fromEuler(
+                q,
+                rng.nextF32() * 360.,
+                rng.nextF32() * 360.,
+                rng.nextF32() * 360.,
+            );
+            let t = &Vector3::fromValues(
+                rng.nextF32() * 20. - 10.,
+                rng.nextF32() * 20. - 10.,
+                rng.nextF32() * 20. - 10.,
+            );
+            let s = &Vector3::fromValues(
+                rng.nextF32() + 0.5,
+                rng.nextF32() + 0.5,
+                rng.nextF32() + 0.5,
+            );
+            Matrix4::fromRotationTranslationScale(m, q, t, s);
+            assert!(invertMat4(out, m, 0.0001));
+            let identity = refMultiply(out, m);
+            for (a, b) in identity.asSlice().iter().zip(Matrix4::create().asSlice()) {
+                assert!(f32::abs(a - b) < 1e-4, "{:?}", identity);
+            }
+        }
+
+        let singular = &Matrix4::fromValues(
+            1., 2., 3., 4., 2., 4., 6., 8., 0., 0., 1., 0., 0., 0., 0., 1.,
+        );
+        assert!(!invertMat4(out, singular, 0.0001));
+        assert!(!invertMat4(out, singular, 0.));
+    }
+
+    #[test]
+    fn transformMat4() {
+        let (out4, out3) = (&mut Vector4::create(), &mut Vector3::create());
+        let translation = &Matrix4::fromValues(
+            1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+        );
+        transformVec4Mat4(out4, &Vector4::fromValues(1., 2., 3., 1.), translation);
+        expect_equalish(&out4.elements(), &[2., 4., 6., 1.]);
+        transformVec3Mat4(out3, &Vector3::fromValues(1., 2., 3.), translation);
+        expect_equalish(&out3.elements(), &[2., 4., 6.]);
+
+        let rng = &mut Pcg32::new(4);
+        for _ in 0..100 {
+            let m = randomMat4(rng);
+            let v = [rng.nextF32(), rng.nextF32(), rng.nextF32(), 1.];
+            transformVec4Mat4(out4, &Vector4::fromValues(v[0], v[1], v[2], v[3]), &m);
+            let expected = refTransform(&m, &v);
+            assert_eq!(out4.asSlice(), &expected[..]);
+
+            transformVec3Mat4(out3, &Vector3::fromValues(v[0], v[1], v[2]), &m);
+            let w = if expected[3].abs() < crate::common::EPSILON {
+                1.
+            } else {
+                expected[3]
+            };
+            assert_eq!(
+                out3.asSlice(),
+                &[expected[0] / w, expected[1] / w, expected[2] / w][..]
+            );
+        }
+    }
+
+    #[test]
+    fn slerp() {
+        let out = &mut Quaternion::create();
+        slerpQuat(
+            out,
+            &Quaternion::fromValues(0., 0., 0., 1.),
+            &Quaternion::fromValues(0., 1., 0., 0.),
+            0.5,
+        );
+        expect_equalish(&out.elements(), &[0., FRAC_1_SQRT_2, 0., FRAC_1_SQRT_2]);
+
+        // `a * (a^-1 * b)^t` on the hemisphere of `a`, without the slerp kernel.
+        let rng = &mut Pcg32::new(5);
+        let (a, b) = (&mut Quaternion::create(), &mut Quaternion::create());
+        let expected = &mut Quaternion::create();
+        for i in 0..100 {
+            Quaternion::fromEuler(a, rng.nextF32() * 360., rng.nextF32() * 360., 0.);
+            Quaternion::fromEuler(b, 0., rng.nextF32() * 360., rng.nextF32() * 360.);
+            let t = i as f32 / 100.;
+            slerpQuat(out, a, b, t);
+
+            Quaternion::conjugate(expected, a);
+            Quaternion::multiply(expected, &Quaternion::clone(expected), b);
+            if expected.3 < 0. {
+                Quaternion::scale(expected, &Quaternion::clone(expected), -1.);
+            }
+            Quaternion::pow(expected, &Quaternion::clone(expected), t);
+            Quaternion::multiply(expected, a, &Quaternion::clone(expected));
+            for (x, y) in out.asSlice().iter().zip(expected.asSlice()) {
+                assert!(f32::abs(x - y) < 1e-4, "{:?} {:?}", out, expected);
+            }
+        }
+
+        // Close quaternions take the linear path.
+        slerpQuat(out, &Quaternion::create(), &Quaternion::create(), 0.5);
+        assert_eq!(out, &Quaternion::create());
+    }
+}
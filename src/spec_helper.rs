@@ -0,0 +1,73 @@
+/**
+ * @File   : spec_helper.rs
+ * @Author : dtysky (dtysky@outlook.com)
+ * @Link   : http://dtysky.moe
+ * @Date   : 2019/2/7 下午9:53:03
+ */
+// Port of spec/spec-helper.ts, shared by the unit tests of every module and of both precisions.
+use core::fmt::Debug;
+
+use crate::common::Scalar;
+
+pub static EPSILON: f32 = 0.00001;
+
+// Tests that seed the shared generator of `RANDOM` hold this, so they are not interleaved.
+pub fn lock_random() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn expect_equalish<T: Scalar + Debug>(e: &[T], a: &[T]) {
+    assert_eq!(e.len(), a.len(), "length mismatch");
+    for i in 0..e.len() {
+        assert_eq!(
+            e[i].isNan(),
+            a[i].isNan(),
+            "expected {:?} to be equalish to {:?}",
+            e,
+            a
+        );
+        assert!(
+            Scalar::abs(e[i] - a[i]) < T::from(EPSILON),
+            "expected {:?} to be equalish to {:?}",
+            e,
+            a
+        );
+    }
+}
+
+pub fn expect_not_equalish<T: Scalar + Debug>(e: &[T], a: &[T]) {
+    if e.len() != a.len() {
+        return;
+    }
+    for i in 0..e.len() {
+        if e[i].isNan() != a[i].isNan() || Scalar::abs(e[i] - a[i]) >= T::from(EPSILON) {
+            return;
+        }
+    }
+    panic!("expected {:?} not to be equalish to {:?}", e, a);
+}
+
+pub fn expect_equalish_scalar<T: Scalar + Debug>(e: T, a: T) {
+    expect_equalish(&[e], &[a]);
+}
+
+// Dual quaternions are very special & unique snowflakes
+pub fn expect_equalish_quat2<T: Scalar + Debug>(e: &[T], a: &[T], epsilon: Option<T>) {
+    let epsilon = epsilon.unwrap_or_else(|| T::from(EPSILON));
+    assert_eq!(
+        e.len(),
+        a.len(),
+        "expected {:?} to have the same length as {:?}",
+        e,
+        a
+    );
+    let same = (0..e.len()).all(|i| Scalar::abs(e[i] - a[i]) < epsilon);
+    let flipped = (0..e.len()).all(|i| Scalar::abs(e[i] + a[i]) < epsilon);
+    assert!(
+        same || flipped,
+        "expected {:?} to be equalish to {:?}",
+        e,
+        a
+    );
+}
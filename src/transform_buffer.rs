@@ -0,0 +1,191 @@
+/**
+ * @File   : transform_buffer.rs
+ * @Author : dtysky (dtysky@outlook.com)
+ * @Link   : http://dtysky.moe
+ * @Date   : 2019/2/7 下午9:53:03
+ */
+#[cfg(feature = "wasm")]
+use js_sys::Float32Array;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(not(feature = "std"))]
+use super::common::{vec, Vec};
+use super::matrix4::*;
+use super::quaternion::*;
+use super::vector3::*;
+
+// Positions, rotations and scales of `count` instances, each kind in its own contiguous array.
+// `computeWorldMatrices` fills `matrices` with 16 elements per instance, ready to upload for instancing.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct TransformBuffer {
+    count: usize,
+    positions: Vec<f32>,
+    rotations: Vec<f32>,
+    scales: Vec<f32>,
+    matrices: Vec<f32>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransformBuffer {
+    // Every instance starts at the origin, without rotation and with scale 1.
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(count: usize) -> TransformBuffer {
+        let mut buffer = TransformBuffer {
+            count,
+            positions: vec![0.; count * 3],
+            rotations: vec![0.; count * 4],
+            scales: vec![1.; count * 3],
+            matrices: vec![0.; count * 16],
+        };
+        for i in 0..count {
+            buffer.rotations[i * 4 + 3] = 1.;
+            Matrix4::create().writeSlice(&mut buffer.matrices[i * 16..]);
+        }
+        buffer
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn setPosition(&mut self, index: usize, x: f32, y: f32, z: f32) {
+        let p = &mut self.positions[index * 3..index * 3 + 3];
+        p[0] = x;
+        p[1] = y;
+        p[2] = z;
+    }
+
+    pub fn setRotation(&mut self, index: usize, x: f32, y: f32, z: f32, w: f32) {
+        let r = &mut self.rotations[index * 4..index * 4 + 4];
+        r[0] = x;
+        r[1] = y;
+        r[2] = z;
+        r[3] = w;
+    }
+
+    pub fn setScale(&mut self, index: usize, x: f32, y: f32, z: f32) {
+        let s = &mut self.scales[index * 3..index * 3 + 3];
+        s[0] = x;
+        s[1] = y;
+        s[2] = z;
+    }
+
+    pub fn getMatrix(&self, index: usize) -> Matrix4 {
+        Matrix4::fromSlice(&self.matrices[index * 16..index * 16 + 16])
+    }
+
+    pub fn computeWorldMatrices(&mut self) {
+        let m = &mut Matrix4::create();
+        for i in 0..self.count {
+            Matrix4::fromRotationTranslationScale(
+                m,
+                &Quaternion::fromSlice(&self.rotations[i * 4..]),
+                &Vector3::fromSlice(&self.positions[i * 3..]),
+                &Vector3::fromSlice(&self.scales[i * 3..]),
+            );
+            m.writeSlice(&mut self.matrices[i * 16..]);
+        }
+    }
+}
+
+impl TransformBuffer {
+    pub fn positions(&self) -> &[f32] {
+        &self.positions
+    }
+
+    pub fn positionsMut(&mut self) -> &mut [f32] {
+        &mut self.positions
+    }
+
+    pub fn rotations(&self) -> &[f32] {
+        &self.rotations
+    }
+
+    pub fn rotationsMut(&mut self) -> &mut [f32] {
+        &mut self.rotations
+    }
+
+    pub fn scales(&self) -> &[f32] {
+        &self.scales
+    }
+
+    pub fn scalesMut(&mut self) -> &mut [f32] {
+        &mut self.scales
+    }
+
+    pub fn matrices(&self) -> &[f32] {
+        &self.matrices
+    }
+}
+
+// The views alias wasm memory as `Matrix4::view`, they are detached when the memory grows.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl TransformBuffer {
+    pub fn positionsView(&self) -> Float32Array {
+        unsafe { Float32Array::view(&self.positions) }
+    }
+
+    pub fn rotationsView(&self) -> Float32Array {
+        unsafe { Float32Array::view(&self.rotations) }
+    }
+
+    pub fn scalesView(&self) -> Float32Array {
+        unsafe { Float32Array::view(&self.scales) }
+    }
+
+    pub fn matricesView(&self) -> Float32Array {
+        unsafe { Float32Array::view(&self.matrices) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spec_helper::*;
+    use std::f32::consts::FRAC_1_SQRT_2;
+
+    #[test]
+    fn new() {
+        let buffer = TransformBuffer::new(2);
+        assert_eq!(buffer.count(), 2);
+        assert_eq!(buffer.positions(), &[0.; 6]);
+        assert_eq!(buffer.rotations(), &[0., 0., 0., 1., 0., 0., 0., 1.]);
+        assert_eq!(buffer.scales(), &[1.; 6]);
+        assert_eq!(buffer.getMatrix(1), Matrix4::create());
+    }
+
+    #[test]
+    fn computeWorldMatrices() {
+        let buffer = &mut TransformBuffer::new(3);
+        buffer.setPosition(0, 1., 2., 3.);
+        // A quarter turn about y, x goes to -z and z to x.
+        buffer.setRotation(1, 0., FRAC_1_SQRT_2, 0., FRAC_1_SQRT_2);
+        buffer.setScale(1, 2., 3., 4.);
+        buffer.setPosition(1, -1., 0., 2.);
+        buffer.positionsMut()[6..].copy_from_slice(&[4., 5., 6.]);
+        buffer.computeWorldMatrices();
+
+        expect_equalish(
+            &buffer.matrices()[..16],
+            &[
+                1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 1., 2., 3., 1.,
+            ],
+        );
+        expect_equalish(
+            &buffer.matrices()[16..32],
+            &[
+                0., 0., -2., 0., 0., 3., 0., 0., 4., 0., 0., 0., -1., 0., 2., 1.,
+            ],
+        );
+        expect_equalish(
+            &buffer.matrices()[32..],
+            &[
+                1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 4., 5., 6., 1.,
+            ],
+        );
+        expect_equalish(&buffer.getMatrix(1).elements(), &buffer.matrices()[16..32]);
+    }
+}
@@ -4,290 +4,858 @@
  * @Link   : http://dtysky.moe
  * @Date   : 2019/2/7 下午9:53:03
  */
-use wasm_bindgen::prelude::*;
-
-use super::common::*;
-use super::matrix2::*;
-use super::matrix2d::*;
-use super::matrix3::*;
-use super::matrix4::*;
-use super::vector3::*;
-
-#[wasm_bindgen]
-pub struct Vector2(pub f32, pub f32);
-
-#[wasm_bindgen]
-impl Vector2 {
-    #[wasm_bindgen(getter)]
-    pub fn elements(&self) -> Box<[f32]> {
-        Box::new([self.0, self.1])
-    }
-
-    pub fn create() -> Vector2 {
-        Vector2(0., 0.)
-    }
-
-    pub fn clone(a: &Vector2) -> Vector2 {
-        Vector2(a.0, a.1)
-    }
-
-    pub fn fromValues(x: f32, y: f32) -> Vector2 {
-        Vector2(x, y)
-    }
-
-    pub fn copy(out: &mut Vector2, a: &Vector2) {
-        out.0 = a.0;
-        out.1 = a.1;
-    }
-
-    pub fn set(out: &mut Vector2, x: f32, y: f32) {
-        out.0 = x;
-        out.1 = y;
-    }
-
-    pub fn add(out: &mut Vector2, a: &Vector2, b: &Vector2) {
-        out.0 = a.0 + b.0;
-        out.1 = a.1 + b.1;
-    }
-
-    pub fn subtract(out: &mut Vector2, a: &Vector2, b: &Vector2) {
-        out.0 = a.0 - b.0;
-        out.1 = a.1 - b.1;
-    }
-
-    pub fn multiply(out: &mut Vector2, a: &Vector2, b: &Vector2) {
-        out.0 = a.0 * b.0;
-        out.1 = a.1 * b.1;
-    }
-
-    pub fn divide(out: &mut Vector2, a: &Vector2, b: &Vector2) {
-        out.0 = a.0 / b.0;
-        out.1 = a.1 / b.1;
-    }
-
-    pub fn ceil(out: &mut Vector2, a: &Vector2) {
-        out.0 = f32::ceil(a.0);
-        out.1 = f32::ceil(a.1);
-    }
-
-    pub fn floor(out: &mut Vector2, a: &Vector2) {
-        out.0 = f32::floor(a.0);
-        out.1 = f32::floor(a.1);
-    }
-
-    pub fn min(out: &mut Vector2, a: &Vector2, b: &Vector2) {
-        out.0 = f32::min(a.0, b.0);
-        out.1 = f32::min(a.1, b.1);
-    }
-
-    pub fn max(out: &mut Vector2, a: &Vector2, b: &Vector2) {
-        out.0 = f32::max(a.0, b.0);
-        out.1 = f32::max(a.1, b.1);
-    }
-
-    pub fn round(out: &mut Vector2, a: &Vector2) {
-        out.0 = f32::round(a.0);
-        out.1 = f32::round(a.1);
-    }
-
-    pub fn scale(out: &mut Vector2, a: &Vector2, b: f32) {
-        out.0 = a.0 * b;
-        out.1 = a.1 * b;
-    }
-
-    pub fn scaleAndAdd(out: &mut Vector2, a: &Vector2, b: &Vector2, scale: f32) {
-        out.0 = a.0 + (b.0 * scale);
-        out.1 = a.1 + (b.1 * scale);
-    }
-
-    pub fn distance(a: &Vector2, b: &Vector2) -> f32 {
-        let x = b.0 - a.0;
-        let y = b.1 - a.1;
-        f32::hypot(x, y)
-    }
-
-    pub fn squaredDistance(a: &Vector2, b: &Vector2) -> f32 {
-        let x = b.0 - a.0;
-        let y = b.1 - a.1;
-        x * x + y * y
-    }
-
-    pub fn len(a: &Vector2) -> f32 {
-        let x = a.0;
-        let y = a.1;
-        f32::hypot(x, y)
-    }
-
-    pub fn squaredLength(a: &Vector2) -> f32 {
-        let x = a.0;
-        let y = a.1;
-        x * x + y * y
-    }
-
-    pub fn negate(out: &mut Vector2, a: &Vector2) {
-        out.0 = -a.0;
-        out.1 = -a.1;
-    }
-
-    pub fn inverse(out: &mut Vector2, a: &Vector2) {
-        out.0 = 1.0 / a.0;
-        out.1 = 1.0 / a.1;
-    }
-
-    pub fn normalize(out: &mut Vector2, a: &Vector2) {
-        let x = a.0;
-        let y = a.1;
-        let mut len = x * x + y * y;
-        if (len > EPSILON) {
-            //TODO: evaluate use of glm_invsqrt here?
-            len = 1. / f32::sqrt(len);
+// `Vector2` here and `Vector2d` in `double`. `$F` is the element type, `$FloatArray` the js array of it
+// that `$fromFloatArray` reads, and the other names are the types of the same precision.
+macro_rules! define_vector2 {
+    (
+        $F:ident,
+        $FloatArray:ident,
+        $fromFloatArray:ident,
+        $Matrix2:ident,
+        $Matrix2d:ident,
+        $Matrix3:ident,
+        $Matrix4:ident,
+        $Quaternion:ident,
+        $Quaternion2:ident,
+        $Vector2:ident,
+        $Vector3:ident,
+        $Vector4:ident
+    ) => {
+        #[cfg(feature = "wasm")]
+        use js_sys::$FloatArray;
+        #[cfg(feature = "wasm")]
+        use wasm_bindgen::prelude::*;
+
+        use core::fmt;
+        use core::str::FromStr;
+
+        use super::common::*;
+        use super::matrix2::*;
+        use super::matrix2d::*;
+        use super::matrix3::*;
+        use super::matrix4::*;
+        use super::vector3::*;
+        use crate::kernels::{self, Elements};
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        #[derive(Clone, Copy, Debug)]
+        #[repr(C)]
+        pub struct $Vector2(pub $F, pub $F);
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        impl $Vector2 {
+            #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+            pub fn elements(&self) -> Box<[$F]> {
+                Box::new([self.0, self.1])
+            }
+
+            pub fn create() -> $Vector2 {
+                $Vector2(0., 0.)
+            }
+
+            #[allow(clippy::should_implement_trait)]
+            pub fn clone(a: &$Vector2) -> $Vector2 {
+                $Vector2(a.0, a.1)
+            }
+
+            pub fn fromValues(x: $F, y: $F) -> $Vector2 {
+                $Vector2(x, y)
+            }
+
+            pub fn copy(out: &mut $Vector2, a: &$Vector2) {
+                *out = *a;
+            }
+
+            pub fn set(out: &mut $Vector2, x: $F, y: $F) {
+                out.0 = x;
+                out.1 = y;
+            }
+
+            pub fn add(out: &mut $Vector2, a: &$Vector2, b: &$Vector2) {
+                kernels::add(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn subtract(out: &mut $Vector2, a: &$Vector2, b: &$Vector2) {
+                kernels::subtract(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn multiply(out: &mut $Vector2, a: &$Vector2, b: &$Vector2) {
+                kernels::multiply(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn divide(out: &mut $Vector2, a: &$Vector2, b: &$Vector2) {
+                kernels::divide(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn ceil(out: &mut $Vector2, a: &$Vector2) {
+                kernels::ceil(out.arrayMut(), a.array());
+            }
+
+            pub fn floor(out: &mut $Vector2, a: &$Vector2) {
+                kernels::floor(out.arrayMut(), a.array());
+            }
+
+            pub fn min(out: &mut $Vector2, a: &$Vector2, b: &$Vector2) {
+                kernels::min(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn max(out: &mut $Vector2, a: &$Vector2, b: &$Vector2) {
+                kernels::max(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn round(out: &mut $Vector2, a: &$Vector2) {
+                kernels::round(out.arrayMut(), a.array());
+            }
+
+            pub fn scale(out: &mut $Vector2, a: &$Vector2, b: $F) {
+                kernels::scale(out.arrayMut(), a.array(), b);
+            }
+
+            pub fn scaleAndAdd(out: &mut $Vector2, a: &$Vector2, b: &$Vector2, scale: $F) {
+                kernels::scaleAndAdd(out.arrayMut(), a.array(), b.array(), scale);
+            }
+
+            pub fn distance(a: &$Vector2, b: &$Vector2) -> $F {
+                let x = b.0 - a.0;
+                let y = b.1 - a.1;
+                $F::hypot(x, y)
+            }
+
+            pub fn squaredDistance(a: &$Vector2, b: &$Vector2) -> $F {
+                kernels::squaredDistance(a.array(), b.array())
+            }
+
+            pub fn len(a: &$Vector2) -> $F {
+                let x = a.0;
+                let y = a.1;
+                $F::hypot(x, y)
+            }
+
+            pub fn squaredLength(a: &$Vector2) -> $F {
+                kernels::squaredLength(a.array())
+            }
+
+            pub fn negate(out: &mut $Vector2, a: &$Vector2) {
+                kernels::negate(out.arrayMut(), a.array());
+            }
+
+            pub fn inverse(out: &mut $Vector2, a: &$Vector2) {
+                kernels::inverse(out.arrayMut(), a.array());
+            }
+
+            pub fn normalize(out: &mut $Vector2, a: &$Vector2) {
+                kernels::normalize(out.arrayMut(), a.array(), EPSILON);
+            }
+
+            pub fn dot(a: &$Vector2, b: &$Vector2) -> $F {
+                kernels::dot(a.array(), b.array())
+            }
+
+            pub fn cross(out: &mut $Vector3, a: &$Vector2, b: &$Vector2) {
+                let z = a.0 * b.1 - a.1 * b.0;
+                out.0 = 0.;
+                out.1 = 0.;
+                out.2 = z;
+            }
+
+            pub fn lerp(out: &mut $Vector2, a: &$Vector2, b: &$Vector2, t: $F) {
+                kernels::lerp(out.arrayMut(), a.array(), b.array(), t);
+            }
+
+            pub fn random(out: &mut $Vector2, scale: Option<$F>) {
+                let scale = scale.unwrap_or(1.);
+                let r = RANDOM() * 2.0 * PI;
+                out.0 = $F::cos(r) * scale;
+                out.1 = $F::sin(r) * scale;
+            }
+
+            pub fn transformMat2(out: &mut $Vector2, a: &$Vector2, m: &$Matrix2) {
+                let x = a.0;
+                let y = a.1;
+                out.0 = m.0 * x + m.2 * y;
+                out.1 = m.1 * x + m.3 * y;
+            }
+
+            pub fn transformMat2d(out: &mut $Vector2, a: &$Vector2, m: &$Matrix2d) {
+                let x = a.0;
+                let y = a.1;
+                out.0 = m.0 * x + m.2 * y + m.4;
+                out.1 = m.1 * x + m.3 * y + m.5;
+            }
+
+            pub fn transformMat3(out: &mut $Vector2, a: &$Vector2, m: &$Matrix3) {
+                let x = a.0;
+                let y = a.1;
+                out.0 = m.0 * x + m.3 * y + m.6;
+                out.1 = m.1 * x + m.4 * y + m.7;
+            }
+
+            pub fn transformMat4(out: &mut $Vector2, a: &$Vector2, m: &$Matrix4) {
+                let x = a.0;
+                let y = a.1;
+                out.0 = m.0 * x + m.4 * y + m.12;
+                out.1 = m.1 * x + m.5 * y + m.13;
+            }
+
+            pub fn rotate(out: &mut $Vector2, a: &$Vector2, b: &$Vector2, c: $F) {
+                //Translate point to the origin
+                let p0 = a.0 - b.0;
+                let p1 = a.1 - b.1;
+                let sinC = $F::sin(c);
+                let cosC = $F::cos(c);
+
+                //perform rotation and translate to correct position
+                out.0 = p0 * cosC - p1 * sinC + b.0;
+                out.1 = p0 * sinC + p1 * cosC + b.1;
+            }
+
+            pub fn angle(a: &$Vector2, b: &$Vector2) -> $F {
+                let x1 = a.0;
+                let y1 = a.1;
+                let x2 = b.0;
+                let y2 = b.1;
+
+                let mut len1 = x1 * x1 + y1 * y1;
+                if len1 > EPSILON {
+                    //TODO: evaluate use of glm_invsqrt here?
+                    len1 = 1. / $F::sqrt(len1);
+                }
+
+                let mut len2 = x2 * x2 + y2 * y2;
+                if len2 > EPSILON {
+                    //TODO: evaluate use of glm_invsqrt here?
+                    len2 = 1. / $F::sqrt(len2);
+                }
+
+                let cosine = (x1 * x2 + y1 * y2) * len1 * len2;
+
+                if cosine > 1.0 {
+                    0.
+                } else if cosine < -1.0 {
+                    PI
+                } else {
+                    $F::acos(cosine)
+                }
+            }
+
+            pub fn zero(out: &mut $Vector2) {
+                out.0 = 0.0;
+                out.1 = 0.0;
+            }
+
+            pub fn str(a: &$Vector2) -> String {
+                a.to_string()
+            }
+
+            pub fn exactEquals(a: &$Vector2, b: &$Vector2) -> bool {
+                kernels::exactEquals(a.array(), b.array())
+            }
+
+            pub fn equals(a: &$Vector2, b: &$Vector2) -> bool {
+                match getEqualsMode() {
+                    EqualsMode::Epsilon => $Vector2::equalsWithEpsilon(a, b, getEpsilon()),
+                    EqualsMode::Ulps => $Vector2::equalsWithUlps(a, b, getMaxUlps()),
+                }
+            }
+
+            pub fn equalsWithEpsilon(a: &$Vector2, b: &$Vector2, epsilon: $F) -> bool {
+                kernels::equalsWithEpsilon(a.array(), b.array(), epsilon)
+            }
+
+            pub fn equalsWithUlps(a: &$Vector2, b: &$Vector2, maxUlps: u32) -> bool {
+                kernels::equalsWithUlps(a.array(), b.array(), maxUlps)
+            }
+
+            pub fn sub(out: &mut $Vector2, a: &$Vector2, b: &$Vector2) {
+                $Vector2::subtract(out, a, b);
+            }
+
+            pub fn mul(out: &mut $Vector2, a: &$Vector2, b: &$Vector2) {
+                $Vector2::multiply(out, a, b);
+            }
+
+            pub fn div(out: &mut $Vector2, a: &$Vector2, b: &$Vector2) {
+                $Vector2::divide(out, a, b);
+            }
+
+            pub fn dist(a: &$Vector2, b: &$Vector2) -> $F {
+                $Vector2::distance(a, b)
+            }
+
+            pub fn sqrDist(a: &$Vector2, b: &$Vector2) -> $F {
+                $Vector2::squaredDistance(a, b)
+            }
+
+            pub fn sqrLen(a: &$Vector2) -> $F {
+                $Vector2::squaredLength(a)
+            }
         }
-        out.0 = a.0 * len;
-        out.1 = a.1 * len;
-    }
-
-    pub fn dot(a: &Vector2, b: &Vector2) -> f32 {
-        a.0 * b.0 + a.1 * b.1
-    }
-
-    pub fn cross(out: &mut Vector3, a: &Vector2, b: &Vector2) {
-        let z = a.0 * b.1 - a.1 * b.0;
-        out.0 = 0.;
-        out.1 = 0.;
-        out.2 = z;
-    }
-
-    pub fn lerp(out: &mut Vector2, a: &Vector2, b: &Vector2, t: f32) {
-        let ax = a.0;
-        let ay = a.1;
-        out.0 = ax + t * (b.0 - ax);
-        out.1 = ay + t * (b.1 - ay);
-    }
-
-    pub fn random(out: &mut Vector2, scale: Option<f32>) {
-        let mut s = 1.;
-        match scale {
-            Some(value) => s = value,
-            None => {}
-        };
-        let scale = s;
-        let r = RANDOM() * 2.0 * PI;
-        out.0 = f32::cos(r) * scale;
-        out.1 = f32::sin(r) * scale;
-    }
-
-    pub fn transformMat2(out: &mut Vector2, a: &Vector2, m: &Matrix2) {
-        let x = a.0;
-        let y = a.1;
-        out.0 = m.0 * x + m.2 * y;
-        out.1 = m.1 * x + m.3 * y;
-    }
-
-    pub fn transformMat2d(out: &mut Vector2, a: &Vector2, m: &Matrix2d) {
-        let x = a.0;
-        let y = a.1;
-        out.0 = m.0 * x + m.2 * y + m.4;
-        out.1 = m.1 * x + m.3 * y + m.5;
-    }
-
-    pub fn transformMat3(out: &mut Vector2, a: &Vector2, m: &Matrix3) {
-        let x = a.0;
-        let y = a.1;
-        out.0 = m.0 * x + m.3 * y + m.6;
-        out.1 = m.1 * x + m.4 * y + m.7;
-    }
-
-    pub fn transformMat4(out: &mut Vector2, a: &Vector2, m: &Matrix4) {
-        let x = a.0;
-        let y = a.1;
-        out.0 = m.0 * x + m.4 * y + m.12;
-        out.1 = m.1 * x + m.5 * y + m.13;
-    }
-
-    pub fn rotate(out: &mut Vector2, a: &Vector2, b: &Vector2, c: f32) {
-        //Translate point to the origin
-        let p0 = a.0 - b.0;
-        let p1 = a.1 - b.1;
-        let sinC = f32::sin(c);
-        let cosC = f32::cos(c);
-
-        //perform rotation and translate to correct position
-        out.0 = p0 * cosC - p1 * sinC + b.0;
-        out.1 = p0 * sinC + p1 * cosC + b.1;
-    }
-
-    pub fn angle(a: &Vector2, b: &Vector2) -> f32 {
-        let x1 = a.0;
-        let y1 = a.1;
-        let x2 = b.0;
-        let y2 = b.1;
-
-        let mut len1 = x1 * x1 + y1 * y1;
-        if (len1 > EPSILON) {
-            //TODO: evaluate use of glm_invsqrt here?
-            len1 = 1. / f32::sqrt(len1);
+
+        // Flat f32 buffers, such as the uniforms or the batch functions.
+        impl $Vector2 {
+            pub fn fromSlice(a: &[$F]) -> $Vector2 {
+                $Vector2(a[0], a[1])
+            }
+
+            pub fn writeSlice(&self, out: &mut [$F]) {
+                out[..2].copy_from_slice(self.array());
+            }
+
+            pub fn asSlice(&self) -> &[$F] {
+                self.array()
+            }
+        }
+
+        // `#[repr(C)]` keeps the 2 elements contiguous.
+        unsafe impl Elements<$F, 2> for $Vector2 {}
+
+        #[cfg(feature = "wasm")]
+        #[wasm_bindgen]
+        impl $Vector2 {
+            // Aliases the elements in wasm memory without copying. The view is detached when the memory grows,
+            // so don't keep it across calls that may allocate.
+            pub fn view(&self) -> $FloatArray {
+                unsafe { $FloatArray::view(self.asSlice()) }
+            }
+
+            // Reads the first 2 elements of `a`, without allocating in wasm memory. Throws a `RangeError`
+            // when `a` is shorter.
+            pub fn $fromFloatArray(a: &$FloatArray) -> Result<$Vector2, JsValue> {
+                check_length(2, a.length() as usize)?;
+                let mut elements = [0.; 2];
+                a.subarray(0, 2).copy_to(&mut elements);
+                Ok($Vector2::fromSlice(&elements))
+            }
+
+            pub fn copyFromArray(out: &mut $Vector2, a: &$FloatArray) -> Result<(), JsValue> {
+                *out = $Vector2::$fromFloatArray(a)?;
+                Ok(())
+            }
+        }
+
+        impl PartialEq for $Vector2 {
+            fn eq(&self, other: &$Vector2) -> bool {
+                $Vector2::exactEquals(self, other)
+            }
         }
 
-        let mut len2 = x2 * x2 + y2 * y2;
-        if (len2 > EPSILON) {
-            //TODO: evaluate use of glm_invsqrt here?
-            len2 = 1. / f32::sqrt(len2);
+        impl ApproxEq for $Vector2 {
+            fn approx_eq(&self, other: &$Vector2) -> bool {
+                $Vector2::equals(self, other)
+            }
         }
 
-        let cosine = (x1 * x2 + y1 * y2) * len1 * len2;
+        impl Default for $Vector2 {
+            fn default() -> $Vector2 {
+                $Vector2::create()
+            }
+        }
+
+        impl fmt::Display for $Vector2 {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "vec2({}, {})", self.0, self.1)
+            }
+        }
+
+        impl FromStr for $Vector2 {
+            type Err = ParseError;
 
-        if (cosine > 1.0) {
-            0.
-        } else if (cosine < -1.0) {
-            PI
-        } else {
-            f32::acos(cosine)
+            fn from_str(s: &str) -> Result<$Vector2, ParseError> {
+                let mut v = [0.; 2];
+                parse_values(s, "vec2", &mut v)?;
+                Ok($Vector2(v[0], v[1]))
+            }
         }
-    }
-
-    pub fn zero(out: &mut Vector2) {
-        out.0 = 0.0;
-        out.1 = 0.0;
-    }
-
-    // pub fn str(a: &Vector2) {
-    //   return "vec2(" + a.0 + ", " + a.1 + ")";
-    // }
-
-    pub fn exactEquals(a: &Vector2, b: &Vector2) -> bool {
-        a.0 == b.0 && a.1 == b.1
-    }
-
-    pub fn equals(a: &Vector2, b: &Vector2) -> bool {
-        let a0 = a.0;
-        let a1 = a.1;
-        let b0 = b.0;
-        let b1 = b.1;
-        f32::abs(a0 - b0) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a0), f32::abs(b0)))
-            && f32::abs(a1 - b1) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a1), f32::abs(b1)))
-    }
-
-    pub fn sub(out: &mut Vector2, a: &Vector2, b: &Vector2) {
-        Vector2::subtract(out, a, b);
-    }
-
-    pub fn mul(out: &mut Vector2, a: &Vector2, b: &Vector2) {
-        Vector2::multiply(out, a, b);
-    }
-
-    pub fn div(out: &mut Vector2, a: &Vector2, b: &Vector2) {
-        Vector2::divide(out, a, b);
-    }
-
-    pub fn dist(a: &Vector2, b: &Vector2) -> f32 {
-        Vector2::distance(a, b)
-    }
-
-    pub fn sqrDist(a: &Vector2, b: &Vector2) -> f32 {
-        Vector2::squaredDistance(a, b)
-    }
-
-    pub fn sqrLen(a: &Vector2) -> f32 {
-        Vector2::squaredLength(a)
-    }
+
+        #[cfg(test)]
+        mod tests {
+            use super::*;
+            use crate::spec_helper::*;
+
+            fn setup() -> ($Vector2, $Vector2, $Vector2) {
+                (
+                    $Vector2::fromValues(0., 0.),
+                    $Vector2::fromValues(1., 2.),
+                    $Vector2::fromValues(3., 4.),
+                )
+            }
+
+            #[test]
+            fn create() {
+                let result = $Vector2::create();
+                expect_equalish(&result.elements(), &[0., 0.]);
+            }
+
+            #[test]
+            fn clone() {
+                let (_, vecA, _) = &mut setup();
+                let result = $Vector2::clone(vecA);
+                expect_equalish(&result.elements(), &vecA.elements());
+            }
+
+            #[test]
+            fn fromValues() {
+                let result = $Vector2::fromValues(1., 2.);
+                expect_equalish(&result.elements(), &[1., 2.]);
+            }
+
+            #[test]
+            fn copy() {
+                let (out, vecA, _) = &mut setup();
+                $Vector2::copy(out, vecA);
+                expect_equalish(&out.elements(), &[1., 2.]);
+            }
+
+            #[test]
+            fn set() {
+                let (out, _, _) = &mut setup();
+                $Vector2::set(out, 1., 2.);
+                expect_equalish(&out.elements(), &[1., 2.]);
+            }
+
+            #[test]
+            fn add() {
+                let (out, vecA, vecB) = &mut setup();
+                $Vector2::add(out, vecA, vecB);
+                expect_equalish(&out.elements(), &[4., 6.]);
+                expect_equalish(&vecA.elements(), &[1., 2.]);
+                expect_equalish(&vecB.elements(), &[3., 4.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector2::add(vecA, &$Vector2::clone(vecA), vecB);
+                expect_equalish(&vecA.elements(), &[4., 6.]);
+                expect_equalish(&vecB.elements(), &[3., 4.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector2::add(vecB, vecA, &$Vector2::clone(vecB));
+                expect_equalish(&vecB.elements(), &[4., 6.]);
+                expect_equalish(&vecA.elements(), &[1., 2.]);
+            }
+
+            #[test]
+            fn subtract() {
+                let (out, vecA, vecB) = &mut setup();
+                $Vector2::subtract(out, vecA, vecB);
+                expect_equalish(&out.elements(), &[-2., -2.]);
+                expect_equalish(&vecA.elements(), &[1., 2.]);
+                expect_equalish(&vecB.elements(), &[3., 4.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector2::subtract(vecA, &$Vector2::clone(vecA), vecB);
+                expect_equalish(&vecA.elements(), &[-2., -2.]);
+                expect_equalish(&vecB.elements(), &[3., 4.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector2::subtract(vecB, vecA, &$Vector2::clone(vecB));
+                expect_equalish(&vecB.elements(), &[-2., -2.]);
+                expect_equalish(&vecA.elements(), &[1., 2.]);
+            }
+
+            #[test]
+            fn multiply() {
+                let (out, vecA, vecB) = &mut setup();
+                $Vector2::multiply(out, vecA, vecB);
+                expect_equalish(&out.elements(), &[3., 8.]);
+                expect_equalish(&vecA.elements(), &[1., 2.]);
+                expect_equalish(&vecB.elements(), &[3., 4.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector2::multiply(vecA, &$Vector2::clone(vecA), vecB);
+                expect_equalish(&vecA.elements(), &[3., 8.]);
+                expect_equalish(&vecB.elements(), &[3., 4.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector2::multiply(vecB, vecA, &$Vector2::clone(vecB));
+                expect_equalish(&vecB.elements(), &[3., 8.]);
+                expect_equalish(&vecA.elements(), &[1., 2.]);
+            }
+
+            #[test]
+            fn divide() {
+                let (out, vecA, vecB) = &mut setup();
+                $Vector2::divide(out, vecA, vecB);
+                expect_equalish(&out.elements(), &[0.3333333, 0.5]);
+                expect_equalish(&vecA.elements(), &[1., 2.]);
+                expect_equalish(&vecB.elements(), &[3., 4.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector2::divide(vecA, &$Vector2::clone(vecA), vecB);
+                expect_equalish(&vecA.elements(), &[0.3333333, 0.5]);
+                expect_equalish(&vecB.elements(), &[3., 4.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector2::divide(vecB, vecA, &$Vector2::clone(vecB));
+                expect_equalish(&vecB.elements(), &[0.3333333, 0.5]);
+                expect_equalish(&vecA.elements(), &[1., 2.]);
+            }
+
+            #[test]
+            fn ceil() {
+                let (out, _, _) = &mut setup();
+                let vecA = &mut $Vector2::fromValues(std::$F::consts::E, PI);
+                $Vector2::ceil(out, vecA);
+                expect_equalish(&out.elements(), &[3., 4.]);
+                expect_equalish(&vecA.elements(), &[std::$F::consts::E, PI]);
+
+                $Vector2::ceil(vecA, &$Vector2::clone(vecA));
+                expect_equalish(&vecA.elements(), &[3., 4.]);
+            }
+
+            #[test]
+            fn floor() {
+                let (out, _, _) = &mut setup();
+                let vecA = &mut $Vector2::fromValues(std::$F::consts::E, PI);
+                $Vector2::floor(out, vecA);
+                expect_equalish(&out.elements(), &[2., 3.]);
+                expect_equalish(&vecA.elements(), &[std::$F::consts::E, PI]);
+
+                $Vector2::floor(vecA, &$Vector2::clone(vecA));
+                expect_equalish(&vecA.elements(), &[2., 3.]);
+            }
+
+            #[test]
+            fn min() {
+                let out = &mut $Vector2::create();
+                let vecA = &mut $Vector2::fromValues(1., 4.);
+                let vecB = &mut $Vector2::fromValues(3., 2.);
+                $Vector2::min(out, vecA, vecB);
+                expect_equalish(&out.elements(), &[1., 2.]);
+                expect_equalish(&vecA.elements(), &[1., 4.]);
+                expect_equalish(&vecB.elements(), &[3., 2.]);
+
+                $Vector2::min(vecA, &$Vector2::clone(vecA), vecB);
+                expect_equalish(&vecA.elements(), &[1., 2.]);
+                expect_equalish(&vecB.elements(), &[3., 2.]);
+
+                let vecA = &mut $Vector2::fromValues(1., 4.);
+                $Vector2::min(vecB, vecA, &$Vector2::clone(vecB));
+                expect_equalish(&vecB.elements(), &[1., 2.]);
+                expect_equalish(&vecA.elements(), &[1., 4.]);
+            }
+
+            #[test]
+            fn max() {
+                let out = &mut $Vector2::create();
+                let vecA = &mut $Vector2::fromValues(1., 4.);
+                let vecB = &mut $Vector2::fromValues(3., 2.);
+                $Vector2::max(out, vecA, vecB);
+                expect_equalish(&out.elements(), &[3., 4.]);
+                expect_equalish(&vecA.elements(), &[1., 4.]);
+                expect_equalish(&vecB.elements(), &[3., 2.]);
+
+                $Vector2::max(vecA, &$Vector2::clone(vecA), vecB);
+                expect_equalish(&vecA.elements(), &[3., 4.]);
+                expect_equalish(&vecB.elements(), &[3., 2.]);
+
+                let vecA = &mut $Vector2::fromValues(1., 4.);
+                $Vector2::max(vecB, vecA, &$Vector2::clone(vecB));
+                expect_equalish(&vecB.elements(), &[3., 4.]);
+                expect_equalish(&vecA.elements(), &[1., 4.]);
+            }
+
+            #[test]
+            fn round() {
+                let (out, _, _) = &mut setup();
+                let vecA = &mut $Vector2::fromValues(std::$F::consts::E, PI);
+                $Vector2::round(out, vecA);
+                expect_equalish(&out.elements(), &[3., 3.]);
+                expect_equalish(&vecA.elements(), &[std::$F::consts::E, PI]);
+
+                $Vector2::round(vecA, &$Vector2::clone(vecA));
+                expect_equalish(&vecA.elements(), &[3., 3.]);
+            }
+
+            #[test]
+            fn scale() {
+                let (out, vecA, _) = &mut setup();
+                $Vector2::scale(out, vecA, 2.);
+                expect_equalish(&out.elements(), &[2., 4.]);
+                expect_equalish(&vecA.elements(), &[1., 2.]);
+
+                $Vector2::scale(vecA, &$Vector2::clone(vecA), 2.);
+                expect_equalish(&vecA.elements(), &[2., 4.]);
+            }
+
+            #[test]
+            fn scaleAndAdd() {
+                let (out, vecA, vecB) = &mut setup();
+                $Vector2::scaleAndAdd(out, vecA, vecB, 0.5);
+                expect_equalish(&out.elements(), &[2.5, 4.]);
+                expect_equalish(&vecA.elements(), &[1., 2.]);
+                expect_equalish(&vecB.elements(), &[3., 4.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector2::scaleAndAdd(vecA, &$Vector2::clone(vecA), vecB, 0.5);
+                expect_equalish(&vecA.elements(), &[2.5, 4.]);
+                expect_equalish(&vecB.elements(), &[3., 4.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector2::scaleAndAdd(vecB, vecA, &$Vector2::clone(vecB), 0.5);
+                expect_equalish(&vecB.elements(), &[2.5, 4.]);
+                expect_equalish(&vecA.elements(), &[1., 2.]);
+            }
+
+            #[test]
+            fn distance() {
+                let (_, vecA, vecB) = &mut setup();
+                expect_equalish_scalar($Vector2::distance(vecA, vecB), 2.828427);
+            }
+
+            #[test]
+            fn squaredDistance() {
+                let (_, vecA, vecB) = &mut setup();
+                expect_equalish_scalar($Vector2::squaredDistance(vecA, vecB), 8.);
+            }
+
+            #[test]
+            fn length() {
+                let (_, vecA, _) = &mut setup();
+                expect_equalish_scalar($Vector2::len(vecA), 2.236067);
+            }
+
+            #[test]
+            fn squaredLength() {
+                let (_, vecA, _) = &mut setup();
+                expect_equalish_scalar($Vector2::squaredLength(vecA), 5.);
+            }
+
+            #[test]
+            fn negate() {
+                let (out, vecA, _) = &mut setup();
+                $Vector2::negate(out, vecA);
+                expect_equalish(&out.elements(), &[-1., -2.]);
+                expect_equalish(&vecA.elements(), &[1., 2.]);
+
+                $Vector2::negate(vecA, &$Vector2::clone(vecA));
+                expect_equalish(&vecA.elements(), &[-1., -2.]);
+            }
+
+            #[test]
+            fn normalize() {
+                let (out, _, _) = &mut setup();
+                let vecA = &mut $Vector2::fromValues(5., 0.);
+                $Vector2::normalize(out, vecA);
+                expect_equalish(&out.elements(), &[1., 0.]);
+                expect_equalish(&vecA.elements(), &[5., 0.]);
+
+                $Vector2::normalize(vecA, &$Vector2::clone(vecA));
+                expect_equalish(&vecA.elements(), &[1., 0.]);
+            }
+
+            #[test]
+            fn dot() {
+                let (_, vecA, vecB) = &mut setup();
+                expect_equalish_scalar($Vector2::dot(vecA, vecB), 11.);
+                expect_equalish(&vecA.elements(), &[1., 2.]);
+                expect_equalish(&vecB.elements(), &[3., 4.]);
+            }
+
+            #[test]
+            fn cross() {
+                let (_, vecA, vecB) = &mut setup();
+                let out3 = &mut $Vector3::fromValues(0., 0., 0.);
+                $Vector2::cross(out3, vecA, vecB);
+                expect_equalish(&out3.elements(), &[0., 0., -2.]);
+                expect_equalish(&vecA.elements(), &[1., 2.]);
+                expect_equalish(&vecB.elements(), &[3., 4.]);
+            }
+
+            #[test]
+            fn lerp() {
+                let (out, vecA, vecB) = &mut setup();
+                $Vector2::lerp(out, vecA, vecB, 0.5);
+                expect_equalish(&out.elements(), &[2., 3.]);
+                expect_equalish(&vecA.elements(), &[1., 2.]);
+                expect_equalish(&vecB.elements(), &[3., 4.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector2::lerp(vecA, &$Vector2::clone(vecA), vecB, 0.5);
+                expect_equalish(&vecA.elements(), &[2., 3.]);
+                expect_equalish(&vecB.elements(), &[3., 4.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector2::lerp(vecB, vecA, &$Vector2::clone(vecB), 0.5);
+                expect_equalish(&vecB.elements(), &[2., 3.]);
+                expect_equalish(&vecA.elements(), &[1., 2.]);
+            }
+
+            #[test]
+            fn random() {
+                let _lock = lock_random();
+                let (out, _, _) = &mut setup();
+                $Vector2::random(out, None);
+                expect_equalish_scalar($Vector2::len(out), 1.0);
+
+                $Vector2::random(out, Some(5.0));
+                expect_equalish_scalar($Vector2::len(out), 5.0);
+            }
+
+            #[test]
+            fn transformMat2() {
+                let (out, vecA, _) = &mut setup();
+                let matA = &$Matrix2::fromValues(1., 2., 3., 4.);
+                $Vector2::transformMat2(out, vecA, matA);
+                expect_equalish(&out.elements(), &[7., 10.]);
+                expect_equalish(&vecA.elements(), &[1., 2.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4.]);
+
+                $Vector2::transformMat2(vecA, &$Vector2::clone(vecA), matA);
+                expect_equalish(&vecA.elements(), &[7., 10.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn transformMat2d() {
+                let (out, vecA, _) = &mut setup();
+                let matA = &$Matrix2d::fromValues(1., 2., 3., 4., 5., 6.);
+                $Vector2::transformMat2d(out, vecA, matA);
+                expect_equalish(&out.elements(), &[12., 16.]);
+                expect_equalish(&vecA.elements(), &[1., 2.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6.]);
+
+                $Vector2::transformMat2d(vecA, &$Vector2::clone(vecA), matA);
+                expect_equalish(&vecA.elements(), &[12., 16.]);
+                expect_equalish(&matA.elements(), &[1., 2., 3., 4., 5., 6.]);
+            }
+
+            #[test]
+            fn rotate() {
+                let (out, _, _) = &mut setup();
+                // rotation around world origin [0, 0, 0]
+                $Vector2::rotate(
+                    out,
+                    &$Vector2::fromValues(0., 1.),
+                    &$Vector2::fromValues(0., 0.),
+                    PI,
+                );
+                expect_equalish(&out.elements(), &[0., -1.]);
+
+                // rotation around an arbitrary origin
+                $Vector2::rotate(
+                    out,
+                    &$Vector2::fromValues(6., -5.),
+                    &$Vector2::fromValues(0., -5.),
+                    PI,
+                );
+                expect_equalish(&out.elements(), &[-6., -5.]);
+            }
+
+            #[test]
+            fn angle() {
+                let vecA = &$Vector2::fromValues(1., 0.);
+                let vecB = &$Vector2::fromValues(1., 2.);
+                expect_equalish_scalar($Vector2::angle(vecA, vecB), 1.10714);
+                expect_equalish(&vecA.elements(), &[1., 0.]);
+                expect_equalish(&vecB.elements(), &[1., 2.]);
+            }
+
+            #[test]
+            fn exactEquals() {
+                let vecA = &$Vector2::fromValues(0., 1.);
+                let vecB = &$Vector2::fromValues(0., 1.);
+                let vecC = &$Vector2::fromValues(1., 2.);
+                assert!($Vector2::exactEquals(vecA, vecB));
+                assert!(!$Vector2::exactEquals(vecA, vecC));
+                expect_equalish(&vecA.elements(), &[0., 1.]);
+                expect_equalish(&vecB.elements(), &[0., 1.]);
+            }
+
+            #[test]
+            fn equals() {
+                let vecA = &$Vector2::fromValues(0., 1.);
+                let vecB = &$Vector2::fromValues(0., 1.);
+                let vecC = &$Vector2::fromValues(1., 2.);
+                let vecD = &$Vector2::fromValues(1e-16, 1.);
+                assert!($Vector2::equals(vecA, vecB));
+                assert!(!$Vector2::equals(vecA, vecC));
+                assert!($Vector2::equals(vecA, vecD));
+                expect_equalish(&vecA.elements(), &[0., 1.]);
+                expect_equalish(&vecB.elements(), &[0., 1.]);
+            }
+
+            #[test]
+            fn zero() {
+                let vecA = &mut $Vector2::fromValues(1., 2.);
+                $Vector2::zero(vecA);
+                expect_equalish(&vecA.elements(), &[0., 0.]);
+            }
+
+            #[test]
+            fn traits() {
+                let a = $Vector2::fromValues(1., 2.);
+                let b = a;
+                assert_eq!(a, b);
+                assert_ne!(a, $Vector2::fromValues(1., 3.));
+                assert_ne!(a, $Vector2::fromValues(1., 2.00001));
+                assert!(a.approx_eq(&$Vector2::fromValues(1., 2.00001)));
+                assert!(!a.approx_eq(&$Vector2::fromValues(1., 3.)));
+                assert_eq!($Vector2::default(), $Vector2::create());
+                assert_eq!(
+                    format!("{:?}", $Vector2::create()),
+                    concat!(stringify!($Vector2), "(0.0, 0.0)")
+                );
+            }
+
+            #[test]
+            fn str() {
+                let a = $Vector2::fromValues(1., 2.);
+                assert_eq!($Vector2::str(&a), "vec2(1, 2)");
+                assert_eq!("vec2(1, 2)".parse::<$Vector2>(), Ok(a));
+            }
+
+            #[test]
+            fn equalsWith() {
+                let a = &$Vector2::fromValues(1., 2.);
+                let b = &$Vector2::fromValues(1.001, 2.);
+                let c = &$Vector2::fromValues($F::from_bits($F::to_bits(1.) + 2), 2.);
+                assert!(!$Vector2::equalsWithEpsilon(a, b, 0.0001));
+                assert!($Vector2::equalsWithEpsilon(a, b, 0.01));
+                assert!($Vector2::equalsWithUlps(a, c, 2));
+                assert!(!$Vector2::equalsWithUlps(a, c, 1));
+                assert!(!$Vector2::equalsWithUlps(a, b, 4));
+            }
+
+            #[test]
+            fn setSeed() {
+                let _lock = lock_random();
+                let (out, _, _) = &mut setup();
+                crate::common::setSeed(42);
+                $Vector2::random(out, Some(2.));
+                let first = $Vector2::clone(out);
+                $Vector2::random(out, Some(2.));
+                assert_ne!(out, &first);
+
+                crate::common::setSeed(42);
+                $Vector2::random(out, Some(2.));
+                assert_eq!(out, &first);
+            }
+
+            #[test]
+            fn slice() {
+                let a = $Vector2::fromValues(1., 2.);
+                assert_eq!(a.asSlice(), &a.elements()[..]);
+
+                let buffer = &mut [0.; 3];
+                a.writeSlice(&mut buffer[1..]);
+                assert_eq!($Vector2::fromSlice(&buffer[1..]), a);
+            }
+        }
+    };
 }
+
+pub(crate) use define_vector2;
+
+define_vector2!(
+    f32,
+    Float32Array,
+    fromFloat32Array,
+    Matrix2,
+    Matrix2d,
+    Matrix3,
+    Matrix4,
+    Quaternion,
+    Quaternion2,
+    Vector2,
+    Vector3,
+    Vector4
+);
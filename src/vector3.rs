@@ -4,438 +4,1183 @@
  * @Link   : http://dtysky.moe
  * @Date   : 2019/2/7 下午9:53:03
  */
-use wasm_bindgen::prelude::*;
-
-use super::common::*;
-use super::matrix3::*;
-use super::matrix4::*;
-use super::quaternion::*;
-
-#[wasm_bindgen]
-pub struct Vector3(pub f32, pub f32, pub f32);
-
-#[wasm_bindgen]
-impl Vector3 {
-    #[wasm_bindgen(getter)]
-    pub fn elements(&self) -> Box<[f32]> {
-        Box::new([self.0, self.1, self.2])
-    }
-
-    pub fn create() -> Vector3 {
-        Vector3(0., 0., 0.)
-    }
-
-    pub fn clone(a: &Vector3) -> Vector3 {
-        Vector3(a.0, a.1, a.2)
-    }
-
-    pub fn len(a: &Vector3) -> f32 {
-        let x = a.0;
-        let y = a.1;
-        let z = a.2;
-        (x.powi(2) + y.powi(2) + z.powi(2)).sqrt()
-    }
-
-    pub fn fromValues(x: f32, y: f32, z: f32) -> Vector3 {
-        Vector3(x, y, z)
-    }
-
-    pub fn copy(out: &mut Vector3, a: &Vector3) {
-        out.0 = a.0;
-        out.1 = a.1;
-        out.2 = a.2;
-    }
-
-    pub fn set(out: &mut Vector3, x: f32, y: f32, z: f32) {
-        out.0 = x;
-        out.1 = y;
-        out.2 = z;
-    }
-
-    pub fn add(out: &mut Vector3, a: &Vector3, b: &Vector3) {
-        out.0 = a.0 + b.0;
-        out.1 = a.1 + b.1;
-        out.2 = a.2 + b.2;
-    }
-
-    pub fn subtract(out: &mut Vector3, a: &Vector3, b: &Vector3) {
-        out.0 = a.0 - b.0;
-        out.1 = a.1 - b.1;
-        out.2 = a.2 - b.2;
-    }
-
-    pub fn multiply(out: &mut Vector3, a: &Vector3, b: &Vector3) {
-        out.0 = a.0 * b.0;
-        out.1 = a.1 * b.1;
-        out.2 = a.2 * b.2;
-    }
-
-    pub fn divide(out: &mut Vector3, a: &Vector3, b: &Vector3) {
-        out.0 = a.0 / b.0;
-        out.1 = a.1 / b.1;
-        out.2 = a.2 / b.2;
-    }
-
-    pub fn ceil(out: &mut Vector3, a: &Vector3) {
-        out.0 = f32::ceil(a.0);
-        out.1 = f32::ceil(a.1);
-        out.2 = f32::ceil(a.2);
-    }
-
-    pub fn floor(out: &mut Vector3, a: &Vector3) {
-        out.0 = f32::floor(a.0);
-        out.1 = f32::floor(a.1);
-        out.2 = f32::floor(a.2);
-    }
-
-    pub fn min(out: &mut Vector3, a: &Vector3, b: &Vector3) {
-        out.0 = f32::min(a.0, b.0);
-        out.1 = f32::min(a.1, b.1);
-        out.2 = f32::min(a.2, b.2);
-    }
-
-    pub fn max(out: &mut Vector3, a: &Vector3, b: &Vector3) {
-        out.0 = f32::max(a.0, b.0);
-        out.1 = f32::max(a.1, b.1);
-        out.2 = f32::max(a.2, b.2);
-    }
-
-    pub fn round(out: &mut Vector3, a: &Vector3) {
-        out.0 = f32::round(a.0);
-        out.1 = f32::round(a.1);
-        out.2 = f32::round(a.2);
-    }
-
-    pub fn scale(out: &mut Vector3, a: &Vector3, b: f32) {
-        out.0 = a.0 * b;
-        out.1 = a.1 * b;
-        out.2 = a.2 * b;
-    }
-
-    pub fn scaleAndAdd(out: &mut Vector3, a: &Vector3, b: &Vector3, scale: f32) {
-        out.0 = a.0 + (b.0 * scale);
-        out.1 = a.1 + (b.1 * scale);
-        out.2 = a.2 + (b.2 * scale);
-    }
-
-    pub fn distance(a: &Vector3, b: &Vector3) -> f32 {
-        let x = b.0 - a.0;
-        let y = b.1 - a.1;
-        let z = b.2 - a.2;
-        (x.powi(2) + y.powi(2) + z.powi(2)).sqrt()
-    }
-
-    pub fn squaredDistance(a: &Vector3, b: &Vector3) -> f32 {
-        let x = b.0 - a.0;
-        let y = b.1 - a.1;
-        let z = b.2 - a.2;
-        x * x + y * y + z * z
-    }
-
-    pub fn squaredLength(a: &Vector3) -> f32 {
-        let x = a.0;
-        let y = a.1;
-        let z = a.2;
-        x * x + y * y + z * z
-    }
-
-    pub fn negate(out: &mut Vector3, a: &Vector3) {
-        out.0 = -a.0;
-        out.1 = -a.1;
-        out.2 = -a.2;
-    }
-
-    pub fn inverse(out: &mut Vector3, a: &Vector3) {
-        out.0 = 1.0 / a.0;
-        out.1 = 1.0 / a.1;
-        out.2 = 1.0 / a.2;
-    }
-
-    pub fn normalize(out: &mut Vector3, a: &Vector3) {
-        let x = a.0;
-        let y = a.1;
-        let z = a.2;
-        let mut len = x * x + y * y + z * z;
-        if len > EPSILON {
-            //TODO: evaluate use of glm_invsqrt here?
-            len = 1. / f32::sqrt(len);
+// `Vector3` here and `Vector3d` in `double`. `$F` is the element type, `$FloatArray` the js array of it
+// that `$fromFloatArray` reads, and the other names are the types of the same precision.
+macro_rules! define_vector3 {
+    (
+        $F:ident,
+        $FloatArray:ident,
+        $fromFloatArray:ident,
+        $Matrix2:ident,
+        $Matrix2d:ident,
+        $Matrix3:ident,
+        $Matrix4:ident,
+        $Quaternion:ident,
+        $Quaternion2:ident,
+        $Vector2:ident,
+        $Vector3:ident,
+        $Vector4:ident
+    ) => {
+        #[cfg(feature = "wasm")]
+        use js_sys::$FloatArray;
+        #[cfg(feature = "wasm")]
+        use wasm_bindgen::prelude::*;
+
+        use core::fmt;
+        use core::str::FromStr;
+
+        use super::common::*;
+        use super::matrix3::*;
+        use super::matrix4::*;
+        use super::quaternion::*;
+        use crate::kernels::{self, Elements};
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        #[derive(Clone, Copy, Debug)]
+        #[repr(C)]
+        pub struct $Vector3(pub $F, pub $F, pub $F);
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        impl $Vector3 {
+            #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+            pub fn elements(&self) -> Box<[$F]> {
+                Box::new([self.0, self.1, self.2])
+            }
+
+            pub fn create() -> $Vector3 {
+                $Vector3(0., 0., 0.)
+            }
+
+            #[allow(clippy::should_implement_trait)]
+            pub fn clone(a: &$Vector3) -> $Vector3 {
+                $Vector3(a.0, a.1, a.2)
+            }
+
+            pub fn len(a: &$Vector3) -> $F {
+                kernels::length(a.array())
+            }
+
+            pub fn fromValues(x: $F, y: $F, z: $F) -> $Vector3 {
+                $Vector3(x, y, z)
+            }
+
+            pub fn copy(out: &mut $Vector3, a: &$Vector3) {
+                *out = *a;
+            }
+
+            pub fn set(out: &mut $Vector3, x: $F, y: $F, z: $F) {
+                out.0 = x;
+                out.1 = y;
+                out.2 = z;
+            }
+
+            pub fn add(out: &mut $Vector3, a: &$Vector3, b: &$Vector3) {
+                kernels::add(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn subtract(out: &mut $Vector3, a: &$Vector3, b: &$Vector3) {
+                kernels::subtract(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn multiply(out: &mut $Vector3, a: &$Vector3, b: &$Vector3) {
+                kernels::multiply(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn divide(out: &mut $Vector3, a: &$Vector3, b: &$Vector3) {
+                kernels::divide(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn ceil(out: &mut $Vector3, a: &$Vector3) {
+                kernels::ceil(out.arrayMut(), a.array());
+            }
+
+            pub fn floor(out: &mut $Vector3, a: &$Vector3) {
+                kernels::floor(out.arrayMut(), a.array());
+            }
+
+            pub fn min(out: &mut $Vector3, a: &$Vector3, b: &$Vector3) {
+                kernels::min(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn max(out: &mut $Vector3, a: &$Vector3, b: &$Vector3) {
+                kernels::max(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn round(out: &mut $Vector3, a: &$Vector3) {
+                kernels::round(out.arrayMut(), a.array());
+            }
+
+            pub fn scale(out: &mut $Vector3, a: &$Vector3, b: $F) {
+                kernels::scale(out.arrayMut(), a.array(), b);
+            }
+
+            pub fn scaleAndAdd(out: &mut $Vector3, a: &$Vector3, b: &$Vector3, scale: $F) {
+                kernels::scaleAndAdd(out.arrayMut(), a.array(), b.array(), scale);
+            }
+
+            pub fn distance(a: &$Vector3, b: &$Vector3) -> $F {
+                kernels::distance(a.array(), b.array())
+            }
+
+            pub fn squaredDistance(a: &$Vector3, b: &$Vector3) -> $F {
+                kernels::squaredDistance(a.array(), b.array())
+            }
+
+            pub fn squaredLength(a: &$Vector3) -> $F {
+                kernels::squaredLength(a.array())
+            }
+
+            pub fn negate(out: &mut $Vector3, a: &$Vector3) {
+                kernels::negate(out.arrayMut(), a.array());
+            }
+
+            pub fn inverse(out: &mut $Vector3, a: &$Vector3) {
+                kernels::inverse(out.arrayMut(), a.array());
+            }
+
+            pub fn normalize(out: &mut $Vector3, a: &$Vector3) {
+                kernels::normalize(out.arrayMut(), a.array(), EPSILON);
+            }
+
+            pub fn dot(a: &$Vector3, b: &$Vector3) -> $F {
+                kernels::dot(a.array(), b.array())
+            }
+
+            pub fn cross(out: &mut $Vector3, a: &$Vector3, b: &$Vector3) {
+                if core::ptr::eq(out, a) {
+                    let ax = out.0;
+                    let ay = out.1;
+                    let az = out.2;
+                    let bx = b.0;
+                    let by = b.1;
+                    let bz = b.2;
+
+                    out.0 = ay * bz - az * by;
+                    out.1 = az * bx - ax * bz;
+                    out.2 = ax * by - ay * bx;
+                    return;
+                }
+
+                if core::ptr::eq(out, b) {
+                    let ax = a.0;
+                    let ay = a.1;
+                    let az = a.2;
+                    let bx = out.0;
+                    let by = out.1;
+                    let bz = out.2;
+
+                    out.0 = ay * bz - az * by;
+                    out.1 = az * bx - ax * bz;
+                    out.2 = ax * by - ay * bx;
+                    return;
+                }
+
+                let ax = a.0;
+                let ay = a.1;
+                let az = a.2;
+                let bx = b.0;
+                let by = b.1;
+                let bz = b.2;
+
+                out.0 = ay * bz - az * by;
+                out.1 = az * bx - ax * bz;
+                out.2 = ax * by - ay * bx;
+            }
+
+            pub fn lerp(out: &mut $Vector3, a: &$Vector3, b: &$Vector3, t: $F) {
+                kernels::lerp(out.arrayMut(), a.array(), b.array(), t);
+            }
+
+            pub fn hermite(
+                out: &mut $Vector3,
+                a: &$Vector3,
+                b: &$Vector3,
+                c: &$Vector3,
+                d: &$Vector3,
+                t: $F,
+            ) {
+                let factorTimes2 = t * t;
+                let factor1 = factorTimes2 * (2. * t - 3.) + 1.;
+                let factor2 = factorTimes2 * (t - 2.) + t;
+                let factor3 = factorTimes2 * (t - 1.);
+                let factor4 = factorTimes2 * (3. - 2. * t);
+
+                out.0 = a.0 * factor1 + b.0 * factor2 + c.0 * factor3 + d.0 * factor4;
+                out.1 = a.1 * factor1 + b.1 * factor2 + c.1 * factor3 + d.1 * factor4;
+                out.2 = a.2 * factor1 + b.2 * factor2 + c.2 * factor3 + d.2 * factor4;
+            }
+
+            pub fn bezier(
+                out: &mut $Vector3,
+                a: &$Vector3,
+                b: &$Vector3,
+                c: &$Vector3,
+                d: &$Vector3,
+                t: $F,
+            ) {
+                let inverseFactor = 1. - t;
+                let inverseFactorTimesTwo = inverseFactor * inverseFactor;
+                let factorTimes2 = t * t;
+                let factor1 = inverseFactorTimesTwo * inverseFactor;
+                let factor2 = 3. * t * inverseFactorTimesTwo;
+                let factor3 = 3. * factorTimes2 * inverseFactor;
+                let factor4 = factorTimes2 * t;
+
+                out.0 = a.0 * factor1 + b.0 * factor2 + c.0 * factor3 + d.0 * factor4;
+                out.1 = a.1 * factor1 + b.1 * factor2 + c.1 * factor3 + d.1 * factor4;
+                out.2 = a.2 * factor1 + b.2 * factor2 + c.2 * factor3 + d.2 * factor4;
+            }
+
+            pub fn random(out: &mut $Vector3, scale: Option<$F>) {
+                let scale = scale.unwrap_or(1.);
+
+                let r = RANDOM() * 2.0 * PI;
+                let z = (RANDOM() * 2.0) - 1.0;
+                let zScale = $F::sqrt(1.0 - z * z) * scale;
+
+                out.0 = $F::cos(r) * zScale;
+                out.1 = $F::sin(r) * zScale;
+                out.2 = z * scale;
+            }
+
+            pub fn transformMat4(out: &mut $Vector3, a: &$Vector3, m: &$Matrix4) {
+                f32_only!(
+                    $F,
+                    if cfg!(feature = "simd") {
+                        crate::simd::transformVec3Mat4(out, a, m);
+                        return;
+                    }
+                );
+
+                let x = a.0;
+                let y = a.1;
+                let z = a.2;
+                let mut w = m.3 * x + m.7 * y + m.11 * z + m.15;
+
+                if w.abs() < EPSILON {
+                    w = 1.0;
+                }
+                out.0 = (m.0 * x + m.4 * y + m.8 * z + m.12) / w;
+                out.1 = (m.1 * x + m.5 * y + m.9 * z + m.13) / w;
+                out.2 = (m.2 * x + m.6 * y + m.10 * z + m.14) / w;
+            }
+
+            pub fn transformMat3(out: &mut $Vector3, a: &$Vector3, m: &$Matrix3) {
+                let x = a.0;
+                let y = a.1;
+                let z = a.2;
+                out.0 = x * m.0 + y * m.3 + z * m.6;
+                out.1 = x * m.1 + y * m.4 + z * m.7;
+                out.2 = x * m.2 + y * m.5 + z * m.8;
+            }
+
+            pub fn transformQuat(out: &mut $Vector3, a: &$Vector3, q: &$Quaternion) {
+                // benchmarks: https://jsperf.com/quaternion-transform-vec3-implementations-fixed
+                let qx = q.0;
+                let qy = q.1;
+                let qz = q.2;
+                let qw = q.3;
+                let x = a.0;
+                let y = a.1;
+                let z = a.2;
+                let mut uvx = qy * z - qz * y;
+                let mut uvy = qz * x - qx * z;
+                let mut uvz = qx * y - qy * x;
+                let mut uuvx = qy * uvz - qz * uvy;
+                let mut uuvy = qz * uvx - qx * uvz;
+                let mut uuvz = qx * uvy - qy * uvx;
+                // vec3.scale(uv, uv, 2 * w);
+                let w2 = qw * 2.;
+                uvx *= w2;
+                uvy *= w2;
+                uvz *= w2;
+                uuvx *= 2.;
+                uuvy *= 2.;
+                uuvz *= 2.;
+                // return vec3.add(out, a, vec3.add(out, uv, uuv));
+                out.0 = x + uvx + uuvx;
+                out.1 = y + uvy + uuvy;
+                out.2 = z + uvz + uuvz;
+            }
+
+            pub fn rotateX(out: &mut $Vector3, a: &$Vector3, b: &$Vector3, c: $F) {
+                let p = &mut $Vector3::create();
+                let r = &mut $Vector3::create();
+                //Translate point to the origin
+                p.0 = a.0 - b.0;
+                p.1 = a.1 - b.1;
+                p.2 = a.2 - b.2;
+
+                //perform rotation
+                r.0 = p.0;
+                r.1 = p.1 * $F::cos(c) - p.2 * $F::sin(c);
+                r.2 = p.1 * $F::sin(c) + p.2 * $F::cos(c);
+
+                //translate to correct position
+                out.0 = r.0 + b.0;
+                out.1 = r.1 + b.1;
+                out.2 = r.2 + b.2;
+            }
+
+            pub fn rotateY(out: &mut $Vector3, a: &$Vector3, b: &$Vector3, c: $F) {
+                let p = &mut $Vector3::create();
+                let r = &mut $Vector3::create();
+                //Translate point to the origin
+                p.0 = a.0 - b.0;
+                p.1 = a.1 - b.1;
+                p.2 = a.2 - b.2;
+
+                //perform rotation
+                r.0 = p.2 * $F::sin(c) + p.0 * $F::cos(c);
+                r.1 = p.1;
+                r.2 = p.2 * $F::cos(c) - p.0 * $F::sin(c);
+
+                //translate to correct position
+                out.0 = r.0 + b.0;
+                out.1 = r.1 + b.1;
+                out.2 = r.2 + b.2;
+            }
+
+            pub fn rotateZ(out: &mut $Vector3, a: &$Vector3, b: &$Vector3, c: $F) {
+                let p = &mut $Vector3::create();
+                let r = &mut $Vector3::create();
+                //Translate point to the origin
+                p.0 = a.0 - b.0;
+                p.1 = a.1 - b.1;
+                p.2 = a.2 - b.2;
+
+                //perform rotation
+                r.0 = p.0 * $F::cos(c) - p.1 * $F::sin(c);
+                r.1 = p.0 * $F::sin(c) + p.1 * $F::cos(c);
+                r.2 = p.2;
+
+                //translate to correct position
+                out.0 = r.0 + b.0;
+                out.1 = r.1 + b.1;
+                out.2 = r.2 + b.2;
+            }
+
+            pub fn angle(a: &$Vector3, b: &$Vector3) -> $F {
+                let tempA = &mut $Vector3::fromValues(a.0, a.1, a.2);
+                let tempB = &mut $Vector3::fromValues(b.0, b.1, b.2);
+
+                $Vector3::normalize(tempA, &$Vector3::clone(tempA));
+                $Vector3::normalize(tempB, &$Vector3::clone(tempB));
+
+                let cosine = $Vector3::dot(tempA, tempB);
+
+                if cosine > 1.0 {
+                    0.
+                } else if cosine < -1.0 {
+                    PI
+                } else {
+                    $F::acos(cosine)
+                }
+            }
+
+            pub fn zero(out: &mut $Vector3) {
+                out.0 = 0.0;
+                out.1 = 0.0;
+                out.2 = 0.0;
+            }
+
+            pub fn str(a: &$Vector3) -> String {
+                a.to_string()
+            }
+
+            pub fn exactEquals(a: &$Vector3, b: &$Vector3) -> bool {
+                kernels::exactEquals(a.array(), b.array())
+            }
+
+            pub fn equals(a: &$Vector3, b: &$Vector3) -> bool {
+                match getEqualsMode() {
+                    EqualsMode::Epsilon => $Vector3::equalsWithEpsilon(a, b, getEpsilon()),
+                    EqualsMode::Ulps => $Vector3::equalsWithUlps(a, b, getMaxUlps()),
+                }
+            }
+
+            pub fn equalsWithEpsilon(a: &$Vector3, b: &$Vector3, epsilon: $F) -> bool {
+                kernels::equalsWithEpsilon(a.array(), b.array(), epsilon)
+            }
+
+            pub fn equalsWithUlps(a: &$Vector3, b: &$Vector3, maxUlps: u32) -> bool {
+                kernels::equalsWithUlps(a.array(), b.array(), maxUlps)
+            }
+
+            pub fn sub(out: &mut $Vector3, a: &$Vector3, b: &$Vector3) {
+                $Vector3::subtract(out, a, b);
+            }
+
+            pub fn mul(out: &mut $Vector3, a: &$Vector3, b: &$Vector3) {
+                $Vector3::multiply(out, a, b);
+            }
+
+            pub fn div(out: &mut $Vector3, a: &$Vector3, b: &$Vector3) {
+                $Vector3::divide(out, a, b);
+            }
+
+            pub fn dist(a: &$Vector3, b: &$Vector3) {
+                $Vector3::distance(a, b);
+            }
+
+            pub fn sqrDist(a: &$Vector3, b: &$Vector3) {
+                $Vector3::squaredDistance(a, b);
+            }
+
+            pub fn sqrLen(a: &$Vector3) -> $F {
+                $Vector3::squaredLength(a)
+            }
+
+            // Transforms `count` vectors packed in `a`, 3 elements each, with the same matrix.
+            pub fn transformMat4Array(out: &mut [$F], a: &[$F], m: &$Matrix4, count: usize) {
+                let len = count * 3;
+                assert!(
+                    out.len() >= len && a.len() >= len,
+                    "the buffers are shorter than {} elements",
+                    len
+                );
+                let v = &mut $Vector3::create();
+                for i in 0..count {
+                    let r = i * 3..i * 3 + 3;
+                    $Vector3::transformMat4(v, &$Vector3::fromSlice(&a[r.clone()]), m);
+                    v.writeSlice(&mut out[r]);
+                }
+            }
         }
-        out.0 = a.0 * len;
-        out.1 = a.1 * len;
-        out.2 = a.2 * len;
-    }
-
-    pub fn dot(a: &Vector3, b: &Vector3) -> f32 {
-        return a.0 * b.0 + a.1 * b.1 + a.2 * b.2;
-    }
-
-    pub fn cross(out: &mut Vector3, a: &Vector3, b: &Vector3) {
-        if (out as *const Vector3) == (a as *const Vector3) {
-            let ax = out.0;
-            let ay = out.1;
-            let az = out.2;
-            let bx = b.0;
-            let by = b.1;
-            let bz = b.2;
-
-            out.0 = ay * bz - az * by;
-            out.1 = az * bx - ax * bz;
-            out.2 = ax * by - ay * bx;
-            return;
+
+        // Flat f32 buffers, such as the uniforms or the batch functions.
+        impl $Vector3 {
+            pub fn fromSlice(a: &[$F]) -> $Vector3 {
+                $Vector3(a[0], a[1], a[2])
+            }
+
+            pub fn writeSlice(&self, out: &mut [$F]) {
+                out[..3].copy_from_slice(self.array());
+            }
+
+            pub fn asSlice(&self) -> &[$F] {
+                self.array()
+            }
+        }
+
+        // `#[repr(C)]` keeps the 3 elements contiguous.
+        unsafe impl Elements<$F, 3> for $Vector3 {}
+
+        #[cfg(feature = "wasm")]
+        #[wasm_bindgen]
+        impl $Vector3 {
+            // Aliases the elements in wasm memory without copying. The view is detached when the memory grows,
+            // so don't keep it across calls that may allocate.
+            pub fn view(&self) -> $FloatArray {
+                unsafe { $FloatArray::view(self.asSlice()) }
+            }
+
+            // Reads the first 3 elements of `a`, without allocating in wasm memory. Throws a `RangeError`
+            // when `a` is shorter.
+            pub fn $fromFloatArray(a: &$FloatArray) -> Result<$Vector3, JsValue> {
+                check_length(3, a.length() as usize)?;
+                let mut elements = [0.; 3];
+                a.subarray(0, 3).copy_to(&mut elements);
+                Ok($Vector3::fromSlice(&elements))
+            }
+
+            pub fn copyFromArray(out: &mut $Vector3, a: &$FloatArray) -> Result<(), JsValue> {
+                *out = $Vector3::$fromFloatArray(a)?;
+                Ok(())
+            }
+        }
+
+        impl PartialEq for $Vector3 {
+            fn eq(&self, other: &$Vector3) -> bool {
+                $Vector3::exactEquals(self, other)
+            }
         }
 
-        if (out as *const Vector3) == (b as *const Vector3) {
-            let ax = a.0;
-            let ay = a.1;
-            let az = a.2;
-            let bx = out.0;
-            let by = out.1;
-            let bz = out.2;
-
-            out.0 = ay * bz - az * by;
-            out.1 = az * bx - ax * bz;
-            out.2 = ax * by - ay * bx;
-            return;            
+        impl ApproxEq for $Vector3 {
+            fn approx_eq(&self, other: &$Vector3) -> bool {
+                $Vector3::equals(self, other)
+            }
         }
 
-        let ax = a.0;
-        let ay = a.1;
-        let az = a.2;
-        let bx = b.0;
-        let by = b.1;
-        let bz = b.2;
-
-        out.0 = ay * bz - az * by;
-        out.1 = az * bx - ax * bz;
-        out.2 = ax * by - ay * bx;
-    }
-
-    pub fn lerp(out: &mut Vector3, a: &Vector3, b: &Vector3, t: f32) {
-        let ax = a.0;
-        let ay = a.1;
-        let az = a.2;
-        out.0 = ax + t * (b.0 - ax);
-        out.1 = ay + t * (b.1 - ay);
-        out.2 = az + t * (b.2 - az);
-    }
-
-    pub fn hermite(out: &mut Vector3, a: &Vector3, b: &Vector3, c: &Vector3, d: &Vector3, t: f32) {
-        let factorTimes2 = t * t;
-        let factor1 = factorTimes2 * (2. * t - 3.) + 1.;
-        let factor2 = factorTimes2 * (t - 2.) + t;
-        let factor3 = factorTimes2 * (t - 1.);
-        let factor4 = factorTimes2 * (3. - 2. * t);
-
-        out.0 = a.0 * factor1 + b.0 * factor2 + c.0 * factor3 + d.0 * factor4;
-        out.1 = a.1 * factor1 + b.1 * factor2 + c.1 * factor3 + d.1 * factor4;
-        out.2 = a.2 * factor1 + b.2 * factor2 + c.2 * factor3 + d.2 * factor4;
-    }
-
-    pub fn bezier(out: &mut Vector3, a: &Vector3, b: &Vector3, c: &Vector3, d: &Vector3, t: f32) {
-        let inverseFactor = 1. - t;
-        let inverseFactorTimesTwo = inverseFactor * inverseFactor;
-        let factorTimes2 = t * t;
-        let factor1 = inverseFactorTimesTwo * inverseFactor;
-        let factor2 = 3. * t * inverseFactorTimesTwo;
-        let factor3 = 3. * factorTimes2 * inverseFactor;
-        let factor4 = factorTimes2 * t;
-
-        out.0 = a.0 * factor1 + b.0 * factor2 + c.0 * factor3 + d.0 * factor4;
-        out.1 = a.1 * factor1 + b.1 * factor2 + c.1 * factor3 + d.1 * factor4;
-        out.2 = a.2 * factor1 + b.2 * factor2 + c.2 * factor3 + d.2 * factor4;
-    }
-
-    pub fn random(out: &mut Vector3, scale: Option<f32>) {
-        let mut s = 1.;
-        match scale {
-            Some(value) => s = value,
-            None => {}
-        };
-        let scale = s;
-
-        let r = RANDOM() * 2.0 * PI;
-        let z = (RANDOM() * 2.0) - 1.0;
-        let zScale = f32::sqrt(1.0 - z * z) * scale;
-
-        out.0 = f32::cos(r) * zScale;
-        out.1 = f32::sin(r) * zScale;
-        out.2 = z * scale;
-    }
-
-    pub fn transformMat4(out: &mut Vector3, a: &Vector3, m: &Matrix4) {
-        let x = a.0;
-        let y = a.1;
-        let z = a.2;
-        let mut w = m.3 * x + m.7 * y + m.11 * z + m.15;
-
-        if w.abs() < EPSILON {
-            w = 1.0;
+        impl Default for $Vector3 {
+            fn default() -> $Vector3 {
+                $Vector3::create()
+            }
         }
-        out.0 = (m.0 * x + m.4 * y + m.8 * z + m.12) / w;
-        out.1 = (m.1 * x + m.5 * y + m.9 * z + m.13) / w;
-        out.2 = (m.2 * x + m.6 * y + m.10 * z + m.14) / w;
-    }
-
-    pub fn transformMat3(out: &mut Vector3, a: &Vector3, m: &Matrix3) {
-        let x = a.0;
-        let y = a.1;
-        let z = a.2;
-        out.0 = x * m.0 + y * m.3 + z * m.6;
-        out.1 = x * m.1 + y * m.4 + z * m.7;
-        out.2 = x * m.2 + y * m.5 + z * m.8;
-    }
-
-    pub fn transformQuat(out: &mut Vector3, a: &Vector3, q: &Quaternion) {
-        // benchmarks: https://jsperf.com/quaternion-transform-vec3-implementations-fixed
-        let qx = q.0;
-        let qy = q.1;
-        let qz = q.2;
-        let qw = q.3;
-        let x = a.0;
-        let y = a.1;
-        let z = a.2;
-        let mut uvx = qy * z - qz * y;
-        let mut uvy = qz * x - qx * z;
-        let mut uvz = qx * y - qy * x;
-        let mut uuvx = qy * uvz - qz * uvy;
-        let mut uuvy = qz * uvx - qx * uvz;
-        let mut uuvz = qx * uvy - qy * uvx;
-        // vec3.scale(uv, uv, 2 * w);
-        let w2 = qw * 2.;
-        uvx *= w2;
-        uvy *= w2;
-        uvz *= w2;
-        uuvx *= 2.;
-        uuvy *= 2.;
-        uuvz *= 2.;
-        // return vec3.add(out, a, vec3.add(out, uv, uuv));
-        out.0 = x + uvx + uuvx;
-        out.1 = y + uvy + uuvy;
-        out.2 = z + uvz + uuvz;
-    }
-
-    pub fn rotateX(out: &mut Vector3, a: &Vector3, b: &Vector3, c: f32) {
-        let p = &mut Vector3::create();
-        let r = &mut Vector3::create();
-        //Translate point to the origin
-        p.0 = a.0 - b.0;
-        p.1 = a.1 - b.1;
-        p.2 = a.2 - b.2;
-
-        //perform rotation
-        r.0 = p.0;
-        r.1 = p.1 * f32::cos(c) - p.2 * f32::sin(c);
-        r.2 = p.1 * f32::sin(c) + p.2 * f32::cos(c);
-
-        //translate to correct position
-        out.0 = r.0 + b.0;
-        out.1 = r.1 + b.1;
-        out.2 = r.2 + b.2;
-    }
-
-    pub fn rotateY(out: &mut Vector3, a: &Vector3, b: &Vector3, c: f32) {
-        let p = &mut Vector3::create();
-        let r = &mut Vector3::create();
-        //Translate point to the origin
-        p.0 = a.0 - b.0;
-        p.1 = a.1 - b.1;
-        p.2 = a.2 - b.2;
-
-        //perform rotation
-        r.0 = p.2 * f32::sin(c) + p.0 * f32::cos(c);
-        r.1 = p.1;
-        r.2 = p.2 * f32::cos(c) - p.0 * f32::sin(c);
-
-        //translate to correct position
-        out.0 = r.0 + b.0;
-        out.1 = r.1 + b.1;
-        out.2 = r.2 + b.2;
-    }
-
-    pub fn rotateZ(out: &mut Vector3, a: &Vector3, b: &Vector3, c: f32) {
-        let p = &mut Vector3::create();
-        let r = &mut Vector3::create();
-        //Translate point to the origin
-        p.0 = a.0 - b.0;
-        p.1 = a.1 - b.1;
-        p.2 = a.2 - b.2;
-
-        //perform rotation
-        r.0 = p.0 * f32::cos(c) - p.1 * f32::sin(c);
-        r.1 = p.0 * f32::sin(c) + p.1 * f32::cos(c);
-        r.2 = p.2;
-
-        //translate to correct position
-        out.0 = r.0 + b.0;
-        out.1 = r.1 + b.1;
-        out.2 = r.2 + b.2;
-    }
-
-    pub fn angle(a: &Vector3, b: &Vector3) -> f32 {
-        let tempA = &mut Vector3::fromValues(a.0, a.1, a.2);
-        let tempB = &mut Vector3::fromValues(b.0, b.1, b.2);
-
-        Vector3::normalize(tempA, &Vector3::clone(tempA));
-        Vector3::normalize(tempB, &Vector3::clone(tempB));
-
-        let cosine = Vector3::dot(tempA, tempB);
-
-        if (cosine > 1.0) {
-            0.
-        } else if (cosine < -1.0) {
-            PI
-        } else {
-            f32::acos(cosine)
+
+        impl fmt::Display for $Vector3 {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "vec3({}, {}, {})", self.0, self.1, self.2)
+            }
+        }
+
+        impl FromStr for $Vector3 {
+            type Err = ParseError;
+
+            fn from_str(s: &str) -> Result<$Vector3, ParseError> {
+                let mut v = [0.; 3];
+                parse_values(s, "vec3", &mut v)?;
+                Ok($Vector3(v[0], v[1], v[2]))
+            }
         }
-    }
-
-    pub fn zero(out: &mut Vector3) {
-        out.0 = 0.0;
-        out.1 = 0.0;
-        out.2 = 0.0;
-    }
-
-    // pub fn str(a: &Vector3) {
-    //   return "vec3(" + a.0 + ", " + a.1 + ", " + a.2 + ")";
-    // }
-
-    pub fn exactEquals(a: &Vector3, b: &Vector3) -> bool {
-        a.0 == b.0 && a.1 == b.1 && a.2 == b.2
-    }
-
-    pub fn equals(a: &Vector3, b: &Vector3) -> bool {
-        let a0 = a.0;
-        let a1 = a.1;
-        let a2 = a.2;
-        let b0 = b.0;
-        let b1 = b.1;
-        let b2 = b.2;
-        f32::abs(a0 - b0) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a0), f32::abs(b0)))
-            && f32::abs(a1 - b1) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a1), f32::abs(b1)))
-            && f32::abs(a2 - b2) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a2), f32::abs(b2)))
-    }
-
-    pub fn sub(out: &mut Vector3, a: &Vector3, b: &Vector3) {
-        Vector3::subtract(out, a, b);
-    }
-
-    pub fn mul(out: &mut Vector3, a: &Vector3, b: &Vector3) {
-        Vector3::multiply(out, a, b);
-    }
-
-    pub fn div(out: &mut Vector3, a: &Vector3, b: &Vector3) {
-        Vector3::divide(out, a, b);
-    }
-
-    pub fn dist(a: &Vector3, b: &Vector3) {
-        Vector3::distance(a, b);
-    }
-
-    pub fn sqrDist(a: &Vector3, b: &Vector3) {
-        Vector3::squaredDistance(a, b);
-    }
-
-    pub fn sqrLen(a: &Vector3) -> f32 {
-        Vector3::squaredLength(a)
-    }
+
+        #[cfg(test)]
+        mod tests {
+            // Some expected values are copied from the gl-matrix specs at double precision.
+            #![allow(clippy::excessive_precision)]
+            use super::*;
+            use crate::spec_helper::*;
+            use std::$F::consts::{E, FRAC_1_SQRT_2, SQRT_2};
+
+            fn setup() -> ($Vector3, $Vector3, $Vector3) {
+                (
+                    $Vector3::fromValues(0., 0., 0.),
+                    $Vector3::fromValues(1., 2., 3.),
+                    $Vector3::fromValues(4., 5., 6.),
+                )
+            }
+
+            #[test]
+            fn rotateX() {
+                let (out, _, _) = &mut setup();
+                // rotation around world origin [0, 0, 0]
+                $Vector3::rotateX(
+                    out,
+                    &$Vector3::fromValues(0., 1., 0.),
+                    &$Vector3::fromValues(0., 0., 0.),
+                    PI,
+                );
+                expect_equalish(&out.elements(), &[0., -1., 0.]);
+
+                // rotation around an arbitrary origin
+                $Vector3::rotateX(
+                    out,
+                    &$Vector3::fromValues(2., 7., 0.),
+                    &$Vector3::fromValues(2., 5., 0.),
+                    PI,
+                );
+                expect_equalish(&out.elements(), &[2., 3., 0.]);
+            }
+
+            #[test]
+            fn rotateY() {
+                let (out, _, _) = &mut setup();
+                // rotation around world origin [0, 0, 0]
+                $Vector3::rotateY(
+                    out,
+                    &$Vector3::fromValues(1., 0., 0.),
+                    &$Vector3::fromValues(0., 0., 0.),
+                    PI,
+                );
+                expect_equalish(&out.elements(), &[-1., 0., 0.]);
+
+                // rotation around an arbitrary origin
+                $Vector3::rotateY(
+                    out,
+                    &$Vector3::fromValues(-2., 3., 10.),
+                    &$Vector3::fromValues(-4., 3., 10.),
+                    PI,
+                );
+                expect_equalish(&out.elements(), &[-6., 3., 10.]);
+            }
+
+            #[test]
+            fn rotateZ() {
+                let (out, _, _) = &mut setup();
+                // rotation around world origin [0, 0, 0]
+                $Vector3::rotateZ(
+                    out,
+                    &$Vector3::fromValues(0., 1., 0.),
+                    &$Vector3::fromValues(0., 0., 0.),
+                    PI,
+                );
+                expect_equalish(&out.elements(), &[0., -1., 0.]);
+
+                // rotation around an arbitrary origin
+                $Vector3::rotateZ(
+                    out,
+                    &$Vector3::fromValues(0., 6., -5.),
+                    &$Vector3::fromValues(0., 0., -5.),
+                    PI,
+                );
+                expect_equalish(&out.elements(), &[0., -6., -5.]);
+            }
+
+            #[test]
+            fn transformMat4() {
+                let (out, vecA, _) = &mut setup();
+                // with an identity
+                let matr = &mut $Matrix4::fromValues(
+                    1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1.,
+                );
+                $Vector3::transformMat4(out, vecA, matr);
+                expect_equalish(&out.elements(), &[1., 2., 3.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+
+                // with a lookAt
+                $Matrix4::lookAt(
+                    matr,
+                    &$Vector3::fromValues(5., 6., 7.),
+                    &$Vector3::fromValues(2., 6., 7.),
+                    &$Vector3::fromValues(0., 1., 0.),
+                );
+                $Vector3::transformMat4(out, vecA, matr);
+                expect_equalish(&out.elements(), &[4., -4., -4.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+
+                // with a perspective matrix (#92)
+                let matr = &$Matrix4::fromValues(
+                    0.750, 0., 0., 0., 0., 1., 0., 0., 0., 0., -1.02, -1., 0., 0., -2.02, 0.,
+                );
+                let v = &mut $Vector3::create();
+                $Vector3::transformMat4(v, &$Vector3::fromValues(10., 20., 30.), matr);
+                expect_equalish(&v.elements(), &[-0.25, -0.666666, 1.087333]);
+            }
+
+            #[test]
+            fn transformMat3() {
+                let (out, vecA, _) = &mut setup();
+                // with an identity
+                let matr = &$Matrix3::fromValues(1., 0., 0., 0., 1., 0., 0., 0., 1.);
+                $Vector3::transformMat3(out, vecA, matr);
+                expect_equalish(&out.elements(), &[1., 2., 3.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+
+                // with 90deg about X
+                $Vector3::transformMat3(
+                    out,
+                    &$Vector3::fromValues(0., 1., 0.),
+                    &$Matrix3::fromValues(1., 0., 0., 0., 0., 1., 0., -1., 0.),
+                );
+                expect_equalish(&out.elements(), &[0., 0., 1.]);
+
+                // with 90deg about Y
+                $Vector3::transformMat3(
+                    out,
+                    &$Vector3::fromValues(1., 0., 0.),
+                    &$Matrix3::fromValues(0., 0., -1., 0., 1., 0., 1., 0., 0.),
+                );
+                expect_equalish(&out.elements(), &[0., 0., -1.]);
+
+                // with 90deg about Z
+                $Vector3::transformMat3(
+                    out,
+                    &$Vector3::fromValues(1., 0., 0.),
+                    &$Matrix3::fromValues(0., 1., 0., -1., 0., 0., 0., 0., 1.),
+                );
+                expect_equalish(&out.elements(), &[0., 1., 0.]);
+
+                // with a lookAt normal matrix
+                let matr = &mut $Matrix4::create();
+                $Matrix4::lookAt(
+                    matr,
+                    &$Vector3::fromValues(5., 6., 7.),
+                    &$Vector3::fromValues(2., 6., 7.),
+                    &$Vector3::fromValues(0., 1., 0.),
+                );
+                let n = &mut $Matrix3::create();
+                $Matrix3::fromMat4(n, matr);
+                $Matrix3::invert(n, &$Matrix3::clone(n));
+                $Matrix3::transpose(n, &$Matrix3::clone(n));
+                $Vector3::transformMat3(out, &$Vector3::fromValues(1., 0., 0.), n);
+                expect_equalish(&out.elements(), &[0., 0., 1.]);
+            }
+
+            #[test]
+            fn transformQuat() {
+                let (out, vecA, _) = &mut setup();
+                $Vector3::transformQuat(
+                    out,
+                    vecA,
+                    &$Quaternion::fromValues(
+                        0.18257418567011074,
+                        0.3651483713402215,
+                        0.5477225570103322,
+                        0.730296742680443,
+                    ),
+                );
+                expect_equalish(&out.elements(), &[1., 2., 3.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+            }
+
+            #[test]
+            fn create() {
+                let result = $Vector3::create();
+                expect_equalish(&result.elements(), &[0., 0., 0.]);
+            }
+
+            #[test]
+            fn clone() {
+                let (_, vecA, _) = &mut setup();
+                let result = $Vector3::clone(vecA);
+                expect_equalish(&result.elements(), &vecA.elements());
+            }
+
+            #[test]
+            fn fromValues() {
+                let result = $Vector3::fromValues(1., 2., 3.);
+                expect_equalish(&result.elements(), &[1., 2., 3.]);
+            }
+
+            #[test]
+            fn copy() {
+                let (out, vecA, _) = &mut setup();
+                $Vector3::copy(out, vecA);
+                expect_equalish(&out.elements(), &[1., 2., 3.]);
+            }
+
+            #[test]
+            fn set() {
+                let (out, _, _) = &mut setup();
+                $Vector3::set(out, 1., 2., 3.);
+                expect_equalish(&out.elements(), &[1., 2., 3.]);
+            }
+
+            #[test]
+            fn add() {
+                let (out, vecA, vecB) = &mut setup();
+                $Vector3::add(out, vecA, vecB);
+                expect_equalish(&out.elements(), &[5., 7., 9.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+                expect_equalish(&vecB.elements(), &[4., 5., 6.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector3::add(vecA, &$Vector3::clone(vecA), vecB);
+                expect_equalish(&vecA.elements(), &[5., 7., 9.]);
+                expect_equalish(&vecB.elements(), &[4., 5., 6.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector3::add(vecB, vecA, &$Vector3::clone(vecB));
+                expect_equalish(&vecB.elements(), &[5., 7., 9.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+            }
+
+            #[test]
+            fn subtract() {
+                let (out, vecA, vecB) = &mut setup();
+                $Vector3::subtract(out, vecA, vecB);
+                expect_equalish(&out.elements(), &[-3., -3., -3.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+                expect_equalish(&vecB.elements(), &[4., 5., 6.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector3::subtract(vecA, &$Vector3::clone(vecA), vecB);
+                expect_equalish(&vecA.elements(), &[-3., -3., -3.]);
+                expect_equalish(&vecB.elements(), &[4., 5., 6.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector3::subtract(vecB, vecA, &$Vector3::clone(vecB));
+                expect_equalish(&vecB.elements(), &[-3., -3., -3.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+            }
+
+            #[test]
+            fn multiply() {
+                let (out, vecA, vecB) = &mut setup();
+                $Vector3::multiply(out, vecA, vecB);
+                expect_equalish(&out.elements(), &[4., 10., 18.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+                expect_equalish(&vecB.elements(), &[4., 5., 6.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector3::multiply(vecA, &$Vector3::clone(vecA), vecB);
+                expect_equalish(&vecA.elements(), &[4., 10., 18.]);
+                expect_equalish(&vecB.elements(), &[4., 5., 6.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector3::multiply(vecB, vecA, &$Vector3::clone(vecB));
+                expect_equalish(&vecB.elements(), &[4., 10., 18.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+            }
+
+            #[test]
+            fn divide() {
+                let (out, vecA, vecB) = &mut setup();
+                $Vector3::divide(out, vecA, vecB);
+                expect_equalish(&out.elements(), &[0.25, 0.4, 0.5]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+                expect_equalish(&vecB.elements(), &[4., 5., 6.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector3::divide(vecA, &$Vector3::clone(vecA), vecB);
+                expect_equalish(&vecA.elements(), &[0.25, 0.4, 0.5]);
+                expect_equalish(&vecB.elements(), &[4., 5., 6.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector3::divide(vecB, vecA, &$Vector3::clone(vecB));
+                expect_equalish(&vecB.elements(), &[0.25, 0.4, 0.5]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+            }
+
+            #[test]
+            fn ceil() {
+                let out = &mut $Vector3::create();
+                let vecA = &mut $Vector3::fromValues(E, PI, SQRT_2);
+                $Vector3::ceil(out, vecA);
+                expect_equalish(&out.elements(), &[3., 4., 2.]);
+                expect_equalish(&vecA.elements(), &[E, PI, SQRT_2]);
+
+                $Vector3::ceil(vecA, &$Vector3::clone(vecA));
+                expect_equalish(&vecA.elements(), &[3., 4., 2.]);
+            }
+
+            #[test]
+            fn floor() {
+                let out = &mut $Vector3::create();
+                let vecA = &mut $Vector3::fromValues(E, PI, SQRT_2);
+                $Vector3::floor(out, vecA);
+                expect_equalish(&out.elements(), &[2., 3., 1.]);
+                expect_equalish(&vecA.elements(), &[E, PI, SQRT_2]);
+
+                $Vector3::floor(vecA, &$Vector3::clone(vecA));
+                expect_equalish(&vecA.elements(), &[2., 3., 1.]);
+            }
+
+            #[test]
+            fn min() {
+                let out = &mut $Vector3::create();
+                let vecA = &mut $Vector3::fromValues(1., 3., 1.);
+                let vecB = &mut $Vector3::fromValues(3., 1., 3.);
+                $Vector3::min(out, vecA, vecB);
+                expect_equalish(&out.elements(), &[1., 1., 1.]);
+                expect_equalish(&vecA.elements(), &[1., 3., 1.]);
+                expect_equalish(&vecB.elements(), &[3., 1., 3.]);
+
+                $Vector3::min(vecA, &$Vector3::clone(vecA), vecB);
+                expect_equalish(&vecA.elements(), &[1., 1., 1.]);
+                expect_equalish(&vecB.elements(), &[3., 1., 3.]);
+
+                let vecA = &mut $Vector3::fromValues(1., 3., 1.);
+                $Vector3::min(vecB, vecA, &$Vector3::clone(vecB));
+                expect_equalish(&vecB.elements(), &[1., 1., 1.]);
+                expect_equalish(&vecA.elements(), &[1., 3., 1.]);
+            }
+
+            #[test]
+            fn max() {
+                let out = &mut $Vector3::create();
+                let vecA = &mut $Vector3::fromValues(1., 3., 1.);
+                let vecB = &mut $Vector3::fromValues(3., 1., 3.);
+                $Vector3::max(out, vecA, vecB);
+                expect_equalish(&out.elements(), &[3., 3., 3.]);
+                expect_equalish(&vecA.elements(), &[1., 3., 1.]);
+                expect_equalish(&vecB.elements(), &[3., 1., 3.]);
+
+                $Vector3::max(vecA, &$Vector3::clone(vecA), vecB);
+                expect_equalish(&vecA.elements(), &[3., 3., 3.]);
+                expect_equalish(&vecB.elements(), &[3., 1., 3.]);
+
+                let vecA = &mut $Vector3::fromValues(1., 3., 1.);
+                $Vector3::max(vecB, vecA, &$Vector3::clone(vecB));
+                expect_equalish(&vecB.elements(), &[3., 3., 3.]);
+                expect_equalish(&vecA.elements(), &[1., 3., 1.]);
+            }
+
+            #[test]
+            fn round() {
+                let out = &mut $Vector3::create();
+                let vecA = &mut $Vector3::fromValues(E, PI, SQRT_2);
+                $Vector3::round(out, vecA);
+                expect_equalish(&out.elements(), &[3., 3., 1.]);
+                expect_equalish(&vecA.elements(), &[E, PI, SQRT_2]);
+
+                $Vector3::round(vecA, &$Vector3::clone(vecA));
+                expect_equalish(&vecA.elements(), &[3., 3., 1.]);
+            }
+
+            #[test]
+            fn scale() {
+                let out = &mut $Vector3::create();
+                let vecA = &mut $Vector3::fromValues(1., 2., 3.);
+                $Vector3::scale(out, vecA, 2.);
+                expect_equalish(&out.elements(), &[2., 4., 6.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+
+                $Vector3::scale(vecA, &$Vector3::clone(vecA), 2.);
+                expect_equalish(&vecA.elements(), &[2., 4., 6.]);
+            }
+
+            #[test]
+            fn scaleAndAdd() {
+                let (out, vecA, vecB) = &mut setup();
+                $Vector3::scaleAndAdd(out, vecA, vecB, 0.5);
+                expect_equalish(&out.elements(), &[3., 4.5, 6.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+                expect_equalish(&vecB.elements(), &[4., 5., 6.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector3::scaleAndAdd(vecA, &$Vector3::clone(vecA), vecB, 0.5);
+                expect_equalish(&vecA.elements(), &[3., 4.5, 6.]);
+                expect_equalish(&vecB.elements(), &[4., 5., 6.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector3::scaleAndAdd(vecB, vecA, &$Vector3::clone(vecB), 0.5);
+                expect_equalish(&vecB.elements(), &[3., 4.5, 6.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+            }
+
+            #[test]
+            fn distance() {
+                let (_, vecA, vecB) = &mut setup();
+                expect_equalish_scalar($Vector3::distance(vecA, vecB), 5.196152);
+            }
+
+            #[test]
+            fn squaredDistance() {
+                let (_, vecA, vecB) = &mut setup();
+                expect_equalish_scalar($Vector3::squaredDistance(vecA, vecB), 27.);
+            }
+
+            #[test]
+            fn length() {
+                let (_, vecA, _) = &mut setup();
+                expect_equalish_scalar($Vector3::len(vecA), 3.741657);
+            }
+
+            #[test]
+            fn squaredLength() {
+                let (_, vecA, _) = &mut setup();
+                expect_equalish_scalar($Vector3::squaredLength(vecA), 14.);
+            }
+
+            #[test]
+            fn negate() {
+                let out = &mut $Vector3::create();
+                let vecA = &mut $Vector3::fromValues(1., 2., 3.);
+                $Vector3::negate(out, vecA);
+                expect_equalish(&out.elements(), &[-1., -2., -3.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+
+                $Vector3::negate(vecA, &$Vector3::clone(vecA));
+                expect_equalish(&vecA.elements(), &[-1., -2., -3.]);
+            }
+
+            #[test]
+            fn normalize() {
+                let out = &mut $Vector3::create();
+                let vecA = &mut $Vector3::fromValues(5., 0., 0.);
+                $Vector3::normalize(out, vecA);
+                expect_equalish(&out.elements(), &[1., 0., 0.]);
+                expect_equalish(&vecA.elements(), &[5., 0., 0.]);
+
+                $Vector3::normalize(vecA, &$Vector3::clone(vecA));
+                expect_equalish(&vecA.elements(), &[1., 0., 0.]);
+            }
+
+            #[test]
+            fn dot() {
+                let (_, vecA, vecB) = &mut setup();
+                expect_equalish_scalar($Vector3::dot(vecA, vecB), 32.);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+                expect_equalish(&vecB.elements(), &[4., 5., 6.]);
+            }
+
+            #[test]
+            fn cross() {
+                let (out, vecA, vecB) = &mut setup();
+                $Vector3::cross(out, vecA, vecB);
+                expect_equalish(&out.elements(), &[-3., 6., -3.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+                expect_equalish(&vecB.elements(), &[4., 5., 6.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector3::cross(vecA, &$Vector3::clone(vecA), vecB);
+                expect_equalish(&vecA.elements(), &[-3., 6., -3.]);
+                expect_equalish(&vecB.elements(), &[4., 5., 6.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector3::cross(vecB, vecA, &$Vector3::clone(vecB));
+                expect_equalish(&vecB.elements(), &[-3., 6., -3.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+            }
+
+            #[test]
+            fn lerp() {
+                let (out, vecA, vecB) = &mut setup();
+                $Vector3::lerp(out, vecA, vecB, 0.5);
+                expect_equalish(&out.elements(), &[2.5, 3.5, 4.5]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+                expect_equalish(&vecB.elements(), &[4., 5., 6.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector3::lerp(vecA, &$Vector3::clone(vecA), vecB, 0.5);
+                expect_equalish(&vecA.elements(), &[2.5, 3.5, 4.5]);
+                expect_equalish(&vecB.elements(), &[4., 5., 6.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector3::lerp(vecB, vecA, &$Vector3::clone(vecB), 0.5);
+                expect_equalish(&vecB.elements(), &[2.5, 3.5, 4.5]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+            }
+
+            #[test]
+            fn random() {
+                let _lock = lock_random();
+                let (out, _, _) = &mut setup();
+                $Vector3::random(out, None);
+                expect_equalish_scalar($Vector3::len(out), 1.0);
+
+                $Vector3::random(out, Some(5.0));
+                expect_equalish_scalar($Vector3::len(out), 5.0);
+            }
+
+            #[test]
+            fn angle() {
+                let (_, vecA, vecB) = &mut setup();
+                expect_equalish_scalar($Vector3::angle(vecA, vecB), 0.225726);
+                expect_equalish(&vecA.elements(), &[1., 2., 3.]);
+                expect_equalish(&vecB.elements(), &[4., 5., 6.]);
+            }
+
+            #[test]
+            fn exactEquals() {
+                let vecA = &$Vector3::fromValues(0., 1., 2.);
+                let vecB = &$Vector3::fromValues(0., 1., 2.);
+                let vecC = &$Vector3::fromValues(1., 2., 3.);
+                assert!($Vector3::exactEquals(vecA, vecB));
+                assert!(!$Vector3::exactEquals(vecA, vecC));
+                expect_equalish(&vecA.elements(), &[0., 1., 2.]);
+                expect_equalish(&vecB.elements(), &[0., 1., 2.]);
+            }
+
+            #[test]
+            fn equals() {
+                let vecA = &$Vector3::fromValues(0., 1., 2.);
+                let vecB = &$Vector3::fromValues(0., 1., 2.);
+                let vecC = &$Vector3::fromValues(1., 2., 3.);
+                let vecD = &$Vector3::fromValues(1e-16, 1., 2.);
+                assert!($Vector3::equals(vecA, vecB));
+                assert!(!$Vector3::equals(vecA, vecC));
+                assert!($Vector3::equals(vecA, vecD));
+                expect_equalish(&vecA.elements(), &[0., 1., 2.]);
+                expect_equalish(&vecB.elements(), &[0., 1., 2.]);
+            }
+
+            #[test]
+            fn zero() {
+                let vecA = &mut $Vector3::fromValues(1., 2., 3.);
+                $Vector3::zero(vecA);
+                expect_equalish(&vecA.elements(), &[0., 0., 0.]);
+            }
+
+            #[test]
+            fn traits() {
+                let a = $Vector3::fromValues(1., 2., 3.);
+                let b = a;
+                assert_eq!(a, b);
+                assert_ne!(a, $Vector3::fromValues(1., 2., 4.));
+                assert_ne!(a, $Vector3::fromValues(1., 2., 3.00001));
+                assert!(a.approx_eq(&$Vector3::fromValues(1., 2., 3.00001)));
+                assert!(!a.approx_eq(&$Vector3::fromValues(1., 2., 4.)));
+                assert_eq!($Vector3::default(), $Vector3::create());
+                assert_eq!(
+                    format!("{:?}", $Vector3::create()),
+                    concat!(stringify!($Vector3), "(0.0, 0.0, 0.0)")
+                );
+            }
+
+            #[test]
+            fn str() {
+                let a = $Vector3::fromValues(1., 2., 3.);
+                assert_eq!($Vector3::str(&a), "vec3(1, 2, 3)");
+                assert_eq!("vec3(1, 2, 3)".parse::<$Vector3>(), Ok(a));
+            }
+
+            #[test]
+            fn parse() {
+                let a = $Vector3::fromValues(0.1, -2.5e-8, 1e10);
+                assert_eq!(a.to_string().parse::<$Vector3>(), Ok(a));
+                assert_eq!(
+                    " vec3 ( 1,2 , 3 ) ".parse(),
+                    Ok($Vector3::fromValues(1., 2., 3.))
+                );
+                assert_eq!(
+                    "vec4(1, 2, 3)".parse::<$Vector3>(),
+                    Err(ParseError::Prefix("vec3"))
+                );
+                assert_eq!(
+                    "vec3(1, 2)".parse::<$Vector3>(),
+                    Err(ParseError::Length(3, 2))
+                );
+                assert_eq!(
+                    "vec3(1, a, 3)".parse::<$Vector3>(),
+                    Err(ParseError::Number("a".to_string()))
+                );
+            }
+
+            #[test]
+            fn equalsWith() {
+                let a = &$Vector3::fromValues(1., 2., 3.);
+                let b = &$Vector3::fromValues(1.001, 2., 3.);
+                let c = &$Vector3::fromValues($F::from_bits($F::to_bits(1.) + 2), 2., 3.);
+                assert!(!$Vector3::equalsWithEpsilon(a, b, 0.0001));
+                assert!($Vector3::equalsWithEpsilon(a, b, 0.01));
+                assert!($Vector3::equalsWithUlps(a, c, 2));
+                assert!(!$Vector3::equalsWithUlps(a, c, 1));
+                assert!(!$Vector3::equalsWithUlps(a, b, 4));
+            }
+
+            #[test]
+            fn setSeed() {
+                let _lock = lock_random();
+                let (out, _, _) = &mut setup();
+                crate::common::setSeed(42);
+                $Vector3::random(out, Some(2.));
+                let first = $Vector3::clone(out);
+                $Vector3::random(out, Some(2.));
+                assert_ne!(out, &first);
+
+                crate::common::setSeed(42);
+                $Vector3::random(out, Some(2.));
+                assert_eq!(out, &first);
+            }
+
+            #[test]
+            fn transformMat4Array() {
+                let m = &mut $Matrix4::create();
+                $Matrix4::fromRotationTranslationScale(
+                    m,
+                    &$Quaternion::fromValues(0., FRAC_1_SQRT_2, 0., FRAC_1_SQRT_2),
+                    &$Vector3::fromValues(1., 2., 3.),
+                    &$Vector3::fromValues(2., 2., 2.),
+                );
+                let a = [1., 2., 3., 4., 5., 6.];
+                let out = &mut [0.; 6];
+                $Vector3::transformMat4Array(out, &a, m, 2);
+
+                let expected = &mut $Vector3::create();
+                $Vector3::transformMat4(expected, &$Vector3::fromValues(1., 2., 3.), m);
+                expect_equalish(&out[..3], &expected.elements());
+                $Vector3::transformMat4(expected, &$Vector3::fromValues(4., 5., 6.), m);
+                expect_equalish(&out[3..], &expected.elements());
+            }
+
+            #[test]
+            fn slice() {
+                let a = $Vector3::fromValues(1., 2., 3.);
+                assert_eq!(a.asSlice(), &a.elements()[..]);
+
+                let buffer = &mut [0.; 4];
+                a.writeSlice(&mut buffer[1..]);
+                assert_eq!($Vector3::fromSlice(&buffer[1..]), a);
+            }
+        }
+    };
 }
+
+pub(crate) use define_vector3;
+
+define_vector3!(
+    f32,
+    Float32Array,
+    fromFloat32Array,
+    Matrix2,
+    Matrix2d,
+    Matrix3,
+    Matrix4,
+    Quaternion,
+    Quaternion2,
+    Vector2,
+    Vector3,
+    Vector4
+);
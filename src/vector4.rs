@@ -4,375 +4,862 @@
  * @Link   : http://dtysky.moe
  * @Date   : 2019/2/7 下午9:53:03
  */
-use wasm_bindgen::prelude::*;
-
-use super::common::*;
-use super::matrix4::*;
-use super::quaternion::*;
-
-#[wasm_bindgen]
-pub struct Vector4(pub f32, pub f32, pub f32, pub f32);
-
-#[wasm_bindgen]
-impl Vector4 {
-    #[wasm_bindgen(getter)]
-    pub fn elements(&self) -> Box<[f32]> {
-        Box::new([self.0, self.1, self.2, self.3])
-    }
-
-    pub fn create() -> Vector4 {
-        Vector4(0., 0., 0., 0.)
-    }
-
-    pub fn clone(a: &Vector4) -> Vector4 {
-        Vector4(a.0, a.1, a.2, a.3)
-    }
-
-    pub fn fromValues(x: f32, y: f32, z: f32, w: f32) -> Vector4 {
-        Vector4(x, y, z, w)
-    }
-
-    pub fn copy(out: &mut Vector4, a: &Vector4) {
-        out.0 = a.0;
-        out.1 = a.1;
-        out.2 = a.2;
-        out.3 = a.3;
-    }
-
-    pub fn set(out: &mut Vector4, x: f32, y: f32, z: f32, w: f32) {
-        out.0 = x;
-        out.1 = y;
-        out.2 = z;
-        out.3 = w;
-    }
-
-    pub fn add(out: &mut Vector4, a: &Vector4, b: &Vector4) {
-        out.0 = a.0 + b.0;
-        out.1 = a.1 + b.1;
-        out.2 = a.2 + b.2;
-        out.3 = a.3 + b.3;
-    }
-
-    pub fn subtract(out: &mut Vector4, a: &Vector4, b: &Vector4) {
-        out.0 = a.0 - b.0;
-        out.1 = a.1 - b.1;
-        out.2 = a.2 - b.2;
-        out.3 = a.3 - b.3;
-    }
-
-    pub fn multiply(out: &mut Vector4, a: &Vector4, b: &Vector4) {
-        out.0 = a.0 * b.0;
-        out.1 = a.1 * b.1;
-        out.2 = a.2 * b.2;
-        out.3 = a.3 * b.3;
-    }
-
-    pub fn divide(out: &mut Vector4, a: &Vector4, b: &Vector4) {
-        out.0 = a.0 / b.0;
-        out.1 = a.1 / b.1;
-        out.2 = a.2 / b.2;
-        out.3 = a.3 / b.3;
-    }
-
-    pub fn ceil(out: &mut Vector4, a: &Vector4) {
-        out.0 = f32::ceil(a.0);
-        out.1 = f32::ceil(a.1);
-        out.2 = f32::ceil(a.2);
-        out.3 = f32::ceil(a.3);
-    }
-
-    pub fn floor(out: &mut Vector4, a: &Vector4) {
-        out.0 = f32::floor(a.0);
-        out.1 = f32::floor(a.1);
-        out.2 = f32::floor(a.2);
-        out.3 = f32::floor(a.3);
-    }
-
-    pub fn min(out: &mut Vector4, a: &Vector4, b: &Vector4) {
-        out.0 = f32::min(a.0, b.0);
-        out.1 = f32::min(a.1, b.1);
-        out.2 = f32::min(a.2, b.2);
-        out.3 = f32::min(a.3, b.3);
-    }
-
-    pub fn max(out: &mut Vector4, a: &Vector4, b: &Vector4) {
-        out.0 = f32::max(a.0, b.0);
-        out.1 = f32::max(a.1, b.1);
-        out.2 = f32::max(a.2, b.2);
-        out.3 = f32::max(a.3, b.3);
-    }
-
-    pub fn round(out: &mut Vector4, a: &Vector4) {
-        out.0 = f32::round(a.0);
-        out.1 = f32::round(a.1);
-        out.2 = f32::round(a.2);
-        out.3 = f32::round(a.3);
-    }
-
-    pub fn scale(out: &mut Vector4, a: &Vector4, b: f32) {
-        out.0 = a.0 * b;
-        out.1 = a.1 * b;
-        out.2 = a.2 * b;
-        out.3 = a.3 * b;
-    }
-
-    pub fn scaleAndAdd(out: &mut Vector4, a: &Vector4, b: &Vector4, scale: f32) {
-        out.0 = a.0 + (b.0 * scale);
-        out.1 = a.1 + (b.1 * scale);
-        out.2 = a.2 + (b.2 * scale);
-        out.3 = a.3 + (b.3 * scale);
-    }
-
-    pub fn distance(a: &Vector4, b: &Vector4) -> f32 {
-        let x = b.0 - a.0;
-        let y = b.1 - a.1;
-        let z = b.2 - a.2;
-        let w = b.3 - a.3;
-        (x.powi(2) + y.powi(2) + z.powi(2) + w.powi(2)).sqrt()
-    }
-
-    pub fn squaredDistance(a: &Vector4, b: &Vector4) -> f32 {
-        let x = b.0 - a.0;
-        let y = b.1 - a.1;
-        let z = b.2 - a.2;
-        let w = b.3 - a.3;
-        x * x + y * y + z * z + w * w
-    }
-
-    pub fn len(a: &Vector4) -> f32 {
-        let x = a.0;
-        let y = a.1;
-        let z = a.2;
-        let w = a.3;
-        (x.powi(2) + y.powi(2) + z.powi(2) + w.powi(2)).sqrt()
-    }
-
-    pub fn squaredLength(a: &Vector4) -> f32 {
-        let x = a.0;
-        let y = a.1;
-        let z = a.2;
-        let w = a.3;
-        x * x + y * y + z * z + w * w
-    }
-
-    pub fn negate(out: &mut Vector4, a: &Vector4) {
-        out.0 = -a.0;
-        out.1 = -a.1;
-        out.2 = -a.2;
-        out.3 = -a.3;
-    }
-
-    pub fn inverse(out: &mut Vector4, a: &Vector4) {
-        out.0 = 1.0 / a.0;
-        out.1 = 1.0 / a.1;
-        out.2 = 1.0 / a.2;
-        out.3 = 1.0 / a.3;
-    }
-
-    pub fn normalize(out: &mut Vector4, a: &Vector4) {
-        let x = a.0;
-        let y = a.1;
-        let z = a.2;
-        let w = a.3;
-        let mut len = x * x + y * y + z * z + w * w;
-        if (len > EPSILON) {
-            len = 1. / f32::sqrt(len);
+// `Vector4` here and `Vector4d` in `double`. `$F` is the element type, `$FloatArray` the js array of it
+// that `$fromFloatArray` reads, and the other names are the types of the same precision.
+macro_rules! define_vector4 {
+    (
+        $F:ident,
+        $FloatArray:ident,
+        $fromFloatArray:ident,
+        $Matrix2:ident,
+        $Matrix2d:ident,
+        $Matrix3:ident,
+        $Matrix4:ident,
+        $Quaternion:ident,
+        $Quaternion2:ident,
+        $Vector2:ident,
+        $Vector3:ident,
+        $Vector4:ident
+    ) => {
+        #[cfg(feature = "wasm")]
+        use js_sys::$FloatArray;
+        #[cfg(feature = "wasm")]
+        use wasm_bindgen::prelude::*;
+
+        use core::fmt;
+        use core::str::FromStr;
+
+        use super::common::*;
+        use super::matrix4::*;
+        use super::quaternion::*;
+        use crate::kernels::{self, Elements};
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        #[derive(Clone, Copy, Debug)]
+        #[repr(C)]
+        pub struct $Vector4(pub $F, pub $F, pub $F, pub $F);
+
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        impl $Vector4 {
+            #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+            pub fn elements(&self) -> Box<[$F]> {
+                Box::new([self.0, self.1, self.2, self.3])
+            }
+
+            pub fn create() -> $Vector4 {
+                $Vector4(0., 0., 0., 0.)
+            }
+
+            #[allow(clippy::should_implement_trait)]
+            pub fn clone(a: &$Vector4) -> $Vector4 {
+                $Vector4(a.0, a.1, a.2, a.3)
+            }
+
+            pub fn fromValues(x: $F, y: $F, z: $F, w: $F) -> $Vector4 {
+                $Vector4(x, y, z, w)
+            }
+
+            pub fn copy(out: &mut $Vector4, a: &$Vector4) {
+                *out = *a;
+            }
+
+            pub fn set(out: &mut $Vector4, x: $F, y: $F, z: $F, w: $F) {
+                out.0 = x;
+                out.1 = y;
+                out.2 = z;
+                out.3 = w;
+            }
+
+            pub fn add(out: &mut $Vector4, a: &$Vector4, b: &$Vector4) {
+                kernels::add(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn subtract(out: &mut $Vector4, a: &$Vector4, b: &$Vector4) {
+                kernels::subtract(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn multiply(out: &mut $Vector4, a: &$Vector4, b: &$Vector4) {
+                kernels::multiply(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn divide(out: &mut $Vector4, a: &$Vector4, b: &$Vector4) {
+                kernels::divide(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn ceil(out: &mut $Vector4, a: &$Vector4) {
+                kernels::ceil(out.arrayMut(), a.array());
+            }
+
+            pub fn floor(out: &mut $Vector4, a: &$Vector4) {
+                kernels::floor(out.arrayMut(), a.array());
+            }
+
+            pub fn min(out: &mut $Vector4, a: &$Vector4, b: &$Vector4) {
+                kernels::min(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn max(out: &mut $Vector4, a: &$Vector4, b: &$Vector4) {
+                kernels::max(out.arrayMut(), a.array(), b.array());
+            }
+
+            pub fn round(out: &mut $Vector4, a: &$Vector4) {
+                kernels::round(out.arrayMut(), a.array());
+            }
+
+            pub fn scale(out: &mut $Vector4, a: &$Vector4, b: $F) {
+                kernels::scale(out.arrayMut(), a.array(), b);
+            }
+
+            pub fn scaleAndAdd(out: &mut $Vector4, a: &$Vector4, b: &$Vector4, scale: $F) {
+                kernels::scaleAndAdd(out.arrayMut(), a.array(), b.array(), scale);
+            }
+
+            pub fn distance(a: &$Vector4, b: &$Vector4) -> $F {
+                kernels::distance(a.array(), b.array())
+            }
+
+            pub fn squaredDistance(a: &$Vector4, b: &$Vector4) -> $F {
+                kernels::squaredDistance(a.array(), b.array())
+            }
+
+            pub fn len(a: &$Vector4) -> $F {
+                kernels::length(a.array())
+            }
+
+            pub fn squaredLength(a: &$Vector4) -> $F {
+                kernels::squaredLength(a.array())
+            }
+
+            pub fn negate(out: &mut $Vector4, a: &$Vector4) {
+                kernels::negate(out.arrayMut(), a.array());
+            }
+
+            pub fn inverse(out: &mut $Vector4, a: &$Vector4) {
+                kernels::inverse(out.arrayMut(), a.array());
+            }
+
+            pub fn normalize(out: &mut $Vector4, a: &$Vector4) {
+                kernels::normalize(out.arrayMut(), a.array(), EPSILON);
+            }
+
+            pub fn dot(a: &$Vector4, b: &$Vector4) -> $F {
+                kernels::dot(a.array(), b.array())
+            }
+
+            pub fn cross(out: &mut $Vector4, u: &$Vector4, v: &$Vector4, w: &$Vector4) {
+                if core::ptr::eq(out, u) {
+                    let v0 = v.0;
+                    let v1 = v.1;
+                    let v2 = v.2;
+                    let v3 = v.3;
+                    let w0 = w.0;
+                    let w1 = w.1;
+                    let w2 = w.2;
+                    let w3 = w.3;
+                    let A = (v0 * w1) - (v1 * w0);
+                    let B = (v0 * w2) - (v2 * w0);
+                    let C = (v0 * w3) - (v3 * w0);
+                    let D = (v1 * w2) - (v2 * w1);
+                    let E = (v1 * w3) - (v3 * w1);
+                    let F = (v2 * w3) - (v3 * w2);
+                    let G = out.0;
+                    let H = out.1;
+                    let I = out.2;
+                    let J = out.3;
+
+                    out.0 = (H * F) - (I * E) + (J * D);
+                    out.1 = -(G * F) + (I * C) - (J * B);
+                    out.2 = (G * E) - (H * C) + (J * A);
+                    out.3 = -(G * D) + (H * B) - (I * A);
+                    return;
+                }
+
+                let v0 = v.0;
+                let v1 = v.1;
+                let v2 = v.2;
+                let v3 = v.3;
+                let w0 = w.0;
+                let w1 = w.1;
+                let w2 = w.2;
+                let w3 = w.3;
+                let A = (v0 * w1) - (v1 * w0);
+                let B = (v0 * w2) - (v2 * w0);
+                let C = (v0 * w3) - (v3 * w0);
+                let D = (v1 * w2) - (v2 * w1);
+                let E = (v1 * w3) - (v3 * w1);
+                let F = (v2 * w3) - (v3 * w2);
+                let G = u.0;
+                let H = u.1;
+                let I = u.2;
+                let J = u.3;
+
+                out.0 = (H * F) - (I * E) + (J * D);
+                out.1 = -(G * F) + (I * C) - (J * B);
+                out.2 = (G * E) - (H * C) + (J * A);
+                out.3 = -(G * D) + (H * B) - (I * A);
+            }
+
+            pub fn lerp(out: &mut $Vector4, a: &$Vector4, b: &$Vector4, t: $F) {
+                kernels::lerp(out.arrayMut(), a.array(), b.array(), t);
+            }
+
+            pub fn random(out: &mut $Vector4, scale: Option<$F>) {
+                let scale = scale.unwrap_or(1.);
+
+                // Marsaglia, George. Choosing a Point from the Surface of a
+                // Sphere. Ann. f32:: Statist. 43 (1972), no. 2, 645--646.
+                // http://projecteuclid.org/euclid.aoms/1177692644;
+                let mut v1 = 0.;
+                let mut v2 = 0.;
+                let mut v3 = 0.;
+                let mut v4 = 0.;
+                let mut s1 = 2.;
+                let mut s2 = 2.;
+
+                while s1 > 1. {
+                    v1 = RANDOM() * 2. - 1.;
+                    v2 = RANDOM() * 2. - 1.;
+                    s1 = v1 * v1 + v2 * v2;
+                }
+
+                while s2 > 1. {
+                    v3 = RANDOM() * 2. - 1.;
+                    v4 = RANDOM() * 2. - 1.;
+                    s2 = v3 * v3 + v4 * v4;
+                }
+
+                let d = $F::sqrt((1. - s1) / s2);
+                out.0 = scale * v1;
+                out.1 = scale * v2;
+                out.2 = scale * v3 * d;
+                out.3 = scale * v4 * d;
+            }
+
+            pub fn transformMat4(out: &mut $Vector4, a: &$Vector4, m: &$Matrix4) {
+                f32_only!(
+                    $F,
+                    if cfg!(feature = "simd") {
+                        crate::simd::transformVec4Mat4(out, a, m);
+                        return;
+                    }
+                );
+
+                let x = a.0;
+                let y = a.1;
+                let z = a.2;
+                let w = a.3;
+                out.0 = m.0 * x + m.4 * y + m.8 * z + m.12 * w;
+                out.1 = m.1 * x + m.5 * y + m.9 * z + m.13 * w;
+                out.2 = m.2 * x + m.6 * y + m.10 * z + m.14 * w;
+                out.3 = m.3 * x + m.7 * y + m.11 * z + m.15 * w;
+            }
+
+            pub fn transformQuat(out: &mut $Vector4, a: &$Vector4, q: &$Quaternion) {
+                let x = a.0;
+                let y = a.1;
+                let z = a.2;
+                let qx = q.0;
+                let qy = q.1;
+                let qz = q.2;
+                let qw = q.3;
+
+                // calculate quat * vec
+                let ix = qw * x + qy * z - qz * y;
+                let iy = qw * y + qz * x - qx * z;
+                let iz = qw * z + qx * y - qy * x;
+                let iw = -qx * x - qy * y - qz * z;
+
+                // calculate result * inverse quat
+                out.0 = ix * qw + iw * -qx + iy * -qz - iz * -qy;
+                out.1 = iy * qw + iw * -qy + iz * -qx - ix * -qz;
+                out.2 = iz * qw + iw * -qz + ix * -qy - iy * -qx;
+                out.3 = a.3;
+            }
+
+            pub fn zero(out: &mut $Vector4) {
+                out.0 = 0.0;
+                out.1 = 0.0;
+                out.2 = 0.0;
+                out.3 = 0.0;
+            }
+
+            pub fn str(a: &$Vector4) -> String {
+                a.to_string()
+            }
+
+            pub fn exactEquals(a: &$Vector4, b: &$Vector4) -> bool {
+                kernels::exactEquals(a.array(), b.array())
+            }
+
+            pub fn equals(a: &$Vector4, b: &$Vector4) -> bool {
+                match getEqualsMode() {
+                    EqualsMode::Epsilon => $Vector4::equalsWithEpsilon(a, b, getEpsilon()),
+                    EqualsMode::Ulps => $Vector4::equalsWithUlps(a, b, getMaxUlps()),
+                }
+            }
+
+            pub fn equalsWithEpsilon(a: &$Vector4, b: &$Vector4, epsilon: $F) -> bool {
+                kernels::equalsWithEpsilon(a.array(), b.array(), epsilon)
+            }
+
+            pub fn equalsWithUlps(a: &$Vector4, b: &$Vector4, maxUlps: u32) -> bool {
+                kernels::equalsWithUlps(a.array(), b.array(), maxUlps)
+            }
+
+            pub fn sub(out: &mut $Vector4, a: &$Vector4, b: &$Vector4) {
+                $Vector4::subtract(out, a, b);
+            }
+
+            pub fn mul(out: &mut $Vector4, a: &$Vector4, b: &$Vector4) {
+                $Vector4::multiply(out, a, b);
+            }
+
+            pub fn div(out: &mut $Vector4, a: &$Vector4, b: &$Vector4) {
+                $Vector4::divide(out, a, b);
+            }
+
+            pub fn dist(a: &$Vector4, b: &$Vector4) -> $F {
+                $Vector4::distance(a, b)
+            }
+
+            pub fn sqrDist(a: &$Vector4, b: &$Vector4) -> $F {
+                $Vector4::squaredDistance(a, b)
+            }
+
+            pub fn sqrLen(a: &$Vector4) -> $F {
+                $Vector4::squaredLength(a)
+            }
         }
-        out.0 = x * len;
-        out.1 = y * len;
-        out.2 = z * len;
-        out.3 = w * len;
-    }
-
-    pub fn dot(a: &Vector4, b: &Vector4) -> f32 {
-        a.0 * b.0 + a.1 * b.1 + a.2 * b.2 + a.3 * b.3
-    }
-
-    pub fn cross(out: &mut Vector4, u: &Vector4, v: &Vector4, w: &Vector4) {
-        if (out as *const Vector4) == (u as *const Vector4) {
-            let v0 = v.0;
-            let v1 = v.1;
-            let v2 = v.2;
-            let v3 = v.3;
-            let w0 = w.0;
-            let w1 = w.1;
-            let w2 = w.2;
-            let w3 = w.3;
-            let A = (v0 * w1) - (v1 * w0);
-            let B = (v0 * w2) - (v2 * w0);
-            let C = (v0 * w3) - (v3 * w0);
-            let D = (v1 * w2) - (v2 * w1);
-            let E = (v1 * w3) - (v3 * w1);
-            let F = (v2 * w3) - (v3 * w2);
-            let G = out.0;
-            let H = out.1;
-            let I = out.2;
-            let J = out.3;
-
-            out.0 = (H * F) - (I * E) + (J * D);
-            out.1 = -(G * F) + (I * C) - (J * B);
-            out.2 = (G * E) - (H * C) + (J * A);
-            out.3 = -(G * D) + (H * B) - (I * A);
-            return;
+
+        // Flat f32 buffers, such as the uniforms or the batch functions.
+        impl $Vector4 {
+            pub fn fromSlice(a: &[$F]) -> $Vector4 {
+                $Vector4(a[0], a[1], a[2], a[3])
+            }
+
+            pub fn writeSlice(&self, out: &mut [$F]) {
+                out[..4].copy_from_slice(self.array());
+            }
+
+            pub fn asSlice(&self) -> &[$F] {
+                self.array()
+            }
+        }
+
+        // `#[repr(C)]` keeps the 4 elements contiguous.
+        unsafe impl Elements<$F, 4> for $Vector4 {}
+
+        #[cfg(feature = "wasm")]
+        #[wasm_bindgen]
+        impl $Vector4 {
+            // Aliases the elements in wasm memory without copying. The view is detached when the memory grows,
+            // so don't keep it across calls that may allocate.
+            pub fn view(&self) -> $FloatArray {
+                unsafe { $FloatArray::view(self.asSlice()) }
+            }
+
+            // Reads the first 4 elements of `a`, without allocating in wasm memory. Throws a `RangeError`
+            // when `a` is shorter.
+            pub fn $fromFloatArray(a: &$FloatArray) -> Result<$Vector4, JsValue> {
+                check_length(4, a.length() as usize)?;
+                let mut elements = [0.; 4];
+                a.subarray(0, 4).copy_to(&mut elements);
+                Ok($Vector4::fromSlice(&elements))
+            }
+
+            pub fn copyFromArray(out: &mut $Vector4, a: &$FloatArray) -> Result<(), JsValue> {
+                *out = $Vector4::$fromFloatArray(a)?;
+                Ok(())
+            }
+        }
+
+        impl PartialEq for $Vector4 {
+            fn eq(&self, other: &$Vector4) -> bool {
+                $Vector4::exactEquals(self, other)
+            }
         }
 
-        let v0 = v.0;
-        let v1 = v.1;
-        let v2 = v.2;
-        let v3 = v.3;
-        let w0 = w.0;
-        let w1 = w.1;
-        let w2 = w.2;
-        let w3 = w.3;
-        let A = (v0 * w1) - (v1 * w0);
-        let B = (v0 * w2) - (v2 * w0);
-        let C = (v0 * w3) - (v3 * w0);
-        let D = (v1 * w2) - (v2 * w1);
-        let E = (v1 * w3) - (v3 * w1);
-        let F = (v2 * w3) - (v3 * w2);
-        let G = u.0;
-        let H = u.1;
-        let I = u.2;
-        let J = u.3;
-
-        out.0 = (H * F) - (I * E) + (J * D);
-        out.1 = -(G * F) + (I * C) - (J * B);
-        out.2 = (G * E) - (H * C) + (J * A);
-        out.3 = -(G * D) + (H * B) - (I * A);
-    }
-
-    pub fn lerp(out: &mut Vector4, a: &Vector4, b: &Vector4, t: f32) {
-        let ax = a.0;
-        let ay = a.1;
-        let az = a.2;
-        let aw = a.3;
-        out.0 = ax + t * (b.0 - ax);
-        out.1 = ay + t * (b.1 - ay);
-        out.2 = az + t * (b.2 - az);
-        out.3 = aw + t * (b.3 - aw);
-    }
-
-    pub fn random(out: &mut Vector4, scale: Option<f32>) {
-        let mut s = 1.;
-        match scale {
-            Some(value) => s = value,
-            None => {}
-        };
-        let scale = s;
-
-        // Marsaglia, George. Choosing a Point from the Surface of a
-        // Sphere. Ann. f32:: Statist. 43 (1972), no. 2, 645--646.
-        // http://projecteuclid.org/euclid.aoms/1177692644;
-        let mut v1 = 0.;
-        let mut v2 = 0.;
-        let mut v3 = 0.;
-        let mut v4 = 0.;
-        let mut s1 = 2.;
-        let mut s2 = 2.;
-
-        while s1 > 1. {
-            v1 = RANDOM() * 2. - 1.;
-            v2 = RANDOM() * 2. - 1.;
-            s1 = v1 * v1 + v2 * v2;
+        impl ApproxEq for $Vector4 {
+            fn approx_eq(&self, other: &$Vector4) -> bool {
+                $Vector4::equals(self, other)
+            }
         }
 
-        while s2 > 1. {
-            v3 = RANDOM() * 2. - 1.;
-            v4 = RANDOM() * 2. - 1.;
-            s2 = v3 * v3 + v4 * v4;
+        impl Default for $Vector4 {
+            fn default() -> $Vector4 {
+                $Vector4::create()
+            }
         }
 
-        let d = f32::sqrt((1. - s1) / s2);
-        out.0 = scale * v1;
-        out.1 = scale * v2;
-        out.2 = scale * v3 * d;
-        out.3 = scale * v4 * d;
-    }
-
-    pub fn transformMat4(out: &mut Vector4, a: &Vector4, m: &Matrix4) {
-        let x = a.0;
-        let y = a.1;
-        let z = a.2;
-        let w = a.3;
-        out.0 = m.0 * x + m.4 * y + m.8 * z + m.12 * w;
-        out.1 = m.1 * x + m.5 * y + m.9 * z + m.13 * w;
-        out.2 = m.2 * x + m.6 * y + m.10 * z + m.14 * w;
-        out.3 = m.3 * x + m.7 * y + m.11 * z + m.15 * w;
-    }
-
-    pub fn transformQuat(out: &mut Vector4, a: &Vector4, q: &Quaternion) {
-        let x = a.0;
-        let y = a.1;
-        let z = a.2;
-        let qx = q.0;
-        let qy = q.1;
-        let qz = q.2;
-        let qw = q.3;
-
-        // calculate quat * vec
-        let ix = qw * x + qy * z - qz * y;
-        let iy = qw * y + qz * x - qx * z;
-        let iz = qw * z + qx * y - qy * x;
-        let iw = -qx * x - qy * y - qz * z;
-
-        // calculate result * inverse quat
-        out.0 = ix * qw + iw * -qx + iy * -qz - iz * -qy;
-        out.1 = iy * qw + iw * -qy + iz * -qx - ix * -qz;
-        out.2 = iz * qw + iw * -qz + ix * -qy - iy * -qx;
-        out.3 = a.3;
-    }
-
-    pub fn zero(out: &mut Vector4) {
-        out.0 = 0.0;
-        out.1 = 0.0;
-        out.2 = 0.0;
-        out.3 = 0.0;
-    }
-
-    // pub fn str(a: &Vector4) {
-    //   return "vec4(" + a.0 + ", " + a.1 + ", " + a.2 + ", " + a.3 + ")";
-    // }
-
-    pub fn exactEquals(a: &Vector4, b: &Vector4) -> bool {
-        a.0 == b.0 && a.1 == b.1 && a.2 == b.2 && a.3 == b.3
-    }
-
-    pub fn equals(a: &Vector4, b: &Vector4) -> bool {
-        let a0 = a.0;
-        let a1 = a.1;
-        let a2 = a.2;
-        let a3 = a.3;
-        let b0 = b.0;
-        let b1 = b.1;
-        let b2 = b.2;
-        let b3 = b.3;
-        f32::abs(a0 - b0) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a0), f32::abs(b0)))
-            && f32::abs(a1 - b1) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a1), f32::abs(b1)))
-            && f32::abs(a2 - b2) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a2), f32::abs(b2)))
-            && f32::abs(a3 - b3) <= EPSILON * f32::max(1.0, f32::max(f32::abs(a3), f32::abs(b3)))
-    }
-
-    pub fn sub(out: &mut Vector4, a: &Vector4, b: &Vector4) {
-        Vector4::subtract(out, a, b);
-    }
-
-    pub fn mul(out: &mut Vector4, a: &Vector4, b: &Vector4) {
-        Vector4::multiply(out, a, b);
-    }
-
-    pub fn div(out: &mut Vector4, a: &Vector4, b: &Vector4) {
-        Vector4::divide(out, a, b);
-    }
-
-    pub fn dist(a: &Vector4, b: &Vector4) -> f32 {
-        Vector4::distance(a, b)
-    }
-
-    pub fn sqrDist(a: &Vector4, b: &Vector4) -> f32 {
-        Vector4::squaredDistance(a, b)
-    }
-
-    pub fn sqrLen(a: &Vector4) -> f32 {
-        Vector4::squaredLength(a)
-    }
+        impl fmt::Display for $Vector4 {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "vec4({}, {}, {}, {})", self.0, self.1, self.2, self.3)
+            }
+        }
+
+        impl FromStr for $Vector4 {
+            type Err = ParseError;
+
+            fn from_str(s: &str) -> Result<$Vector4, ParseError> {
+                let mut v = [0.; 4];
+                parse_values(s, "vec4", &mut v)?;
+                Ok($Vector4(v[0], v[1], v[2], v[3]))
+            }
+        }
+
+        #[cfg(test)]
+        mod tests {
+            use super::*;
+            use crate::spec_helper::*;
+            use std::$F::consts::{E, FRAC_1_SQRT_2, SQRT_2};
+
+            fn setup() -> ($Vector4, $Vector4, $Vector4) {
+                (
+                    $Vector4::fromValues(0., 0., 0., 0.),
+                    $Vector4::fromValues(1., 2., 3., 4.),
+                    $Vector4::fromValues(5., 6., 7., 8.),
+                )
+            }
+
+            #[test]
+            fn create() {
+                let result = $Vector4::create();
+                expect_equalish(&result.elements(), &[0., 0., 0., 0.]);
+            }
+
+            #[test]
+            fn clone() {
+                let (_, vecA, _) = &mut setup();
+                let result = $Vector4::clone(vecA);
+                expect_equalish(&result.elements(), &vecA.elements());
+            }
+
+            #[test]
+            fn fromValues() {
+                let result = $Vector4::fromValues(1., 2., 3., 4.);
+                expect_equalish(&result.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn copy() {
+                let (out, vecA, _) = &mut setup();
+                $Vector4::copy(out, vecA);
+                expect_equalish(&out.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn set() {
+                let (out, _, _) = &mut setup();
+                $Vector4::set(out, 1., 2., 3., 4.);
+                expect_equalish(&out.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn add() {
+                let (out, vecA, vecB) = &mut setup();
+                $Vector4::add(out, vecA, vecB);
+                expect_equalish(&out.elements(), &[6., 8., 10., 12.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3., 4.]);
+                expect_equalish(&vecB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector4::add(vecA, &$Vector4::clone(vecA), vecB);
+                expect_equalish(&vecA.elements(), &[6., 8., 10., 12.]);
+                expect_equalish(&vecB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector4::add(vecB, vecA, &$Vector4::clone(vecB));
+                expect_equalish(&vecB.elements(), &[6., 8., 10., 12.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn subtract() {
+                let (out, vecA, vecB) = &mut setup();
+                $Vector4::subtract(out, vecA, vecB);
+                expect_equalish(&out.elements(), &[-4., -4., -4., -4.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3., 4.]);
+                expect_equalish(&vecB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector4::subtract(vecA, &$Vector4::clone(vecA), vecB);
+                expect_equalish(&vecA.elements(), &[-4., -4., -4., -4.]);
+                expect_equalish(&vecB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector4::subtract(vecB, vecA, &$Vector4::clone(vecB));
+                expect_equalish(&vecB.elements(), &[-4., -4., -4., -4.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn multiply() {
+                let (out, vecA, vecB) = &mut setup();
+                $Vector4::multiply(out, vecA, vecB);
+                expect_equalish(&out.elements(), &[5., 12., 21., 32.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3., 4.]);
+                expect_equalish(&vecB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector4::multiply(vecA, &$Vector4::clone(vecA), vecB);
+                expect_equalish(&vecA.elements(), &[5., 12., 21., 32.]);
+                expect_equalish(&vecB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector4::multiply(vecB, vecA, &$Vector4::clone(vecB));
+                expect_equalish(&vecB.elements(), &[5., 12., 21., 32.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn divide() {
+                let (out, vecA, vecB) = &mut setup();
+                $Vector4::divide(out, vecA, vecB);
+                expect_equalish(&out.elements(), &[0.2, 0.333333, 0.428571, 0.5]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3., 4.]);
+                expect_equalish(&vecB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector4::divide(vecA, &$Vector4::clone(vecA), vecB);
+                expect_equalish(&vecA.elements(), &[0.2, 0.333333, 0.428571, 0.5]);
+                expect_equalish(&vecB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector4::divide(vecB, vecA, &$Vector4::clone(vecB));
+                expect_equalish(&vecB.elements(), &[0.2, 0.333333, 0.428571, 0.5]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn ceil() {
+                let out = &mut $Vector4::create();
+                let vecA = &mut $Vector4::fromValues(E, PI, SQRT_2, FRAC_1_SQRT_2);
+                $Vector4::ceil(out, vecA);
+                expect_equalish(&out.elements(), &[3., 4., 2., 1.]);
+                expect_equalish(&vecA.elements(), &[E, PI, SQRT_2, FRAC_1_SQRT_2]);
+
+                $Vector4::ceil(vecA, &$Vector4::clone(vecA));
+                expect_equalish(&vecA.elements(), &[3., 4., 2., 1.]);
+            }
+
+            #[test]
+            fn floor() {
+                let out = &mut $Vector4::create();
+                let vecA = &mut $Vector4::fromValues(E, PI, SQRT_2, FRAC_1_SQRT_2);
+                $Vector4::floor(out, vecA);
+                expect_equalish(&out.elements(), &[2., 3., 1., 0.]);
+                expect_equalish(&vecA.elements(), &[E, PI, SQRT_2, FRAC_1_SQRT_2]);
+
+                $Vector4::floor(vecA, &$Vector4::clone(vecA));
+                expect_equalish(&vecA.elements(), &[2., 3., 1., 0.]);
+            }
+
+            #[test]
+            fn min() {
+                let out = &mut $Vector4::create();
+                let vecA = &mut $Vector4::fromValues(1., 3., 1., 3.);
+                let vecB = &mut $Vector4::fromValues(3., 1., 3., 1.);
+                $Vector4::min(out, vecA, vecB);
+                expect_equalish(&out.elements(), &[1., 1., 1., 1.]);
+                expect_equalish(&vecA.elements(), &[1., 3., 1., 3.]);
+                expect_equalish(&vecB.elements(), &[3., 1., 3., 1.]);
+
+                $Vector4::min(vecA, &$Vector4::clone(vecA), vecB);
+                expect_equalish(&vecA.elements(), &[1., 1., 1., 1.]);
+                expect_equalish(&vecB.elements(), &[3., 1., 3., 1.]);
+
+                let vecA = &mut $Vector4::fromValues(1., 3., 1., 3.);
+                $Vector4::min(vecB, vecA, &$Vector4::clone(vecB));
+                expect_equalish(&vecB.elements(), &[1., 1., 1., 1.]);
+                expect_equalish(&vecA.elements(), &[1., 3., 1., 3.]);
+            }
+
+            #[test]
+            fn max() {
+                let out = &mut $Vector4::create();
+                let vecA = &mut $Vector4::fromValues(1., 3., 1., 3.);
+                let vecB = &mut $Vector4::fromValues(3., 1., 3., 1.);
+                $Vector4::max(out, vecA, vecB);
+                expect_equalish(&out.elements(), &[3., 3., 3., 3.]);
+                expect_equalish(&vecA.elements(), &[1., 3., 1., 3.]);
+                expect_equalish(&vecB.elements(), &[3., 1., 3., 1.]);
+
+                $Vector4::max(vecA, &$Vector4::clone(vecA), vecB);
+                expect_equalish(&vecA.elements(), &[3., 3., 3., 3.]);
+                expect_equalish(&vecB.elements(), &[3., 1., 3., 1.]);
+
+                let vecA = &mut $Vector4::fromValues(1., 3., 1., 3.);
+                $Vector4::max(vecB, vecA, &$Vector4::clone(vecB));
+                expect_equalish(&vecB.elements(), &[3., 3., 3., 3.]);
+                expect_equalish(&vecA.elements(), &[1., 3., 1., 3.]);
+            }
+
+            #[test]
+            fn round() {
+                let out = &mut $Vector4::create();
+                let vecA = &mut $Vector4::fromValues(E, PI, SQRT_2, FRAC_1_SQRT_2);
+                $Vector4::round(out, vecA);
+                expect_equalish(&out.elements(), &[3., 3., 1., 1.]);
+                expect_equalish(&vecA.elements(), &[E, PI, SQRT_2, FRAC_1_SQRT_2]);
+
+                $Vector4::round(vecA, &$Vector4::clone(vecA));
+                expect_equalish(&vecA.elements(), &[3., 3., 1., 1.]);
+            }
+
+            #[test]
+            fn scale() {
+                let out = &mut $Vector4::create();
+                let vecA = &mut $Vector4::fromValues(1., 2., 3., 4.);
+                $Vector4::scale(out, vecA, 2.);
+                expect_equalish(&out.elements(), &[2., 4., 6., 8.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3., 4.]);
+
+                $Vector4::scale(vecA, &$Vector4::clone(vecA), 2.);
+                expect_equalish(&vecA.elements(), &[2., 4., 6., 8.]);
+            }
+
+            #[test]
+            fn scaleAndAdd() {
+                let (out, vecA, vecB) = &mut setup();
+                $Vector4::scaleAndAdd(out, vecA, vecB, 0.5);
+                expect_equalish(&out.elements(), &[3.5, 5., 6.5, 8.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3., 4.]);
+                expect_equalish(&vecB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector4::scaleAndAdd(vecA, &$Vector4::clone(vecA), vecB, 0.5);
+                expect_equalish(&vecA.elements(), &[3.5, 5., 6.5, 8.]);
+                expect_equalish(&vecB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector4::scaleAndAdd(vecB, vecA, &$Vector4::clone(vecB), 0.5);
+                expect_equalish(&vecB.elements(), &[3.5, 5., 6.5, 8.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn distance() {
+                let (_, vecA, vecB) = &mut setup();
+                expect_equalish_scalar($Vector4::distance(vecA, vecB), 8.);
+            }
+
+            #[test]
+            fn squaredDistance() {
+                let (_, vecA, vecB) = &mut setup();
+                expect_equalish_scalar($Vector4::squaredDistance(vecA, vecB), 64.);
+            }
+
+            #[test]
+            fn length() {
+                let (_, vecA, _) = &mut setup();
+                expect_equalish_scalar($Vector4::len(vecA), 5.477225);
+            }
+
+            #[test]
+            fn squaredLength() {
+                let (_, vecA, _) = &mut setup();
+                expect_equalish_scalar($Vector4::squaredLength(vecA), 30.);
+            }
+
+            #[test]
+            fn negate() {
+                let out = &mut $Vector4::create();
+                let vecA = &mut $Vector4::fromValues(1., 2., 3., 4.);
+                $Vector4::negate(out, vecA);
+                expect_equalish(&out.elements(), &[-1., -2., -3., -4.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3., 4.]);
+
+                $Vector4::negate(vecA, &$Vector4::clone(vecA));
+                expect_equalish(&vecA.elements(), &[-1., -2., -3., -4.]);
+            }
+
+            #[test]
+            fn normalize() {
+                let out = &mut $Vector4::create();
+                let vecA = &mut $Vector4::fromValues(5., 0., 0., 0.);
+                $Vector4::normalize(out, vecA);
+                expect_equalish(&out.elements(), &[1., 0., 0., 0.]);
+                expect_equalish(&vecA.elements(), &[5., 0., 0., 0.]);
+
+                $Vector4::normalize(vecA, &$Vector4::clone(vecA));
+                expect_equalish(&vecA.elements(), &[1., 0., 0., 0.]);
+            }
+
+            #[test]
+            fn dot() {
+                let (_, vecA, vecB) = &mut setup();
+                expect_equalish_scalar($Vector4::dot(vecA, vecB), 70.);
+                expect_equalish(&vecA.elements(), &[1., 2., 3., 4.]);
+                expect_equalish(&vecB.elements(), &[5., 6., 7., 8.]);
+            }
+
+            #[test]
+            fn lerp() {
+                let (out, vecA, vecB) = &mut setup();
+                $Vector4::lerp(out, vecA, vecB, 0.5);
+                expect_equalish(&out.elements(), &[3., 4., 5., 6.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3., 4.]);
+                expect_equalish(&vecB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector4::lerp(vecA, &$Vector4::clone(vecA), vecB, 0.5);
+                expect_equalish(&vecA.elements(), &[3., 4., 5., 6.]);
+                expect_equalish(&vecB.elements(), &[5., 6., 7., 8.]);
+
+                let (_, vecA, vecB) = &mut setup();
+                $Vector4::lerp(vecB, vecA, &$Vector4::clone(vecB), 0.5);
+                expect_equalish(&vecB.elements(), &[3., 4., 5., 6.]);
+                expect_equalish(&vecA.elements(), &[1., 2., 3., 4.]);
+            }
+
+            #[test]
+            fn random() {
+                let _lock = lock_random();
+                let (out, _, _) = &mut setup();
+                $Vector4::random(out, None);
+                expect_equalish_scalar($Vector4::len(out), 1.0);
+
+                $Vector4::random(out, Some(5.0));
+                expect_equalish_scalar($Vector4::len(out), 5.0);
+            }
+
+            #[test]
+            fn cross() {
+                let out = &mut $Vector4::create();
+                let vecA = &mut $Vector4::fromValues(1., 0., 0., 0.);
+                let vecB = &mut $Vector4::fromValues(0., 1., 0., 0.);
+                let vecC = &mut $Vector4::fromValues(0., 0., 1., 0.);
+                $Vector4::cross(out, vecA, vecB, vecC);
+                expect_equalish(&out.elements(), &[0., 0., 0., -1.]);
+                expect_equalish(&vecA.elements(), &[1., 0., 0., 0.]);
+                expect_equalish(&vecB.elements(), &[0., 1., 0., 0.]);
+                expect_equalish(&vecC.elements(), &[0., 0., 1., 0.]);
+
+                $Vector4::cross(vecA, &$Vector4::clone(vecA), vecB, vecC);
+                expect_equalish(&vecA.elements(), &[0., 0., 0., -1.]);
+                expect_equalish(&vecB.elements(), &[0., 1., 0., 0.]);
+                expect_equalish(&vecC.elements(), &[0., 0., 1., 0.]);
+
+                let vecA = &mut $Vector4::fromValues(1., 0., 0., 0.);
+                $Vector4::cross(vecB, vecA, &$Vector4::clone(vecB), vecC);
+                expect_equalish(&vecB.elements(), &[0., 0., 0., -1.]);
+                expect_equalish(&vecA.elements(), &[1., 0., 0., 0.]);
+                expect_equalish(&vecC.elements(), &[0., 0., 1., 0.]);
+
+                let vecB = &mut $Vector4::fromValues(0., 1., 0., 0.);
+                $Vector4::cross(vecC, vecA, vecB, &$Vector4::clone(vecC));
+                expect_equalish(&vecC.elements(), &[0., 0., 0., -1.]);
+                expect_equalish(&vecA.elements(), &[1., 0., 0., 0.]);
+                expect_equalish(&vecB.elements(), &[0., 1., 0., 0.]);
+            }
+
+            #[test]
+            fn exactEquals() {
+                let vecA = &$Vector4::fromValues(0., 1., 2., 3.);
+                let vecB = &$Vector4::fromValues(0., 1., 2., 3.);
+                let vecC = &$Vector4::fromValues(1., 2., 3., 4.);
+                assert!($Vector4::exactEquals(vecA, vecB));
+                assert!(!$Vector4::exactEquals(vecA, vecC));
+                expect_equalish(&vecA.elements(), &[0., 1., 2., 3.]);
+                expect_equalish(&vecB.elements(), &[0., 1., 2., 3.]);
+            }
+
+            #[test]
+            fn equals() {
+                let vecA = &$Vector4::fromValues(0., 1., 2., 3.);
+                let vecB = &$Vector4::fromValues(0., 1., 2., 3.);
+                let vecC = &$Vector4::fromValues(1., 2., 3., 4.);
+                let vecD = &$Vector4::fromValues(1e-16, 1., 2., 3.);
+                assert!($Vector4::equals(vecA, vecB));
+                assert!(!$Vector4::equals(vecA, vecC));
+                assert!($Vector4::equals(vecA, vecD));
+                expect_equalish(&vecA.elements(), &[0., 1., 2., 3.]);
+                expect_equalish(&vecB.elements(), &[0., 1., 2., 3.]);
+            }
+
+            #[test]
+            fn zero() {
+                let vecA = &mut $Vector4::fromValues(1., 2., 3., 4.);
+                $Vector4::zero(vecA);
+                expect_equalish(&vecA.elements(), &[0., 0., 0., 0.]);
+            }
+
+            #[test]
+            fn traits() {
+                let a = $Vector4::fromValues(1., 2., 3., 4.);
+                let b = a;
+                assert_eq!(a, b);
+                assert_ne!(a, $Vector4::fromValues(1., 2., 3., 5.));
+                assert_ne!(a, $Vector4::fromValues(1., 2., 3., 4.00001));
+                assert!(a.approx_eq(&$Vector4::fromValues(1., 2., 3., 4.00001)));
+                assert!(!a.approx_eq(&$Vector4::fromValues(1., 2., 3., 5.)));
+                assert_eq!($Vector4::default(), $Vector4::create());
+                assert_eq!(
+                    format!("{:?}", $Vector4::create()),
+                    concat!(stringify!($Vector4), "(0.0, 0.0, 0.0, 0.0)")
+                );
+            }
+
+            #[test]
+            fn str() {
+                let a = $Vector4::fromValues(1., 2., 3., 4.);
+                assert_eq!($Vector4::str(&a), "vec4(1, 2, 3, 4)");
+                assert_eq!("vec4(1, 2, 3, 4)".parse::<$Vector4>(), Ok(a));
+            }
+
+            #[test]
+            fn equalsWith() {
+                let a = &$Vector4::fromValues(1., 2., 3., 4.);
+                let b = &$Vector4::fromValues(1.001, 2., 3., 4.);
+                let c = &$Vector4::fromValues($F::from_bits($F::to_bits(1.) + 2), 2., 3., 4.);
+                assert!(!$Vector4::equalsWithEpsilon(a, b, 0.0001));
+                assert!($Vector4::equalsWithEpsilon(a, b, 0.01));
+                assert!($Vector4::equalsWithUlps(a, c, 2));
+                assert!(!$Vector4::equalsWithUlps(a, c, 1));
+                assert!(!$Vector4::equalsWithUlps(a, b, 4));
+            }
+
+            #[test]
+            fn setSeed() {
+                let _lock = lock_random();
+                let (out, _, _) = &mut setup();
+                crate::common::setSeed(42);
+                $Vector4::random(out, Some(2.));
+                let first = $Vector4::clone(out);
+                $Vector4::random(out, Some(2.));
+                assert_ne!(out, &first);
+
+                crate::common::setSeed(42);
+                $Vector4::random(out, Some(2.));
+                assert_eq!(out, &first);
+            }
+
+            #[test]
+            fn slice() {
+                let a = $Vector4::fromValues(1., 2., 3., 4.);
+                assert_eq!(a.asSlice(), &a.elements()[..]);
+
+                let buffer = &mut [0.; 5];
+                a.writeSlice(&mut buffer[1..]);
+                assert_eq!($Vector4::fromSlice(&buffer[1..]), a);
+            }
+        }
+    };
 }
+
+pub(crate) use define_vector4;
+
+define_vector4!(
+    f32,
+    Float32Array,
+    fromFloat32Array,
+    Matrix2,
+    Matrix2d,
+    Matrix3,
+    Matrix4,
+    Quaternion,
+    Quaternion2,
+    Vector2,
+    Vector3,
+    Vector4
+);
@@ -0,0 +1,37 @@
+/**
+ * @File   : settings.rs
+ * @Author : dtysky (dtysky@outlook.com)
+ * @Link   : http://dtysky.moe
+ * @Date   : 2019/2/7 下午9:53:03
+ */
+// The comparison settings are global to the process, so they are changed here, apart from the unit
+// tests that read them. Everything is in one test to keep the changes in sequence.
+use gl_matrix_wasm::common::*;
+use gl_matrix_wasm::vector3::*;
+
+#[test]
+fn settings() {
+    assert_eq!(getEpsilon(), EPSILON);
+    assert!(equalsScalar(1., 1.00001));
+
+    setEpsilon(0.001);
+    assert_eq!(getEpsilon(), 0.001);
+    assert!(equalsScalar(1., 1.0005));
+    assert!(Vector3::equals(
+        &Vector3::fromValues(1., 2., 3.),
+        &Vector3::fromValues(1.0005, 2., 3.)
+    ));
+    setEpsilon(EPSILON);
+
+    setEqualsMode(EqualsMode::Ulps);
+    assert_eq!(getEqualsMode(), EqualsMode::Ulps);
+    assert_eq!(getMaxUlps(), 4);
+    assert!(!equalsScalar(1., 1.00001));
+    assert!(equalsScalar(1., f32::from_bits(1f32.to_bits() + 4)));
+    setMaxUlps(8);
+    assert_eq!(getMaxUlps(), 8);
+    assert!(equalsScalar(1., f32::from_bits(1f32.to_bits() + 8)));
+    assert!(!equalsScalar(1., f32::from_bits(1f32.to_bits() + 9)));
+    setMaxUlps(4);
+    setEqualsMode(EqualsMode::Epsilon);
+}
@@ -0,0 +1,8 @@
+/**
+ * @File   : lib.rs
+ * @Author : dtysky (dtysky@outlook.com)
+ * @Link   : http://dtysky.moe
+ * @Date   : 2019/2/7 下午9:53:03
+ */
+// Every export is defined in `gl-matrix-wasm`, linking it is enough for wasm-bindgen to find them.
+pub use gl_matrix_wasm::*;
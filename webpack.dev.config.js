@@ -114,7 +114,8 @@ module.exports = {
       template: './demo/index.html'
     }),
     new WasmPackPlugin({
-      crateDirectory: path.resolve(__dirname, './'),
+      crateDirectory: path.resolve(__dirname, './wasm'),
+      extraArgs: '--out-dir ../pkg --out-name gl_matrix_wasm',
       forceMode: 'development'
     })
   ]